@@ -0,0 +1,149 @@
+#ifndef LOCAL_ROBOT_MAP_H
+#define LOCAL_ROBOT_MAP_H
+
+/* Generated with cbindgen, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Status codes returned by the C API.
+typedef enum LrmStatus {
+  // The call succeeded.
+  LRM_STATUS_OK = 0,
+  // A required pointer argument was null.
+  LRM_STATUS_NULL_POINTER = 1,
+  // The requested location is outside the map area.
+  LRM_STATUS_OUT_OF_MAP = 2,
+  // The given byte does not correspond to any map state.
+  LRM_STATUS_INVALID_STATE = 3,
+  // The provided output buffer is too small.
+  LRM_STATUS_BUFFER_TOO_SMALL = 4,
+  // [`lrm_map_partition`] was called before registering a callback.
+  LRM_STATUS_NO_PARTITION_CALLBACK = 5,
+  // The partition callback reported a failure.
+  LRM_STATUS_PARTITION_FAILED = 6,
+  // The map is too large to be rendered as an image.
+  LRM_STATUS_TOO_LARGE = 7,
+} LrmStatus;
+
+// Opaque handle to a map, created with [`lrm_map_new`] and released with
+// [`lrm_map_free`].
+typedef struct LrmMap LrmMap;
+
+// Callback invoked by [`lrm_map_partition`].
+//
+// It receives the map handle and the `user_data` pointer given to
+// [`lrm_map_register_partition`], and is expected to update the map in place
+// (e.g. using [`lrm_map_set_location`]). A non-zero return value is considered
+// a failure and reported as [`LrmStatus::PartitionFailed`].
+typedef int32_t (*LrmPartitionFn)(struct LrmMap *map, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a new map spanning the bounding box given by two corners, with all
+// cells unexplored. See [`CellMap::try_new`].
+//
+// Returns null if the corners or the resolution are invalid, e.g. NaN, or
+// the map would be too large. Otherwise, the returned handle must be
+// released using [`lrm_map_free`].
+struct LrmMap *lrm_map_new(double x1,
+                           double y1,
+                           double z1,
+                           double x2,
+                           double y2,
+                           double z2,
+                           double resolution_x,
+                           double resolution_y,
+                           double resolution_z);
+
+// Release a map created with [`lrm_map_new`]. Passing null is a no-op.
+//
+// # Safety
+//
+// `map` must be null or a handle obtained from [`lrm_map_new`] which has not
+// been freed yet.
+void lrm_map_free(struct LrmMap *map);
+
+// Number of cells along the `x` axis, or `0` if `map` is null.
+//
+// # Safety
+//
+// `map` must be null or a valid handle.
+size_t lrm_map_width(const struct LrmMap *map);
+
+// Number of cells along the `y` axis, or `0` if `map` is null.
+//
+// # Safety
+//
+// `map` must be null or a valid handle.
+size_t lrm_map_height(const struct LrmMap *map);
+
+// Retrieve the state at the given real-world location and write it to
+// `state`. See [`Location::get_location`].
+//
+// # Safety
+//
+// `map` must be null or a valid handle and `state` must be null or point to
+// writable memory.
+enum LrmStatus lrm_map_get_location(const struct LrmMap *map,
+                                    double x,
+                                    double y,
+                                    double z,
+                                    uint8_t *state);
+
+// Set the state at the given real-world location. See
+// [`Location::set_location`].
+//
+// # Safety
+//
+// `map` must be null or a valid handle.
+enum LrmStatus lrm_map_set_location(struct LrmMap *map, double x, double y, double z, uint8_t state);
+
+// Register the partitioning callback to be run by [`lrm_map_partition`].
+//
+// Registering a new callback replaces the previous one, while a null
+// `callback` removes it, after which [`lrm_map_partition`] returns
+// [`LrmStatus::NoPartitionCallback`]. The `user_data` pointer is passed on
+// to the callback as-is and may be null.
+//
+// # Safety
+//
+// `map` must be null or a valid handle. `user_data` must remain valid for as
+// long as the callback is registered.
+enum LrmStatus lrm_map_register_partition(struct LrmMap *map,
+                                          LrmPartitionFn callback,
+                                          void *user_data);
+
+// Run the callback registered with [`lrm_map_register_partition`].
+//
+// # Safety
+//
+// `map` must be null or a valid handle.
+enum LrmStatus lrm_map_partition(struct LrmMap *map);
+
+// Number of bytes needed by [`lrm_map_image_rgb`], or `0` if `map` is null.
+//
+// # Safety
+//
+// `map` must be null or a valid handle.
+size_t lrm_map_image_rgb_len(const struct LrmMap *map);
+
+// Render the map (see [`CellMap::try_as_image`]) and copy the RGB pixels to
+// `buffer`, row by row, three bytes per pixel.
+//
+// # Safety
+//
+// `map` must be null or a valid handle and `buffer` must be null or point to
+// at least `len` writable bytes.
+enum LrmStatus lrm_map_image_rgb(const struct LrmMap *map, uint8_t *buffer, size_t len);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* LOCAL_ROBOT_MAP_H */
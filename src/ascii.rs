@@ -0,0 +1,184 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{CellMap, MapState};
+
+impl MapState {
+    /// Returns the character representing the [`MapState`] in
+    /// [`CellMap::to_ascii`].
+    pub fn to_char(&self) -> char {
+        self.into()
+    }
+}
+
+impl From<&MapState> for char {
+    fn from(value: &MapState) -> Self {
+        match value {
+            MapState::OutOfMap => ' ',
+            MapState::OtherRobot => 'R',
+            MapState::MyRobot => '@',
+            MapState::Explored => '.',
+            MapState::Unexplored => '?',
+            MapState::Frontier => 'F',
+            MapState::Assigned => 'A',
+            MapState::Obstacle => '#',
+        }
+    }
+}
+
+impl CellMap {
+    /// Render the map as text, using one character per cell (see
+    /// [`MapState::to_char`]) followed by a legend of the states shown.
+    ///
+    /// Rows are printed in the same order as the rows of
+    /// [`Visualize::as_image`](crate::Visualize::as_image), i.e. the first
+    /// line holds the cells with the lowest `y` values.
+    ///
+    /// If the map has more columns or rows than `max_size` (given as
+    /// `[columns, rows]`), blocks of cells are merged into a single
+    /// character. The state with the highest [`MapState::priority`] within a
+    /// block is shown, such that robots, obstacles and frontiers remain
+    /// visible.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let robot = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+    /// map.set_location(&robot, MapState::MyRobot).unwrap();
+    /// let wall = RealWorldLocation::from_xyz(3.5, 1.5, 0.0);
+    /// map.set_location(&wall, MapState::Obstacle).unwrap();
+    ///
+    /// assert_eq!(
+    ///     map.to_ascii(None),
+    ///     "@???\n???#\n\n@ MyRobot\n? Unexplored\n# Obstacle\n"
+    /// );
+    /// // Merge blocks of 2x1 cells.
+    /// assert_eq!(
+    ///     map.to_ascii(Some([2, 2])),
+    ///     "@?\n?#\n\n@ MyRobot\n? Unexplored\n# Obstacle\n"
+    /// );
+    /// ```
+    pub fn to_ascii(&self, max_size: Option<[usize; 2]>) -> String {
+        let grid = self.ascii_grid(max_size);
+        let mut shown: Vec<MapState> = Vec::new();
+        for state in grid.iter().flatten() {
+            if !shown.contains(state) {
+                shown.push(*state);
+            }
+        }
+
+        let mut text = String::new();
+        for row in &grid {
+            text.extend(row.iter().map(MapState::to_char));
+            text.push('\n');
+        }
+        text.push('\n');
+        for state in shown {
+            let name: &str = (&state).into();
+            text.push(state.to_char());
+            text.push(' ');
+            text.push_str(name);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// States of the characters of [`CellMap::to_ascii`], row by row.
+    fn ascii_grid(&self, max_size: Option<[usize; 2]>) -> Vec<Vec<MapState>> {
+        let [block_width, block_height] = match max_size {
+            Some([columns, rows]) => [
+                self.width().div_ceil(columns.max(1)).max(1),
+                self.height().div_ceil(rows.max(1)).max(1),
+            ],
+            None => [1, 1],
+        };
+
+        (0..self.height())
+            .step_by(block_height)
+            .map(|row| {
+                (0..self.width())
+                    .step_by(block_width)
+                    .map(|col| {
+                        let rows = row..(row + block_height).min(self.height());
+                        let cols = col..(col + block_width).min(self.width());
+                        rows.flat_map(|r| cols.clone().map(move |c| [r, c]))
+                            .map(|index| self.cells()[index])
+                            .max_by_key(MapState::priority)
+                            .expect("Blocks contain at least one cell")
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Render the map as text without downsampling and without a legend. See
+/// [`CellMap::to_ascii`].
+impl fmt::Display for CellMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.ascii_grid(None) {
+            let line: String = row.iter().map(MapState::to_char).collect();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location, RealWorldLocation};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(6.0, 3.0, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    #[test]
+    fn display_without_legend() {
+        let mut map = make_map();
+        map.set_location(
+            &RealWorldLocation::from_xyz(5.5, 2.5, 0.0),
+            MapState::Frontier,
+        )
+        .unwrap();
+
+        assert_eq!(alloc::format!("{map}"), "??????\n??????\n?????F\n");
+    }
+
+    #[test]
+    fn downsampling_keeps_important_states() {
+        let mut map = make_map();
+        map.set_location(
+            &RealWorldLocation::from_xyz(1.5, 1.5, 0.0),
+            MapState::OtherRobot,
+        )
+        .unwrap();
+        map.set_location(
+            &RealWorldLocation::from_xyz(4.5, 0.5, 0.0),
+            MapState::Explored,
+        )
+        .unwrap();
+
+        assert_eq!(
+            map.to_ascii(Some([2, 1])),
+            "R.\n\nR OtherRobot\n. Explored\n"
+        );
+        // Sizes larger than the map, or of zero, do not break rendering.
+        assert_eq!(map.ascii_grid(Some([10, 10])).len(), 3);
+        assert_eq!(map.ascii_grid(Some([0, 0])), [[MapState::OtherRobot]]);
+    }
+}
@@ -0,0 +1,363 @@
+//! Optimal assignment of goals to robots.
+//!
+//! Once the map is partitioned and candidate goals (e.g. frontier clusters,
+//! see [`CellMap::cluster_frontiers`](crate::CellMap::cluster_frontiers)) are
+//! known, each robot needs to be allocated a goal. Given a cost matrix with one
+//! row per robot and one column per goal, [`hungarian`] finds the matching
+//! which minimizes the total cost.
+//!
+//! [`LocalMap::assign_goals`] and [`LocalMap::assign_goals_with`] build the
+//! cost matrix from the robots of a [`LocalMap`].
+
+use alloc::vec;
+use alloc::vec::Vec;
+use ndarray::Array2;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{LocalMap, Location, MaskMapState, RealWorldLocation};
+
+/// Result of solving an assignment problem.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Assignment {
+    goals: Vec<Option<usize>>,
+    cost: f64,
+}
+
+impl Assignment {
+    /// The goal (column) assigned to each robot (row).
+    ///
+    /// If there are more robots than goals, the robots without a goal are
+    /// [`None`].
+    pub fn goals(&self) -> &[Option<usize>] {
+        &self.goals
+    }
+    /// The goal assigned to the given robot, if any.
+    pub fn goal_of(&self, robot: usize) -> Option<usize> {
+        self.goals.get(robot).copied().flatten()
+    }
+    /// The sum of the costs of all assigned robot-goal pairs.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AssignmentError {
+    /// The cost matrix contains a cost which is infinite or NaN. The offending
+    /// `[robot, goal]` index is given.
+    NonFiniteCost([usize; 2]),
+}
+
+/// Find the assignment of goals to robots minimizing the total cost.
+///
+/// `costs` holds one row per robot and one column per goal. Each robot is
+/// assigned at most one goal, and each goal at most one robot. If there are
+/// more goals than robots, every robot gets a goal; otherwise, every goal is
+/// taken.
+///
+/// Uses the Hungarian algorithm, which runs in `O(n² m)` for `n` being the
+/// smaller and `m` the larger dimension of the matrix.
+///
+/// # Errors
+///
+/// Returns [`AssignmentError::NonFiniteCost`] if any cost is infinite or NaN.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::assignment::hungarian;
+/// use ndarray::array;
+///
+/// let costs = array![[4.0, 1.0, 3.0], [2.0, 0.0, 5.0], [3.0, 2.0, 2.0]];
+/// let assignment = hungarian(&costs).unwrap();
+///
+/// assert_eq!(assignment.goals(), &[Some(1), Some(0), Some(2)]);
+/// assert_eq!(assignment.cost(), 5.0);
+/// ```
+pub fn hungarian(costs: &Array2<f64>) -> Result<Assignment, AssignmentError> {
+    if let Some((index, _)) =
+        costs.indexed_iter().find(|(_, cost)| !cost.is_finite())
+    {
+        return Err(AssignmentError::NonFiniteCost([index.0, index.1]));
+    }
+
+    let (robots, goals) = costs.dim();
+    let goals_of_robots = if robots <= goals {
+        solve(costs)
+    } else {
+        // Solve with goals as rows, then invert the matching.
+        let mut assigned = vec![None; robots];
+        for (goal, robot) in
+            solve(&costs.t().to_owned()).into_iter().enumerate()
+        {
+            if let Some(robot) = robot {
+                assigned[robot] = Some(goal);
+            }
+        }
+        assigned
+    };
+
+    let cost = goals_of_robots
+        .iter()
+        .enumerate()
+        .filter_map(|(robot, goal)| goal.map(|goal| costs[[robot, goal]]))
+        .sum();
+
+    Ok(Assignment {
+        goals: goals_of_robots,
+        cost,
+    })
+}
+
+/// Hungarian algorithm with potentials for `rows <= columns`, assigning a
+/// column to every row.
+fn solve(costs: &Array2<f64>) -> Vec<Option<usize>> {
+    let (n, m) = costs.dim();
+    // Arrays are 1-based; index 0 is a virtual column used while augmenting.
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; m + 1];
+    let mut row_of = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+
+    for row in 1..=n {
+        row_of[0] = row;
+        let mut column = 0;
+        let mut min_slack = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[column] = true;
+            let current_row = row_of[column];
+            let mut delta = f64::INFINITY;
+            let mut next_column = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let slack =
+                    costs[[current_row - 1, j - 1]] - u[current_row] - v[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = column;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    next_column = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+            column = next_column;
+            if row_of[column] == 0 {
+                break;
+            }
+        }
+        while column != 0 {
+            let previous = way[column];
+            row_of[column] = row_of[previous];
+            column = previous;
+        }
+    }
+
+    let mut assigned = vec![None; n];
+    for (column, row) in row_of.into_iter().enumerate().skip(1) {
+        if row != 0 {
+            assigned[row - 1] = Some(column - 1);
+        }
+    }
+    assigned
+}
+
+impl<T, P> LocalMap<T, P>
+where
+    T: Location + MaskMapState + core::fmt::Debug,
+{
+    /// Assign goals to the robots, minimizing the total straight-line
+    /// distance in the `x`-`y` plane.
+    ///
+    /// Robot `0` of the resulting [`Assignment`] is [`LocalMap::my_robot`],
+    /// followed by [`LocalMap::other_robots`] in order. See
+    /// [`LocalMap::assign_goals_with`] for using other costs, e.g. path
+    /// lengths.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, LocalMap, RealWorldLocation, Robot,
+    /// };
+    ///
+    /// let map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     Robot::new(RealWorldLocation::from_xyz(1.0, 1.0, 0.0), ()),
+    ///     vec![Robot::new(RealWorldLocation::from_xyz(9.0, 9.0, 0.0), ())],
+    /// )
+    /// .unwrap();
+    /// let goals = [
+    ///     RealWorldLocation::from_xyz(8.0, 9.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+    /// ];
+    ///
+    /// let assignment = map.assign_goals(&goals).unwrap();
+    ///
+    /// assert_eq!(assignment.goals(), &[Some(1), Some(0)]);
+    /// ```
+    pub fn assign_goals(
+        &self,
+        goals: &[RealWorldLocation],
+    ) -> Result<Assignment, AssignmentError> {
+        self.assign_goals_with(goals, |robot, goal| {
+            ((robot.x - goal.x).powi(2) + (robot.y - goal.y).powi(2)).sqrt()
+        })
+    }
+
+    /// Assign goals to the robots, minimizing the total cost given by `cost`.
+    ///
+    /// `cost` is called with a robot's location and a goal. Robot `0` of the
+    /// resulting [`Assignment`] is [`LocalMap::my_robot`], followed by
+    /// [`LocalMap::other_robots`] in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AssignmentError::NonFiniteCost`] if `cost` returns an
+    /// infinite or NaN value.
+    pub fn assign_goals_with(
+        &self,
+        goals: &[RealWorldLocation],
+        cost: impl Fn(&RealWorldLocation, &RealWorldLocation) -> f64,
+    ) -> Result<Assignment, AssignmentError> {
+        let robots: Vec<_> = core::iter::once(self.my_position())
+            .chain(self.other_robots().iter().map(|robot| robot.location()))
+            .collect();
+        let costs =
+            Array2::from_shape_fn((robots.len(), goals.len()), |(r, g)| {
+                cost(robots[r], &goals[g])
+            });
+
+        hungarian(&costs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, CellMap, Robot};
+    use ndarray::array;
+
+    /// Minimum cost over all assignments, by trying every permutation.
+    fn brute_force(costs: &Array2<f64>) -> f64 {
+        fn go(costs: &Array2<f64>, row: usize, used: &mut Vec<bool>) -> f64 {
+            if row == costs.nrows() {
+                return 0.0;
+            }
+            let mut best = f64::INFINITY;
+            for col in 0..costs.ncols() {
+                if !used[col] {
+                    used[col] = true;
+                    best =
+                        best.min(costs[[row, col]] + go(costs, row + 1, used));
+                    used[col] = false;
+                }
+            }
+            best
+        }
+        go(costs, 0, &mut vec![false; costs.ncols()])
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let costs = Array2::from_shape_fn((5, 6), |(r, c)| {
+            ((r * 7 + c * 13) % 11) as f64 + (r as f64 - c as f64).abs() * 0.3
+        });
+
+        let assignment = hungarian(&costs).unwrap();
+
+        assert!((assignment.cost() - brute_force(&costs)).abs() < 1e-9);
+        let mut goals: Vec<_> =
+            assignment.goals().iter().map(|g| g.unwrap()).collect();
+        goals.sort();
+        goals.dedup();
+        assert_eq!(goals.len(), 5);
+    }
+
+    #[test]
+    fn more_robots_than_goals() {
+        let costs = array![[5.0, 9.0], [1.0, 7.0], [8.0, 2.0]];
+
+        let assignment = hungarian(&costs).unwrap();
+
+        assert_eq!(assignment.goals(), &[None, Some(0), Some(1)]);
+        assert_eq!(assignment.cost(), 3.0);
+        assert_eq!(assignment.goal_of(0), None);
+        assert_eq!(assignment.goal_of(2), Some(1));
+        assert_eq!(assignment.goal_of(3), None);
+    }
+
+    #[test]
+    fn negative_costs() {
+        let costs = array![[-1.0, -5.0], [-2.0, -3.0]];
+
+        let assignment = hungarian(&costs).unwrap();
+
+        assert_eq!(assignment.goals(), &[Some(1), Some(0)]);
+        assert_eq!(assignment.cost(), -7.0);
+    }
+
+    #[test]
+    fn empty() {
+        let assignment = hungarian(&Array2::zeros((2, 0))).unwrap();
+        assert_eq!(assignment.goals(), &[None, None]);
+
+        let assignment = hungarian(&Array2::zeros((0, 3))).unwrap();
+        assert!(assignment.goals().is_empty());
+        assert_eq!(assignment.cost(), 0.0);
+    }
+
+    #[test]
+    fn non_finite_cost() {
+        let costs = array![[1.0, 2.0], [f64::NAN, 3.0]];
+
+        assert_eq!(
+            hungarian(&costs),
+            Err(AssignmentError::NonFiniteCost([1, 0]))
+        );
+    }
+
+    #[test]
+    fn local_map_custom_cost() {
+        let map = LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(1.0, 1.0, 0.0), ()),
+            vec![
+                Robot::new(RealWorldLocation::from_xyz(5.0, 5.0, 0.0), ()),
+                Robot::new(RealWorldLocation::from_xyz(9.0, 9.0, 0.0), ()),
+            ],
+        )
+        .unwrap();
+        let goals = [RealWorldLocation::from_xyz(9.5, 9.5, 0.0)];
+
+        // Manhattan distance.
+        let assignment = map
+            .assign_goals_with(&goals, |robot, goal| {
+                (robot.x - goal.x).abs() + (robot.y - goal.y).abs()
+            })
+            .unwrap();
+
+        assert_eq!(assignment.goals(), &[None, None, Some(0)]);
+        assert_eq!(assignment.cost(), 1.0);
+    }
+}
@@ -0,0 +1,400 @@
+//! C API for integrating the crate with non-Rust stacks.
+//!
+//! Only available with the `capi` feature. The corresponding header lives in
+//! `include/local_robot_map.h` and is generated using
+//! [cbindgen](https://github.com/mozilla/cbindgen):
+//!
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/local_robot_map.h
+//! ```
+//!
+//! A shared or static library can be built using
+//!
+//! ```sh
+//! cargo rustc --release --lib --features capi --crate-type cdylib
+//! cargo rustc --release --lib --features capi --crate-type staticlib
+//! ```
+//!
+//! The map is exposed as an opaque [`LrmMap`] handle which owns a [`CellMap`].
+//! Map states are exchanged as bytes using the same values as
+//! [`CellMap::to_bytes`] (see [`u8::from`]). All functions report failures
+//! through an [`LrmStatus`] instead of panicking across the FFI boundary.
+
+use crate::{
+    AxisResolution, CellMap, Location, LocationError, MapState,
+    RealWorldLocation,
+};
+
+/// Callback invoked by [`lrm_map_partition`].
+///
+/// It receives the map handle and the `user_data` pointer given to
+/// [`lrm_map_register_partition`], and is expected to update the map in place
+/// (e.g. using [`lrm_map_set_location`]). A non-zero return value is considered
+/// a failure and reported as [`LrmStatus::PartitionFailed`].
+pub type LrmPartitionFn =
+    extern "C" fn(map: *mut LrmMap, user_data: *mut core::ffi::c_void) -> i32;
+
+/// Opaque handle to a map, created with [`lrm_map_new`] and released with
+/// [`lrm_map_free`].
+pub struct LrmMap {
+    map: CellMap,
+    partition: Option<(LrmPartitionFn, *mut core::ffi::c_void)>,
+}
+
+/// Status codes returned by the C API.
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LrmStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// The requested location is outside the map area.
+    OutOfMap = 2,
+    /// The given byte does not correspond to any map state.
+    InvalidState = 3,
+    /// The provided output buffer is too small.
+    BufferTooSmall = 4,
+    /// [`lrm_map_partition`] was called before registering a callback.
+    NoPartitionCallback = 5,
+    /// The partition callback reported a failure.
+    PartitionFailed = 6,
+    /// The map is too large to be rendered as an image.
+    TooLarge = 7,
+}
+
+impl From<LocationError> for LrmStatus {
+    fn from(value: LocationError) -> Self {
+        match value {
+            LocationError::OutOfMap(_) => LrmStatus::OutOfMap,
+        }
+    }
+}
+
+/// Create a new map spanning the bounding box given by two corners, with all
+/// cells unexplored. See [`CellMap::try_new`].
+///
+/// Returns null if the corners or the resolution are invalid, e.g. NaN, or
+/// the map would be too large. Otherwise, the returned handle must be
+/// released using [`lrm_map_free`].
+#[no_mangle]
+pub extern "C" fn lrm_map_new(
+    x1: f64,
+    y1: f64,
+    z1: f64,
+    x2: f64,
+    y2: f64,
+    z2: f64,
+    resolution_x: f64,
+    resolution_y: f64,
+    resolution_z: f64,
+) -> *mut LrmMap {
+    match CellMap::try_new(
+        RealWorldLocation::from_xyz(x1, y1, z1),
+        RealWorldLocation::from_xyz(x2, y2, z2),
+        AxisResolution::new(resolution_x, resolution_y, resolution_z),
+    ) {
+        Ok(map) => Box::into_raw(Box::new(LrmMap {
+            map,
+            partition: None,
+        })),
+        Err(_) => core::ptr::null_mut(),
+    }
+}
+
+/// Release a map created with [`lrm_map_new`]. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `map` must be null or a handle obtained from [`lrm_map_new`] which has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_free(map: *mut LrmMap) {
+    if !map.is_null() {
+        drop(Box::from_raw(map));
+    }
+}
+
+/// Number of cells along the `x` axis, or `0` if `map` is null.
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_width(map: *const LrmMap) -> usize {
+    map.as_ref().map_or(0, |m| m.map.width())
+}
+
+/// Number of cells along the `y` axis, or `0` if `map` is null.
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_height(map: *const LrmMap) -> usize {
+    map.as_ref().map_or(0, |m| m.map.height())
+}
+
+/// Retrieve the state at the given real-world location and write it to
+/// `state`. See [`Location::get_location`].
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle and `state` must be null or point to
+/// writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_get_location(
+    map: *const LrmMap,
+    x: f64,
+    y: f64,
+    z: f64,
+    state: *mut u8,
+) -> LrmStatus {
+    let (Some(map), false) = (map.as_ref(), state.is_null()) else {
+        return LrmStatus::NullPointer;
+    };
+    match map.map.get_location(&RealWorldLocation::from_xyz(x, y, z)) {
+        Ok(value) => {
+            *state = (&value).into();
+            LrmStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Set the state at the given real-world location. See
+/// [`Location::set_location`].
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_set_location(
+    map: *mut LrmMap,
+    x: f64,
+    y: f64,
+    z: f64,
+    state: u8,
+) -> LrmStatus {
+    let Some(map) = map.as_mut() else {
+        return LrmStatus::NullPointer;
+    };
+    let Ok(value) = MapState::try_from(state) else {
+        return LrmStatus::InvalidState;
+    };
+    match map
+        .map
+        .set_location(&RealWorldLocation::from_xyz(x, y, z), value)
+    {
+        Ok(()) => LrmStatus::Ok,
+        Err(e) => e.into(),
+    }
+}
+
+/// Register the partitioning callback to be run by [`lrm_map_partition`].
+///
+/// Registering a new callback replaces the previous one, while a null
+/// `callback` removes it, after which [`lrm_map_partition`] returns
+/// [`LrmStatus::NoPartitionCallback`]. The `user_data` pointer is passed on
+/// to the callback as-is and may be null.
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle. `user_data` must remain valid for as
+/// long as the callback is registered.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_register_partition(
+    map: *mut LrmMap,
+    callback: Option<LrmPartitionFn>,
+    user_data: *mut core::ffi::c_void,
+) -> LrmStatus {
+    let Some(map) = map.as_mut() else {
+        return LrmStatus::NullPointer;
+    };
+    map.partition = callback.map(|callback| (callback, user_data));
+    LrmStatus::Ok
+}
+
+/// Run the callback registered with [`lrm_map_register_partition`].
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_partition(map: *mut LrmMap) -> LrmStatus {
+    let Some(handle) = map.as_mut() else {
+        return LrmStatus::NullPointer;
+    };
+    let Some((callback, user_data)) = handle.partition else {
+        return LrmStatus::NoPartitionCallback;
+    };
+    match callback(map, user_data) {
+        0 => LrmStatus::Ok,
+        _ => LrmStatus::PartitionFailed,
+    }
+}
+
+/// Number of bytes needed by [`lrm_map_image_rgb`], or `0` if `map` is null.
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_image_rgb_len(map: *const LrmMap) -> usize {
+    map.as_ref()
+        .map_or(0, |m| m.map.width() * m.map.height() * 3)
+}
+
+/// Render the map (see [`CellMap::try_as_image`]) and copy the RGB pixels to
+/// `buffer`, row by row, three bytes per pixel.
+///
+/// # Safety
+///
+/// `map` must be null or a valid handle and `buffer` must be null or point to
+/// at least `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn lrm_map_image_rgb(
+    map: *const LrmMap,
+    buffer: *mut u8,
+    len: usize,
+) -> LrmStatus {
+    let (Some(map), false) = (map.as_ref(), buffer.is_null()) else {
+        return LrmStatus::NullPointer;
+    };
+    let Ok(image) = map.map.try_as_image() else {
+        return LrmStatus::TooLarge;
+    };
+    let pixels = image.into_raw();
+    if len < pixels.len() {
+        return LrmStatus::BufferTooSmall;
+    }
+    core::ptr::copy_nonoverlapping(pixels.as_ptr(), buffer, pixels.len());
+    LrmStatus::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_map() -> *mut LrmMap {
+        lrm_map_new(0.0, 0.0, 0.0, 4.0, 2.0, 0.0, 1.0, 1.0, 1.0)
+    }
+
+    #[test]
+    fn create_and_free() {
+        let map = make_map();
+        unsafe {
+            assert_eq!(lrm_map_width(map), 4);
+            assert_eq!(lrm_map_height(map), 2);
+            lrm_map_free(map);
+            lrm_map_free(core::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn invalid_map_is_null() {
+        let nan = lrm_map_new(0.0, 0.0, 0.0, f64::NAN, 2.0, 0.0, 1.0, 1.0, 1.0);
+        let huge = lrm_map_new(0.0, 0.0, 0.0, 1e10, 1e10, 0.0, 1.0, 1.0, 1.0);
+        let empty = lrm_map_new(0.0, 0.0, 0.0, 4.0, 2.0, 0.0, 0.0, 1.0, 1.0);
+
+        assert!(nan.is_null());
+        assert!(huge.is_null());
+        assert!(empty.is_null());
+        unsafe {
+            assert_eq!(lrm_map_width(nan), 0);
+        }
+    }
+
+    #[test]
+    fn set_and_get_location() {
+        let map = make_map();
+        let explored: u8 = (&MapState::Explored).into();
+        let mut state = 0;
+        unsafe {
+            assert_eq!(
+                lrm_map_set_location(map, 1.5, 0.5, 0.0, explored),
+                LrmStatus::Ok
+            );
+            assert_eq!(
+                lrm_map_get_location(map, 1.5, 0.5, 0.0, &mut state),
+                LrmStatus::Ok
+            );
+            assert_eq!(state, explored);
+            assert_eq!(
+                lrm_map_set_location(map, 10.0, 0.5, 0.0, explored),
+                LrmStatus::OutOfMap
+            );
+            assert_eq!(
+                lrm_map_set_location(map, 1.5, 0.5, 0.0, 200),
+                LrmStatus::InvalidState
+            );
+            assert_eq!(
+                lrm_map_get_location(map, 1.5, 0.5, 0.0, core::ptr::null_mut()),
+                LrmStatus::NullPointer
+            );
+            lrm_map_free(map);
+        }
+    }
+
+    #[test]
+    fn partition_callback() {
+        extern "C" fn assign_origin(
+            map: *mut LrmMap,
+            user_data: *mut core::ffi::c_void,
+        ) -> i32 {
+            unsafe {
+                *(user_data as *mut u32) += 1;
+                lrm_map_set_location(
+                    map,
+                    0.0,
+                    0.0,
+                    0.0,
+                    (&MapState::Assigned).into(),
+                ) as i32
+            }
+        }
+
+        let map = make_map();
+        let mut calls: u32 = 0;
+        let mut state = 0;
+        unsafe {
+            assert_eq!(lrm_map_partition(map), LrmStatus::NoPartitionCallback);
+            lrm_map_register_partition(
+                map,
+                Some(assign_origin),
+                &mut calls as *mut u32 as *mut core::ffi::c_void,
+            );
+            assert_eq!(lrm_map_partition(map), LrmStatus::Ok);
+            lrm_map_get_location(map, 0.0, 0.0, 0.0, &mut state);
+            assert_eq!(
+                lrm_map_register_partition(map, None, core::ptr::null_mut()),
+                LrmStatus::Ok
+            );
+            assert_eq!(lrm_map_partition(map), LrmStatus::NoPartitionCallback);
+            lrm_map_free(map);
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(state, (&MapState::Assigned).into());
+    }
+
+    #[test]
+    fn export_image() {
+        let map = make_map();
+        unsafe {
+            let len = lrm_map_image_rgb_len(map);
+            assert_eq!(len, 4 * 2 * 3);
+
+            let mut buffer = vec![0; len];
+            assert_eq!(
+                lrm_map_image_rgb(map, buffer.as_mut_ptr(), len - 1),
+                LrmStatus::BufferTooSmall
+            );
+            assert_eq!(
+                lrm_map_image_rgb(map, buffer.as_mut_ptr(), len),
+                LrmStatus::Ok
+            );
+            assert_eq!(&buffer[..3], &MapState::Unexplored.to_rgb().0);
+            lrm_map_free(map);
+        }
+    }
+}
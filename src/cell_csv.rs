@@ -0,0 +1,278 @@
+use std::io::{self, BufRead, Write};
+
+use num::ToPrimitive;
+
+use crate::{AxisResolution, CellMap, Coords, MapState, MapStateMatrix};
+
+/// Header of the CSV representation, see [`CellMap::to_csv`].
+const HEADER: &str = "x,y,state";
+
+impl CellMap {
+    /// Write the cell states as CSV, one row per cell, e.g. for analysis with
+    /// pandas or R.
+    ///
+    /// The columns are `x` and `y`, the real-world location of the cell's
+    /// center, and `state`, the name of the [`MapState`] (e.g. `Explored`).
+    /// Rows are written in the order of [`CellMap::cells`], i.e. row by row
+    /// starting at the map's offset. Use [`CellMap::from_csv`] to read the map
+    /// back.
+    ///
+    /// Note that only the cell states are written; timestamps (see
+    /// [`CellMap::set_clock`]) are not.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of `writer`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_location(
+    ///     &RealWorldLocation::from_xyz(1.5, 0.5, 0.0),
+    ///     MapState::Obstacle,
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut csv = Vec::new();
+    /// map.to_csv(&mut csv).unwrap();
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(csv).unwrap(),
+    ///     "x,y,state\n0.5,0.5,Unexplored\n1.5,0.5,Obstacle\n"
+    /// );
+    /// ```
+    pub fn to_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{HEADER}")?;
+        for ((row, col), state) in self.cells().indexed_iter() {
+            let center = self.index_center::<f64>([row, col]);
+            writeln!(
+                writer,
+                "{},{},{}",
+                center.x,
+                center.y,
+                <&str>::from(state)
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Read a map previously written with [`CellMap::to_csv`], or any CSV
+    /// with the same columns.
+    ///
+    /// Each row sets the state of the cell containing its location, given the
+    /// `resolution` and the `offset` of the map, i.e. its bottom left corner.
+    /// The map extends from the offset up to the cell of the furthest row.
+    /// Cells without any row are [`MapState::Unexplored`], and later rows
+    /// overwrite earlier ones for the same cell.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the CSV cannot be read or does
+    /// not describe a valid map. See [`CellMapCsvError`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, CellMap, Coords, MapState};
+    /// use ndarray::array;
+    ///
+    /// let csv = "x,y,state\n0.5,0.5,Explored\n1.5,1.5,Obstacle\n";
+    ///
+    /// let map = CellMap::from_csv(
+    ///     csv.as_bytes(),
+    ///     AxisResolution::uniform(1.0),
+    ///     Coords::new(0.0, 0.0, 0.0),
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     map.cells(),
+    ///     array![
+    ///         [MapState::Explored, MapState::Unexplored],
+    ///         [MapState::Unexplored, MapState::Obstacle],
+    ///     ]
+    /// );
+    /// ```
+    pub fn from_csv<R: BufRead>(
+        reader: R,
+        resolution: AxisResolution,
+        offset: Coords,
+    ) -> Result<Self, CellMapCsvError> {
+        let valid = |value: f64| value.is_finite() && value > 0.0;
+        if !valid(resolution.x) || !valid(resolution.y) {
+            return Err(CellMapCsvError::InvalidResolution);
+        }
+
+        let mut lines = reader.lines();
+        let header = lines.next().transpose()?;
+        if header.as_deref().map(str::trim) != Some(HEADER) {
+            return Err(CellMapCsvError::InvalidHeader);
+        }
+
+        let mut cells: Vec<([usize; 2], MapState)> = Vec::new();
+        for (line, text) in lines.enumerate() {
+            // The header is line 1.
+            let line = line + 2;
+            let text = text?;
+            if text.trim().is_empty() {
+                continue;
+            }
+            let mut fields = text.split(',').map(str::trim);
+            let (Some(x), Some(y), Some(state), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(CellMapCsvError::InvalidRow(line));
+            };
+            let to_index = |value: &str, offset: f64, resolution: f64| {
+                let value: f64 = value.parse().ok()?;
+                Some((value - offset) * resolution)
+                    .filter(|value| *value >= 0.0)
+                    .and_then(|value| value.floor().to_usize())
+            };
+            let (Some(col), Some(row)) = (
+                to_index(x, offset.x, resolution.x),
+                to_index(y, offset.y, resolution.y),
+            ) else {
+                return Err(CellMapCsvError::InvalidLocation(line));
+            };
+            let state = parse_state(state)
+                .ok_or(CellMapCsvError::UnknownState(line))?;
+            cells.push(([row, col], state));
+        }
+
+        let rows = cells.iter().map(|([row, _], _)| *row).max();
+        let columns = cells.iter().map(|([_, col], _)| *col).max();
+        let (Some(rows), Some(columns)) = (rows, columns) else {
+            return Err(CellMapCsvError::Empty);
+        };
+        let (Some(rows), Some(columns)) =
+            (rows.checked_add(1), columns.checked_add(1))
+        else {
+            return Err(CellMapCsvError::TooLarge);
+        };
+        Self::check_size(rows, columns)
+            .map_err(|_| CellMapCsvError::TooLarge)?;
+        let mut matrix =
+            MapStateMatrix::from_elem((rows, columns), MapState::Unexplored);
+        for (index, state) in cells {
+            matrix[index] = state;
+        }
+        Ok(Self::from_raster(matrix, resolution, offset))
+    }
+}
+
+/// The [`MapState`] with the given name, see [`CellMap::to_csv`].
+fn parse_state(name: &str) -> Option<MapState> {
+    (0..=u8::MAX)
+        .map_while(|byte| MapState::try_from(byte).ok())
+        .find(|state| <&str>::from(state) == name)
+}
+
+#[derive(Debug)]
+pub enum CellMapCsvError {
+    /// The CSV could not be read.
+    Io(io::Error),
+    /// The `x` or `y` resolution is zero, negative, NaN or infinite.
+    InvalidResolution,
+    /// The first line is not the expected `x,y,state` header.
+    InvalidHeader,
+    /// The row on the given line does not consist of exactly three columns.
+    InvalidRow(usize),
+    /// The location of the row on the given line is not a number, or lies
+    /// below or left of the offset.
+    InvalidLocation(usize),
+    /// The state of the row on the given line is not the name of any
+    /// [`MapState`].
+    UnknownState(usize),
+    /// The CSV does not contain any row.
+    Empty,
+    /// The locations span more than [`CellMap::MAX_CELLS`] cells.
+    TooLarge,
+}
+
+impl From<io::Error> for CellMapCsvError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cell_map::tests::make_map, RealWorldLocation};
+
+    #[test]
+    fn roundtrip() {
+        let (map, _) = make_map();
+        let mut csv = Vec::new();
+        map.to_csv(&mut csv).unwrap();
+
+        let read =
+            CellMap::from_csv(csv.as_slice(), *map.resolution(), *map.offset())
+                .unwrap();
+
+        assert_eq!(read.cells(), map.cells());
+        assert_eq!(read.offset(), map.offset());
+    }
+
+    #[test]
+    fn roundtrip_negative_offset() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(-3.5, -1.25, 0.0),
+            RealWorldLocation::from_xyz(2.0, 4.0, 0.0),
+            AxisResolution::new(2.0, 4.0, 1.0),
+        );
+        let mut csv = Vec::new();
+        map.to_csv(&mut csv).unwrap();
+
+        let read =
+            CellMap::from_csv(csv.as_slice(), *map.resolution(), *map.offset())
+                .unwrap();
+
+        assert_eq!(read.cells(), map.cells());
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let read = |csv: &str| {
+            CellMap::from_csv(
+                csv.as_bytes(),
+                AxisResolution::uniform(1.0),
+                Coords::new(0.0, 0.0, 0.0),
+            )
+            .unwrap_err()
+        };
+
+        assert!(matches!(read(""), CellMapCsvError::InvalidHeader));
+        assert!(matches!(read("x,y,state\n"), CellMapCsvError::Empty));
+        assert!(matches!(
+            read("x,y,state\n0.5,0.5\n"),
+            CellMapCsvError::InvalidRow(2)
+        ));
+        assert!(matches!(
+            read("x,y,state\n0.5,0.5,Explored\n-0.5,0.5,Explored\n"),
+            CellMapCsvError::InvalidLocation(3)
+        ));
+        assert!(matches!(
+            read("x,y,state\n0.5,0.5,Visited\n"),
+            CellMapCsvError::UnknownState(2)
+        ));
+        assert!(matches!(
+            read(
+                "x,y,state
+1e12,1e12,Explored
+"
+            ),
+            CellMapCsvError::TooLarge
+        ));
+    }
+}
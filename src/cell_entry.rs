@@ -0,0 +1,174 @@
+use crate::{
+    CellMap, LocationError, LocationType, MapState, RealWorldLocation,
+};
+
+/// A single cell of a [`CellMap`], obtained through [`CellMap::entry`].
+///
+/// The location is converted into a matrix index once, after which the cell
+/// can be read and modified any number of times. All modifications stamp the
+/// cell with the map's clock, like [`Location::set_location`] does.
+///
+/// Similar to [`std::collections::hash_map::Entry`], a cell is considered
+/// *vacant* as long as it is [`MapState::Unexplored`], the state every cell of
+/// a new map starts in.
+///
+/// [`Location::set_location`]: crate::Location::set_location
+#[derive(Debug)]
+pub struct CellEntry<'a> {
+    map: &'a mut CellMap,
+    index: [usize; 2],
+}
+
+impl<'a> CellEntry<'a> {
+    /// The `[row, col]` index of the cell, see
+    /// [`CellMap::location_to_map_index`].
+    pub fn index(&self) -> [usize; 2] {
+        self.index
+    }
+
+    /// The current state of the cell.
+    pub fn get(&self) -> LocationType {
+        self.map.cells()[self.index]
+    }
+
+    /// Overwrite the state of the cell.
+    pub fn set(&mut self, value: LocationType) {
+        self.map.set_cell(self.index, value);
+    }
+
+    /// Whether the cell is still [`MapState::Unexplored`].
+    pub fn is_vacant(&self) -> bool {
+        self.get() == MapState::Unexplored
+    }
+
+    /// Modify the state of the cell in place.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut LocationType)) -> Self {
+        let mut value = self.get();
+        f(&mut value);
+        self.set(value);
+        self
+    }
+
+    /// Set the cell to `value` if it is vacant, and return a mutable reference
+    /// to its state.
+    pub fn or_set(self, value: LocationType) -> &'a mut LocationType {
+        self.or_set_with(|| value)
+    }
+
+    /// Same as [`CellEntry::or_set`], but only computes the value if the cell
+    /// is vacant.
+    pub fn or_set_with(
+        mut self,
+        f: impl FnOnce() -> LocationType,
+    ) -> &'a mut LocationType {
+        if self.is_vacant() {
+            self.set(f());
+        }
+        self.into_mut()
+    }
+
+    /// Convert the entry into a mutable reference to the cell's state.
+    ///
+    /// The cell is stamped with the map's clock, as it may be modified through
+    /// the returned reference.
+    pub fn into_mut(self) -> &'a mut LocationType {
+        self.map.get_index_mut(self.index)
+    }
+}
+
+impl CellMap {
+    /// Access the cell at the given location for in-place manipulation.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`CellMap::location_to_map_index`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let location = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+    ///
+    /// // Only mark the cell as a frontier if nothing is known about it yet.
+    /// map.entry(&location).unwrap().or_set(MapState::Frontier);
+    /// map.entry(&location).unwrap().or_set(MapState::Explored);
+    /// assert_eq!(map.get_location(&location), Ok(MapState::Frontier));
+    ///
+    /// map.entry(&location).unwrap().and_modify(|state| {
+    ///     if *state == MapState::Frontier {
+    ///         *state = MapState::Explored;
+    ///     }
+    /// });
+    /// assert_eq!(map.get_location(&location), Ok(MapState::Explored));
+    /// ```
+    pub fn entry(
+        &mut self,
+        location: &RealWorldLocation,
+    ) -> Result<CellEntry<'_>, LocationError> {
+        let index = self.location_to_map_index(location)?;
+        Ok(CellEntry { map: self, index })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    #[test]
+    fn or_set_only_when_vacant() {
+        let mut map = make_map();
+        let location = RealWorldLocation::from_xyz(1.5, 2.5, 0.0);
+
+        assert_eq!(
+            *map.entry(&location).unwrap().or_set(MapState::Obstacle),
+            MapState::Obstacle
+        );
+        assert_eq!(
+            *map.entry(&location).unwrap().or_set_with(|| unreachable!()),
+            MapState::Obstacle
+        );
+        assert_eq!(map.entry(&location).unwrap().index(), [2, 1]);
+    }
+
+    #[test]
+    fn modifications_stamp_clock() {
+        let mut map = make_map();
+        let location = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        map.set_clock(4.0);
+
+        let mut entry = map.entry(&location).unwrap();
+        entry.set(MapState::Frontier);
+        let entry = entry.and_modify(|state| *state = MapState::Explored);
+        assert_eq!(entry.get(), MapState::Explored);
+
+        assert_eq!(map.last_update(&location), Ok(4.0));
+        assert_eq!(map.get_location(&location), Ok(MapState::Explored));
+    }
+
+    #[test]
+    fn entry_outside_map() {
+        let mut map = make_map();
+
+        assert!(matches!(
+            map.entry(&RealWorldLocation::from_xyz(3.5, 0.5, 0.0)),
+            Err(LocationError::OutOfMap(_))
+        ));
+    }
+}
@@ -0,0 +1,317 @@
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{Cell, RealWorldLocation};
+
+/// Spatial index over a set of cell locations.
+///
+/// The index is a 2D k-d tree over the `x` and `y` components of the
+/// locations, answering k-nearest-neighbor and radius queries without
+/// comparing against every location. This is useful when the same set of
+/// locations is queried repeatedly, e.g. when a partitioning algorithm asks
+/// which robot is closest to each cell of a map.
+///
+/// Query results refer to locations by their position in the input the index
+/// was built from, together with their Euclidean distance to the queried
+/// location. Results are ordered by increasing distance.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{CellIndex, Location, RealWorldLocation};
+///
+/// let robots = [
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(10.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(5.0, 8.0, 0.0),
+/// ];
+/// let index = CellIndex::from_locations(robots.iter().cloned());
+///
+/// let cell = RealWorldLocation::from_xyz(6.0, 6.0, 0.0);
+/// let (closest, distance) = index.nearest(&cell).unwrap();
+///
+/// assert_eq!(closest, 2);
+/// assert!((distance - 5.0_f64.sqrt()).abs() < 1e-12);
+/// assert_eq!(
+///     index
+///         .within_radius(&cell, 8.0)
+///         .into_iter()
+///         .map(|(i, _)| i)
+///         .collect::<Vec<_>>(),
+///     vec![2, 1]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CellIndex {
+    locations: Vec<RealWorldLocation>,
+    /// Permutation of indices into `locations`, laid out as an implicit k-d
+    /// tree: the median of each slice is the node, the halves its subtrees.
+    tree: Vec<usize>,
+}
+
+impl CellIndex {
+    /// Build the index from cells, e.g. the result of
+    /// [`Mask::get_map_region`](crate::Mask::get_map_region) or
+    /// [`MaskMapState::get_map_state`](crate::MaskMapState::get_map_state).
+    pub fn new(cells: &[Cell]) -> Self {
+        Self::from_locations(cells.iter().map(|cell| *cell.location()))
+    }
+
+    /// Build the index from arbitrary locations, e.g. robot positions.
+    pub fn from_locations<I>(locations: I) -> Self
+    where
+        I: IntoIterator<Item = RealWorldLocation>,
+    {
+        let locations: Vec<_> = locations.into_iter().collect();
+        let mut tree: Vec<_> = (0..locations.len()).collect();
+        build(&locations, &mut tree, 0);
+
+        Self { locations, tree }
+    }
+
+    /// Number of locations in the index.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Whether the index contains no locations.
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// The location at position `index` of the input the index was built from.
+    pub fn location(&self, index: usize) -> Option<&RealWorldLocation> {
+        self.locations.get(index)
+    }
+
+    /// The closest location to `from` and its distance.
+    ///
+    /// Returns [`None`] if the index is empty.
+    pub fn nearest(&self, from: &RealWorldLocation) -> Option<(usize, f64)> {
+        self.k_nearest(from, 1).pop()
+    }
+
+    /// The `k` closest locations to `from`, closest first.
+    ///
+    /// Fewer than `k` results are returned if the index holds fewer
+    /// locations.
+    pub fn k_nearest(
+        &self,
+        from: &RealWorldLocation,
+        k: usize,
+    ) -> Vec<(usize, f64)> {
+        let mut best: Vec<(usize, f64)> = Vec::with_capacity(k + 1);
+        if k == 0 {
+            return best;
+        }
+
+        let mut limit = f64::INFINITY;
+        self.search(
+            &self.tree,
+            0,
+            from,
+            &mut limit,
+            &mut |index, d2, limit| {
+                let position = best.partition_point(|&(_, other)| other <= d2);
+                best.insert(position, (index, d2));
+                best.truncate(k);
+                if best.len() == k {
+                    *limit = best[k - 1].1;
+                }
+            },
+        );
+
+        best.into_iter().map(|(i, d2)| (i, d2.sqrt())).collect()
+    }
+
+    /// All locations within `radius` of `from` (inclusive), closest first.
+    pub fn within_radius(
+        &self,
+        from: &RealWorldLocation,
+        radius: f64,
+    ) -> Vec<(usize, f64)> {
+        let mut found = Vec::new();
+        let mut limit = radius * radius;
+        self.search(&self.tree, 0, from, &mut limit, &mut |index, d2, _| {
+            found.push((index, d2));
+        });
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        found.into_iter().map(|(i, d2)| (i, d2.sqrt())).collect()
+    }
+
+    /// Visit all locations whose squared distance to `from` is at most
+    /// `limit`, pruning subtrees which cannot contain such locations. The
+    /// visitor may shrink `limit` as it goes.
+    fn search<V>(
+        &self,
+        nodes: &[usize],
+        depth: usize,
+        from: &RealWorldLocation,
+        limit: &mut f64,
+        visit: &mut V,
+    ) where
+        V: FnMut(usize, f64, &mut f64),
+    {
+        if nodes.is_empty() {
+            return;
+        }
+        let mid = nodes.len() / 2;
+        let index = nodes[mid];
+        let location = &self.locations[index];
+
+        let d2 = (location.x - from.x).powi(2) + (location.y - from.y).powi(2);
+        if d2 <= *limit {
+            visit(index, d2, limit);
+        }
+
+        let diff = axis_value(from, depth) - axis_value(location, depth);
+        let (near, far) = if diff < 0.0 {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+        self.search(near, depth + 1, from, limit, visit);
+        if diff * diff <= *limit {
+            self.search(far, depth + 1, from, limit, visit);
+        }
+    }
+}
+
+fn axis_value(location: &RealWorldLocation, depth: usize) -> f64 {
+    if depth % 2 == 0 {
+        location.x
+    } else {
+        location.y
+    }
+}
+
+fn build(locations: &[RealWorldLocation], nodes: &mut [usize], depth: usize) {
+    if nodes.len() <= 1 {
+        return;
+    }
+    let mid = nodes.len() / 2;
+    nodes.select_nth_unstable_by(mid, |&a, &b| {
+        axis_value(&locations[a], depth)
+            .total_cmp(&axis_value(&locations[b], depth))
+    });
+    let (left, right) = nodes.split_at_mut(mid);
+    build(locations, left, depth + 1);
+    build(locations, &mut right[1..], depth + 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell_map::tests::make_map;
+    use crate::{Location, MapState, MaskMapState};
+
+    fn grid_locations() -> Vec<RealWorldLocation> {
+        // Deterministic but irregular point cloud.
+        (0..60)
+            .map(|i| {
+                let i = i as f64;
+                RealWorldLocation::from_xyz(
+                    (i * 7.3) % 11.0 - 5.0,
+                    (i * 3.7) % 9.0 - 4.0,
+                    0.0,
+                )
+            })
+            .collect()
+    }
+
+    fn brute_force(
+        locations: &[RealWorldLocation],
+        from: &RealWorldLocation,
+    ) -> Vec<f64> {
+        let mut distances: Vec<f64> = locations
+            .iter()
+            .map(|l| ((l.x - from.x).powi(2) + (l.y - from.y).powi(2)).sqrt())
+            .collect();
+        distances.sort_by(f64::total_cmp);
+        distances
+    }
+
+    #[test]
+    fn k_nearest_matches_brute_force() {
+        let locations = grid_locations();
+        let index = CellIndex::from_locations(locations.iter().cloned());
+
+        for (x, y) in [(0.0, 0.0), (-6.0, 3.0), (2.5, -1.5), (20.0, 20.0)] {
+            let from = RealWorldLocation::from_xyz(x, y, 0.0);
+            let expected = brute_force(&locations, &from);
+            let found = index.k_nearest(&from, 5);
+
+            assert_eq!(found.len(), 5);
+            for ((i, d), e) in found.iter().zip(&expected) {
+                assert_eq!(d, e);
+                let l = &locations[*i];
+                assert_eq!(((l.x - x).powi(2) + (l.y - y).powi(2)).sqrt(), *d);
+            }
+        }
+    }
+
+    #[test]
+    fn within_radius_matches_brute_force() {
+        let locations = grid_locations();
+        let index = CellIndex::from_locations(locations.iter().cloned());
+        let from = RealWorldLocation::from_xyz(1.0, 0.5, 0.0);
+
+        let expected: Vec<f64> = brute_force(&locations, &from)
+            .into_iter()
+            .filter(|d| *d <= 3.0)
+            .collect();
+        let found: Vec<f64> = index
+            .within_radius(&from, 3.0)
+            .into_iter()
+            .map(|(_, d)| d)
+            .collect();
+
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn build_from_cells() {
+        let (mut map, _) = make_map();
+        map.set_location(
+            &RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+            MapState::Frontier,
+        )
+        .unwrap();
+        let cells = map.get_map_state(MapState::Frontier);
+        let index = CellIndex::new(&cells);
+
+        assert_eq!(index.len(), cells.len());
+        let (i, d) = index.nearest(cells[0].location()).unwrap();
+        assert_eq!(d, 0.0);
+        assert_eq!(index.location(i), Some(cells[i].location()));
+    }
+
+    #[test]
+    fn empty_index() {
+        let index = CellIndex::from_locations(Vec::new());
+        let from = RealWorldLocation::from_xyz(0.0, 0.0, 0.0);
+
+        assert!(index.is_empty());
+        assert!(index.nearest(&from).is_none());
+        assert!(index.k_nearest(&from, 3).is_empty());
+        assert!(index.within_radius(&from, 10.0).is_empty());
+    }
+
+    #[test]
+    fn k_larger_than_len() {
+        let index =
+            CellIndex::from_locations(grid_locations().into_iter().take(3));
+
+        assert_eq!(
+            index
+                .k_nearest(&RealWorldLocation::from_xyz(0.0, 0.0, 0.0), 10)
+                .len(),
+            3
+        );
+        assert!(index
+            .k_nearest(&RealWorldLocation::from_xyz(0.0, 0.0, 0.0), 0)
+            .is_empty());
+    }
+}
@@ -1,9 +1,16 @@
 use crate::{
     coords::InternalLocation, AxisResolution, Coords, Location, LocationError,
-    LocationType, MapStateMatrix, Mask, RealWorldLocation, Visualize,
+    LocationType, MapState, MapStateMatrix, Mask, OutOfMapError,
+    RealWorldLocation, Timestamp,
 };
-use num::cast::ToPrimitive;
-
+#[cfg(feature = "std")]
+use crate::{ColorMap, Visualize};
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+use ndarray::{Array2, ArrayView2, ArrayViewMut2, Zip};
+use num::{cast::ToPrimitive, Float};
+
+#[cfg(feature = "std")]
 use image::{ImageBuffer, RgbImage};
 
 /// Describe a map using a 2D grid of cells.
@@ -69,7 +76,7 @@ use image::{ImageBuffer, RgbImage};
 /// assert_eq!(map.width(), 1);
 /// assert_eq!(map.height(), 3);
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct CellMap {
     /// A matrix representing the cells along with their states.
     cells: MapStateMatrix,
@@ -81,37 +88,184 @@ pub struct CellMap {
     /// corner to `Coords { x: 0.0, y: 0.0, z: 0.0 }`. Even positive
     /// coordinates will be shifted as a matter of consistency.
     offset: Coords,
+    /// The [`Timestamp`] at which each cell was last written to. It has the
+    /// same shape as `cells`.
+    last_update: Array2<Timestamp>,
+    /// The current time of the map, used to stamp cells upon modification.
+    /// See [`CellMap::set_clock`].
+    clock: Timestamp,
+    /// Indices of the cells changed since the last call to
+    /// [`CellMap::clear_dirty`], in the order they were first changed.
+    dirty: Vec<[usize; 2]>,
+    /// Marks the cells listed in `dirty`, to avoid listing them twice. It has
+    /// the same shape as `cells`.
+    dirty_mask: Array2<bool>,
 }
 
 impl CellMap {
     /// Create a new [`CellMap`]. It takes 2 [`Coords`] indicating the square
     /// bounding box area. The resolution affects how many pixels/cells per
     /// meter will be generated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bounds or the resolution contain NaN or infinite values,
+    /// if the resolution is negative, or if the map would be too large to be
+    /// allocated, i.e. whenever [`CellMap::try_new`] fails for a reason other
+    /// than [`CellMapError::Empty`] or a zero resolution. Use
+    /// [`CellMap::try_new`] when the input is not trusted, e.g. when it was
+    /// received from another robot.
     pub fn new(
         point1: RealWorldLocation,
         point2: RealWorldLocation,
         resolution: AxisResolution,
     ) -> Self {
-        let columns = point1.distance_x(&point2) * resolution.x;
-        let rows = point1.distance_y(&point2) * resolution.y;
+        Self::try_with_bounds(point1, point2, resolution).unwrap_or_else(
+            |error| panic!("Invalid map bounds or resolution: {error:?}"),
+        )
+    }
+
+    /// Fallible version of [`CellMap::new`].
+    ///
+    /// # Errors
+    ///
+    /// - [`CellMapError::InvalidResolution`] if the `x` or `y` resolution is
+    ///   zero, negative, NaN or infinite.
+    /// - [`CellMapError::InvalidBounds`] if a corner has a NaN or infinite
+    ///   component.
+    /// - [`CellMapError::Empty`] if the map would not contain any cell, e.g.
+    ///   because both corners share the same `x` or `y` value.
+    /// - [`CellMapError::TooLarge`] if the number of cells cannot be
+    ///   allocated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, CellMapError, RealWorldLocation,
+    /// };
+    ///
+    /// let map = CellMap::try_new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+    ///     AxisResolution::uniform(2.0),
+    /// )
+    /// .unwrap();
+    /// assert_eq!((map.width(), map.height()), (4, 2));
+    ///
+    /// assert_eq!(
+    ///     CellMap::try_new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+    ///         AxisResolution::new(2.0, f64::NAN, 1.0),
+    ///     )
+    ///     .unwrap_err(),
+    ///     CellMapError::InvalidResolution
+    /// );
+    /// ```
+    pub fn try_new(
+        point1: RealWorldLocation,
+        point2: RealWorldLocation,
+        resolution: AxisResolution,
+    ) -> Result<Self, CellMapError> {
+        if resolution.x == 0.0 || resolution.y == 0.0 {
+            return Err(CellMapError::InvalidResolution);
+        }
+        let map = Self::try_with_bounds(point1, point2, resolution)?;
+        if map.cells.is_empty() {
+            return Err(CellMapError::Empty);
+        }
+        Ok(map)
+    }
+
+    /// Like [`CellMap::try_new`], but allows empty maps, as
+    /// [`CellMap::new`] does.
+    fn try_with_bounds(
+        point1: RealWorldLocation,
+        point2: RealWorldLocation,
+        resolution: AxisResolution,
+    ) -> Result<Self, CellMapError> {
+        let [rows, columns] = Self::dimensions(&point1, &point2, resolution)?;
+        Ok(Self::with_dimensions(
+            [rows, columns],
+            &point1,
+            &point2,
+            resolution,
+        ))
+    }
+
+    /// Number of `[rows, columns]` of a map spanning the given bounds.
+    ///
+    /// Zero is a valid resolution here, resulting in an empty map, which only
+    /// [`CellMap::try_new`] rejects.
+    fn dimensions(
+        point1: &RealWorldLocation,
+        point2: &RealWorldLocation,
+        resolution: AxisResolution,
+    ) -> Result<[usize; 2], CellMapError> {
+        let valid = |value: f64| value.is_finite() && value >= 0.0;
+        if !valid(resolution.x) || !valid(resolution.y) {
+            return Err(CellMapError::InvalidResolution);
+        }
+        if [point1, point2].iter().any(|point| {
+            !(point.x.is_finite() && point.y.is_finite() && point.z.is_finite())
+        }) {
+            return Err(CellMapError::InvalidBounds);
+        }
 
+        let to_cells = |cells: f64| {
+            cells
+                .to_usize()
+                .filter(|cells| *cells <= isize::MAX as usize)
+                .ok_or(CellMapError::TooLarge)
+        };
+        let columns = to_cells(point1.distance_x(point2) * resolution.x)?;
+        let rows = to_cells(point1.distance_y(point2) * resolution.y)?;
+        Self::check_size(rows, columns)?;
+
+        Ok([rows, columns])
+    }
+
+    /// The largest number of cells a map may contain, i.e. `2^28` such as
+    /// `16384` by `16384` cells.
+    ///
+    /// Maps built from untrusted input, e.g. decoded from bytes received
+    /// from another robot, are checked against this limit, such that a
+    /// corrupt size cannot exhaust the memory. See [`CellMapError::TooLarge`].
+    pub const MAX_CELLS: usize = 1 << 28;
+
+    /// Internal helper checking that a map of the given size does not exceed
+    /// [`CellMap::MAX_CELLS`].
+    pub(crate) fn check_size(
+        rows: usize,
+        columns: usize,
+    ) -> Result<(), CellMapError> {
+        match rows.checked_mul(columns) {
+            Some(cells) if cells <= Self::MAX_CELLS => Ok(()),
+            _ => Err(CellMapError::TooLarge),
+        }
+    }
+
+    fn with_dimensions(
+        [rows, columns]: [usize; 2],
+        point1: &RealWorldLocation,
+        point2: &RealWorldLocation,
+        resolution: AxisResolution,
+    ) -> Self {
         let offset = Coords {
             x: point1.x.min(point2.x),
             y: point1.y.min(point2.y),
             z: point1.z.min(point2.z),
         };
 
-        Self {
-            cells: MapStateMatrix::from_elem(
-                (
-                    rows.to_usize().expect("No conversion issues"),
-                    columns.to_usize().expect("No conversion issues"),
-                ),
+        Self::from_raster(
+            MapStateMatrix::from_elem(
+                (rows, columns),
                 LocationType::Unexplored,
             ),
             resolution,
             offset,
-        }
+        )
     }
 
     /// Manually create a [`CellMap`] based off an existing matrix.
@@ -119,18 +273,82 @@ impl CellMap {
     /// Note that the values passed on to this function will be taken *as-is*.
     /// This means that there are no checks to ensure the `resolution` and
     /// `offset` were correctly specified.
+    ///
+    /// All cells are considered to have been last updated at time `0.0`, which
+    /// is also the initial value of the map's clock.
     pub fn from_raster(
         cells: MapStateMatrix,
         resolution: AxisResolution,
         offset: Coords,
     ) -> Self {
         Self {
+            last_update: Array2::zeros(cells.raw_dim()),
+            dirty: Vec::new(),
+            dirty_mask: Array2::from_elem(cells.raw_dim(), false),
             cells,
             resolution,
             offset,
+            clock: 0.0,
         }
     }
 
+    /// Same as [`CellMap::from_raster`], copying the cells from a view, e.g.
+    /// a window of a larger matrix.
+    ///
+    /// Use [`CellMap::from_raster`] instead when the matrix is owned, which
+    /// takes it over without copying.
+    pub fn from_array_view(
+        cells: ArrayView2<'_, LocationType>,
+        resolution: AxisResolution,
+        offset: Coords,
+    ) -> Self {
+        Self::from_raster(cells.to_owned(), resolution, offset)
+    }
+
+    /// Create a [`CellMap`] of `[rows, columns]` cells, computing the state
+    /// of each cell from its `[row, col]` index.
+    ///
+    /// As with [`CellMap::from_raster`], the `resolution` and `offset` are
+    /// taken as-is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, CellMap, Coords, MapState};
+    ///
+    /// // A border of obstacles around unexplored cells.
+    /// let map = CellMap::from_shape_fn(
+    ///     [4, 5],
+    ///     AxisResolution::uniform(1.0),
+    ///     Coords::new(0.0, 0.0, 0.0),
+    ///     |[row, col]| {
+    ///         if row % 3 == 0 || col % 4 == 0 {
+    ///             MapState::Obstacle
+    ///         } else {
+    ///             MapState::Unexplored
+    ///         }
+    ///     },
+    /// );
+    ///
+    /// assert_eq!((map.width(), map.height()), (5, 4));
+    /// assert_eq!(map.cells()[[1, 1]], MapState::Unexplored);
+    /// assert_eq!(map.cells()[[3, 2]], MapState::Obstacle);
+    /// ```
+    pub fn from_shape_fn(
+        [rows, columns]: [usize; 2],
+        resolution: AxisResolution,
+        offset: Coords,
+        mut f: impl FnMut([usize; 2]) -> LocationType,
+    ) -> Self {
+        Self::from_raster(
+            MapStateMatrix::from_shape_fn((rows, columns), |(row, col)| {
+                f([row, col])
+            }),
+            resolution,
+            offset,
+        )
+    }
+
     /// Convert a floating point location into its corresponding
     /// [`MapStateMatrix`] cell index.
     ///
@@ -140,7 +358,9 @@ impl CellMap {
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
+    /// This function will return [`LocationError::OutOfMap`] if the location is
+    /// outside the map, including when it contains NaN or infinite values. The
+    /// `z` component is only checked against the bottom of the map.
     ///
     /// # More details on what it does
     ///
@@ -155,32 +375,555 @@ impl CellMap {
     /// end up with smaller rectangular subdivisions. Converting the real-world
     /// floating point location then consists in identifying which such
     /// rectangle the location in question corresponds to.
-    pub fn location_to_map_index(
+    ///
+    /// The conversion is carried out in the scalar type of the location, so
+    /// [`f32`] locations do not require any [`f64`] arithmetic; only the map's
+    /// offset and resolution are converted.
+    pub fn location_to_map_index<S: Float>(
         &self,
-        location: &RealWorldLocation,
+        location: &RealWorldLocation<S>,
     ) -> Result<[usize; 2], LocationError> {
-        let coord: InternalLocation = match location
-            .clone()
-            .into_internal(self.offset, self.resolution)
-        {
-            Ok(c) => c,
-            Err((location_error, _)) => return Err(location_error),
+        let convert = |coords: Coords| {
+            let scalar =
+                |value: f64| S::from(value).expect("f64 to float should work");
+            Coords::new(scalar(coords.x), scalar(coords.y), scalar(coords.z))
         };
+        let internal = (**location - convert(self.offset))
+            * convert(Coords::from(self.resolution));
+        // Written such that NaN and values beyond `usize::MAX` are rejected.
+        let to_index = |value: S, len: usize| {
+            Some(value)
+                .filter(|value| *value >= S::zero())
+                .and_then(|value| value.floor().to_usize())
+                .filter(|index| *index < len)
+        };
+        let col = to_index(internal.x, self.width());
+        let row = to_index(internal.y, self.height());
+        let below = internal.z.is_nan() || internal.z < S::zero();
+
+        match (row, col) {
+            (Some(row), Some(col)) if !below => Ok([row, col]),
+            _ => {
+                let scalar = |value: S| {
+                    value.to_f64().expect("float to f64 should work")
+                };
+                Err(LocationError::from(OutOfMapError::new(
+                    RealWorldLocation::from_xyz(
+                        scalar(location.x()),
+                        scalar(location.y()),
+                        scalar(location.z()),
+                    ),
+                    Some(self.bounds()),
+                    [col.is_none(), row.is_none(), below],
+                )))
+            }
+        }
+    }
 
-        let col: usize =
-            coord.x().floor().to_usize().expect(
-                "An overflow likely occured when converting f64 to usize",
-            );
-        let row: usize =
-            coord.y().floor().to_usize().expect(
-                "An overflow likely occured when converting f64 to usize",
-            );
-
-        if col >= self.width() || row >= self.height() {
-            return Err(LocationError::OutOfMap);
+    /// The bottom left and top right corners of the map.
+    ///
+    /// As the map is two-dimensional, both corners share the `z` value of the
+    /// map's offset. Locations above it are accepted, see
+    /// [`CellMap::location_to_map_index`].
+    pub fn bounds(&self) -> [RealWorldLocation; 2] {
+        let extent = |cells: usize, resolution: f64| {
+            cells.to_f64().expect("usize to f64 should work") / resolution
         };
+        [
+            RealWorldLocation::new(self.offset),
+            RealWorldLocation::from_xyz(
+                self.offset.x + extent(self.width(), self.resolution.x),
+                self.offset.y + extent(self.height(), self.resolution.y),
+                self.offset.z,
+            ),
+        ]
+    }
+
+    /// Advance the map's clock to the given time.
+    ///
+    /// Every cell modified through [`Location::set_location`] is stamped with
+    /// the current clock value. This allows [`CellMap::decay`] to determine how
+    /// long ago a cell was last updated.
+    ///
+    /// The map has no notion of wall-clock time; it is up to the caller to
+    /// decide what the [`Timestamp`] represents (e.g. seconds since the start
+    /// of the mission) and to keep it consistent across calls.
+    pub fn set_clock(&mut self, now: Timestamp) {
+        self.clock = now;
+    }
+
+    /// Retrieve the time at which the given location was last updated.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`CellMap::location_to_map_index`].
+    pub fn last_update(
+        &self,
+        location: &RealWorldLocation,
+    ) -> Result<Timestamp, LocationError> {
+        let index = self.location_to_map_index(location)?;
+        Ok(self.last_update[index])
+    }
+
+    /// Revert [`MapState::Explored`] cells back to [`MapState::Unexplored`] if
+    /// they have not been updated for longer than `age_threshold`.
+    ///
+    /// The age of a cell is computed relative to the map's clock (see
+    /// [`CellMap::set_clock`]). Reverted cells are stamped with the current
+    /// clock value. Returns the number of cells which were reverted.
+    ///
+    /// This is useful in dynamic environments, where areas which were explored
+    /// a long time ago should be re-visited eventually.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let old = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+    /// let recent = RealWorldLocation::from_xyz(1.5, 0.5, 0.0);
+    ///
+    /// map.set_location(&old, MapState::Explored).unwrap();
+    /// map.set_clock(10.0);
+    /// map.set_location(&recent, MapState::Explored).unwrap();
+    /// map.set_clock(15.0);
+    ///
+    /// assert_eq!(map.decay(8.0), 1);
+    /// assert_eq!(map.get_location(&old), Ok(MapState::Unexplored));
+    /// assert_eq!(map.get_location(&recent), Ok(MapState::Explored));
+    /// ```
+    pub fn decay(&mut self, age_threshold: Timestamp) -> usize {
+        let now = self.clock;
+        let mut reverted = 0;
+        let dirty = &mut self.dirty;
+        Zip::indexed(&mut self.cells)
+            .and(&mut self.last_update)
+            .and(&mut self.dirty_mask)
+            .for_each(|(row, col), cell, stamp, is_dirty| {
+                if *cell == MapState::Explored && now - *stamp > age_threshold {
+                    *cell = MapState::Unexplored;
+                    *stamp = now;
+                    reverted += 1;
+                    if !*is_dirty {
+                        *is_dirty = true;
+                        dirty.push([row, col]);
+                    }
+                }
+            });
+        reverted
+    }
+
+    /// Iterate over all cells of the map in row-major order.
+    ///
+    /// Each [`Cell`] holds the real-world location of the cell (its bottom
+    /// left corner) along with its state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(-1.0, -1.0, 0.0),
+    ///     RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let locations: Vec<(f64, f64)> =
+    ///     map.iter_cells().map(|cell| (*cell.x(), *cell.y())).collect();
+    ///
+    /// assert_eq!(
+    ///     locations,
+    ///     vec![(-1.0, -1.0), (0.0, -1.0), (-1.0, 0.0), (0.0, 0.0)]
+    /// );
+    /// assert!(map
+    ///     .iter_cells()
+    ///     .all(|cell| cell.value() == &MapState::Unexplored));
+    /// ```
+    pub fn iter_cells(&self) -> impl Iterator<Item = Cell<'_>> + '_ {
+        self.cells.indexed_iter().map(|((row, col), value)| {
+            Cell::new(
+                index_to_internal([row, col], self.offset, self.resolution),
+                value,
+            )
+        })
+    }
+
+    /// Iterate mutably over all cells of the map in row-major order.
+    ///
+    /// Yields the real-world location of each cell (its bottom left corner)
+    /// along with a mutable reference to its state.
+    ///
+    /// Note that cells modified this way are **not** stamped with the map's
+    /// clock (see [`CellMap::set_clock`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, MaskMapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// for (location, state) in map.iter_cells_mut() {
+    ///     if location.x() < 2.0 {
+    ///         *state = MapState::Explored;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(map.get_map_state(MapState::Explored).len(), 8);
+    /// ```
+    pub fn iter_cells_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (RealWorldLocation, &mut LocationType)> + '_ {
+        let (offset, resolution) = (self.offset, self.resolution);
+        self.cells
+            .indexed_iter_mut()
+            .map(move |((row, col), value)| {
+                (
+                    index_to_internal([row, col], offset, resolution)
+                        .into_real_world(),
+                    value,
+                )
+            })
+    }
+
+    /// Same as [`CellMap::iter_cells`], but also yields the `[row, col]` index
+    /// of each cell as used by [`CellMap::location_to_map_index`].
+    pub fn indexed_iter_locations(
+        &self,
+    ) -> impl Iterator<Item = ([usize; 2], RealWorldLocation, &LocationType)> + '_
+    {
+        self.cells.indexed_iter().map(|((row, col), value)| {
+            (
+                [row, col],
+                index_to_internal([row, col], self.offset, self.resolution)
+                    .into_real_world(),
+                value,
+            )
+        })
+    }
+
+    /// Retrieve a reference to the state at the given location, or [`None`] if
+    /// the location is outside the map.
+    ///
+    /// This is the non-panicking counterpart of indexing the map with a
+    /// [`RealWorldLocation`].
+    pub fn get(&self, location: &RealWorldLocation) -> Option<&LocationType> {
+        let index = self.location_to_map_index(location).ok()?;
+        Some(&self.cells[index])
+    }
 
-        Ok([row, col])
+    /// Retrieve a mutable reference to the state at the given location, or
+    /// [`None`] if the location is outside the map.
+    ///
+    /// Note that, unlike [`Location::set_location`], modifying the state this
+    /// way does **not** stamp the cell with the map's clock.
+    pub fn get_mut(
+        &mut self,
+        location: &RealWorldLocation,
+    ) -> Option<&mut LocationType> {
+        let index = self.location_to_map_index(location).ok()?;
+        Some(&mut self.cells[index])
+    }
+
+    /// Retrieve a mutable reference to the state at the given location.
+    ///
+    /// Unlike [`CellMap::get_mut`], the cell is stamped with the map's clock,
+    /// as it may be modified through the returned reference. To only update
+    /// the cell depending on its current state, see [`CellMap::entry`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`CellMap::location_to_map_index`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, CellMap, MapState, RealWorldLocation};
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_clock(3.0);
+    /// let location = RealWorldLocation::from_xyz(1.5, 0.5, 0.0);
+    ///
+    /// let state = map.get_location_mut(&location).unwrap();
+    /// if *state == MapState::Unexplored {
+    ///     *state = MapState::Explored;
+    /// }
+    ///
+    /// assert_eq!(map[&location], MapState::Explored);
+    /// assert_eq!(map.last_update(&location), Ok(3.0));
+    /// ```
+    pub fn get_location_mut(
+        &mut self,
+        location: &RealWorldLocation,
+    ) -> Result<&mut LocationType, LocationError> {
+        let index = self.location_to_map_index(location)?;
+        Ok(self.get_index_mut(index))
+    }
+
+    /// Set all given locations to the same state in a single pass.
+    ///
+    /// Locations which cannot be set do not interrupt the process; all valid
+    /// locations will be updated regardless.
+    ///
+    /// # Errors
+    ///
+    /// If any location could not be set, all failures are returned at once,
+    /// each consisting of the [`LocationError`] and the offending location.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, LocationError, MapState, MaskMapState,
+    ///     RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let locations = vec![
+    ///     RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+    ///     RealWorldLocation::from_xyz(9.0, 0.5, 0.0),
+    ///     RealWorldLocation::from_xyz(1.5, 0.5, 0.0),
+    /// ];
+    ///
+    /// let errors = map
+    ///     .set_locations(&locations, MapState::Explored)
+    ///     .unwrap_err();
+    /// assert_eq!(errors.len(), 1);
+    /// assert!(matches!(errors[0].0, LocationError::OutOfMap(_)));
+    /// assert_eq!(errors[0].1, RealWorldLocation::from_xyz(9.0, 0.5, 0.0));
+    /// assert_eq!(map.get_map_state(MapState::Explored).len(), 2);
+    /// ```
+    pub fn set_locations<'a>(
+        &mut self,
+        locations: impl IntoIterator<Item = &'a RealWorldLocation>,
+        value: LocationType,
+    ) -> Result<(), Vec<(LocationError, RealWorldLocation)>> {
+        self.set_location_states(
+            locations.into_iter().map(|location| (location, value)),
+        )
+    }
+
+    /// Same as [`CellMap::set_locations`], but each location is given along
+    /// with its own state.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`CellMap::set_locations`].
+    pub fn set_location_states<'a>(
+        &mut self,
+        states: impl IntoIterator<Item = (&'a RealWorldLocation, LocationType)>,
+    ) -> Result<(), Vec<(LocationError, RealWorldLocation)>> {
+        let errors: Vec<(LocationError, RealWorldLocation)> = states
+            .into_iter()
+            .filter_map(|(location, value)| {
+                match self.location_to_map_index(location) {
+                    Ok(index) => {
+                        self.set_cell(index, value);
+                        None
+                    }
+                    Err(e) => Some((e, *location)),
+                }
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Internal helper to set a cell by its matrix index.
+    ///
+    /// Takes care of stamping the cell with the map's clock, just like
+    /// [`Location::set_location`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub(crate) fn set_cell(&mut self, index: [usize; 2], value: LocationType) {
+        if self.cells[index] != value {
+            self.mark_dirty(index);
+        }
+        self.cells[index] = value;
+        self.last_update[index] = self.clock;
+    }
+
+    /// Internal helper to set a cell by its matrix index, stamping it with
+    /// the given time rather than the map's clock, e.g. when taking over the
+    /// state of another map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub(crate) fn set_cell_stamped(
+        &mut self,
+        index: [usize; 2],
+        value: LocationType,
+        stamp: Timestamp,
+    ) {
+        if self.cells[index] != value {
+            self.mark_dirty(index);
+        }
+        self.cells[index] = value;
+        self.last_update[index] = stamp;
+    }
+
+    /// Internal helper returning the time a cell was last updated, by its
+    /// matrix index.
+    pub(crate) fn index_last_update(&self, index: [usize; 2]) -> Timestamp {
+        self.last_update[index]
+    }
+
+    /// Mutable access to a cell, which is stamped with the map's clock and
+    /// marked as dirty.
+    pub(crate) fn get_index_mut(
+        &mut self,
+        index: [usize; 2],
+    ) -> &mut LocationType {
+        self.mark_dirty(index);
+        self.last_update[index] = self.clock;
+        &mut self.cells[index]
+    }
+
+    fn mark_dirty(&mut self, index: [usize; 2]) {
+        if !self.dirty_mask[index] {
+            self.dirty_mask[index] = true;
+            self.dirty.push(index);
+        }
+    }
+
+    /// The `[row, col]` indices of the cells changed since the last call to
+    /// [`CellMap::clear_dirty`] (or since the map was created), in the order
+    /// they were first changed.
+    ///
+    /// This allows processing only the changed part of the map, e.g. when
+    /// recomputing frontiers or redrawing an image. Changes are tracked the
+    /// same way as the map's clock: writing a cell through the [`Location`]
+    /// trait, [`CellMap::get_location_mut`], [`CellMap::entry`] or
+    /// [`CellMap::decay`] or mutable indexing marks it, while
+    /// [`CellMap::get_mut`], [`CellMap::iter_cells_mut`] and
+    /// [`CellMap::cells_view_mut`] do not. Writing the state a cell already has
+    /// does not mark it, except through mutable references.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let locations = [(1.5, 0.5), (3.5, 2.5), (0.5, 1.5)]
+    ///     .map(|(x, y)| RealWorldLocation::from_xyz(x, y, 0.0));
+    /// map.set_locations(&locations, MapState::Explored).unwrap();
+    ///
+    /// assert_eq!(map.dirty_cells(), [[0, 1], [2, 3], [1, 0]]);
+    /// assert_eq!(map.dirty_bounds(), Some([[0, 0], [2, 3]]));
+    ///
+    /// map.clear_dirty();
+    /// map.set_location(&locations[0], MapState::Explored).unwrap();
+    /// assert!(map.dirty_cells().is_empty());
+    /// ```
+    pub fn dirty_cells(&self) -> &[[usize; 2]] {
+        &self.dirty
+    }
+
+    /// The smallest and largest `[row, col]` indices (both inclusive) of the
+    /// cells listed by [`CellMap::dirty_cells`], or [`None`] if no cell
+    /// changed.
+    pub fn dirty_bounds(&self) -> Option<[[usize; 2]; 2]> {
+        let (first, rest) = self.dirty.split_first()?;
+        Some(
+            rest.iter()
+                .fold([*first, *first], |[min, max], [row, col]| {
+                    [
+                        [min[0].min(*row), min[1].min(*col)],
+                        [max[0].max(*row), max[1].max(*col)],
+                    ]
+                }),
+        )
+    }
+
+    /// Forget about the changes listed by [`CellMap::dirty_cells`], e.g. once
+    /// they were processed.
+    pub fn clear_dirty(&mut self) {
+        for index in self.dirty.drain(..) {
+            self.dirty_mask[index] = false;
+        }
+    }
+
+    /// A view of the cells, indexed like [`CellMap::cells`], e.g. to run
+    /// custom [`ndarray`] operations such as slicing or windowing without
+    /// copying the matrix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_location(
+    ///     &RealWorldLocation::from_xyz(1.5, 1.5, 0.0),
+    ///     MapState::Obstacle,
+    /// )
+    /// .unwrap();
+    ///
+    /// // Number of obstacles in each 3x3 window.
+    /// let counts: Vec<usize> = map
+    ///     .cells_view()
+    ///     .windows((3, 3))
+    ///     .into_iter()
+    ///     .map(|window| {
+    ///         window.iter().filter(|s| **s == MapState::Obstacle).count()
+    ///     })
+    ///     .collect();
+    ///
+    /// assert_eq!(counts, [1, 1, 1, 1]);
+    /// ```
+    pub fn cells_view(&self) -> ArrayView2<'_, LocationType> {
+        self.cells.view()
+    }
+
+    /// A mutable view of the cells, see [`CellMap::cells_view`].
+    ///
+    /// Note that, like [`CellMap::iter_cells_mut`], cells modified this way
+    /// are **not** stamped with the map's clock (see [`CellMap::set_clock`])
+    /// nor marked as dirty (see [`CellMap::dirty_cells`]).
+    pub fn cells_view_mut(&mut self) -> ArrayViewMut2<'_, LocationType> {
+        self.cells.view_mut()
     }
 
     pub fn resolution(&self) -> &AxisResolution {
@@ -192,6 +935,9 @@ impl CellMap {
     pub fn cells(&self) -> &MapStateMatrix {
         &self.cells
     }
+    pub fn clock(&self) -> Timestamp {
+        self.clock
+    }
     pub fn ncols(&self) -> usize {
         self.cells().ncols()
     }
@@ -204,52 +950,170 @@ impl CellMap {
     pub fn height(&self) -> usize {
         self.nrows()
     }
+
+    /// Fallible version of [`Visualize::as_image`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CellMapError::TooLarge`] if the map is wider or higher than
+    /// [`u32::MAX`] cells, which is the largest image size supported.
+    #[cfg(feature = "std")]
+    pub fn try_as_image(&self) -> Result<RgbImage, CellMapError> {
+        self.try_as_image_with(&ColorMap::DEFAULT)
+    }
+
+    /// Same as [`Visualize::as_image`], but with the colors of `colors`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map is wider or higher than [`u32::MAX`] cells. See
+    /// [`CellMap::try_as_image_with`] for a non-panicking alternative.
+    #[cfg(feature = "std")]
+    pub fn as_image_with(&self, colors: &ColorMap) -> RgbImage {
+        self.try_as_image_with(colors)
+            .expect("Map should fit into an image")
+    }
+
+    /// Fallible version of [`CellMap::as_image_with`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CellMapError::TooLarge`] if the map is wider or higher than
+    /// [`u32::MAX`] cells, which is the largest image size supported.
+    #[cfg(feature = "std")]
+    pub fn try_as_image_with(
+        &self,
+        colors: &ColorMap,
+    ) -> Result<RgbImage, CellMapError> {
+        let width = self.width().to_u32().ok_or(CellMapError::TooLarge)?;
+        let height = self.height().to_u32().ok_or(CellMapError::TooLarge)?;
+        Ok(ImageBuffer::from_fn(
+            width,
+            height,
+            |x, y| -> image::Rgb<_> {
+                // Both are bounded by the map's dimensions, so they fit a usize.
+                let cell: LocationType = self.cells[[y as usize, x as usize]];
+                colors.color(cell)
+            },
+        ))
+    }
 }
 
+#[cfg(feature = "std")]
 impl Visualize for CellMap {
     type ImageType = RgbImage;
 
+    /// # Panics
+    ///
+    /// Panics if the map is wider or higher than [`u32::MAX`] cells. See
+    /// [`CellMap::try_as_image`] for a non-panicking alternative.
     fn as_image(&self) -> Self::ImageType {
-        ImageBuffer::from_fn(
-            self.width().to_u32().expect("No conversion issues"),
-            self.height().to_u32().expect("No conversion issues"),
-            |x, y| -> image::Rgb<_> {
-                let row = y.to_usize().expect("No conversion issues");
-                let col = x.to_usize().expect("No conversion issues");
-                let cell: LocationType = self.cells[[row, col]];
-                cell.to_rgb()
-            },
-        )
+        self.try_as_image().expect("Map should fit into an image")
+    }
+
+    #[cfg(feature = "inspector")]
+    fn visualize(&self) {
+        use std::sync::{Arc, RwLock};
+
+        crate::inspector::MapInspector::new(Arc::new(RwLock::new(
+            self.clone(),
+        )))
+        .run("Map")
+        .expect("Map inspector window should open");
     }
 }
 
 impl Mask for CellMap {
     fn get_map_region(
         &self,
-        filter: impl Fn(LocationType) -> bool,
-    ) -> Vec<Cell> {
-        self.cells
-            .indexed_iter()
-            .filter(|((_, _), e)| filter(**e))
-            .map(|((row, col), e)| {
-                Cell::new(
-                    InternalLocation::new(
-                        Coords::new(
-                            col.to_f64().expect("usize to f64 should work"),
-                            row.to_f64().expect("usize to f64 should work"),
-                            0.0,
-                        ),
-                        *self.offset(),
-                        *self.resolution(),
-                    )
-                    .expect("indexed_iter() will not return negative indexes"),
-                    e,
-                )
-            })
+        filter: &dyn Fn(LocationType) -> bool,
+    ) -> Vec<Cell<'_>> {
+        self.iter_cells()
+            .filter(|cell| filter(*cell.value()))
             .collect()
     }
 }
 
+/// Internal helper to convert a matrix index into an [`InternalLocation`]
+/// describing the cell's bottom left corner.
+///
+/// It is a free function rather than a method so that it can be used while
+/// the cells of the map are mutably borrowed.
+pub(crate) fn index_to_internal(
+    [row, col]: [usize; 2],
+    offset: Coords,
+    resolution: AxisResolution,
+) -> InternalLocation {
+    InternalLocation::new(
+        Coords::new(
+            col.to_f64().expect("usize to f64 should work"),
+            row.to_f64().expect("usize to f64 should work"),
+            0.0,
+        ),
+        offset,
+        resolution,
+    )
+    .expect("Matrix indexes are never negative")
+}
+
+/// Maps are equal if their offset, resolution and cells match. The
+/// timestamps, the clock and the dirty cells are ignored, such that e.g. a
+/// map decoded using [`CellMap::from_bytes`] equals the encoded one.
+impl PartialEq for CellMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset
+            && self.resolution == other.resolution
+            && self.cells == other.cells
+    }
+}
+
+/// Access the state at a given location.
+///
+/// # Panics
+///
+/// Panics if the location is outside the map. See [`CellMap::get`] for a
+/// non-panicking alternative.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{AxisResolution, CellMap, MapState, RealWorldLocation};
+///
+/// let mut map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+/// let location = RealWorldLocation::from_xyz(1.5, 0.5, 0.0);
+///
+/// map[&location] = MapState::Explored;
+/// assert_eq!(map[&location], MapState::Explored);
+/// ```
+impl Index<&RealWorldLocation> for CellMap {
+    type Output = LocationType;
+
+    fn index(&self, location: &RealWorldLocation) -> &Self::Output {
+        self.get(location)
+            .expect("Location should be inside the map")
+    }
+}
+
+/// Mutably access the state at a given location.
+///
+/// Like [`CellMap::get_location_mut`], the cell is stamped with the map's clock
+/// and marked as dirty, as it may be modified through the returned reference.
+///
+/// # Panics
+///
+/// Panics if the location is outside the map. See
+/// [`CellMap::get_location_mut`] for a non-panicking alternative.
+impl IndexMut<&RealWorldLocation> for CellMap {
+    fn index_mut(&mut self, location: &RealWorldLocation) -> &mut Self::Output {
+        self.get_location_mut(location)
+            .expect("Location should be inside the map")
+    }
+}
+
 impl Location for CellMap {
     fn get_location(
         &self,
@@ -265,9 +1129,45 @@ impl Location for CellMap {
         value: LocationType,
     ) -> Result<(), crate::LocationError> {
         let index = self.location_to_map_index(coord)?;
-        self.cells[index] = value;
+        self.set_cell(index, value);
         Ok(())
     }
+
+    fn locations_within(
+        &self,
+        center: &RealWorldLocation,
+        radius: f64,
+    ) -> Vec<RealWorldLocation> {
+        self.disc_indices(center, radius)
+            .map(|index| {
+                let mut location = self.index_center(index);
+                location.z = center.z();
+                RealWorldLocation::new(location)
+            })
+            .collect()
+    }
+
+    /// Whether `a` and `b` map to the same index. Locations outside the map
+    /// are never in the same cell.
+    fn same_cell(&self, a: &RealWorldLocation, b: &RealWorldLocation) -> bool {
+        match (self.location_to_map_index(a), self.location_to_map_index(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CellMapError {
+    /// The resolution is zero, negative, NaN or infinite.
+    InvalidResolution,
+    /// The bounds of the map contain NaN or infinite values.
+    InvalidBounds,
+    /// The map would not contain any cells.
+    Empty,
+    /// The map would contain more than [`CellMap::MAX_CELLS`] cells, or too
+    /// many to be rendered as an image.
+    TooLarge,
 }
 
 #[derive(Debug, PartialEq)]
@@ -327,6 +1227,8 @@ impl<'a> Cell<'a> {
         ))
     }
 
+    /// The bottom left corner of the cell. See [`CellMap::cell_center`] for
+    /// its center.
     pub fn location(&self) -> &RealWorldLocation {
         &self.location
     }
@@ -341,6 +1243,66 @@ impl<'a> Cell<'a> {
     }
 }
 
+/// A cell holding a copy of its state, such that it does not borrow the map.
+///
+/// While a [`Cell`] is alive, the map it was retrieved from cannot be
+/// modified. [`CellValue`]s can be kept around instead, e.g. to update the
+/// cells matching a query, see [`Mask::get_map_region_owned`].
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, Location, MapState, MaskMapState,
+///     RealWorldLocation,
+/// };
+///
+/// let mut map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+///
+/// for cell in map.get_map_state_owned(MapState::Unexplored) {
+///     map.set_location(cell.location(), MapState::Frontier).unwrap();
+/// }
+///
+/// assert_eq!(map.get_map_state(MapState::Frontier).len(), 4);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct CellValue {
+    location: RealWorldLocation,
+    state: LocationType,
+}
+
+impl CellValue {
+    pub fn new(location: RealWorldLocation, state: LocationType) -> Self {
+        Self { location, state }
+    }
+
+    pub fn location(&self) -> &RealWorldLocation {
+        &self.location
+    }
+    pub fn x(&self) -> f64 {
+        self.location.x
+    }
+    pub fn y(&self) -> f64 {
+        self.location.y
+    }
+    pub fn state(&self) -> LocationType {
+        self.state
+    }
+}
+
+impl From<Cell<'_>> for CellValue {
+    fn from(cell: Cell<'_>) -> Self {
+        Self {
+            state: *cell.value,
+            location: cell.location,
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::collections::HashMap;
@@ -598,7 +1560,7 @@ pub mod tests {
     fn submap_get_map_region() {
         let (map, offset) = make_map();
 
-        let cells = map.get_map_region(|e| e == LocationType::OutOfMap);
+        let cells = map.get_map_region(&|e| e == LocationType::OutOfMap);
 
         assert_eq!(cells.len(), 2);
         assert_eq!(
@@ -652,7 +1614,7 @@ pub mod tests {
             offset,
         );
 
-        let cells = map.get_map_region(|e| e == OTR);
+        let cells = map.get_map_region(&|e| e == OTR);
 
         assert_eq!(cells.len(), 3);
         assert_eq!(
@@ -859,9 +1821,13 @@ pub mod tests {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn save_map_to_png() {
         let (map, _) = make_map();
-        map.as_image().save("test_save_map.png").unwrap();
+        let path = std::env::temp_dir()
+            .join(format!("test_save_map_{}.png", std::process::id()));
+        map.as_image().save(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
     }
 
     #[test]
@@ -921,6 +1887,11 @@ pub mod tests {
         assert_eq!(index, [map.nrows() - 1, map.ncols() - 1]);
     }
 
+    fn violated_axes(error: LocationError) -> [bool; 3] {
+        let LocationError::OutOfMap(error) = error;
+        error.violated_axes()
+    }
+
     #[test]
     fn location_index_too_far_right() {
         let (map, _) = make_map();
@@ -929,7 +1900,7 @@ pub mod tests {
             0.0,
             0.0,
         ));
-        assert_eq!(index, Err(LocationError::OutOfMap));
+        assert_eq!(violated_axes(index.unwrap_err()), [true, false, false]);
     }
 
     #[test]
@@ -938,7 +1909,7 @@ pub mod tests {
         let index = map.location_to_map_index(&RealWorldLocation::from_xyz(
             -1.0, 0.0, 0.0,
         ));
-        assert_eq!(index, Err(LocationError::OutOfMap));
+        assert_eq!(violated_axes(index.unwrap_err()), [true, false, false]);
     }
 
     #[test]
@@ -949,7 +1920,7 @@ pub mod tests {
             map.height() as f64 + 1.0,
             0.0,
         ));
-        assert_eq!(index, Err(LocationError::OutOfMap));
+        assert_eq!(violated_axes(index.unwrap_err()), [false, true, false]);
     }
 
     #[test]
@@ -958,6 +1929,477 @@ pub mod tests {
         let index = map.location_to_map_index(&RealWorldLocation::from_xyz(
             0.0, -1.0, 0.0,
         ));
-        assert_eq!(index, Err(LocationError::OutOfMap));
+        assert_eq!(violated_axes(index.unwrap_err()), [false, true, false]);
+    }
+
+    #[test]
+    fn iter_cells_matches_get_map_region() {
+        let (map, _) = make_map();
+
+        assert_eq!(
+            map.iter_cells().collect::<Vec<Cell>>(),
+            map.get_map_region(&|_| true)
+        );
+        assert_eq!(map.iter_cells().count(), map.width() * map.height());
+    }
+
+    #[test]
+    fn iter_cells_mut_locations() {
+        let (mut map, _) = make_map();
+
+        for (location, state) in map.iter_cells_mut() {
+            if location.y() >= 3.0 {
+                *state = LocationType::Assigned;
+            }
+        }
+
+        assert_eq!(map.get_map_state(LocationType::Assigned).len(), 2 * 3 + 1);
+    }
+
+    #[test]
+    fn indexed_iter_locations_roundtrip() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(-1.0, -1.0, 0.0),
+            RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+            AxisResolution::new(4.0, 2.0, 1.0),
+        );
+
+        for (index, location, state) in map.indexed_iter_locations() {
+            assert_eq!(map.location_to_map_index(&location), Ok(index));
+            assert_eq!(map.get_location(&location), Ok(*state));
+        }
+    }
+
+    #[test]
+    fn get_inside_and_outside_map() {
+        let (mut map, _) = make_map();
+
+        assert_eq!(
+            map.get(&RealWorldLocation::from_xyz(2.5, 1.5, 0.0)),
+            Some(&LocationType::Explored)
+        );
+        assert_eq!(map.get(&RealWorldLocation::from_xyz(3.5, 1.5, 0.0)), None);
+        assert_eq!(
+            map.get_mut(&RealWorldLocation::from_xyz(-0.5, 1.5, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn index_and_index_mut() {
+        let (mut map, _) = make_map();
+        let location = RealWorldLocation::from_xyz(0.5, 4.5, 0.0);
+
+        assert_eq!(map[&location], LocationType::Unexplored);
+        map.clear_dirty();
+        map.set_clock(2.0);
+        map[&location] = LocationType::Frontier;
+        assert_eq!(map[&location], LocationType::Frontier);
+        assert_eq!(map.get_location(&location), Ok(LocationType::Frontier));
+        assert_eq!(map.last_update(&location), Ok(2.0));
+        assert_eq!(
+            map.dirty_cells(),
+            [map.location_to_map_index(&location).unwrap()]
+        );
+
+        map.set_clock(3.0);
+        let index = map.index_of(&location).unwrap();
+        map[index] = LocationType::Obstacle;
+        assert_eq!(map.last_update(&location), Ok(3.0));
+        assert_eq!(map.dirty_cells().len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_map_panics() {
+        let (map, _) = make_map();
+        let _ = map[&RealWorldLocation::from_xyz(10.0, 0.0, 0.0)];
+    }
+
+    #[test]
+    fn set_locations_all_valid() {
+        let (mut map, _) = make_map();
+        let locations = vec![
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+        ];
+
+        assert_eq!(
+            map.set_locations(&locations, LocationType::Assigned),
+            Ok(())
+        );
+        for location in &locations {
+            assert_eq!(map.get_location(location), Ok(LocationType::Assigned));
+        }
+    }
+
+    #[test]
+    fn set_location_states_aggregates_errors() {
+        let (mut map, _) = make_map();
+        let inside = RealWorldLocation::from_xyz(1.0, 0.0, 0.0);
+        let left = RealWorldLocation::from_xyz(-1.0, 0.0, 0.0);
+        let up = RealWorldLocation::from_xyz(0.0, 6.0, 0.0);
+
+        let result = map.set_location_states(vec![
+            (&left, LocationType::Explored),
+            (&inside, LocationType::Frontier),
+            (&up, LocationType::Explored),
+        ]);
+
+        assert_eq!(
+            result.unwrap_err(),
+            vec![
+                (
+                    LocationError::from(OutOfMapError::new(
+                        left,
+                        Some(map.bounds()),
+                        [true, false, false]
+                    )),
+                    left
+                ),
+                (
+                    LocationError::from(OutOfMapError::new(
+                        up,
+                        Some(map.bounds()),
+                        [false, true, false]
+                    )),
+                    up
+                ),
+            ]
+        );
+        assert_eq!(map.get_location(&inside), Ok(LocationType::Frontier));
+    }
+
+    #[test]
+    fn set_location_stamps_clock() {
+        let (mut map, _) = make_map();
+        let location = RealWorldLocation::from_xyz(1.0, 1.0, 0.0);
+
+        map.set_clock(42.0);
+        map.set_location(&location, LocationType::Explored).unwrap();
+
+        assert_eq!(map.last_update(&location), Ok(42.0));
+        assert_eq!(
+            map.last_update(&RealWorldLocation::from_xyz(0.0, 0.0, 0.0)),
+            Ok(0.0)
+        );
+    }
+
+    #[test]
+    fn decay_reverts_only_old_explored_cells() {
+        let (mut map, _) = make_map();
+        map.set_clock(5.0);
+        map.set_location(
+            &RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+            LocationType::Explored,
+        )
+        .unwrap();
+        map.set_clock(10.0);
+
+        // the other explored cell of `make_map` was stamped at time 0
+        assert_eq!(map.decay(7.0), 1);
+        assert_eq!(map.get_map_state(LocationType::Explored).len(), 1);
+        assert_eq!(
+            map.get_location(&RealWorldLocation::from_xyz(1.0, 4.0, 0.0)),
+            Ok(LocationType::Unexplored)
+        );
+        assert_eq!(
+            map.last_update(&RealWorldLocation::from_xyz(1.0, 4.0, 0.0)),
+            Ok(10.0)
+        );
+
+        assert_eq!(map.decay(0.0), 1);
+        assert_eq!(map.get_map_state(LocationType::Explored).len(), 0);
+    }
+
+    #[test]
+    fn try_new_rejects_invalid_input() {
+        let origin = RealWorldLocation::from_xyz(0.0, 0.0, 0.0);
+        let corner = RealWorldLocation::from_xyz(4.0, 2.0, 0.0);
+
+        for resolution in [
+            AxisResolution::uniform(0.0),
+            AxisResolution::uniform(-1.0),
+            AxisResolution::new(1.0, f64::INFINITY, 1.0),
+        ] {
+            assert_eq!(
+                CellMap::try_new(origin, corner, resolution).unwrap_err(),
+                CellMapError::InvalidResolution
+            );
+        }
+        assert_eq!(
+            CellMap::try_new(
+                RealWorldLocation::from_xyz(f64::NAN, 0.0, 0.0),
+                corner,
+                AxisResolution::uniform(1.0)
+            )
+            .unwrap_err(),
+            CellMapError::InvalidBounds
+        );
+        assert_eq!(
+            CellMap::try_new(
+                origin,
+                RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+                AxisResolution::uniform(1.0)
+            )
+            .unwrap_err(),
+            CellMapError::Empty
+        );
+        assert_eq!(
+            CellMap::try_new(origin, corner, AxisResolution::uniform(1e-3))
+                .unwrap_err(),
+            CellMapError::Empty
+        );
+        assert_eq!(
+            CellMap::try_new(
+                origin,
+                RealWorldLocation::from_xyz(1e300, 1e300, 0.0),
+                AxisResolution::uniform(1.0)
+            )
+            .unwrap_err(),
+            CellMapError::TooLarge
+        );
+        assert_eq!(
+            CellMap::try_new(
+                origin,
+                RealWorldLocation::from_xyz(1e10, 1e10, 0.0),
+                AxisResolution::uniform(1.0)
+            )
+            .unwrap_err(),
+            CellMapError::TooLarge
+        );
+    }
+
+    #[test]
+    fn try_new_matches_new() {
+        let point1 = RealWorldLocation::from_xyz(-1.0, -2.0, 0.0);
+        let point2 = RealWorldLocation::from_xyz(0.5, 1.0, 0.0);
+        let resolution = AxisResolution::uniform(2.0);
+
+        assert_eq!(
+            CellMap::try_new(point1, point2, resolution),
+            Ok(CellMap::new(point1, point2, resolution))
+        );
+    }
+
+    #[test]
+    fn location_to_map_index_non_finite() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        for location in [
+            RealWorldLocation::from_xyz(f64::NAN, 0.5, 0.0),
+            RealWorldLocation::from_xyz(0.5, f64::INFINITY, 0.0),
+            RealWorldLocation::from_xyz(1e300, 0.5, 0.0),
+        ] {
+            assert!(matches!(
+                map.location_to_map_index(&location),
+                Err(LocationError::OutOfMap(_))
+            ));
+            assert_eq!(map.get(&location), None);
+        }
+    }
+
+    #[test]
+    fn location_to_map_index_f32() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(-1.0, -1.0, 0.0),
+            RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+            AxisResolution::new(2.0, 4.0, 1.0),
+        );
+
+        for (x, y) in [(-1.0, -1.0), (0.3, -0.2), (0.9, 0.9)] {
+            assert_eq!(
+                map.location_to_map_index(&RealWorldLocation::from_xyz(
+                    x as f32, y as f32, 0.0,
+                )),
+                map.location_to_map_index(&RealWorldLocation::from_xyz(
+                    x, y, 0.0
+                )),
+            );
+        }
+        assert!(matches!(
+            map.location_to_map_index(&RealWorldLocation::from_xyz(
+                f32::NAN,
+                0.0,
+                0.0,
+            )),
+            Err(LocationError::OutOfMap(_))
+        ));
+        assert_eq!(
+            map.index_center::<f32>([5, 2]),
+            Coords::new(0.25, 0.375, 0.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidBounds")]
+    fn new_panics_on_non_finite_bounds() {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(f64::INFINITY, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+    }
+
+    #[test]
+    fn new_allows_empty_maps() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(0.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        assert_eq!((map.width(), map.height()), (0, 2));
+    }
+
+    #[test]
+    fn equality_ignores_bookkeeping() {
+        let (map, _) = make_map();
+        let mut changed = map.clone();
+        changed.set_clock(3.0);
+        let value = changed.cells()[[0, 0]];
+        changed.set_cell([0, 0], MapState::Obstacle);
+        assert_ne!(changed, map);
+
+        changed.set_cell([0, 0], value);
+
+        assert_eq!(changed, map);
+        assert!(!changed.dirty_cells().is_empty());
+        assert_eq!(CellMap::from_bytes(&map.to_bytes()).unwrap(), map);
+        let moved = CellMap::from_raster(
+            map.cells().clone(),
+            *map.resolution(),
+            Coords::new(1.0, 0.0, 0.0),
+        );
+        assert_ne!(moved, map);
+    }
+
+    #[test]
+    fn dirty_tracking_follows_clock_stamping() {
+        let (mut map, _) = make_map();
+        map.set_clock(10.0);
+        assert_eq!(map.dirty_bounds(), None);
+
+        *map.get_mut(&RealWorldLocation::from_xyz(1.5, 1.5, 0.0))
+            .unwrap() = LocationType::Obstacle;
+        assert!(map.dirty_cells().is_empty());
+
+        assert_eq!(map.decay(5.0), 2);
+        *map.get_index_mut([4, 2]) = LocationType::Frontier;
+        map.set_cell([0, 0], LocationType::Assigned);
+        map.set_cell([1, 2], LocationType::Unexplored);
+        assert_eq!(map.dirty_cells(), [[1, 2], [4, 1], [4, 2], [0, 0]]);
+        assert_eq!(map.dirty_bounds(), Some([[0, 0], [4, 2]]));
+
+        map.clear_dirty();
+        assert!(map.dirty_cells().is_empty());
+        map.set_cell([4, 1], LocationType::Explored);
+        assert_eq!(map.dirty_cells(), [[4, 1]]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_as_image_matches_as_image() {
+        let (map, _) = make_map();
+
+        assert_eq!(map.try_as_image(), Ok(map.as_image()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn as_image_scaled_grid_lines() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_location(
+            &RealWorldLocation::from_xyz(2.5, 1.5, 0.0),
+            MapState::Obstacle,
+        )
+        .unwrap();
+        let grid = image::Rgb([1, 2, 3]);
+
+        let image = map.as_image_scaled(4, Some(grid));
+
+        assert_eq!(image.dimensions(), (12, 8));
+        assert_eq!(*image.get_pixel(0, 0), MapState::Unexplored.to_rgb());
+        assert_eq!(*image.get_pixel(4, 1), grid);
+        assert_eq!(*image.get_pixel(1, 4), grid);
+        assert_eq!(*image.get_pixel(11, 7), MapState::Obstacle.to_rgb());
+        // Grid lines would hide small cells.
+        assert_eq!(
+            map.as_image_scaled(2, Some(grid)).get_pixel(2, 0),
+            image.get_pixel(0, 0)
+        );
+        assert_eq!(map.as_image_scaled(1, None), map.as_image());
+        assert_eq!(map.as_image_scaled(0, None).dimensions(), (0, 0));
+    }
+
+    #[test]
+    fn out_of_map_details() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(-1.0, -1.0, 0.0),
+            RealWorldLocation::from_xyz(1.0, 2.0, 0.0),
+            AxisResolution::uniform(2.0),
+        );
+        let location = RealWorldLocation::from_xyz(5.0, -3.0, -1.0);
+
+        assert_eq!(
+            map.get_location(&location),
+            Err(LocationError::from(OutOfMapError::new(
+                location,
+                Some([
+                    RealWorldLocation::from_xyz(-1.0, -1.0, 0.0),
+                    RealWorldLocation::from_xyz(1.0, 2.0, 0.0),
+                ]),
+                [true, true, true]
+            )))
+        );
+    }
+
+    #[test]
+    fn owned_cells_match_borrowed_cells() {
+        let (map, _) = make_map();
+
+        let owned = map.get_map_state_owned(LocationType::Frontier);
+
+        assert_eq!(
+            owned,
+            map.get_map_state(LocationType::Frontier)
+                .into_iter()
+                .map(|cell| CellValue::new(*cell.location(), *cell.value()))
+                .collect::<Vec<_>>()
+        );
+        assert!(!owned.is_empty());
+    }
+
+    #[test]
+    fn from_array_view_copies_window() {
+        let (map, _) = make_map();
+        let window = map.cells_view().slice_move(ndarray::s![1.., ..2]);
+
+        let copy =
+            CellMap::from_array_view(window, *map.resolution(), *map.offset());
+
+        assert_eq!(copy.cells(), window);
+        assert!(copy.dirty_cells().is_empty());
+    }
+
+    #[test]
+    fn cells_view_mut_is_not_tracked() {
+        let (mut map, _) = make_map();
+        map.set_clock(5.0);
+
+        map.cells_view_mut().fill(LocationType::Explored);
+
+        assert!(map.cells().iter().all(|s| *s == LocationType::Explored));
+        assert!(map.dirty_cells().is_empty());
+        assert_eq!(map.last_update(&map.bounds()[0]), Ok(0.0));
     }
 }
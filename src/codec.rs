@@ -0,0 +1,528 @@
+use alloc::vec::Vec;
+
+use crate::{
+    AxisResolution, CellMap, Coords, LocationType, MapState, MapStateMatrix,
+};
+
+/// Magic bytes identifying an encoded [`CellMap`].
+const MAGIC: &[u8; 3] = b"LRM";
+/// Version of the encoding, bumped whenever the layout changes.
+const VERSION: u8 = 1;
+/// Parameters of the 64-bit FNV-1a hash used by [`CellMap::content_hash`].
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+impl CellMap {
+    /// Encode the map into a compact binary representation.
+    ///
+    /// Maps mostly consist of large uniform regions, so the cell states are
+    /// compressed using run-length encoding. This makes the output suitable for
+    /// bandwidth-constrained links between robots. Use [`CellMap::from_bytes`]
+    /// to decode it again.
+    ///
+    /// # Layout
+    ///
+    /// All numbers are little-endian.
+    ///
+    /// - The magic bytes `LRM` followed by a version byte.
+    /// - The offset and resolution as `x`, `y`, `z` [`f64`] triplets.
+    /// - The number of rows and columns as [`u64`].
+    /// - The runs, each made of a state byte (see [`u8::from`]) followed by
+    ///   the run length as an [LEB128](https://en.wikipedia.org/wiki/LEB128)
+    ///   encoded integer. Cells are traversed in row-major order.
+    ///
+    /// Note that only the cell states are encoded; timestamps (see
+    /// [`CellMap::set_clock`]) are not.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, CellMap, RealWorldLocation};
+    ///
+    /// let map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(100.0, 100.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let bytes = map.to_bytes();
+    ///
+    /// assert!(bytes.len() < 100);
+    /// assert_eq!(CellMap::from_bytes(&bytes).unwrap().cells(), map.cells());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        for value in [
+            self.offset().x,
+            self.offset().y,
+            self.offset().z,
+            self.resolution().x,
+            self.resolution().y,
+            self.resolution().z,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.nrows() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.ncols() as u64).to_le_bytes());
+
+        let mut cells = self.cells().iter();
+        if let Some(first) = cells.next() {
+            let mut current = first;
+            let mut run: u64 = 1;
+            for cell in cells {
+                if cell == current {
+                    run += 1;
+                } else {
+                    push_run(&mut bytes, current, run);
+                    current = cell;
+                    run = 1;
+                }
+            }
+            push_run(&mut bytes, current, run);
+        }
+
+        bytes
+    }
+
+    /// Decode a map previously encoded with [`CellMap::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the bytes do not describe a valid
+    /// map. See [`CellMapDecodeError`] for details.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CellMapDecodeError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(CellMapDecodeError::InvalidHeader);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(CellMapDecodeError::UnsupportedVersion(version));
+        }
+
+        let offset = Coords::new(reader.f64()?, reader.f64()?, reader.f64()?);
+        if !(offset.x.is_finite()
+            && offset.y.is_finite()
+            && offset.z.is_finite())
+        {
+            return Err(CellMapDecodeError::InvalidOffset);
+        }
+        let resolution =
+            AxisResolution::new(reader.f64()?, reader.f64()?, reader.f64()?);
+        let valid = |value: f64| value.is_finite() && value > 0.0;
+        if !valid(resolution.x) || !valid(resolution.y) {
+            return Err(CellMapDecodeError::InvalidResolution);
+        }
+        let rows = usize::try_from(reader.u64()?)
+            .map_err(|_| CellMapDecodeError::TooLarge)?;
+        let columns = usize::try_from(reader.u64()?)
+            .map_err(|_| CellMapDecodeError::TooLarge)?;
+        CellMap::check_size(rows, columns)
+            .map_err(|_| CellMapDecodeError::TooLarge)?;
+        let expected = rows * columns;
+
+        // The runs are read before allocating the cells, such that the
+        // header alone cannot trigger a large allocation.
+        let mut runs = Vec::new();
+        let mut total: usize = 0;
+        while !reader.bytes.is_empty() {
+            let state = MapState::try_from(reader.take(1)?[0])?;
+            let run = usize::try_from(reader.leb128()?)
+                .map_err(|_| CellMapDecodeError::CellCountMismatch)?;
+            if run == 0 {
+                return Err(CellMapDecodeError::InvalidRun);
+            }
+            total = total
+                .checked_add(run)
+                .filter(|total| *total <= expected)
+                .ok_or(CellMapDecodeError::CellCountMismatch)?;
+            runs.push((state, run));
+        }
+        if total != expected {
+            return Err(CellMapDecodeError::CellCountMismatch);
+        }
+        let mut cells: Vec<LocationType> = Vec::with_capacity(expected);
+        for (state, run) in runs {
+            cells.resize(cells.len() + run, state);
+        }
+
+        Ok(Self::from_raster(
+            MapStateMatrix::from_shape_vec((rows, columns), cells)
+                .expect("Number of cells was checked above"),
+            resolution,
+            offset,
+        ))
+    }
+
+    /// A hash of the contents of the map, to cheaply detect whether
+    /// anything changed, e.g. since the last partitioning or sync.
+    ///
+    /// The hash covers the offset, the resolution, the dimensions and the
+    /// state of every cell, i.e. the same data as [`CellMap::to_bytes`], but
+    /// not the timestamps. It is computed using 64-bit
+    /// [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function)
+    /// and is stable across platforms and runs, so robots can compare the
+    /// hashes of their maps instead of the maps themselves. Like any hash,
+    /// different maps may collide, though this is unlikely.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let synced = map.content_hash();
+    ///
+    /// let location = RealWorldLocation::from_xyz(2.5, 2.5, 0.0);
+    /// map.set_location(&location, MapState::Explored).unwrap();
+    /// assert_ne!(map.content_hash(), synced);
+    ///
+    /// map.set_location(&location, MapState::Unexplored).unwrap();
+    /// assert_eq!(map.content_hash(), synced);
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut write = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        for value in [
+            self.offset().x,
+            self.offset().y,
+            self.offset().z,
+            self.resolution().x,
+            self.resolution().y,
+            self.resolution().z,
+        ] {
+            // Adding zero turns `-0.0` into `0.0`, which compare equal.
+            write(&(value + 0.0).to_le_bytes());
+        }
+        write(&(self.nrows() as u64).to_le_bytes());
+        write(&(self.ncols() as u64).to_le_bytes());
+        for cell in self.cells() {
+            write(&[cell.into()]);
+        }
+        hash
+    }
+}
+
+/// Append a single run to the encoded bytes.
+fn push_run(bytes: &mut Vec<u8>, state: &LocationType, mut run: u64) {
+    bytes.push(state.into());
+    loop {
+        let byte = (run & 0x7f) as u8;
+        run >>= 7;
+        if run == 0 {
+            bytes.push(byte);
+            break;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Internal helper to consume the encoded bytes from the front.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CellMapDecodeError> {
+        if self.bytes.len() < n {
+            return Err(CellMapDecodeError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u64(&mut self) -> Result<u64, CellMapDecodeError> {
+        Ok(u64::from_le_bytes(
+            self.take(8)?
+                .try_into()
+                .expect("Exactly 8 bytes were taken"),
+        ))
+    }
+
+    fn f64(&mut self) -> Result<f64, CellMapDecodeError> {
+        Ok(f64::from_le_bytes(
+            self.take(8)?
+                .try_into()
+                .expect("Exactly 8 bytes were taken"),
+        ))
+    }
+
+    fn leb128(&mut self) -> Result<u64, CellMapDecodeError> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            // Only the lowest bit of the tenth byte fits into a `u64`.
+            if shift == 63 && byte & 0x7f > 1 {
+                return Err(CellMapDecodeError::InvalidRun);
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CellMapDecodeError::InvalidRun)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CellMapDecodeError {
+    /// The bytes ended before the map was fully decoded.
+    UnexpectedEnd,
+    /// The bytes do not start with the expected magic bytes.
+    InvalidHeader,
+    /// The bytes were encoded using an unknown version of the format.
+    UnsupportedVersion(u8),
+    /// A byte does not correspond to any [`MapState`].
+    UnknownState(u8),
+    /// The runs do not add up to the number of cells given in the header.
+    CellCountMismatch,
+    /// The header describes a map with more than [`CellMap::MAX_CELLS`]
+    /// cells.
+    TooLarge,
+    /// The `x` or `y` resolution is zero, negative, NaN or infinite.
+    InvalidResolution,
+    /// The offset contains NaN or infinite values.
+    InvalidOffset,
+    /// A run is empty or its length does not fit into a [`u64`].
+    InvalidRun,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cell_map::tests::make_map, RealWorldLocation};
+
+    #[test]
+    fn roundtrip() {
+        let (map, _) = make_map();
+
+        let decoded = CellMap::from_bytes(&map.to_bytes()).unwrap();
+
+        assert_eq!(decoded.cells(), map.cells());
+        assert_eq!(decoded.offset(), map.offset());
+        assert_eq!(decoded.resolution(), map.resolution());
+    }
+
+    #[test]
+    fn roundtrip_negative_offset() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(-3.5, -1.25, 0.0),
+            RealWorldLocation::from_xyz(2.0, 4.0, 0.0),
+            AxisResolution::new(2.0, 4.0, 1.0),
+        );
+
+        let decoded = CellMap::from_bytes(&map.to_bytes()).unwrap();
+
+        assert_eq!(decoded.cells(), map.cells());
+        assert_eq!(decoded.offset(), map.offset());
+        assert_eq!(decoded.resolution(), map.resolution());
+    }
+
+    #[test]
+    fn content_hash_changes_with_contents() {
+        let (map, _) = make_map();
+        let hash = map.content_hash();
+
+        assert_eq!(map.clone().content_hash(), hash);
+        assert_eq!(
+            CellMap::from_bytes(&map.to_bytes()).unwrap().content_hash(),
+            hash
+        );
+        let mut changed = map.clone();
+        changed.set_cell([0, 0], MapState::Obstacle);
+        changed.set_cell([0, 0], MapState::Frontier);
+        assert_ne!(changed.content_hash(), hash);
+        let moved = CellMap::from_raster(
+            map.cells().clone(),
+            *map.resolution(),
+            Coords::new(1.0, 0.0, 0.0),
+        );
+        assert_ne!(moved.content_hash(), hash);
+        let transposed = CellMap::from_raster(
+            map.cells().t().to_owned(),
+            *map.resolution(),
+            *map.offset(),
+        );
+        assert_ne!(transposed.content_hash(), hash);
+    }
+
+    #[test]
+    fn content_hash_is_stable() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(-0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        let mut timed = map.clone();
+        timed.set_clock(5.0);
+        timed.set_cell([0, 1], MapState::Unexplored);
+
+        assert_eq!(map.content_hash(), 0x3c43_4c9c_898c_9bcb);
+        assert_eq!(timed.content_hash(), map.content_hash());
+    }
+
+    #[test]
+    fn uniform_map_is_a_single_run() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(1000.0, 1000.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        // header (4 + 6 * 8 + 2 * 8 bytes) + state byte + 3 byte varint
+        assert_eq!(map.to_bytes().len(), 68 + 1 + 3);
+    }
+
+    #[test]
+    fn empty_map_roundtrip() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        let decoded = CellMap::from_bytes(&map.to_bytes()).unwrap();
+
+        assert_eq!(decoded.cells(), map.cells());
+    }
+
+    #[test]
+    fn invalid_header() {
+        let (map, _) = make_map();
+        let mut bytes = map.to_bytes();
+        bytes[0] = b'X';
+
+        assert_eq!(
+            CellMap::from_bytes(&bytes),
+            Err(CellMapDecodeError::InvalidHeader)
+        );
+    }
+
+    #[test]
+    fn truncated_bytes() {
+        let (map, _) = make_map();
+        let bytes = map.to_bytes();
+
+        assert_eq!(
+            CellMap::from_bytes(&bytes[..20]),
+            Err(CellMapDecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            CellMap::from_bytes(&bytes[..bytes.len() - 2]),
+            Err(CellMapDecodeError::CellCountMismatch)
+        );
+    }
+
+    #[test]
+    fn untrusted_header() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        let with_size = |rows: u64, columns: u64, runs: &[u8]| {
+            let mut bytes = map.to_bytes()[..52].to_vec();
+            bytes.extend_from_slice(&rows.to_le_bytes());
+            bytes.extend_from_slice(&columns.to_le_bytes());
+            bytes.extend_from_slice(runs);
+            CellMap::from_bytes(&bytes)
+        };
+
+        assert_eq!(
+            with_size(u64::MAX, u64::MAX, &[4, 1]),
+            Err(CellMapDecodeError::TooLarge)
+        );
+        assert_eq!(
+            with_size(1 << 20, 1 << 20, &[4, 0xff, 0xff, 0xff, 0x7f]),
+            Err(CellMapDecodeError::TooLarge)
+        );
+        // A single run claiming more cells than the header, and runs whose
+        // lengths overflow when added up.
+        assert_eq!(
+            with_size(1 << 13, 1 << 13, &[4, 0xff, 0xff, 0xff, 0x7f]),
+            Err(CellMapDecodeError::CellCountMismatch)
+        );
+        let huge = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+        let runs: Vec<u8> = [&[4][..], &huge, &[4], &huge].concat();
+        assert_eq!(
+            with_size(1 << 13, 1 << 13, &runs),
+            Err(CellMapDecodeError::CellCountMismatch)
+        );
+        assert!(with_size(2, 2, &[4, 4]).is_ok());
+
+        let mut bytes = map.to_bytes();
+        bytes[28..36].copy_from_slice(&0.0f64.to_le_bytes());
+        assert_eq!(
+            CellMap::from_bytes(&bytes),
+            Err(CellMapDecodeError::InvalidResolution)
+        );
+        bytes[28..36].copy_from_slice(&f64::INFINITY.to_le_bytes());
+        assert_eq!(
+            CellMap::from_bytes(&bytes),
+            Err(CellMapDecodeError::InvalidResolution)
+        );
+
+        let mut bytes = map.to_bytes();
+        bytes[12..20].copy_from_slice(&f64::NAN.to_le_bytes());
+        assert_eq!(
+            CellMap::from_bytes(&bytes),
+            Err(CellMapDecodeError::InvalidOffset)
+        );
+    }
+
+    #[test]
+    fn invalid_runs() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        let with_runs = |runs: &[u8]| {
+            let mut bytes = map.to_bytes()[..68].to_vec();
+            bytes.extend_from_slice(runs);
+            CellMap::from_bytes(&bytes)
+        };
+
+        assert_eq!(
+            with_runs(&[4, 0, 4, 4]),
+            Err(CellMapDecodeError::InvalidRun)
+        );
+        // 2^64 + 4 would wrap around to 4 if the excess bits were dropped.
+        let wrapping = [0x84, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80];
+        assert_eq!(
+            with_runs(&[&[4][..], &wrapping, &[0x02]].concat()),
+            Err(CellMapDecodeError::InvalidRun)
+        );
+        assert_eq!(
+            with_runs(&[&[4][..], &wrapping, &[0x80, 0x00]].concat()),
+            Err(CellMapDecodeError::InvalidRun)
+        );
+        assert!(with_runs(&[4, 4]).is_ok());
+    }
+
+    #[test]
+    fn unknown_state() {
+        let (map, _) = make_map();
+        let mut bytes = map.to_bytes();
+        bytes[68] = 200;
+
+        assert_eq!(
+            CellMap::from_bytes(&bytes),
+            Err(CellMapDecodeError::UnknownState(200))
+        );
+    }
+}
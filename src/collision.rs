@@ -0,0 +1,307 @@
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+use num::ToPrimitive;
+
+use crate::{CellMap, Footprint, LocationType, MapState, RealWorldLocation};
+
+impl MapState {
+    /// Whether a robot cannot move into a location with this state.
+    ///
+    /// This is the case for [`MapState::Obstacle`], [`MapState::OutOfMap`] and
+    /// [`MapState::OtherRobot`]. Locations occupied by the current robot are
+    /// free, as are [`MapState::Unexplored`] ones.
+    pub fn is_blocked(&self) -> bool {
+        matches!(
+            self,
+            MapState::Obstacle | MapState::OutOfMap | MapState::OtherRobot
+        )
+    }
+}
+
+impl CellMap {
+    /// Whether a robot can be located at `location`, see
+    /// [`MapState::is_blocked`]. Locations outside the map are not free.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let wall = RealWorldLocation::from_xyz(1.5, 1.5, 0.0);
+    /// map.set_location(&wall, MapState::Obstacle).unwrap();
+    ///
+    /// assert!(!map.is_free(&wall));
+    /// assert!(map.is_free(&RealWorldLocation::from_xyz(2.5, 1.5, 0.0)));
+    /// assert!(!map.is_free(&RealWorldLocation::from_xyz(5.0, 1.5, 0.0)));
+    /// ```
+    pub fn is_free(&self, location: &RealWorldLocation) -> bool {
+        self.location_to_map_index(location)
+            .is_ok_and(|index| !self.cells()[index].is_blocked())
+    }
+
+    /// Whether a robot with the given footprint, facing `heading` (see
+    /// [`crate::Robot::heading`]), can be located at `location`.
+    ///
+    /// The cell containing `location` and all cells whose center is covered
+    /// by the footprint must be free, see [`CellMap::is_free`]. In
+    /// particular, the footprint must not stick out of the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Footprint, Location, MapState,
+    ///     RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_location(
+    ///     &RealWorldLocation::from_xyz(6.5, 5.5, 0.0),
+    ///     MapState::Obstacle,
+    /// )
+    /// .unwrap();
+    /// let robot = RealWorldLocation::from_xyz(4.5, 5.5, 0.0);
+    ///
+    /// assert!(map.is_footprint_free(&robot, &Footprint::Circle(1.5), None));
+    /// assert!(!map.is_footprint_free(&robot, &Footprint::Circle(2.0), None));
+    /// ```
+    pub fn is_footprint_free(
+        &self,
+        location: &RealWorldLocation,
+        footprint: &Footprint,
+        heading: Option<f64>,
+    ) -> bool {
+        if !self.is_free(location) {
+            return false;
+        }
+        let radius = footprint.bounding_radius();
+        let range = |center: f64, offset: f64, resolution: f64| {
+            let start = ((center - radius - offset) * resolution).floor();
+            let end = ((center + radius - offset) * resolution).ceil();
+            (start as i64)..(end as i64)
+        };
+        let resolution = self.resolution();
+        let rows = range(location.y, self.offset().y, resolution.y);
+        let cols = range(location.x, self.offset().x, resolution.x);
+
+        rows.flat_map(|row| cols.clone().map(move |col| (row, col)))
+            .all(|(row, col)| {
+                let center = RealWorldLocation::from_xyz(
+                    (col as f64 + 0.5) / resolution.x + self.offset().x,
+                    (row as f64 + 0.5) / resolution.y + self.offset().y,
+                    location.z,
+                );
+                !footprint.contains(location, heading, &center)
+                    || self.is_free(&center)
+            })
+    }
+
+    /// Mark all cells whose center lies within `radius` of the center of a
+    /// cell in the given state as that state, e.g. to grow obstacles by the
+    /// radius of a robot.
+    ///
+    /// Planning on the inflated map keeps the center of a robot at least
+    /// `radius` away from obstacles, so that the single cell of a robot can
+    /// stand in for its whole footprint. Only cells which are neither blocked
+    /// (see [`MapState::is_blocked`]) nor occupied by the current robot are
+    /// changed; they are stamped with the map's clock (see
+    /// [`CellMap::set_clock`]). Clone the map first to keep the original
+    /// one, e.g. for partitioning.
+    ///
+    /// A radius reaching beyond the map, including an infinite one, covers
+    /// the whole map, while a negative or NaN radius does not change any cell.
+    ///
+    /// Returns the number of cells which were changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, MaskMapState,
+    ///     RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let wall = RealWorldLocation::from_xyz(4.5, 4.5, 0.0);
+    /// map.set_location(&wall, MapState::Obstacle).unwrap();
+    ///
+    /// let mut inflated = map.clone();
+    /// assert_eq!(inflated.inflate(MapState::Obstacle, 1.0), 4);
+    ///
+    /// assert_eq!(inflated.get_map_state(MapState::Obstacle).len(), 5);
+    /// assert_eq!(map.get_map_state(MapState::Obstacle).len(), 1);
+    /// ```
+    pub fn inflate(&mut self, state: LocationType, radius: f64) -> usize {
+        let resolution = self.resolution();
+        // Offsets beyond the extent of the map never reach another cell.
+        let reach = |resolution: f64, len: usize| {
+            (radius * resolution)
+                .floor()
+                .min(len.to_f64().expect("usize to f64 should work"))
+                .to_isize()
+                .unwrap_or(0)
+        };
+        let (rows, cols) = (
+            reach(resolution.y, self.height()),
+            reach(resolution.x, self.width()),
+        );
+        // Offsets between cells whose centers are within the radius.
+        let kernel: Vec<[isize; 2]> = (-rows..=rows)
+            .flat_map(|row| (-cols..=cols).map(move |col| [row, col]))
+            .filter(|[row, col]| {
+                let dx = *col as f64 / resolution.x;
+                let dy = *row as f64 / resolution.y;
+                dx * dx + dy * dy <= radius * radius
+            })
+            .collect();
+        let sources: Vec<[usize; 2]> = self
+            .cells()
+            .indexed_iter()
+            .filter(|(_, value)| **value == state)
+            .map(|((row, col), _)| [row, col])
+            .collect();
+
+        let mut count = 0;
+        for [row, col] in sources {
+            for [d_row, d_col] in &kernel {
+                let index = [
+                    row.checked_add_signed(*d_row),
+                    col.checked_add_signed(*d_col),
+                ];
+                let [Some(row), Some(col)] = index else {
+                    continue;
+                };
+                let Some(value) = self.cells().get([row, col]) else {
+                    continue;
+                };
+                if *value != state
+                    && !value.is_blocked()
+                    && *value != MapState::MyRobot
+                {
+                    self.set_cell([row, col], state);
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Coords, Location};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    #[test]
+    fn robot_states() {
+        let mut map = make_map();
+        let mine = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        let other = RealWorldLocation::from_xyz(1.5, 0.5, 0.0);
+        map.set_location(&mine, MapState::MyRobot).unwrap();
+        map.set_location(&other, MapState::OtherRobot).unwrap();
+
+        assert!(map.is_free(&mine));
+        assert!(!map.is_free(&other));
+    }
+
+    #[test]
+    fn footprint_sticking_out_of_map() {
+        let map = make_map();
+        let location = RealWorldLocation::from_xyz(1.0, 5.0, 0.0);
+
+        assert!(map.is_footprint_free(
+            &location,
+            &Footprint::Circle(1.0),
+            None
+        ));
+        assert!(!map.is_footprint_free(
+            &location,
+            &Footprint::Circle(1.6),
+            None
+        ));
+    }
+
+    #[test]
+    fn inflate_keeps_robots_and_respects_resolution() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+            AxisResolution::new(2.0, 1.0, 1.0),
+        );
+        let wall = RealWorldLocation::from_xyz(5.25, 5.5, 0.0);
+        let robot = RealWorldLocation::from_xyz(5.75, 5.5, 0.0);
+        map.set_location(&wall, MapState::Obstacle).unwrap();
+        map.set_location(&robot, MapState::MyRobot).unwrap();
+
+        // Cells are half as wide as high: two cells left of the wall, one
+        // right of the robot, plus one above and one below the wall.
+        assert_eq!(map.inflate(MapState::Obstacle, 1.0), 2 + 1 + 2);
+        assert_eq!(map.get_location(&robot), Ok(MapState::MyRobot));
+        assert_eq!(
+            map.get_location(&RealWorldLocation::from_xyz(6.25, 5.5, 0.0)),
+            Ok(MapState::Obstacle)
+        );
+        assert_eq!(map.inflate(MapState::Obstacle, 0.0), 0);
+    }
+
+    #[test]
+    fn inflate_non_finite_radius() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 3.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_cell([0, 0], MapState::Obstacle);
+
+        assert_eq!(map.inflate(MapState::Obstacle, f64::NAN), 0);
+        assert_eq!(map.inflate(MapState::Obstacle, f64::NEG_INFINITY), 0);
+        assert_eq!(map.inflate(MapState::Obstacle, f64::INFINITY), 11);
+    }
+
+    #[test]
+    fn polygon_footprint_follows_heading() {
+        let mut map = make_map();
+        map.set_location(
+            &RealWorldLocation::from_xyz(5.5, 7.5, 0.0),
+            MapState::Obstacle,
+        )
+        .unwrap();
+        let location = RealWorldLocation::from_xyz(5.5, 5.5, 0.0);
+        let footprint = Footprint::Polygon(
+            [[-2.5, -0.5], [2.5, -0.5], [2.5, 0.5], [-2.5, 0.5]]
+                .map(|[x, y]| Coords::new(x, y, 0.0))
+                .to_vec(),
+        );
+
+        assert!(map.is_footprint_free(&location, &footprint, None));
+        assert!(!map.is_footprint_free(
+            &location,
+            &footprint,
+            Some(core::f64::consts::FRAC_PI_2)
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+use image::Rgb;
+
+use crate::MapState;
+
+/// Colors used to render each [`MapState`] when converting maps to images.
+///
+/// The default colors are the ones of [`MapState::to_rgb`]. Other palettes
+/// can be built from scratch using [`ColorMap::from_fn`], or by adjusting
+/// single colors using [`ColorMap::with`].
+///
+/// # Example
+///
+/// ```
+/// use image::Rgb;
+/// use local_robot_map::{
+///     AxisResolution, CellMap, ColorMap, MapState, RealWorldLocation,
+/// };
+///
+/// let map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+/// let colors = ColorMap::colorblind_safe()
+///     .with(MapState::Unexplored, Rgb([255, 255, 255]));
+///
+/// let image = map.as_image_with(&colors);
+///
+/// assert_eq!(*image.get_pixel(0, 0), Rgb([255, 255, 255]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorMap {
+    /// Colors indexed by the byte representation of the states.
+    colors: [Rgb<u8>; 8],
+}
+
+impl ColorMap {
+    /// The default colors, see [`MapState::to_rgb`].
+    pub const DEFAULT: Self = Self {
+        colors: [
+            Rgb([0, 0, 0]),       // OutOfMap
+            Rgb([50, 255, 50]),   // OtherRobot
+            Rgb([255, 50, 50]),   // MyRobot
+            Rgb([200, 200, 200]), // Explored
+            Rgb([100, 100, 100]), // Unexplored
+            Rgb([255, 100, 255]), // Frontier
+            Rgb([255, 255, 0]),   // Assigned
+            Rgb([40, 40, 140]),   // Obstacle
+        ],
+    };
+
+    /// Create a palette by computing the color of each state.
+    pub fn from_fn(f: impl Fn(MapState) -> Rgb<u8>) -> Self {
+        let mut colors = Self::DEFAULT.colors;
+        for (byte, color) in (0u8..).zip(colors.iter_mut()) {
+            let state = MapState::try_from(byte)
+                .expect("Every index corresponds to a state");
+            *color = f(state);
+        }
+        Self { colors }
+    }
+
+    /// Palette distinguishable with the common forms of color blindness,
+    /// based on the colors proposed by Okabe and Ito.
+    pub fn colorblind_safe() -> Self {
+        Self::from_fn(|state| match state {
+            MapState::OutOfMap => Rgb([0, 0, 0]),
+            MapState::OtherRobot => Rgb([0, 158, 115]),
+            MapState::MyRobot => Rgb([213, 94, 0]),
+            MapState::Explored => Rgb([220, 220, 220]),
+            MapState::Unexplored => Rgb([120, 120, 120]),
+            MapState::Frontier => Rgb([204, 121, 167]),
+            MapState::Assigned => Rgb([240, 228, 66]),
+            MapState::Obstacle => Rgb([0, 114, 178]),
+        })
+    }
+
+    /// Gray levels of [`MapState::to_luma`], e.g. for print.
+    pub fn grayscale() -> Self {
+        Self::from_fn(|state| {
+            let [level] = state.to_luma().0;
+            Rgb([level; 3])
+        })
+    }
+
+    /// The color of the given state.
+    pub fn color(&self, state: MapState) -> Rgb<u8> {
+        self.colors[usize::from(u8::from(&state))]
+    }
+
+    /// Change the color of a single state.
+    pub fn set(&mut self, state: MapState, color: Rgb<u8>) {
+        self.colors[usize::from(u8::from(&state))] = color;
+    }
+
+    /// Same as [`ColorMap::set`], but allows chaining.
+    pub fn with(mut self, state: MapState, color: Rgb<u8>) -> Self {
+        self.set(state, color);
+        self
+    }
+}
+
+impl Default for ColorMap {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATES: [MapState; 8] = [
+        MapState::OutOfMap,
+        MapState::OtherRobot,
+        MapState::MyRobot,
+        MapState::Explored,
+        MapState::Unexplored,
+        MapState::Frontier,
+        MapState::Assigned,
+        MapState::Obstacle,
+    ];
+
+    #[test]
+    fn from_fn_covers_all_states() {
+        let colors = ColorMap::from_fn(|state| Rgb([u8::from(&state); 3]));
+
+        for (i, state) in STATES.into_iter().enumerate() {
+            assert_eq!(colors.color(state), Rgb([i as u8; 3]));
+        }
+    }
+
+    #[test]
+    fn palettes_are_distinct() {
+        for colors in [
+            ColorMap::default(),
+            ColorMap::colorblind_safe(),
+            ColorMap::grayscale(),
+        ] {
+            for (i, a) in STATES.iter().enumerate() {
+                for b in &STATES[i + 1..] {
+                    assert_ne!(colors.color(*a), colors.color(*b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn with_changes_single_color() {
+        let colors =
+            ColorMap::default().with(MapState::Frontier, Rgb([1, 2, 3]));
+
+        assert_eq!(colors.color(MapState::Frontier), Rgb([1, 2, 3]));
+        assert_eq!(
+            colors.color(MapState::Explored),
+            MapState::Explored.to_rgb()
+        );
+    }
+}
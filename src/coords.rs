@@ -1,9 +1,20 @@
-use std::ops::{Add, Deref, Div, Mul, Sub};
+use core::ops::{Add, Deref, Div, Mul, Sub};
+use num::Float;
 
-use crate::LocationError;
+use crate::{CellMapError, LocationError, OutOfMapError};
 
 /// Create 3D coordinates. Assumes *meter* as the unit of measurement.
 ///
+/// The scalar type `S` defaults to [`f64`]. On targets without hardware
+/// support for double precision, [`f32`] can be used to store and compute
+/// with coordinates; see the [`From`] implementations for converting between
+/// the two. The maps (e.g. [`crate::CellMap`]) store their parameters as
+/// [`f64`], but the conversion of locations into map indices (see
+/// [`crate::CellMap::location_to_map_index`]) is carried out in the scalar type
+/// of the location, so [`f32`] locations can be looked up without [`f64`]
+/// arithmetic. Accessing the map through [`crate::Location`] still requires
+/// [`f64`] locations.
+///
 /// # Examples
 ///
 /// ```
@@ -14,14 +25,14 @@ use crate::LocationError;
 /// assert_eq!(coords.z, 3.0);
 /// ```
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Coords {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+pub struct Coords<S = f64> {
+    pub x: S,
+    pub y: S,
+    pub z: S,
 }
 
-impl Coords {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+impl<S: Float> Coords<S> {
+    pub fn new(x: S, y: S, z: S) -> Self {
         Self { x, y, z }
     }
 
@@ -36,7 +47,7 @@ impl Coords {
     /// let p2 = Coords::new(1.0, 2.0, 3.0);
     /// assert_eq!(p1.distance_x(&p2), 1.0);
     /// ```
-    pub fn distance_x(&self, other: &Self) -> f64 {
+    pub fn distance_x(&self, other: &Self) -> S {
         (other.x - self.x).abs()
     }
 
@@ -50,7 +61,7 @@ impl Coords {
     /// let p2 = Coords::new(1.0, 2.0, 3.0);
     /// assert_eq!(p1.distance_y(&p2), 2.0);
     /// ```
-    pub fn distance_y(&self, other: &Self) -> f64 {
+    pub fn distance_y(&self, other: &Self) -> S {
         (other.y - self.y).abs()
     }
 
@@ -64,7 +75,7 @@ impl Coords {
     /// let p2 = Coords::new(1.0, 2.0, 3.0);
     /// assert_eq!(p1.distance_z(&p2), 3.0);
     /// ```
-    pub fn distance_z(&self, other: &Self) -> f64 {
+    pub fn distance_z(&self, other: &Self) -> S {
         (other.z - self.z).abs()
     }
 
@@ -97,25 +108,97 @@ impl Coords {
     /// let p2 = Coords::new(1.0, 1.0, random_z_value);
     /// assert_eq!(p1.distance(&p2), 2.0_f64.sqrt());
     /// ```
-    pub fn distance(&self, other: &Self) -> f64 {
+    pub fn distance(&self, other: &Self) -> S {
         (self.distance_x(other).powi(2)
             + self.distance_y(other).powi(2)
             + self.distance_z(other).powi(2))
         .sqrt()
     }
 
-    pub fn x(&self) -> f64 {
+    /// Compute the dot product, treating both coordinates as vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::Coords;
+    ///
+    /// let a = Coords::new(1.0, 2.0, 3.0);
+    /// let b = Coords::new(4.0, -5.0, 6.0);
+    /// assert_eq!(a.dot(&b), 12.0);
+    /// ```
+    pub fn dot(&self, other: &Self) -> S {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Compute the length of the coordinates as a vector, i.e. their distance
+    /// to the origin. See also [`Coords::distance`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::Coords;
+    ///
+    /// assert_eq!(Coords::new(3.0, 0.0, 4.0).norm(), 5.0);
+    /// ```
+    pub fn norm(&self) -> S {
+        self.dot(self).sqrt()
+    }
+
+    /// Scale the coordinates to a length of `1`, e.g. to obtain a direction.
+    ///
+    /// Returns [`None`] if the length is zero, NaN or infinite, since there is
+    /// no such direction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::Coords;
+    ///
+    /// let robot = Coords::new(1.0, 1.0, 0.0);
+    /// let goal = Coords::new(4.0, 5.0, 0.0);
+    ///
+    /// let direction = (goal - robot).normalize().unwrap();
+    ///
+    /// assert_eq!(direction, Coords::new(0.6, 0.8, 0.0));
+    /// assert_eq!(Coords::new(0.0, 0.0, 0.0).normalize(), None);
+    /// ```
+    pub fn normalize(&self) -> Option<Self> {
+        let norm = self.norm();
+        norm.is_normal().then(|| *self / norm)
+    }
+
+    /// Linearly interpolate between these coordinates (`t = 0`) and `other`
+    /// (`t = 1`), e.g. to place intermediate goals along a straight path.
+    ///
+    /// Values of `t` outside of `[0, 1]` extrapolate beyond either end.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::Coords;
+    ///
+    /// let start = Coords::new(0.0, 0.0, 0.0);
+    /// let end = Coords::new(10.0, -4.0, 2.0);
+    ///
+    /// assert_eq!(start.lerp(&end, 0.25), Coords::new(2.5, -1.0, 0.5));
+    /// assert_eq!(start.lerp(&end, 1.0), end);
+    /// ```
+    pub fn lerp(&self, other: &Self, t: S) -> Self {
+        *self + (*other - *self) * t
+    }
+
+    pub fn x(&self) -> S {
         self.x
     }
-    pub fn y(&self) -> f64 {
+    pub fn y(&self) -> S {
         self.y
     }
-    pub fn z(&self) -> f64 {
+    pub fn z(&self) -> S {
         self.z
     }
 }
 
-impl Add for Coords {
+impl<S: Float> Add for Coords<S> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -123,7 +206,7 @@ impl Add for Coords {
     }
 }
 
-impl Sub for Coords {
+impl<S: Float> Sub for Coords<S> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
@@ -131,7 +214,7 @@ impl Sub for Coords {
     }
 }
 
-impl Div for Coords {
+impl<S: Float> Div for Coords<S> {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
@@ -139,7 +222,7 @@ impl Div for Coords {
     }
 }
 
-impl Mul for Coords {
+impl<S: Float> Mul for Coords<S> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
@@ -147,12 +230,55 @@ impl Mul for Coords {
     }
 }
 
-impl From<AxisResolution> for Coords {
-    fn from(value: AxisResolution) -> Self {
+/// Scale all components by the same factor.
+impl<S: Float> Mul<S> for Coords<S> {
+    type Output = Self;
+
+    fn mul(self, rhs: S) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+/// Divide all components by the same divisor.
+impl<S: Float> Div<S> for Coords<S> {
+    type Output = Self;
+
+    fn div(self, rhs: S) -> Self::Output {
+        Self::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+impl<S: Float> From<AxisResolution<S>> for Coords<S> {
+    fn from(value: AxisResolution<S>) -> Self {
         Self::new(value.x, value.y, value.z)
     }
 }
 
+impl From<Coords<f32>> for Coords<f64> {
+    fn from(value: Coords<f32>) -> Self {
+        Self::new(value.x.into(), value.y.into(), value.z.into())
+    }
+}
+
+/// A [`geo::Coord`] lies in the plane, so the `z` component is `0`.
+#[cfg(feature = "std")]
+impl<S: Float + geo::CoordNum> From<geo::Coord<S>> for Coords<S> {
+    fn from(value: geo::Coord<S>) -> Self {
+        Self::new(value.x, value.y, S::zero())
+    }
+}
+
+/// The `z` component is dropped, as [`geo`] works in the plane.
+#[cfg(feature = "std")]
+impl<S: Float + geo::CoordNum> From<Coords<S>> for geo::Coord<S> {
+    fn from(value: Coords<S>) -> Self {
+        geo::Coord {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
 /// Explicitly describe real world coordinates.
 ///
 /// A thin wrapper around [`Coords`] which allows making a clear distinction
@@ -169,14 +295,14 @@ impl From<AxisResolution> for Coords {
 /// where offsetting takes place internally, but it is outside the scope of this
 /// description.
 // See [`RealWorldLocation::into_internal`] for more details.
-#[derive(Debug, PartialEq, Clone)]
-pub struct RealWorldLocation {
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RealWorldLocation<S = f64> {
     /// The location in terms of real world coordinates.
-    location: Coords,
+    location: Coords<S>,
 }
 
-impl RealWorldLocation {
-    pub fn new(location: Coords) -> Self {
+impl<S: Float> RealWorldLocation<S> {
+    pub fn new(location: Coords<S>) -> Self {
         Self { location }
     }
 
@@ -184,10 +310,66 @@ impl RealWorldLocation {
     ///
     /// Think of it as a convenience function which takes care of creating the
     /// actual [`Coords`] type for you. See also [`Coords::new`].
-    pub fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+    pub fn from_xyz(x: S, y: S, z: S) -> Self {
         Self::new(Coords::new(x, y, z))
     }
 
+    pub fn location(&self) -> &Coords<S> {
+        &self.location
+    }
+    pub fn x(&self) -> S {
+        self.location().x
+    }
+    pub fn y(&self) -> S {
+        self.location().y
+    }
+    pub fn z(&self) -> S {
+        self.location().z
+    }
+}
+
+impl RealWorldLocation {
+    /// Quantize the location into a key which can be hashed, e.g. to keep
+    /// track of visited locations in a [`HashSet`](std::collections::HashSet).
+    ///
+    /// Each component is multiplied by the `resolution` of its axis and
+    /// rounded down, so all locations within the same cell of a grid with
+    /// that resolution share the same key. The grid starts at the origin and
+    /// ignores the offset of any map: its cells only line up with those of a
+    /// map whose offset is a multiple of the cell size, otherwise use
+    /// [`crate::CellMap::location_to_map_index`].
+    ///
+    /// A resolution of `0` ignores the axis, e.g. to only consider the `x`
+    /// and `y` components. NaN components are mapped to `0`, and components
+    /// beyond the range of [`i64`] saturate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use local_robot_map::{AxisResolution, RealWorldLocation};
+    ///
+    /// let resolution = AxisResolution::new(2.0, 2.0, 0.0);
+    /// let mut visited = HashSet::new();
+    ///
+    /// let location = RealWorldLocation::from_xyz(1.1, 3.9, 0.0);
+    /// visited.insert(location.hash_key(resolution));
+    ///
+    /// assert!(visited.contains(
+    ///     &RealWorldLocation::from_xyz(1.4, 3.6, 5.0).hash_key(resolution)
+    /// ));
+    /// assert!(!visited.contains(
+    ///     &RealWorldLocation::from_xyz(1.6, 3.6, 0.0).hash_key(resolution)
+    /// ));
+    /// ```
+    pub fn hash_key(&self, resolution: AxisResolution) -> [i64; 3] {
+        let quantized = self.location * Coords::from(resolution);
+        // Casting saturates and maps NaN to zero, as documented above.
+        [quantized.x, quantized.y, quantized.z]
+            .map(|value| value.floor() as i64)
+    }
+
     /// Translate from real-world coordinates to internal ones.
     ///
     /// # What is happening
@@ -209,31 +391,40 @@ impl RealWorldLocation {
         resolution: AxisResolution,
     ) -> Result<InternalLocation, (LocationError, InternalLocation)> {
         InternalLocation::new(
-            (self.location - offset) * resolution.into(),
+            (self.location - offset) * Coords::from(resolution),
             offset,
             resolution,
         )
     }
+}
 
-    pub fn location(&self) -> &Coords {
-        &self.location
+impl From<RealWorldLocation<f32>> for RealWorldLocation<f64> {
+    fn from(value: RealWorldLocation<f32>) -> Self {
+        Self::new(value.location.into())
     }
-    pub fn x(&self) -> f64 {
-        self.location().x
-    }
-    pub fn y(&self) -> f64 {
-        self.location().y
+}
+
+/// The `z` component is `0`, see [`Coords`].
+#[cfg(feature = "std")]
+impl<S: Float + geo::CoordNum> From<geo::Coord<S>> for RealWorldLocation<S> {
+    fn from(value: geo::Coord<S>) -> Self {
+        Self::new(value.into())
     }
-    pub fn z(&self) -> f64 {
-        self.location().z
+}
+
+/// The `z` component is dropped, see [`Coords`].
+#[cfg(feature = "std")]
+impl<S: Float + geo::CoordNum> From<&RealWorldLocation<S>> for geo::Coord<S> {
+    fn from(value: &RealWorldLocation<S>) -> Self {
+        (*value.location()).into()
     }
 }
 
-impl Deref for RealWorldLocation {
-    type Target = Coords;
+impl<S> Deref for RealWorldLocation<S> {
+    type Target = Coords<S>;
 
     fn deref(&self) -> &Self::Target {
-        self.location()
+        &self.location
     }
 }
 
@@ -275,17 +466,27 @@ impl InternalLocation {
             offset,
             resolution,
         };
-        if location.x() >= 0.0 && location.y() >= 0.0 && location.z() >= 0.0 {
-            Ok(iloc)
+        let axes = [location.x(), location.y(), location.z()]
+            .map(|value| value.is_nan() || value < 0.0);
+        if axes.contains(&true) {
+            // The extent of the map is not known at this point.
+            let error = OutOfMapError::new(
+                RealWorldLocation::new(
+                    location / Coords::from(resolution) + offset,
+                ),
+                None,
+                axes,
+            );
+            Err((error.into(), iloc))
         } else {
-            Err((LocationError::OutOfMap, iloc))
+            Ok(iloc)
         }
     }
 
     /// Translate from internal location back to the original real-world one.
     pub(crate) fn into_real_world(self) -> RealWorldLocation {
         RealWorldLocation::new(
-            self.location / self.resolution.into() + self.offset,
+            self.location / Coords::from(self.resolution) + self.offset,
         )
     }
 
@@ -374,13 +575,13 @@ impl InternalLocation {
 /// assert_eq!(map.height(), 10);
 /// ```
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub struct AxisResolution {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+pub struct AxisResolution<S = f64> {
+    pub x: S,
+    pub y: S,
+    pub z: S,
 }
 
-impl AxisResolution {
+impl<S: Float> AxisResolution<S> {
     /// Create an [`AxisResolution`]
     ///
     /// # Example
@@ -397,10 +598,40 @@ impl AxisResolution {
     ///     }
     /// );
     /// ```
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+    pub fn new(x: S, y: S, z: S) -> Self {
         Self { x, y, z }
     }
 
+    /// Fallible version of [`AxisResolution::new`], rejecting resolutions
+    /// which cannot describe a map.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CellMapError::InvalidResolution`] if any axis is zero,
+    /// negative, NaN or infinite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, CellMapError};
+    ///
+    /// assert!(AxisResolution::try_new(1.0, 2.0, 1.0).is_ok());
+    /// assert_eq!(
+    ///     AxisResolution::try_new(1.0, 0.0, 1.0),
+    ///     Err(CellMapError::InvalidResolution)
+    /// );
+    /// ```
+    pub fn try_new(x: S, y: S, z: S) -> Result<Self, CellMapError> {
+        if [x, y, z]
+            .iter()
+            .all(|value| value.is_finite() && *value > S::zero())
+        {
+            Ok(Self::new(x, y, z))
+        } else {
+            Err(CellMapError::InvalidResolution)
+        }
+    }
+
     /// Create [`AxisResolution`] with the same resolution for each axis
     ///
     /// # Example
@@ -417,7 +648,7 @@ impl AxisResolution {
     ///     }
     /// );
     /// ```
-    pub fn uniform(resolution: f64) -> Self {
+    pub fn uniform(resolution: S) -> Self {
         Self {
             x: resolution,
             y: resolution,
@@ -426,9 +657,15 @@ impl AxisResolution {
     }
 }
 
-impl Default for AxisResolution {
+impl<S: Float> Default for AxisResolution<S> {
     fn default() -> Self {
-        AxisResolution::new(1.0, 1.0, 1.0)
+        AxisResolution::uniform(S::one())
+    }
+}
+
+impl From<AxisResolution<f32>> for AxisResolution<f64> {
+    fn from(value: AxisResolution<f32>) -> Self {
+        Self::new(value.x.into(), value.y.into(), value.z.into())
     }
 }
 
@@ -436,6 +673,25 @@ impl Default for AxisResolution {
 mod tests {
     use super::*;
 
+    #[test]
+    fn single_precision() {
+        let p1: Coords<f32> = Coords::new(0.0, 0.0, 0.0);
+        let p2 = Coords::new(3.0_f32, 4.0, 0.0);
+        assert_eq!(p1.distance(&p2), 5.0_f32);
+
+        let location = RealWorldLocation::from_xyz(1.5_f32, -2.25, 0.5);
+        let location: RealWorldLocation = location.into();
+        assert_eq!(location, RealWorldLocation::from_xyz(1.5, -2.25, 0.5));
+
+        let resolution: AxisResolution =
+            AxisResolution::uniform(0.5_f32).into();
+        assert_eq!(resolution, AxisResolution::uniform(0.5));
+        assert_eq!(
+            AxisResolution::<f32>::default(),
+            AxisResolution::uniform(1.0)
+        );
+    }
+
     /// Considering the explanation from [`RealWorldLocation::into_internal`],
     /// we can draw a bounding box around the following locations whose
     /// origin sits at `(-1.0, -1.0, -1.0)`; this will be our offset into
@@ -604,4 +860,15 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn scalar_operations() {
+        let coords = Coords::new(1.0, -2.0, 4.0);
+
+        assert_eq!(coords * 2.0, Coords::new(2.0, -4.0, 8.0));
+        assert_eq!(coords / 4.0, Coords::new(0.25, -0.5, 1.0));
+        assert_eq!(coords.lerp(&coords, 0.7), coords);
+        assert_eq!(coords.normalize().unwrap().norm(), 1.0);
+        assert_eq!(Coords::new(f64::NAN, 0.0, 0.0).normalize(), None);
+    }
 }
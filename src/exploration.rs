@@ -0,0 +1,285 @@
+//! Selection of the next exploration goal.
+//!
+//! Choosing where to go next is a trade-off between how much a goal reveals
+//! and how far away it is. [`select_next_goal`] combines the building blocks
+//! of the crate into a single call:
+//!
+//! 1. Candidate goals are the centroids of the frontier clusters, see
+//!    [`CellMap::cluster_frontiers`].
+//! 2. The information gain of a candidate is the number of unexplored cells a
+//!    sensor would see from it, see [`CellMap::information_gain`].
+//! 3. The travel cost is the distance from the current robot around
+//!    obstacles, see [`CellMap::wavefront`].
+//!
+//! The candidates are ranked by their utility, a weighted difference of gain
+//! and cost, see [`GoalWeights`].
+
+use alloc::vec::Vec;
+use num::ToPrimitive;
+
+use crate::{CellMap, LocalMap, RealWorldLocation};
+
+/// Weights of the terms of the utility of a goal, see [`select_next_goal`].
+///
+/// The utility of a goal is `gain * information gain - distance * travel
+/// distance`, where the information gain is a number of cells and the travel
+/// distance is given in real-world units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoalWeights {
+    /// Utility of each unexplored cell seen from the goal.
+    pub gain: f64,
+    /// Cost of each real-world unit traveled to the goal.
+    pub distance: f64,
+    /// Radius within which the sensor sees unexplored cells, in real-world
+    /// units.
+    pub sensor_radius: f64,
+    /// Frontier clusters with fewer cells are not considered, see
+    /// [`CellMap::cluster_frontiers`].
+    pub min_cluster_size: usize,
+}
+
+impl Default for GoalWeights {
+    /// Gain and distance weighted equally, a sensor radius of `1.0` and no
+    /// minimum cluster size.
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            distance: 1.0,
+            sensor_radius: 1.0,
+            min_cluster_size: 1,
+        }
+    }
+}
+
+/// A candidate goal along with the terms of its utility.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedGoal {
+    location: RealWorldLocation,
+    cluster_size: usize,
+    gain: usize,
+    distance: f64,
+    utility: f64,
+}
+
+impl RankedGoal {
+    /// Where to go, i.e. the centroid of a frontier cluster.
+    pub fn location(&self) -> &RealWorldLocation {
+        &self.location
+    }
+    /// Number of frontier cells of the cluster.
+    pub fn cluster_size(&self) -> usize {
+        self.cluster_size
+    }
+    /// Number of unexplored cells which are visible from the goal.
+    pub fn gain(&self) -> usize {
+        self.gain
+    }
+    /// Travel distance from the current robot to the goal.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+    /// The weighted utility the goals are ranked by.
+    pub fn utility(&self) -> f64 {
+        self.utility
+    }
+}
+
+/// Rank the frontier clusters of the map as goals for the current robot, best
+/// first.
+///
+/// See the [module](self) documentation for how goals are ranked. Goals with
+/// the same utility are ordered by increasing distance. Clusters whose
+/// centroid cannot be reached, e.g. because it lies on an obstacle, are left
+/// out, as are all goals if the current robot is outside the map.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     exploration::{select_next_goal, GoalWeights},
+///     AxisResolution, CellMap, LocalMap, MapState, RealWorldLocation, Robot,
+/// };
+///
+/// let mut map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+/// // Everything is explored, except for a large area on the right bordered by
+/// // frontiers. Another frontier cell close to the robot has nothing
+/// // unexplored behind it.
+/// map.set_rect(
+///     &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     &RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+///     MapState::Explored,
+/// );
+/// map.set_rect(
+///     &RealWorldLocation::from_xyz(15.0, 0.0, 0.0),
+///     &RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+///     MapState::Unexplored,
+/// );
+/// map.set_rect(
+///     &RealWorldLocation::from_xyz(14.0, 0.0, 0.0),
+///     &RealWorldLocation::from_xyz(15.0, 10.0, 0.0),
+///     MapState::Frontier,
+/// );
+/// map.set_rect(
+///     &RealWorldLocation::from_xyz(3.0, 0.0, 0.0),
+///     &RealWorldLocation::from_xyz(4.0, 1.0, 0.0),
+///     MapState::Frontier,
+/// );
+/// let map = LocalMap::new_noexpand(
+///     map,
+///     Robot::new(RealWorldLocation::from_xyz(1.5, 5.5, 0.0), ()),
+///     vec![],
+/// )
+/// .unwrap();
+/// let weights = GoalWeights {
+///     gain: 2.0,
+///     distance: 0.5,
+///     sensor_radius: 3.0,
+///     ..Default::default()
+/// };
+///
+/// let goals = select_next_goal(&map, &weights);
+///
+/// // The large unexplored area is worth the longer way.
+/// assert_eq!(goals.len(), 2);
+/// assert_eq!(
+///     goals[0].location(),
+///     &RealWorldLocation::from_xyz(14.5, 5.0, 0.0)
+/// );
+/// assert!(goals[0].distance() > goals[1].distance());
+/// ```
+pub fn select_next_goal<P>(
+    map: &LocalMap<CellMap, P>,
+    weights: &GoalWeights,
+) -> Vec<RankedGoal> {
+    let cells = map.map();
+    let Ok(distances) =
+        cells.wavefront(core::slice::from_ref(map.my_position()))
+    else {
+        return Vec::new();
+    };
+
+    let mut goals: Vec<RankedGoal> = cells
+        .cluster_frontiers(weights.min_cluster_size)
+        .into_iter()
+        .filter_map(|cluster| {
+            let location = *cluster.centroid();
+            let distance =
+                distances[cells.location_to_map_index(&location).ok()?];
+            if !distance.is_finite() {
+                return None;
+            }
+            let gain = cells
+                .information_gain(&location, weights.sensor_radius)
+                .ok()?;
+            let utility = weights.gain
+                * gain.to_f64().expect("usize to f64 should work")
+                - weights.distance * distance;
+            Some(RankedGoal {
+                location,
+                cluster_size: cluster.size(),
+                gain,
+                distance,
+                utility,
+            })
+        })
+        .collect();
+    goals.sort_by(|a, b| {
+        b.utility
+            .total_cmp(&a.utility)
+            .then(a.distance.total_cmp(&b.distance))
+    });
+    goals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, MapState, Robot};
+
+    /// A map with a frontier cell on either side of the robot, at the given
+    /// distances, with unexplored cells behind them.
+    fn make_local_map(left: f64, right: f64) -> LocalMap<CellMap, ()> {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(-10.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 1.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_rect(
+            &RealWorldLocation::from_xyz(-left, 0.0, 0.0),
+            &RealWorldLocation::from_xyz(right, 1.0, 0.0),
+            MapState::Explored,
+        );
+        for x in [-left - 0.5, right + 0.5] {
+            map.set_rect(
+                &RealWorldLocation::from_xyz(x - 0.5, 0.0, 0.0),
+                &RealWorldLocation::from_xyz(x + 0.5, 1.0, 0.0),
+                MapState::Frontier,
+            );
+        }
+        LocalMap::new_noexpand(
+            map,
+            Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+            vec![],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn equal_gain_prefers_closer_goal() {
+        let map = make_local_map(3.0, 5.0);
+
+        let goals = select_next_goal(&map, &GoalWeights::default());
+
+        assert_eq!(goals.len(), 2);
+        assert_eq!(goals[0].location().x(), -3.5);
+        assert_eq!(goals[0].gain(), goals[1].gain());
+        assert_eq!(goals[0].distance(), 4.0);
+        assert_eq!(goals[0].utility(), goals[0].gain() as f64 - 4.0);
+    }
+
+    #[test]
+    fn only_distance_counts_without_gain_weight() {
+        let map = make_local_map(6.0, 2.0);
+        let weights = GoalWeights {
+            gain: 0.0,
+            sensor_radius: 5.0,
+            ..Default::default()
+        };
+
+        let goals = select_next_goal(&map, &weights);
+
+        assert_eq!(goals[0].location().x(), 2.5);
+        assert_eq!(goals[0].utility(), -2.0);
+    }
+
+    #[test]
+    fn unreachable_clusters_are_left_out() {
+        let mut map = make_local_map(3.0, 5.0);
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
+            &RealWorldLocation::from_xyz(3.0, 1.0, 0.0),
+            MapState::Obstacle,
+        );
+
+        let goals = select_next_goal(&map, &GoalWeights::default());
+
+        assert_eq!(goals.len(), 1);
+        assert_eq!(goals[0].location().x(), -3.5);
+    }
+
+    #[test]
+    fn no_frontiers() {
+        let mut map = make_local_map(3.0, 5.0);
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(-10.0, 0.0, 0.0),
+            &RealWorldLocation::from_xyz(10.0, 1.0, 0.0),
+            MapState::Explored,
+        );
+
+        assert!(select_next_goal(&map, &GoalWeights::default()).is_empty());
+    }
+}
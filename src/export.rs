@@ -0,0 +1,213 @@
+//! Export of waypoint lists and planned paths.
+//!
+//! Once goals are ordered (see [`crate::tour`]) or a coverage path is planned,
+//! the waypoints need to be handed to whatever drives the robot. Two formats
+//! are supported:
+//!
+//! - CSV, e.g. for simulators, via [`write_csv`]. The local coordinates are
+//!   always written, and latitude, longitude and altitude are added when a
+//!   [`LocalTangentPlane`] is given.
+//! - GPX, for GPS-driven vehicles and flight controllers, via [`write_gpx`].
+//!   The waypoints are written as a route, converted to WGS84 using the given
+//!   [`LocalTangentPlane`].
+//!
+//! Requires the `std` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use local_robot_map::{
+//!     export::{write_csv, write_gpx},
+//!     geo_coords::{GeoPoint, LocalTangentPlane},
+//!     RealWorldLocation,
+//! };
+//!
+//! let plane = LocalTangentPlane::new(
+//!     GeoPoint::from_degrees(48.137, 11.575, 520.0).unwrap(),
+//! );
+//! let waypoints = [
+//!     RealWorldLocation::from_xyz(0.0, 0.0, 10.0),
+//!     RealWorldLocation::from_xyz(25.0, 40.0, 10.0),
+//! ];
+//!
+//! let mut csv = Vec::new();
+//! write_csv(&mut csv, &waypoints, None).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(csv).unwrap(),
+//!     "index,x,y,z\n0,0,0,10\n1,25,40,10\n"
+//! );
+//!
+//! let mut gpx = Vec::new();
+//! write_gpx(&mut gpx, "Coverage path", &waypoints, &plane).unwrap();
+//! let gpx = String::from_utf8(gpx).unwrap();
+//! assert!(gpx.contains(r#"<rtept lat="48.137000000" lon="11.575000000">"#));
+//! assert!(gpx.contains("<ele>530.000</ele>"));
+//! ```
+
+use std::io::{self, Write};
+
+use crate::geo_coords::LocalTangentPlane;
+use crate::RealWorldLocation;
+
+/// Write `waypoints` as CSV, one row per waypoint in order.
+///
+/// The columns are `index`, `x`, `y` and `z` in the local frame. With a
+/// `plane`, the columns `latitude` and `longitude` in degrees and `altitude`
+/// in meters above the WGS84 ellipsoid are added.
+///
+/// # Errors
+///
+/// Returns any error of `writer`.
+pub fn write_csv<W: Write>(
+    mut writer: W,
+    waypoints: &[RealWorldLocation],
+    plane: Option<&LocalTangentPlane>,
+) -> io::Result<()> {
+    write!(writer, "index,x,y,z")?;
+    if plane.is_some() {
+        write!(writer, ",latitude,longitude,altitude")?;
+    }
+    writeln!(writer)?;
+
+    for (index, waypoint) in waypoints.iter().enumerate() {
+        write!(
+            writer,
+            "{index},{},{},{}",
+            waypoint.x(),
+            waypoint.y(),
+            waypoint.z()
+        )?;
+        if let Some(plane) = plane {
+            let point = plane.to_geodetic(waypoint);
+            write!(
+                writer,
+                ",{:.9},{:.9},{:.3}",
+                point.latitude(),
+                point.longitude(),
+                point.altitude()
+            )?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// Write `waypoints` as a GPX 1.1 document holding a single route called
+/// `name`.
+///
+/// The waypoints are converted to WGS84 using `plane`. The altitude is
+/// written as the elevation of each route point.
+///
+/// # Errors
+///
+/// Returns any error of `writer`.
+pub fn write_gpx<W: Write>(
+    mut writer: W,
+    name: &str,
+    waypoints: &[RealWorldLocation],
+    plane: &LocalTangentPlane,
+) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<gpx version="1.1" creator="local-robot-map" xmlns="http://www.topografix.com/GPX/1/1">"#
+    )?;
+    writeln!(writer, "  <rte>")?;
+    writeln!(writer, "    <name>{}</name>", escape(name))?;
+    for waypoint in waypoints {
+        let point = plane.to_geodetic(waypoint);
+        writeln!(
+            writer,
+            r#"    <rtept lat="{:.9}" lon="{:.9}"><ele>{:.3}</ele></rtept>"#,
+            point.latitude(),
+            point.longitude(),
+            point.altitude()
+        )?;
+    }
+    writeln!(writer, "  </rte>")?;
+    writeln!(writer, "</gpx>")?;
+    writer.flush()
+}
+
+/// Escape the characters with a special meaning in XML text.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_owned(),
+            '<' => "&lt;".to_owned(),
+            '>' => "&gt;".to_owned(),
+            '"' => "&quot;".to_owned(),
+            '\'' => "&apos;".to_owned(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo_coords::GeoPoint;
+
+    fn make_plane() -> LocalTangentPlane {
+        LocalTangentPlane::new(
+            GeoPoint::from_degrees(-33.9, 18.4, 0.0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn csv_with_geo_reference() {
+        let plane = make_plane();
+        let waypoints = [RealWorldLocation::from_xyz(100.0, -50.0, 0.0)];
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &waypoints, Some(&plane)).unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("index,x,y,z,latitude,longitude,altitude")
+        );
+        let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(row[..4], ["0", "100", "-50", "0"]);
+        let latitude: f64 = row[4].parse().unwrap();
+        let longitude: f64 = row[5].parse().unwrap();
+        let back = plane.to_local(
+            &GeoPoint::from_degrees(latitude, longitude, 0.0).unwrap(),
+        );
+        assert!((back.x() - 100.0).abs() < 1e-3);
+        assert!((back.y() + 50.0).abs() < 1e-3);
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn gpx_route_in_order() {
+        let waypoints: Vec<_> = (0..3)
+            .map(|i| RealWorldLocation::from_xyz(0.0, f64::from(i) * 10.0, 0.0))
+            .collect();
+
+        let mut gpx = Vec::new();
+        write_gpx(&mut gpx, "A <test> & more", &waypoints, &make_plane())
+            .unwrap();
+
+        let gpx = String::from_utf8(gpx).unwrap();
+        assert!(gpx.contains("<name>A &lt;test&gt; &amp; more</name>"));
+        let latitudes: Vec<f64> = gpx
+            .lines()
+            .filter_map(|line| line.split(r#"lat=""#).nth(1))
+            .map(|rest| rest.split('"').next().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(latitudes.len(), 3);
+        // Heading north.
+        assert!(latitudes.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(gpx.trim_end().ends_with("</gpx>"));
+    }
+
+    #[test]
+    fn empty_waypoints() {
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &[], None).unwrap();
+
+        assert_eq!(String::from_utf8(csv).unwrap(), "index,x,y,z\n");
+    }
+}
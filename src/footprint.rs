@@ -0,0 +1,127 @@
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{Coords, RealWorldLocation};
+
+/// The area occupied by a robot, see [`Robot::with_footprint`].
+///
+/// A [`LocalMap`] marks all cells covered by the footprint of a robot, rather
+/// than only the cell containing its location. This matters for large
+/// vehicles on maps with a fine resolution.
+///
+/// [`Robot::with_footprint`]: crate::Robot::with_footprint
+/// [`LocalMap`]: crate::LocalMap
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Footprint {
+    /// The robot only occupies the cell containing its location.
+    #[default]
+    Point,
+    /// A disc of the given radius around the location of the robot.
+    Circle(f64),
+    /// A polygon given by its vertices relative to the location of the
+    /// robot, in the robot's frame: `x` points forward and `y` to the left.
+    /// It is rotated by the [`Robot::heading`](crate::Robot::heading), if
+    /// known. Only the `x` and `y` components are used.
+    Polygon(Vec<Coords>),
+}
+
+impl Footprint {
+    /// Radius of the smallest disc around the location of the robot which
+    /// contains the footprint.
+    pub fn bounding_radius(&self) -> f64 {
+        match self {
+            Footprint::Point => 0.0,
+            Footprint::Circle(radius) => radius.max(0.0),
+            Footprint::Polygon(vertices) => vertices
+                .iter()
+                .map(|vertex| vertex.x.hypot(vertex.y))
+                .fold(0.0, f64::max),
+        }
+    }
+
+    /// Whether the footprint of a robot located at `robot` and facing `yaw`
+    /// contains `location`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{Coords, Footprint, RealWorldLocation};
+    ///
+    /// // A 4x2 rectangle, centered on the robot.
+    /// let footprint = Footprint::Polygon(vec![
+    ///     Coords::new(-2.0, -1.0, 0.0),
+    ///     Coords::new(2.0, -1.0, 0.0),
+    ///     Coords::new(2.0, 1.0, 0.0),
+    ///     Coords::new(-2.0, 1.0, 0.0),
+    /// ]);
+    /// let robot = RealWorldLocation::from_xyz(10.0, 10.0, 0.0);
+    /// let ahead = RealWorldLocation::from_xyz(11.5, 10.0, 0.0);
+    /// let left = RealWorldLocation::from_xyz(10.0, 11.5, 0.0);
+    ///
+    /// assert!(footprint.contains(&robot, None, &ahead));
+    /// assert!(!footprint.contains(&robot, None, &left));
+    /// // Facing along the `y` axis.
+    /// let yaw = Some(core::f64::consts::FRAC_PI_2);
+    /// assert!(!footprint.contains(&robot, yaw, &ahead));
+    /// assert!(footprint.contains(&robot, yaw, &left));
+    /// ```
+    pub fn contains(
+        &self,
+        robot: &RealWorldLocation,
+        yaw: Option<f64>,
+        location: &RealWorldLocation,
+    ) -> bool {
+        let (dx, dy) = (location.x - robot.x, location.y - robot.y);
+        match self {
+            Footprint::Point => dx == 0.0 && dy == 0.0,
+            Footprint::Circle(radius) => dx.hypot(dy) <= *radius,
+            Footprint::Polygon(vertices) => {
+                // Rotate the location into the robot's frame.
+                let (sin, cos) = yaw.unwrap_or(0.0).sin_cos();
+                let (x, y) = (dx * cos + dy * sin, dy * cos - dx * sin);
+                let mut inside = false;
+                for (i, a) in vertices.iter().enumerate() {
+                    let b = &vertices[(i + 1) % vertices.len()];
+                    if (a.y > y) != (b.y > y)
+                        && x < (b.x - a.x) * (y - a.y) / (b.y - a.y) + a.x
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounding_radius() {
+        let triangle = Footprint::Polygon(vec![
+            Coords::new(3.0, 0.0, 0.0),
+            Coords::new(-1.0, 1.0, 0.0),
+            Coords::new(-1.0, -1.0, 0.0),
+        ]);
+
+        assert_eq!(Footprint::Point.bounding_radius(), 0.0);
+        assert_eq!(Footprint::Circle(2.0).bounding_radius(), 2.0);
+        assert_eq!(Footprint::Circle(-2.0).bounding_radius(), 0.0);
+        assert_eq!(triangle.bounding_radius(), 3.0);
+    }
+
+    #[test]
+    fn degenerate_polygons_contain_nothing() {
+        let robot = RealWorldLocation::from_xyz(0.0, 0.0, 0.0);
+
+        assert!(!Footprint::Polygon(vec![]).contains(&robot, None, &robot));
+        let line = Footprint::Polygon(vec![
+            Coords::new(-1.0, 0.0, 0.0),
+            Coords::new(1.0, 0.0, 0.0),
+        ]);
+        assert!(!line.contains(&robot, None, &robot));
+    }
+}
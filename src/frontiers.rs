@@ -0,0 +1,161 @@
+use alloc::vec::Vec;
+use num::ToPrimitive;
+
+use crate::{CellMap, MapState, RealWorldLocation};
+
+/// A group of adjacent frontier cells, serving as a candidate exploration
+/// goal.
+///
+/// See [`CellMap::cluster_frontiers`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct FrontierCluster {
+    centroid: RealWorldLocation,
+    size: usize,
+}
+
+impl FrontierCluster {
+    /// The mean of the cluster's cell centers.
+    ///
+    /// Note that for non-convex clusters the centroid does not necessarily
+    /// lie on a frontier cell.
+    pub fn centroid(&self) -> &RealWorldLocation {
+        &self.centroid
+    }
+    /// The number of cells in the cluster.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl CellMap {
+    /// Group adjacent [`MapState::Frontier`] cells into clusters.
+    ///
+    /// Cells are considered adjacent if they share an edge or a corner. Only
+    /// clusters with at least `min_size` cells are returned, ordered by
+    /// decreasing size. This allows to discard tiny frontiers caused by
+    /// sensor noise and hand a manageable number of goals to an allocator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// // A frontier along the bottom edge and a single, isolated one.
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(4.0, 1.0, 0.0),
+    ///     MapState::Frontier,
+    /// );
+    /// map.set_location(
+    ///     &RealWorldLocation::from_xyz(8.5, 8.5, 0.0),
+    ///     MapState::Frontier,
+    /// )
+    /// .unwrap();
+    ///
+    /// let clusters = map.cluster_frontiers(2);
+    ///
+    /// assert_eq!(clusters.len(), 1);
+    /// assert_eq!(clusters[0].size(), 4);
+    /// assert_eq!(
+    ///     clusters[0].centroid(),
+    ///     &RealWorldLocation::from_xyz(2.0, 0.5, 0.0)
+    /// );
+    /// ```
+    pub fn cluster_frontiers(&self, min_size: usize) -> Vec<FrontierCluster> {
+        let mut clusters: Vec<_> = self
+            .components(|value| *value == MapState::Frontier)
+            .into_iter()
+            .filter(|cells| cells.len() >= min_size.max(1))
+            .map(|cells| {
+                let size = cells.len();
+                let (x, y) = cells.iter().fold((0.0, 0.0), |(x, y), index| {
+                    let center = self.index_center::<f64>(*index);
+                    (x + center.x, y + center.y)
+                });
+                let n = size.to_f64().expect("usize to f64 should work");
+                FrontierCluster {
+                    centroid: RealWorldLocation::from_xyz(
+                        x / n,
+                        y / n,
+                        self.offset().z,
+                    ),
+                    size,
+                }
+            })
+            .collect();
+        clusters.sort_by_key(|cluster| core::cmp::Reverse(cluster.size));
+        clusters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(-5.0, -5.0, 0.0),
+            RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    fn set(map: &mut CellMap, x: f64, y: f64) {
+        map.set_location(
+            &RealWorldLocation::from_xyz(x, y, 0.0),
+            MapState::Frontier,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn diagonal_cells_are_connected() {
+        let mut map = make_map();
+        set(&mut map, 0.5, 0.5);
+        set(&mut map, 1.5, 1.5);
+        set(&mut map, 2.5, 2.5);
+
+        let clusters = map.cluster_frontiers(1);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].size(), 3);
+        assert_eq!(
+            clusters[0].centroid(),
+            &RealWorldLocation::from_xyz(1.5, 1.5, 0.0)
+        );
+    }
+
+    #[test]
+    fn sorted_by_size_and_filtered() {
+        let mut map = make_map();
+        set(&mut map, -4.5, -4.5);
+        set(&mut map, 4.5, 4.5);
+        set(&mut map, 3.5, 4.5);
+        set(&mut map, -4.5, 4.5);
+        set(&mut map, -4.5, 3.5);
+        set(&mut map, -4.5, 2.5);
+
+        let sizes: Vec<_> =
+            map.cluster_frontiers(0).iter().map(|c| c.size()).collect();
+        assert_eq!(sizes, [3, 2, 1]);
+
+        let sizes: Vec<_> =
+            map.cluster_frontiers(2).iter().map(|c| c.size()).collect();
+        assert_eq!(sizes, [3, 2]);
+    }
+
+    #[test]
+    fn no_frontiers() {
+        let map = make_map();
+
+        assert!(map.cluster_frontiers(1).is_empty());
+    }
+}
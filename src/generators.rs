@@ -0,0 +1,719 @@
+//! Random maps for tests, benchmarks and experiments.
+//!
+//! All generators draw their randomness from a caller-provided [`Rng`], such
+//! that seeding it (see [`rand::SeedableRng`]) makes the generated maps
+//! reproducible.
+//!
+//! Requires the `generators` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use local_robot_map::{
+//!     generators::obstacle_field, AxisResolution, MapState, MaskMapState,
+//!     RealWorldLocation,
+//! };
+//! use rand::{rngs::SmallRng, SeedableRng};
+//!
+//! let generate = |seed| {
+//!     obstacle_field(
+//!         &mut SmallRng::seed_from_u64(seed),
+//!         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+//!         RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+//!         AxisResolution::uniform(1.0),
+//!         0.3,
+//!     )
+//!     .unwrap()
+//! };
+//!
+//! // The same seed always results in the same map.
+//! assert_eq!(generate(7), generate(7));
+//! assert!(!generate(7).get_map_state(MapState::Obstacle).is_empty());
+//! ```
+
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+use num::ToPrimitive;
+use rand::Rng;
+
+use crate::{
+    AxisResolution, CellMap, CellMapError, MapState, RealWorldLocation,
+};
+#[cfg(feature = "std")]
+use crate::{PolygonMap, PolygonMapError};
+
+/// Create a map in which every cell independently is a
+/// [`MapState::Obstacle`] with probability `density`, and
+/// [`MapState::Unexplored`] otherwise.
+///
+/// # Errors
+///
+/// - [`GeneratorError::InvalidProbability`] if `density` is not within
+///   `0.0..=1.0`.
+/// - [`GeneratorError::InvalidMap`] if the map cannot be created, see
+///   [`CellMap::try_new`].
+pub fn obstacle_field<R: Rng + ?Sized>(
+    rng: &mut R,
+    point1: RealWorldLocation,
+    point2: RealWorldLocation,
+    resolution: AxisResolution,
+    density: f64,
+) -> Result<CellMap, GeneratorError> {
+    check_probability(density)?;
+    let mut map = CellMap::try_new(point1, point2, resolution)?;
+    for row in 0..map.height() {
+        for col in 0..map.width() {
+            if rng.gen_bool(density) {
+                map.set_cell([row, col], MapState::Obstacle);
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// Create a map in which a random, connected region covering the fraction
+/// `explored` of the map is [`MapState::Explored`].
+///
+/// The region grows from a random cell by repeatedly exploring a random cell
+/// next to it. The unexplored cells bordering the region are marked as
+/// [`MapState::Frontier`], as an exploring robot would.
+///
+/// # Errors
+///
+/// - [`GeneratorError::InvalidProbability`] if `explored` is not within
+///   `0.0..=1.0`.
+/// - [`GeneratorError::InvalidMap`] if the map cannot be created, see
+///   [`CellMap::try_new`].
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     generators::partially_explored, AxisResolution, MapState, MaskMapState,
+///     RealWorldLocation,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let map = partially_explored(
+///     &mut SmallRng::seed_from_u64(1),
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+///     AxisResolution::uniform(1.0),
+///     0.25,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(map.get_map_state(MapState::Explored).len(), 25);
+/// assert!(!map.get_map_state(MapState::Frontier).is_empty());
+/// ```
+pub fn partially_explored<R: Rng + ?Sized>(
+    rng: &mut R,
+    point1: RealWorldLocation,
+    point2: RealWorldLocation,
+    resolution: AxisResolution,
+    explored: f64,
+) -> Result<CellMap, GeneratorError> {
+    check_probability(explored)?;
+    let mut map = CellMap::try_new(point1, point2, resolution)?;
+    let total = map.width() * map.height();
+    let target = (explored * total as f64)
+        .round()
+        .to_usize()
+        .unwrap_or(total)
+        .min(total);
+    if target == 0 {
+        return Ok(map);
+    }
+
+    let mut candidates = alloc::vec![[
+        rng.gen_range(0..map.height()),
+        rng.gen_range(0..map.width())
+    ]];
+    let mut count = 0;
+    while count < target {
+        // The region stays connected, so candidates only run out once all
+        // cells were explored.
+        let index = candidates.swap_remove(rng.gen_range(0..candidates.len()));
+        if map.cells()[index] == MapState::Explored {
+            continue;
+        }
+        map.set_cell(index, MapState::Explored);
+        count += 1;
+        candidates.extend(
+            map.neighbor_indices(index).filter(|neighbor| {
+                map.cells()[*neighbor] != MapState::Explored
+            }),
+        );
+    }
+
+    let frontier: Vec<[usize; 2]> = map
+        .cells()
+        .indexed_iter()
+        .filter(|(_, value)| **value == MapState::Unexplored)
+        .map(|((row, col), _)| [row, col])
+        .filter(|index| {
+            map.neighbor_indices(*index)
+                .any(|neighbor| map.cells()[neighbor] == MapState::Explored)
+        })
+        .collect();
+    for index in frontier {
+        map.set_cell(index, MapState::Frontier);
+    }
+    Ok(map)
+}
+
+/// Create a random simple polygon with `vertices` vertices around `center`.
+///
+/// The polygon is star-shaped: the vertices are placed at random angles
+/// around the center, each at a random distance within
+/// `min_radius..=max_radius`, and connected in counter-clockwise order.
+///
+/// # Errors
+///
+/// - [`GeneratorError::NotEnoughVertices`] if fewer than 3 vertices are
+///   requested.
+/// - [`GeneratorError::InvalidRadius`] if the radii are not finite, or not
+///   satisfying `0.0 < min_radius <= max_radius`.
+/// - [`GeneratorError::InvalidCenter`] if the center is not finite.
+/// - [`GeneratorError::InvalidPolygon`] if the vertices do not form a valid
+///   polygon, e.g. because they lie too far from the origin to be finite.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{generators::random_polygon, RealWorldLocation};
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let polygon = random_polygon(
+///     &mut SmallRng::seed_from_u64(3),
+///     &RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+///     2.0,
+///     4.0,
+///     8,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(polygon.areas()[0].len(), 8);
+/// ```
+#[cfg(feature = "std")]
+pub fn random_polygon<R: Rng + ?Sized>(
+    rng: &mut R,
+    center: &RealWorldLocation,
+    min_radius: f64,
+    max_radius: f64,
+    vertices: usize,
+) -> Result<PolygonMap, GeneratorError> {
+    if vertices < 3 {
+        return Err(GeneratorError::NotEnoughVertices);
+    }
+    if !(min_radius.is_finite()
+        && max_radius.is_finite()
+        && min_radius > 0.0
+        && min_radius <= max_radius)
+    {
+        return Err(GeneratorError::InvalidRadius);
+    }
+    if !(center.x().is_finite()
+        && center.y().is_finite()
+        && center.z().is_finite())
+    {
+        return Err(GeneratorError::InvalidCenter);
+    }
+
+    // Jittering evenly spaced angles keeps the vertices in order while
+    // avoiding degenerate, nearly identical angles.
+    let step = core::f64::consts::TAU / vertices as f64;
+    let points = (0..vertices)
+        .map(|i| {
+            let angle = (i as f64 + rng.gen_range(0.0..0.8)) * step;
+            let radius = rng.gen_range(min_radius..=max_radius);
+            let (sin, cos) = angle.sin_cos();
+            RealWorldLocation::from_xyz(
+                center.x() + radius * cos,
+                center.y() + radius * sin,
+                center.z(),
+            )
+        })
+        .collect();
+    PolygonMap::new(points).map_err(GeneratorError::InvalidPolygon)
+}
+
+/// Create a map whose obstacles follow Perlin noise, resembling natural
+/// terrain with smooth, blob-like obstacles.
+///
+/// The noise is sampled at the center of each cell, and the fraction
+/// `obstacles` of the cells with the highest values become
+/// [`MapState::Obstacle`]; all others are [`MapState::Unexplored`]. The
+/// `feature_size` is the wavelength of the coarsest noise layer in meters, and
+/// thus roughly the size of the obstacles. Finer layers at half the wavelength
+/// (four in total) add detail to their outlines.
+///
+/// # Errors
+///
+/// - [`GeneratorError::InvalidProbability`] if `obstacles` is not within
+///   `0.0..=1.0`.
+/// - [`GeneratorError::InvalidScale`] if `feature_size` is not finite or not
+///   positive.
+/// - [`GeneratorError::InvalidMap`] if the map cannot be created, see
+///   [`CellMap::try_new`].
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     generators::perlin_terrain, AxisResolution, MapState, MaskMapState,
+///     RealWorldLocation,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let map = perlin_terrain(
+///     &mut SmallRng::seed_from_u64(11),
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(50.0, 50.0, 0.0),
+///     AxisResolution::uniform(2.0),
+///     10.0,
+///     0.3,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(map.get_map_state(MapState::Obstacle).len(), 3000);
+/// ```
+pub fn perlin_terrain<R: Rng + ?Sized>(
+    rng: &mut R,
+    point1: RealWorldLocation,
+    point2: RealWorldLocation,
+    resolution: AxisResolution,
+    feature_size: f64,
+    obstacles: f64,
+) -> Result<CellMap, GeneratorError> {
+    check_probability(obstacles)?;
+    if !(feature_size.is_finite() && feature_size > 0.0) {
+        return Err(GeneratorError::InvalidScale);
+    }
+    let mut map = CellMap::try_new(point1, point2, resolution)?;
+    let noise = Perlin::new(rng);
+
+    let offset = *map.offset();
+    let mut cells: Vec<([usize; 2], f64)> = map
+        .cells()
+        .indexed_iter()
+        .map(|((row, col), _)| {
+            let center = map.index_center::<f64>([row, col]);
+            let (x, y) = (center.x - offset.x, center.y - offset.y);
+            let value = (0..4)
+                .map(|octave| {
+                    let frequency = f64::from(1 << octave) / feature_size;
+                    noise.sample(x * frequency, y * frequency)
+                        / f64::from(1 << octave)
+                })
+                .sum::<f64>();
+            ([row, col], value)
+        })
+        .collect();
+    cells.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let count = (obstacles * cells.len() as f64)
+        .round()
+        .to_usize()
+        .unwrap_or(cells.len());
+    for (index, _) in cells.into_iter().take(count) {
+        map.set_cell(index, MapState::Obstacle);
+    }
+    Ok(map)
+}
+
+/// Create a cave-like map using a cellular automaton.
+///
+/// Every cell starts out as a [`MapState::Obstacle`] with probability `fill`.
+/// Then, for the given number of `iterations`, a cell becomes an obstacle if
+/// more than 4 of its 8 neighbors are obstacles, and free space
+/// ([`MapState::Unexplored`]) if fewer than 4 are; otherwise it is kept as is.
+/// Neighbors outside the map count as obstacles, which closes off the caves
+/// at the map's border.
+///
+/// A `fill` of about `0.45` with 4 to 5 iterations results in large,
+/// winding caves.
+///
+/// # Errors
+///
+/// - [`GeneratorError::InvalidProbability`] if `fill` is not within
+///   `0.0..=1.0`.
+/// - [`GeneratorError::InvalidMap`] if the map cannot be created, see
+///   [`CellMap::try_new`].
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     generators::cellular_caves, AxisResolution, MapState, MaskMapState,
+///     RealWorldLocation,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let map = cellular_caves(
+///     &mut SmallRng::seed_from_u64(2),
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(40.0, 30.0, 0.0),
+///     AxisResolution::uniform(1.0),
+///     0.45,
+///     5,
+/// )
+/// .unwrap();
+///
+/// assert!(!map.get_map_state(MapState::Obstacle).is_empty());
+/// assert!(!map.get_map_state(MapState::Unexplored).is_empty());
+/// ```
+pub fn cellular_caves<R: Rng + ?Sized>(
+    rng: &mut R,
+    point1: RealWorldLocation,
+    point2: RealWorldLocation,
+    resolution: AxisResolution,
+    fill: f64,
+    iterations: usize,
+) -> Result<CellMap, GeneratorError> {
+    let mut map = obstacle_field(rng, point1, point2, resolution, fill)?;
+    for _ in 0..iterations {
+        let next: Vec<([usize; 2], MapState)> = map
+            .cells()
+            .indexed_iter()
+            .filter_map(|((row, col), value)| {
+                let inside = map.neighbor_indices([row, col]).count();
+                let walls = 8 - inside
+                    + map
+                        .neighbor_indices([row, col])
+                        .filter(|index| {
+                            map.cells()[*index] == MapState::Obstacle
+                        })
+                        .count();
+                let state = match walls {
+                    0..=3 => MapState::Unexplored,
+                    4 => *value,
+                    _ => MapState::Obstacle,
+                };
+                (state != *value).then_some(([row, col], state))
+            })
+            .collect();
+        if next.is_empty() {
+            break;
+        }
+        for (index, state) in next {
+            map.set_cell(index, state);
+        }
+    }
+    Ok(map)
+}
+
+/// Two-dimensional gradient noise, see [Perlin noise on
+/// Wikipedia](https://en.wikipedia.org/wiki/Perlin_noise).
+struct Perlin {
+    /// Randomly shuffled lattice hashes.
+    permutation: [u8; 256],
+}
+
+impl Perlin {
+    fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut permutation = [0; 256];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+        // Fisher-Yates shuffle.
+        for i in (1..permutation.len()).rev() {
+            permutation.swap(i, rng.gen_range(0..=i));
+        }
+        Self { permutation }
+    }
+
+    /// Noise value at the given position, within about `-1.0..=1.0`. The
+    /// lattice has a spacing of `1.0`.
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (dx, dy) = (x - x0, y - y0);
+        // Wrapping the lattice coordinates repeats the noise every 256 units.
+        let wrap = |value: f64| (value as i64).rem_euclid(256) as usize;
+        let (xi, yi) = (wrap(x0), wrap(y0));
+
+        let gradient = |i: usize, j: usize, dx: f64, dy: f64| {
+            let hash = self.permutation
+                [(usize::from(self.permutation[i % 256]) + j) % 256];
+            let (gx, gy) = GRADIENTS[usize::from(hash) % GRADIENTS.len()];
+            gx * dx + gy * dy
+        };
+        let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let lerp = |a: f64, b: f64, t: f64| a + t * (b - a);
+
+        let (u, v) = (fade(dx), fade(dy));
+        let bottom = lerp(
+            gradient(xi, yi, dx, dy),
+            gradient(xi + 1, yi, dx - 1.0, dy),
+            u,
+        );
+        let top = lerp(
+            gradient(xi, yi + 1, dx, dy - 1.0),
+            gradient(xi + 1, yi + 1, dx - 1.0, dy - 1.0),
+            u,
+        );
+        lerp(bottom, top, v)
+    }
+}
+
+/// Gradient directions of [`Perlin`] noise, scaled such that the noise stays
+/// within about `-1.0..=1.0`.
+const GRADIENTS: [(f64, f64); 8] = [
+    (1.0, 1.0),
+    (-1.0, 1.0),
+    (1.0, -1.0),
+    (-1.0, -1.0),
+    (1.414, 0.0),
+    (-1.414, 0.0),
+    (0.0, 1.414),
+    (0.0, -1.414),
+];
+
+fn check_probability(value: f64) -> Result<(), GeneratorError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(GeneratorError::InvalidProbability)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GeneratorError {
+    /// The map could not be created.
+    InvalidMap(CellMapError),
+    /// A probability or fraction is NaN or outside of `0.0..=1.0`.
+    InvalidProbability,
+    /// At least 3 vertices are needed to form a polygon.
+    NotEnoughVertices,
+    /// The radii are not finite, not positive, or the minimum radius exceeds
+    /// the maximum one.
+    InvalidRadius,
+    /// The scale of the noise is not finite or not positive.
+    InvalidScale,
+    /// The center of the polygon is not finite.
+    InvalidCenter,
+    /// The generated vertices do not form a valid polygon.
+    #[cfg(feature = "std")]
+    InvalidPolygon(PolygonMapError),
+}
+
+impl From<CellMapError> for GeneratorError {
+    fn from(value: CellMapError) -> Self {
+        Self::InvalidMap(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MaskMapState;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn corners() -> (RealWorldLocation, RealWorldLocation) {
+        (
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(30.0, 20.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn obstacle_density() {
+        let (point1, point2) = corners();
+        let resolution = AxisResolution::uniform(1.0);
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        let empty =
+            obstacle_field(&mut rng, point1, point2, resolution, 0.0).unwrap();
+        let full =
+            obstacle_field(&mut rng, point1, point2, resolution, 1.0).unwrap();
+        let half =
+            obstacle_field(&mut rng, point1, point2, resolution, 0.5).unwrap();
+
+        assert!(empty.get_map_state(MapState::Obstacle).is_empty());
+        assert_eq!(full.get_map_state(MapState::Obstacle).len(), 600);
+        let obstacles = half.get_map_state(MapState::Obstacle).len();
+        assert!((200..400).contains(&obstacles));
+    }
+
+    #[test]
+    fn explored_region_is_connected() {
+        let (point1, point2) = corners();
+
+        for explored in [0.0, 0.1, 0.5, 1.0] {
+            let map = partially_explored(
+                &mut SmallRng::seed_from_u64(5),
+                point1,
+                point2,
+                AxisResolution::uniform(1.0),
+                explored,
+            )
+            .unwrap();
+
+            let components =
+                map.components(|value| *value == MapState::Explored);
+            let cells: usize = components.iter().map(Vec::len).sum();
+            assert_eq!(cells, (600.0 * explored) as usize);
+            assert!(components.len() <= 1);
+            assert_eq!(
+                map.get_map_state(MapState::Frontier).is_empty(),
+                explored == 0.0 || explored == 1.0
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn polygon_within_radii() {
+        let center = RealWorldLocation::from_xyz(-3.0, 4.0, 0.0);
+
+        let polygon = random_polygon(
+            &mut SmallRng::seed_from_u64(9),
+            &center,
+            1.0,
+            2.5,
+            12,
+        )
+        .unwrap();
+
+        for vertex in &polygon.areas()[0] {
+            let distance =
+                (vertex.x() - center.x()).hypot(vertex.y() - center.y());
+            assert!((1.0..=2.5).contains(&distance));
+        }
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let (point1, point2) = corners();
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        assert_eq!(
+            obstacle_field(
+                &mut rng,
+                point1,
+                point2,
+                AxisResolution::uniform(1.0),
+                f64::NAN
+            ),
+            Err(GeneratorError::InvalidProbability)
+        );
+        assert_eq!(
+            partially_explored(
+                &mut rng,
+                point1,
+                point1,
+                AxisResolution::uniform(1.0),
+                0.5
+            ),
+            Err(GeneratorError::InvalidMap(CellMapError::Empty))
+        );
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            random_polygon(&mut rng, &point2, 1.0, 2.0, 2),
+            Err(GeneratorError::NotEnoughVertices)
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            random_polygon(&mut rng, &point2, 3.0, 2.0, 5),
+            Err(GeneratorError::InvalidRadius)
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            random_polygon(
+                &mut rng,
+                &RealWorldLocation::from_xyz(f64::NAN, 0.0, 0.0),
+                1.0,
+                2.0,
+                5
+            ),
+            Err(GeneratorError::InvalidCenter)
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            random_polygon(
+                &mut rng,
+                &RealWorldLocation::from_xyz(f64::MAX, 0.0, 0.0),
+                f64::MAX,
+                f64::MAX,
+                5
+            ),
+            Err(GeneratorError::InvalidPolygon(
+                PolygonMapError::NonFiniteCoordinates
+            ))
+        ));
+    }
+
+    #[test]
+    fn perlin_terrain_is_smooth() {
+        let (point1, point2) = corners();
+
+        let map = perlin_terrain(
+            &mut SmallRng::seed_from_u64(3),
+            point1,
+            point2,
+            AxisResolution::uniform(1.0),
+            10.0,
+            0.4,
+        )
+        .unwrap();
+
+        assert_eq!(map.get_map_state(MapState::Obstacle).len(), 240);
+        // Smooth noise results in few, large obstacles rather than scattered
+        // single cells.
+        let components = map.components(|value| *value == MapState::Obstacle);
+        assert!(components.len() < 20);
+    }
+
+    #[test]
+    fn caves_are_reproducible() {
+        let (point1, point2) = corners();
+        let caves = |seed, iterations| {
+            cellular_caves(
+                &mut SmallRng::seed_from_u64(seed),
+                point1,
+                point2,
+                AxisResolution::uniform(1.0),
+                0.45,
+                iterations,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(caves(8, 4), caves(8, 4));
+        // Smoothing merges scattered cells into fewer, larger regions.
+        let free = |map: &CellMap| {
+            map.components(|value| *value != MapState::Obstacle).len()
+        };
+        assert!(free(&caves(8, 4)) < free(&caves(8, 0)));
+    }
+
+    #[test]
+    fn perlin_noise_range() {
+        let noise = Perlin::new(&mut SmallRng::seed_from_u64(1));
+
+        for i in 0..1000 {
+            let value = noise.sample(i as f64 * 0.37, i as f64 * 0.11 - 20.0);
+            assert!((-1.0..=1.0).contains(&value));
+        }
+        // The noise vanishes on the lattice points.
+        assert_eq!(noise.sample(3.0, -7.0), 0.0);
+    }
+
+    #[test]
+    fn invalid_noise_scale() {
+        let (point1, point2) = corners();
+
+        assert_eq!(
+            perlin_terrain(
+                &mut SmallRng::seed_from_u64(0),
+                point1,
+                point2,
+                AxisResolution::uniform(1.0),
+                0.0,
+                0.5
+            ),
+            Err(GeneratorError::InvalidScale)
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Conversion between GPS (WGS84) coordinates and local map coordinates.
+//!
+//! Missions are often defined in latitude and longitude, while the maps of
+//! this crate work in a local, metric frame. [`LocalTangentPlane`] anchors such
+//! a frame at a reference point and converts between the two, with `x`
+//! pointing east, `y` pointing north and `z` pointing up (ENU), all in meters.
+//!
+//! Angles are always given in degrees and altitudes in meters above the WGS84
+//! ellipsoid. To avoid mixing up units, [`GeoPoint`] can only be created from
+//! degrees and validates its inputs.
+//!
+//! # Example
+//!
+//! ```
+//! use local_robot_map::geo_coords::{GeoPoint, LocalTangentPlane};
+//!
+//! let reference = GeoPoint::from_degrees(48.137, 11.575, 520.0).unwrap();
+//! let plane = LocalTangentPlane::new(reference);
+//!
+//! // Roughly 111 m north of the reference point.
+//! let north = GeoPoint::from_degrees(48.138, 11.575, 520.0).unwrap();
+//! let local = plane.to_local(&north);
+//!
+//! assert!(local.x().abs() < 1e-6);
+//! assert!((local.y() - 111.2).abs() < 0.1);
+//!
+//! let back = plane.to_geodetic(&local);
+//! assert!((back.latitude() - 48.138).abs() < 1e-9);
+//! ```
+
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::RealWorldLocation;
+
+/// Semi-major axis of the WGS84 ellipsoid in meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// Flattening of the WGS84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// First eccentricity squared of the WGS84 ellipsoid.
+const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// A point on earth given by WGS84 latitude, longitude and altitude.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GeoPoint {
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+}
+
+impl GeoPoint {
+    /// Create a point from latitude and longitude in degrees, and the altitude
+    /// in meters above the WGS84 ellipsoid.
+    ///
+    /// # Errors
+    ///
+    /// - [`GeoCoordsError::InvalidLatitude`] if the latitude is not within
+    ///   `-90.0..=90.0`.
+    /// - [`GeoCoordsError::InvalidLongitude`] if the longitude is not within
+    ///   `-180.0..=180.0`.
+    /// - [`GeoCoordsError::InvalidAltitude`] if the altitude is not finite.
+    pub fn from_degrees(
+        latitude: f64,
+        longitude: f64,
+        altitude: f64,
+    ) -> Result<Self, GeoCoordsError> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(GeoCoordsError::InvalidLatitude);
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(GeoCoordsError::InvalidLongitude);
+        }
+        if !altitude.is_finite() {
+            return Err(GeoCoordsError::InvalidAltitude);
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+            altitude,
+        })
+    }
+
+    /// Latitude in degrees.
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+    /// Longitude in degrees.
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+    /// Altitude in meters above the WGS84 ellipsoid.
+    pub fn altitude(&self) -> f64 {
+        self.altitude
+    }
+
+    /// Earth-centered, earth-fixed coordinates in meters.
+    fn to_ecef(self) -> [f64; 3] {
+        let (sin_lat, cos_lat) = self.latitude.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.to_radians().sin_cos();
+        let n = WGS84_A / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+        [
+            (n + self.altitude) * cos_lat * cos_lon,
+            (n + self.altitude) * cos_lat * sin_lon,
+            (n * (1.0 - WGS84_E2) + self.altitude) * sin_lat,
+        ]
+    }
+
+    /// Inverse of [`GeoPoint::to_ecef`], iterating on the latitude.
+    fn from_ecef([x, y, z]: [f64; 3]) -> Self {
+        let p = (x * x + y * y).sqrt();
+        let longitude = y.atan2(x);
+        let mut latitude = z.atan2(p * (1.0 - WGS84_E2));
+        let mut altitude = 0.0;
+        for _ in 0..5 {
+            let sin_lat = latitude.sin();
+            let n = WGS84_A / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+            altitude = p / latitude.cos() - n;
+            latitude = z.atan2(p * (1.0 - WGS84_E2 * n / (n + altitude)));
+        }
+        Self {
+            latitude: latitude.to_degrees(),
+            longitude: longitude.to_degrees(),
+            altitude,
+        }
+    }
+}
+
+/// Local east-north-up frame anchored at a reference point.
+///
+/// The reference point is the origin of the local frame. The frame is exact
+/// at the reference point; as it is a plane tangent to the ellipsoid, points
+/// far away appear below the `x`-`y` plane (about 8 m at a distance of 10 km).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LocalTangentPlane {
+    reference: GeoPoint,
+    reference_ecef: [f64; 3],
+    /// Rows are the east, north and up unit vectors in ECEF coordinates.
+    rotation: [[f64; 3]; 3],
+}
+
+impl LocalTangentPlane {
+    pub fn new(reference: GeoPoint) -> Self {
+        let (sin_lat, cos_lat) = reference.latitude.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = reference.longitude.to_radians().sin_cos();
+        Self {
+            reference,
+            reference_ecef: reference.to_ecef(),
+            rotation: [
+                [-sin_lon, cos_lon, 0.0],
+                [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+                [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+            ],
+        }
+    }
+
+    pub fn reference(&self) -> &GeoPoint {
+        &self.reference
+    }
+
+    /// Convert a GPS point into the local frame.
+    pub fn to_local(&self, point: &GeoPoint) -> RealWorldLocation {
+        let ecef = point.to_ecef();
+        let delta = [
+            ecef[0] - self.reference_ecef[0],
+            ecef[1] - self.reference_ecef[1],
+            ecef[2] - self.reference_ecef[2],
+        ];
+        let [east, north, up] = self.rotation.map(|axis| {
+            axis[0] * delta[0] + axis[1] * delta[1] + axis[2] * delta[2]
+        });
+        RealWorldLocation::from_xyz(east, north, up)
+    }
+
+    /// Convert a location in the local frame back into a GPS point.
+    pub fn to_geodetic(&self, location: &RealWorldLocation) -> GeoPoint {
+        let enu = [location.x(), location.y(), location.z()];
+        let r = &self.rotation;
+        let ecef = [0, 1, 2].map(|i| {
+            self.reference_ecef[i]
+                + r[0][i] * enu[0]
+                + r[1][i] * enu[1]
+                + r[2][i] * enu[2]
+        });
+        GeoPoint::from_ecef(ecef)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GeoCoordsError {
+    /// The latitude is NaN or outside of `-90.0..=90.0` degrees.
+    InvalidLatitude,
+    /// The longitude is NaN or outside of `-180.0..=180.0` degrees.
+    InvalidLongitude,
+    /// The altitude is NaN or infinite.
+    InvalidAltitude,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(latitude: f64, longitude: f64, altitude: f64) -> GeoPoint {
+        GeoPoint::from_degrees(latitude, longitude, altitude).unwrap()
+    }
+
+    #[test]
+    fn reference_is_origin() {
+        let reference = point(-33.8688, 151.2093, 58.0);
+        let plane = LocalTangentPlane::new(reference);
+
+        let local = plane.to_local(&reference);
+
+        assert!(local.x().abs() < 1e-6);
+        assert!(local.y().abs() < 1e-6);
+        assert!(local.z().abs() < 1e-6);
+    }
+
+    #[test]
+    fn axes_point_east_north_up() {
+        let plane = LocalTangentPlane::new(point(0.0, 0.0, 0.0));
+
+        let east = plane.to_local(&point(0.0, 0.001, 0.0));
+        assert!(east.x() > 111.0 && east.x() < 112.0);
+        assert!(east.y().abs() < 1e-6);
+
+        let up = plane.to_local(&point(0.0, 0.0, 10.0));
+        assert!((up.z() - 10.0).abs() < 1e-6);
+        assert!(up.x().abs() < 1e-6 && up.y().abs() < 1e-6);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let plane = LocalTangentPlane::new(point(52.52, 13.405, 34.0));
+
+        for (lat, lon, alt) in
+            [(52.53, 13.41, 40.0), (52.5, 13.3, 0.0), (52.6, 13.5, 120.0)]
+        {
+            let back =
+                plane.to_geodetic(&plane.to_local(&point(lat, lon, alt)));
+
+            assert!((back.latitude() - lat).abs() < 1e-9);
+            assert!((back.longitude() - lon).abs() < 1e-9);
+            assert!((back.altitude() - alt).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        assert_eq!(
+            GeoPoint::from_degrees(91.0, 0.0, 0.0),
+            Err(GeoCoordsError::InvalidLatitude)
+        );
+        assert_eq!(
+            GeoPoint::from_degrees(f64::NAN, 0.0, 0.0),
+            Err(GeoCoordsError::InvalidLatitude)
+        );
+        assert_eq!(
+            GeoPoint::from_degrees(0.0, 180.5, 0.0),
+            Err(GeoCoordsError::InvalidLongitude)
+        );
+        assert_eq!(
+            GeoPoint::from_degrees(0.0, 0.0, f64::INFINITY),
+            Err(GeoCoordsError::InvalidAltitude)
+        );
+    }
+}
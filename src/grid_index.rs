@@ -0,0 +1,268 @@
+use core::ops::{Index, IndexMut};
+use num::Float;
+
+use crate::{
+    regions::grid_neighbors, CellMap, Connectivity, Coords, LocationError,
+    LocationType, RealWorldLocation,
+};
+
+/// Index of a cell in the grid of a [`CellMap`].
+///
+/// Rows run along the `y` axis and columns along the `x` axis, starting at
+/// the map's offset, i.e. `row` and `col` index [`CellMap::cells`] as
+/// `[row, col]`. Algorithms working in grid space, such as a breadth-first
+/// search, can use it instead of real-world locations and convert at the
+/// boundaries using [`CellMap::index_of`] and [`CellMap::location_of`].
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, GridIndex, MapState, RealWorldLocation,
+/// };
+///
+/// let mut map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+///
+/// let index = map
+///     .index_of(&RealWorldLocation::from_xyz(2.7, 1.2, 0.0))
+///     .unwrap();
+/// map[index] = MapState::Obstacle;
+///
+/// assert_eq!(index, GridIndex::new(1, 2));
+/// assert_eq!(map.cells()[[1, 2]], MapState::Obstacle);
+/// assert_eq!(
+///     map.location_of(index),
+///     Some(RealWorldLocation::from_xyz(2.5, 1.5, 0.0))
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GridIndex {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl GridIndex {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    /// The indices of the up to four cells sharing an edge with this one,
+    /// which lie within a grid of `[rows, columns]` cells.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::GridIndex;
+    ///
+    /// let neighbors: Vec<_> =
+    ///     GridIndex::new(0, 1).neighbors([2, 2]).collect();
+    ///
+    /// assert_eq!(neighbors, [GridIndex::new(0, 0), GridIndex::new(1, 1)]);
+    /// ```
+    pub fn neighbors(
+        self,
+        [rows, columns]: [usize; 2],
+    ) -> impl Iterator<Item = GridIndex> {
+        grid_neighbors(self.into(), [rows, columns], Connectivity::Four)
+            .map(Self::from)
+    }
+}
+
+impl From<[usize; 2]> for GridIndex {
+    fn from([row, col]: [usize; 2]) -> Self {
+        Self::new(row, col)
+    }
+}
+
+impl From<GridIndex> for [usize; 2] {
+    fn from(value: GridIndex) -> Self {
+        [value.row, value.col]
+    }
+}
+
+impl CellMap {
+    /// The index of the cell containing the given location.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`CellMap::location_to_map_index`].
+    pub fn index_of<S: Float>(
+        &self,
+        location: &RealWorldLocation<S>,
+    ) -> Result<GridIndex, LocationError> {
+        self.location_to_map_index(location).map(GridIndex::from)
+    }
+
+    /// The center of the cell at the given index, or [`None`] if the index
+    /// lies outside the map. Same as [`CellMap::cell_center`].
+    pub fn location_of(&self, index: GridIndex) -> Option<RealWorldLocation> {
+        self.cell_center(index)
+    }
+
+    /// The bottom left and top right corners of the cell at the given index,
+    /// or [`None`] if the index lies outside the map.
+    ///
+    /// A cell covers its bottom and left edges, but not its top and right
+    /// ones, which belong to the neighboring cells. Like [`CellMap::bounds`],
+    /// both corners share the `z` value of the map's offset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, GridIndex, RealWorldLocation,
+    /// };
+    ///
+    /// let map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(-2.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     AxisResolution::new(2.0, 1.0, 1.0),
+    /// );
+    /// let index = GridIndex::new(1, 3);
+    ///
+    /// assert_eq!(
+    ///     map.cell_bounds(index),
+    ///     Some([
+    ///         RealWorldLocation::from_xyz(-0.5, 1.0, 0.0),
+    ///         RealWorldLocation::from_xyz(0.0, 2.0, 0.0),
+    ///     ])
+    /// );
+    /// assert_eq!(
+    ///     map.cell_center(index),
+    ///     Some(RealWorldLocation::from_xyz(-0.25, 1.5, 0.0))
+    /// );
+    /// ```
+    pub fn cell_bounds(
+        &self,
+        index: GridIndex,
+    ) -> Option<[RealWorldLocation; 2]> {
+        let center = self.cell_center(index)?;
+        let half = Coords::new(
+            0.5 / self.resolution().x,
+            0.5 / self.resolution().y,
+            0.0,
+        );
+        Some([
+            RealWorldLocation::new(*center - half),
+            RealWorldLocation::new(*center + half),
+        ])
+    }
+
+    /// The center of the cell at the given index, or [`None`] if the index
+    /// lies outside the map.
+    ///
+    /// Unlike the location of a [`Cell`](crate::Cell), which is the cell's
+    /// bottom left corner, the center is the natural target when navigating
+    /// to a cell. See also [`CellMap::cell_bounds`].
+    pub fn cell_center(&self, index: GridIndex) -> Option<RealWorldLocation> {
+        if index.row >= self.nrows() || index.col >= self.ncols() {
+            return None;
+        }
+        Some(RealWorldLocation::new(
+            self.index_center([index.row, index.col]),
+        ))
+    }
+}
+
+/// Access the state of the cell at a given index.
+///
+/// # Panics
+///
+/// Panics if the index is outside the map.
+impl Index<GridIndex> for CellMap {
+    type Output = LocationType;
+
+    fn index(&self, index: GridIndex) -> &Self::Output {
+        &self.cells()[<[usize; 2]>::from(index)]
+    }
+}
+
+/// Mutably access the state of the cell at a given index.
+///
+/// The cell is stamped with the map's clock and marked as dirty, as it may be
+/// modified through the returned reference.
+///
+/// # Panics
+///
+/// Panics if the index is outside the map.
+impl IndexMut<GridIndex> for CellMap {
+    fn index_mut(&mut self, index: GridIndex) -> &mut Self::Output {
+        self.get_index_mut(index.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AxisResolution;
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(-1.0, -2.0, 0.5),
+            RealWorldLocation::from_xyz(1.0, 2.0, 0.5),
+            AxisResolution::new(2.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn index_and_location_roundtrip() {
+        let map = make_map();
+
+        for row in 0..map.nrows() {
+            for col in 0..map.ncols() {
+                let index = GridIndex::new(row, col);
+                let location = map.location_of(index).unwrap();
+                assert_eq!(map.index_of(&location), Ok(index));
+            }
+        }
+        assert_eq!(
+            map.location_of(GridIndex::new(0, 0)),
+            Some(RealWorldLocation::from_xyz(-0.75, -1.5, 0.5))
+        );
+    }
+
+    #[test]
+    fn outside_the_map() {
+        let map = make_map();
+
+        assert_eq!(map.location_of(GridIndex::new(4, 0)), None);
+        assert_eq!(map.location_of(GridIndex::new(0, 4)), None);
+        assert!(map
+            .index_of(&RealWorldLocation::from_xyz(1.5, 0.0, 0.5))
+            .is_err());
+    }
+
+    #[test]
+    fn bounds_contain_center() {
+        let map = make_map();
+        let index = GridIndex::new(2, 1);
+
+        let [min, max] = map.cell_bounds(index).unwrap();
+        let center = map.cell_center(index).unwrap();
+
+        assert_eq!(map.index_of(&min), Ok(index));
+        assert_ne!(map.index_of(&max), Ok(index));
+        assert_eq!(center, RealWorldLocation::new(min.lerp(&max, 0.5)));
+        assert_eq!(map.cell_bounds(GridIndex::new(4, 1)), None);
+    }
+
+    #[test]
+    fn neighbors_within_grid() {
+        let mut neighbors: Vec<_> =
+            GridIndex::new(1, 1).neighbors([3, 2]).collect();
+        neighbors.sort();
+
+        assert_eq!(
+            neighbors,
+            [
+                GridIndex::new(0, 1),
+                GridIndex::new(1, 0),
+                GridIndex::new(2, 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Rendering of scalar layers, such as distance fields, costs or information
+//! gain, as color images.
+//!
+//! A layer is an [`Array2<f64>`] with one value per cell, indexed like
+//! [`CellMap::cells`]. [`render_heatmap`] turns such a layer into an image on
+//! its own, while [`CellMap::heatmap_overlay`] blends it over the image of the
+//! map's states, such that both line up pixel by pixel.
+//!
+//! Values are normalized between the smallest and the largest finite value
+//! of the layer. Non-finite values (e.g. the infinite distance of unreachable
+//! cells) are not colored.
+//!
+//! Requires the `std` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use local_robot_map::heatmap::{render_heatmap, Gradient};
+//! use ndarray::array;
+//!
+//! let distances = array![[0.0, 1.0], [2.0, f64::INFINITY]];
+//!
+//! let image = render_heatmap(&distances, &Gradient::Grayscale).unwrap();
+//!
+//! assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0]);
+//! assert_eq!(image.get_pixel(1, 0).0, [128, 128, 128]);
+//! assert_eq!(image.get_pixel(0, 1).0, [255, 255, 255]);
+//! ```
+
+use image::{Rgb, RgbImage};
+use ndarray::Array2;
+use num::ToPrimitive;
+
+use crate::{CellMap, MapState};
+
+/// Colors used for the values of a layer, from the smallest to the largest.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Gradient {
+    /// From black to white.
+    Grayscale,
+    /// The perceptually uniform colormap of matplotlib, from dark purple to
+    /// yellow. It remains readable for colorblind people and in grayscale.
+    #[default]
+    Viridis,
+    /// Evenly spaced colors, interpolated linearly. An empty list renders all
+    /// values black.
+    Custom(Vec<Rgb<u8>>),
+}
+
+const VIRIDIS: [Rgb<u8>; 9] = [
+    Rgb([68, 1, 84]),
+    Rgb([71, 45, 123]),
+    Rgb([59, 82, 139]),
+    Rgb([44, 114, 142]),
+    Rgb([33, 145, 140]),
+    Rgb([40, 174, 128]),
+    Rgb([94, 201, 98]),
+    Rgb([173, 220, 48]),
+    Rgb([253, 231, 37]),
+];
+
+impl Gradient {
+    /// The color of `t`, from `0.0` for the smallest value to `1.0` for the
+    /// largest one. Values outside of that range are clamped.
+    pub fn color(&self, t: f64) -> Rgb<u8> {
+        let stops: &[Rgb<u8>] = match self {
+            Gradient::Grayscale => &[Rgb([0, 0, 0]), Rgb([255, 255, 255])],
+            Gradient::Viridis => &VIRIDIS,
+            Gradient::Custom(stops) => stops,
+        };
+        let Some(last) = stops.len().checked_sub(1) else {
+            return Rgb([0, 0, 0]);
+        };
+        let position =
+            if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) } * last as f64;
+        let lower = (position.floor() as usize).min(last);
+        let upper = (lower + 1).min(last);
+        let fraction = position - lower as f64;
+        Rgb([0, 1, 2].map(|i| {
+            let (a, b) =
+                (f64::from(stops[lower][i]), f64::from(stops[upper][i]));
+            (a + (b - a) * fraction).round() as u8
+        }))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HeatmapError {
+    /// The layer has a different number of rows or columns than the map.
+    ShapeMismatch,
+    /// The layer is wider or higher than [`u32::MAX`] cells, which is the
+    /// largest image size supported.
+    TooLarge,
+}
+
+/// Render a layer as an image, with one pixel per value. Non-finite values are
+/// drawn in the color of [`MapState::OutOfMap`].
+///
+/// Rows of the image match the rows of the layer, like
+/// [`Visualize::as_image`](crate::Visualize::as_image).
+///
+/// # Errors
+///
+/// Returns [`HeatmapError::TooLarge`] if the layer does not fit into an image.
+pub fn render_heatmap(
+    values: &Array2<f64>,
+    gradient: &Gradient,
+) -> Result<RgbImage, HeatmapError> {
+    let width = values.ncols().to_u32().ok_or(HeatmapError::TooLarge)?;
+    let height = values.nrows().to_u32().ok_or(HeatmapError::TooLarge)?;
+    let normalize = normalization(values);
+    Ok(RgbImage::from_fn(width, height, |x, y| {
+        // Both are bounded by the layer's dimensions, so they fit a usize.
+        match normalize(values[[y as usize, x as usize]]) {
+            Some(t) => gradient.color(t),
+            None => MapState::OutOfMap.to_rgb(),
+        }
+    }))
+}
+
+/// Maps finite values of the layer onto `0.0..=1.0`, and the others to
+/// [`None`]. A constant layer maps to `0.0`.
+fn normalization(values: &Array2<f64>) -> impl Fn(f64) -> Option<f64> {
+    let (min, max) = values
+        .iter()
+        .filter(|value| value.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(*value), max.max(*value))
+        });
+    let range = max - min;
+    move |value| {
+        value.is_finite().then(|| {
+            if range > 0.0 {
+                (value - min) / range
+            } else {
+                0.0
+            }
+        })
+    }
+}
+
+impl CellMap {
+    /// Render the map like [`Visualize::as_image`](crate::Visualize::as_image)
+    /// and blend a layer over it, by `opacity` from `0.0` (only the map) to
+    /// `1.0` (only the layer). Cells with non-finite values show the map only.
+    ///
+    /// # Errors
+    ///
+    /// - [`HeatmapError::ShapeMismatch`] if the layer does not have the same
+    ///   shape as [`CellMap::cells`].
+    /// - [`HeatmapError::TooLarge`] if the map does not fit into an image.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     heatmap::{Gradient, HeatmapError},
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    /// use ndarray::Array2;
+    ///
+    /// let map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 3.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let costs =
+    ///     Array2::from_shape_fn((3, 4), |(row, col)| (row + col) as f64);
+    ///
+    /// let image =
+    ///     map.heatmap_overlay(&costs, &Gradient::Viridis, 0.5).unwrap();
+    ///
+    /// assert_eq!(image.dimensions(), (4, 3));
+    /// assert_ne!(*image.get_pixel(0, 0), MapState::Unexplored.to_rgb());
+    /// let transposed = Array2::zeros((4, 3));
+    /// assert_eq!(
+    ///     map.heatmap_overlay(&transposed, &Gradient::Viridis, 0.5),
+    ///     Err(HeatmapError::ShapeMismatch)
+    /// );
+    /// ```
+    pub fn heatmap_overlay(
+        &self,
+        values: &Array2<f64>,
+        gradient: &Gradient,
+        opacity: f32,
+    ) -> Result<RgbImage, HeatmapError> {
+        if values.dim() != self.cells().dim() {
+            return Err(HeatmapError::ShapeMismatch);
+        }
+        let mut image =
+            self.try_as_image().map_err(|_| HeatmapError::TooLarge)?;
+        blend_heatmap(&mut image, values, gradient, opacity);
+        Ok(image)
+    }
+}
+
+/// Blend a layer with the same dimensions as `image` over it, see
+/// [`CellMap::heatmap_overlay`].
+pub(crate) fn blend_heatmap(
+    image: &mut RgbImage,
+    values: &Array2<f64>,
+    gradient: &Gradient,
+    opacity: f32,
+) {
+    let opacity = f64::from(opacity.clamp(0.0, 1.0));
+    let normalize = normalization(values);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        // Both are bounded by the layer's dimensions, so they fit a usize.
+        let Some(t) = normalize(values[[y as usize, x as usize]]) else {
+            continue;
+        };
+        let color = gradient.color(t);
+        *pixel = Rgb([0, 1, 2].map(|i| {
+            let (base, over) = (f64::from(pixel[i]), f64::from(color[i]));
+            (base + (over - base) * opacity).round() as u8
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, RealWorldLocation};
+    use ndarray::array;
+
+    #[test]
+    fn gradient_interpolates_and_clamps() {
+        let gradient =
+            Gradient::Custom(vec![Rgb([0, 0, 0]), Rgb([200, 100, 0])]);
+
+        assert_eq!(gradient.color(0.5), Rgb([100, 50, 0]));
+        assert_eq!(gradient.color(-1.0), Rgb([0, 0, 0]));
+        assert_eq!(gradient.color(2.0), Rgb([200, 100, 0]));
+        assert_eq!(gradient.color(f64::NAN), Rgb([0, 0, 0]));
+        assert_eq!(Gradient::Viridis.color(0.0), VIRIDIS[0]);
+        assert_eq!(Gradient::Viridis.color(1.0), VIRIDIS[8]);
+        assert_eq!(Gradient::Custom(vec![]).color(0.3), Rgb([0, 0, 0]));
+        let single = Gradient::Custom(vec![Rgb([1, 2, 3])]);
+        assert_eq!(single.color(0.7), Rgb([1, 2, 3]));
+    }
+
+    #[test]
+    fn constant_and_non_finite_layers() {
+        let constant = array![[3.0, 3.0]];
+        let image = render_heatmap(&constant, &Gradient::Grayscale).unwrap();
+        assert!(image.pixels().all(|pixel| *pixel == Rgb([0, 0, 0])));
+
+        let empty = array![[f64::NAN, f64::NEG_INFINITY]];
+        let image = render_heatmap(&empty, &Gradient::Viridis).unwrap();
+        assert!(image
+            .pixels()
+            .all(|pixel| *pixel == MapState::OutOfMap.to_rgb()));
+    }
+
+    #[test]
+    fn overlay_keeps_map_for_non_finite_values() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        let values = array![[f64::INFINITY, 1.0]];
+
+        let full = map
+            .heatmap_overlay(&values, &Gradient::Grayscale, 1.0)
+            .unwrap();
+        let none = map
+            .heatmap_overlay(&values, &Gradient::Grayscale, 0.0)
+            .unwrap();
+
+        assert_eq!(*full.get_pixel(0, 0), MapState::Unexplored.to_rgb());
+        assert_eq!(*full.get_pixel(1, 0), Rgb([0, 0, 0]));
+        assert_eq!(none, map.try_as_image().unwrap());
+    }
+}
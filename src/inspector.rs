@@ -0,0 +1,322 @@
+//! Interactive window for inspecting a map while it is being updated.
+//!
+//! [`MapInspector`] shows a [`CellMap`] shared with the rest of the
+//! application, e.g. a mapping thread, and redraws it periodically. Hovering
+//! a cell shows its index, state and real-world location, and the side panel
+//! allows hiding states and blending scalar layers (see [`crate::heatmap`])
+//! over the map.
+//!
+//! Requires the `inspector` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::sync::{Arc, RwLock};
+//!
+//! use local_robot_map::{
+//!     inspector::MapInspector, AxisResolution, CellMap, RealWorldLocation,
+//! };
+//! use ndarray::Array2;
+//!
+//! let map = Arc::new(RwLock::new(CellMap::new(
+//!     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+//!     RealWorldLocation::from_xyz(30.0, 20.0, 0.0),
+//!     AxisResolution::uniform(1.0),
+//! )));
+//! let costs =
+//!     Array2::from_shape_fn((20, 30), |(row, col)| (row * col) as f64);
+//!
+//! // Keep a handle to update the map from another thread.
+//! let shared = Arc::clone(&map);
+//! std::thread::spawn(move || {
+//!     // ... shared.write().unwrap().set_location(...)
+//!     # drop(shared);
+//! });
+//!
+//! MapInspector::new(map).layer("costs", costs).run("Map").unwrap();
+//! ```
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use eframe::egui;
+use image::RgbImage;
+use ndarray::Array2;
+
+use crate::heatmap::{blend_heatmap, Gradient};
+use crate::{CellMap, ColorMap, MapState};
+
+/// All states, in the order of their byte representation.
+const STATES: [MapState; 8] = [
+    MapState::OutOfMap,
+    MapState::OtherRobot,
+    MapState::MyRobot,
+    MapState::Explored,
+    MapState::Unexplored,
+    MapState::Frontier,
+    MapState::Assigned,
+    MapState::Obstacle,
+];
+
+/// A scalar layer which can be blended over the map.
+struct Layer {
+    name: String,
+    values: Array2<f64>,
+    visible: bool,
+}
+
+/// Window showing a live [`CellMap`], see the [module](self) documentation.
+pub struct MapInspector {
+    map: Arc<RwLock<CellMap>>,
+    colors: ColorMap,
+    visible_states: [bool; 8],
+    layers: Vec<Layer>,
+    gradient: Gradient,
+    opacity: f32,
+    pixels_per_cell: f32,
+    refresh_interval: Duration,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl MapInspector {
+    /// Inspect the given map. It is read again every
+    /// [`MapInspector::refresh_interval`], so changes made through other
+    /// handles show up while the window is open.
+    pub fn new(map: Arc<RwLock<CellMap>>) -> Self {
+        Self {
+            map,
+            colors: ColorMap::DEFAULT,
+            visible_states: [true; 8],
+            layers: Vec::new(),
+            gradient: Gradient::default(),
+            opacity: 0.6,
+            pixels_per_cell: 8.0,
+            refresh_interval: Duration::from_millis(100),
+            texture: None,
+        }
+    }
+
+    /// Colors used for the states of the map.
+    pub fn colors(mut self, colors: ColorMap) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Add a scalar layer, hidden until enabled in the side panel.
+    ///
+    /// Layers whose shape differs from the one of the map (e.g. after the map
+    /// was expanded) are not drawn.
+    pub fn layer(
+        mut self,
+        name: impl Into<String>,
+        values: Array2<f64>,
+    ) -> Self {
+        self.layers.push(Layer {
+            name: name.into(),
+            values,
+            visible: false,
+        });
+        self
+    }
+
+    /// Colors used for the scalar layers.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = gradient;
+        self
+    }
+
+    /// How often the map is read and redrawn.
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Open the window, blocking until it is closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window cannot be created, e.g. because no
+    /// display is available.
+    pub fn run(self, title: &str) -> eframe::Result<()> {
+        eframe::run_native(
+            title,
+            eframe::NativeOptions::default(),
+            Box::new(|_| Ok(Box::new(self))),
+        )
+    }
+
+    /// The map as drawn in the window, with hidden states shown as
+    /// [`MapState::OutOfMap`] and the visible layers blended over it.
+    fn render(&self, map: &CellMap) -> RgbImage {
+        let background = self.colors.color(MapState::OutOfMap);
+        let mut colors = self.colors;
+        for (state, visible) in STATES.iter().zip(self.visible_states) {
+            if !visible {
+                colors.set(*state, background);
+            }
+        }
+        let mut image = map.as_image_with(&colors);
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            if layer.values.dim() == map.cells().dim() {
+                blend_heatmap(
+                    &mut image,
+                    &layer.values,
+                    &self.gradient,
+                    self.opacity,
+                );
+            }
+        }
+        image
+    }
+
+    fn side_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("States");
+        for (state, visible) in STATES.iter().zip(&mut self.visible_states) {
+            let name: &str = state.into();
+            ui.checkbox(visible, name);
+        }
+        if !self.layers.is_empty() {
+            ui.separator();
+            ui.heading("Layers");
+            for layer in &mut self.layers {
+                ui.checkbox(&mut layer.visible, layer.name.as_str());
+            }
+            ui.add(
+                egui::Slider::new(&mut self.opacity, 0.0..=1.0).text("Opacity"),
+            );
+        }
+        ui.separator();
+        ui.add(
+            egui::Slider::new(&mut self.pixels_per_cell, 1.0..=32.0)
+                .text("Zoom"),
+        );
+    }
+
+    /// Text describing the cell at `index`.
+    fn describe(&self, map: &CellMap, index: [usize; 2]) -> Option<String> {
+        let state = map.cells().get(index)?;
+        let center = map.index_center::<f64>(index);
+        let mut text = format!(
+            "Cell {index:?}: {}\nLocation: ({:.2}, {:.2})",
+            <&str>::from(state),
+            center.x,
+            center.y,
+        );
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            if let Some(value) = layer.values.get(index) {
+                text.push_str(&format!("\n{}: {value:.3}", layer.name));
+            }
+        }
+        Some(text)
+    }
+}
+
+/// The index of the cell under `position`, for an image of `pixels_per_cell`
+/// pixels per cell whose top left corner is at `origin`.
+fn cell_at(
+    origin: egui::Pos2,
+    pixels_per_cell: f32,
+    position: egui::Pos2,
+) -> Option<[usize; 2]> {
+    let offset = (position - origin) / pixels_per_cell;
+    (offset.x >= 0.0 && offset.y >= 0.0)
+        .then_some([offset.y as usize, offset.x as usize])
+}
+
+impl eframe::App for MapInspector {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let map = Arc::clone(&self.map);
+        // A panic in a writer must not take the inspector down as well.
+        let map = map.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let image = self.render(&map);
+        let size = [image.width() as usize, image.height() as usize];
+        let image = egui::ColorImage::from_rgb(size, image.as_raw());
+        let options = egui::TextureOptions::NEAREST;
+        match &mut self.texture {
+            Some(texture) => texture.set(image, options),
+            None => {
+                self.texture = Some(ctx.load_texture("map", image, options))
+            }
+        }
+
+        egui::SidePanel::left("layers").show(ctx, |ui| self.side_panel(ui));
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let Some(texture) = &self.texture else {
+                return;
+            };
+            let display_size = texture.size_vec2() * self.pixels_per_cell;
+            egui::ScrollArea::both().show(ui, |ui| {
+                let response = ui.add(
+                    egui::Image::new((texture.id(), display_size))
+                        .sense(egui::Sense::hover()),
+                );
+                let hovered = response.hover_pos().and_then(|position| {
+                    cell_at(response.rect.min, self.pixels_per_cell, position)
+                });
+                if let Some(text) =
+                    hovered.and_then(|index| self.describe(&map, index))
+                {
+                    response.on_hover_text_at_pointer(text);
+                }
+            });
+        });
+
+        ctx.request_repaint_after(self.refresh_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location, RealWorldLocation, Visualize};
+
+    fn make_inspector() -> MapInspector {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_location(
+            &RealWorldLocation::from_xyz(1.5, 0.5, 0.0),
+            MapState::Obstacle,
+        )
+        .unwrap();
+        MapInspector::new(Arc::new(RwLock::new(map)))
+    }
+
+    #[test]
+    fn hidden_states_and_layers() {
+        let mut inspector = make_inspector()
+            .layer("constant", Array2::zeros((2, 3)))
+            .layer("wrong shape", Array2::zeros((3, 2)));
+        let map = Arc::clone(&inspector.map);
+        let map = map.read().unwrap();
+
+        assert_eq!(inspector.render(&map), map.as_image());
+
+        inspector.visible_states[usize::from(u8::from(&MapState::Obstacle))] =
+            false;
+        let image = inspector.render(&map);
+        assert_eq!(*image.get_pixel(1, 0), MapState::OutOfMap.to_rgb());
+        assert_eq!(*image.get_pixel(0, 0), MapState::Unexplored.to_rgb());
+
+        inspector.layers[1].visible = true;
+        assert_eq!(inspector.render(&map), image);
+        inspector.layers[0].visible = true;
+        assert_ne!(inspector.render(&map), image);
+    }
+
+    #[test]
+    fn hovered_cell() {
+        let inspector = make_inspector();
+        let map = inspector.map.read().unwrap();
+        let origin = egui::pos2(10.0, 20.0);
+
+        assert_eq!(cell_at(origin, 8.0, egui::pos2(27.0, 29.0)), Some([1, 2]));
+        assert_eq!(cell_at(origin, 8.0, egui::pos2(9.0, 29.0)), None);
+        let text = inspector.describe(&map, [0, 1]).unwrap();
+        assert_eq!(text, "Cell [0, 1]: Obstacle\nLocation: (1.50, 0.50)");
+        assert_eq!(inspector.describe(&map, [2, 0]), None);
+    }
+}
@@ -14,25 +14,153 @@
 //! which it can make decisions. Synchronizing of the maps across robots is
 //! outside the scope of this library; this one merely provides a basis on which
 //! to get started.
+//!
+//! # Features
+//!
+//! - `std` (enabled by default): everything which requires the standard
+//!   library, i.e. image export (see [`Visualize`]) and the polygon based maps
+//!   (`PolygonMap`). Disabling it leaves the core types ([`Coords`],
+//!   [`CellMap`], [`LocalMap`] and the map traits) which only require `alloc`,
+//!   allowing them to run on embedded targets.
+//!
+//!   Apart from `MapRecorder::save_png_sequence`, the `std` feature does not
+//!   perform any file IO and is compatible with the `wasm32-unknown-unknown`
+//!   target. This allows rendering maps in a browser, see
+//!   [`Visualize::as_rgba_bytes`].
+//! - `capi`: C API for integrating the crate with non-Rust stacks; see the
+//!   `capi` module.
+//! - `generators`: random maps for tests and benchmarks, drawn from a seedable
+//!   random number generator; see the `generators` module.
+//! - `inspector`: interactive window showing a live map, based on `egui`; see
+//!   the `inspector` module.
+//! - `nalgebra`: conversions between [`Coords`] or [`RealWorldLocation`] and
+//!   `nalgebra` points and vectors, and `RealWorldLocation::transform` to
+//!   apply an `Isometry3`.
+//! - `threads`: partitioning on a worker thread, see
+//!   `LocalMap::partition_async`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
+mod ascii;
+pub mod assignment;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+mod cell_csv;
+mod cell_entry;
+mod cell_index;
 mod cell_map;
+mod codec;
+mod collision;
+#[cfg(feature = "std")]
+mod color_map;
 mod coords;
+pub mod exploration;
+#[cfg(feature = "std")]
+pub mod export;
+mod footprint;
+mod frontiers;
+#[cfg(feature = "generators")]
+pub mod generators;
+pub mod geo_coords;
+mod grid_index;
+#[cfg(feature = "std")]
+pub mod heatmap;
+#[cfg(feature = "inspector")]
+pub mod inspector;
 mod local_map;
+mod map_delta;
+mod map_graph;
+pub mod merge;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_interop;
+mod observed_map;
+#[cfg(feature = "std")]
+mod overlay;
+mod partition_control;
+mod partition_factors;
+mod partition_outcome;
+pub mod partitioning;
+mod passages;
+#[cfg(feature = "std")]
 mod polygon_map;
+#[cfg(feature = "std")]
+mod recorder;
+mod regions;
+pub mod registration;
+mod resample;
+mod sensor;
+mod skeleton;
+mod sliced_map;
+pub mod tour;
+mod versioned_map;
+mod visibility;
+mod wavefront;
+#[cfg(feature = "threads")]
+mod worker;
+mod workload;
 
+#[cfg(feature = "std")]
+pub use cell_csv::CellMapCsvError;
+pub use cell_entry::CellEntry;
+pub use cell_index::CellIndex;
 pub use cell_map::Cell;
-pub use cell_map::CellMap;
+pub use cell_map::{CellMap, CellMapError, CellValue};
+pub use codec::CellMapDecodeError;
+#[cfg(feature = "std")]
+pub use color_map::ColorMap;
 pub use coords::AxisResolution;
 pub use coords::Coords;
+pub use footprint::Footprint;
+pub use frontiers::FrontierCluster;
+pub use grid_index::GridIndex;
 
 pub use coords::RealWorldLocation;
 use ndarray::Array2;
-pub use polygon_map::{PolygonMap, PolygonMapError};
+#[cfg(feature = "std")]
+pub use polygon_map::{
+    PixelSampling, PolygonMap, PolygonMapError, RasterOptions,
+};
+#[cfg(feature = "std")]
+pub use recorder::MapRecorder;
+pub use regions::Connectivity;
 
 pub use local_map::{LocalMap, Robot};
+pub use map_delta::{MapDelta, MapDeltaError};
+pub use map_graph::{MapEdge, MapGraph, MapNode};
+pub use observed_map::{ObservedMap, ObserverId};
+#[cfg(feature = "std")]
+pub use overlay::ImageOverlay;
+pub use partition_control::{
+    PartitionCancelled, PartitionControl, PartitionProgress,
+};
+pub use partition_factors::{
+    IntoFactors, PartitionFactors, PartitionFactorsBuilder,
+    PartitionFactorsError,
+};
+pub use partition_outcome::PartitionOutcome;
+pub use passages::Passage;
+pub use sensor::{CircularSensor, ConeSensor, SensorModel};
+pub use sliced_map::{SlicedMap, SlicedMapError};
+pub use versioned_map::{VersionedMap, VersionedMapError};
+#[cfg(feature = "threads")]
+pub use worker::PartitionHandle;
+pub use workload::CoverageEstimate;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 
 pub type LocationType = MapState;
 pub type MapStateMatrix = Array2<LocationType>;
+/// A point in time, used to keep track of when locations were last updated.
+///
+/// No particular unit or epoch is assumed; a common choice is seconds since
+/// the start of the mission. See [`CellMap::set_clock`].
+pub type Timestamp = f64;
 /// The function signature which the partitioning algorithm should have.
 ///
 /// `T` is the type of the map to be partitioned. The function is intended to
@@ -43,8 +171,27 @@ pub type MapStateMatrix = Array2<LocationType>;
 /// such a factor and used for weighting other metrics.
 ///
 /// Note that `F` is given as an [`Option`], allowing to not pass any additional
-/// factors beyond what is already encoded in the map `T`.
-pub type Algorithm<T> = fn(T) -> T;
+/// factors beyond what is already encoded in the map `T`. See
+/// [`PartitionFactors`] for a ready-made factors type.
+///
+/// This is the function pointer form, e.g. to store an algorithm or to choose
+/// one at runtime. [`Partition::partition`] accepts any function or closure
+/// with this signature, including closures capturing their environment.
+pub type Algorithm<T, F> = fn(T, Option<F>) -> T;
+/// Same as [`Algorithm`], for algorithms which additionally report a
+/// [`PartitionOutcome`], see [`Partition::partition_with_outcome`].
+pub type OutcomeAlgorithm<T, F> = fn(T, Option<F>) -> (T, PartitionOutcome);
+/// Same as [`OutcomeAlgorithm`], for algorithms which report their progress
+/// and can be cancelled through a [`PartitionControl`], see
+/// [`Partition::partition_with_control`].
+///
+/// A cancelled algorithm returns the map it was given, unchanged.
+pub type ControlledAlgorithm<T, F> =
+    fn(
+        T,
+        Option<F>,
+        &PartitionControl,
+    ) -> Result<(T, PartitionOutcome), PartitionCancelled<T>>;
 
 /// Visualize a map.
 pub trait Visualize {
@@ -70,16 +217,114 @@ pub trait Visualize {
     /// [`LocationType`] variants to colors that can be used by the
     /// [`image::ImageBuffer`] being output in this function.
     fn as_image(&self) -> Self::ImageType;
+    /// Render the map into a buffer of RGBA bytes.
+    ///
+    /// The pixels are given row by row, four bytes per pixel, which is the
+    /// layout expected by an HTML canvas' `ImageData`. The dimensions of the
+    /// image are the same as the one returned by [`Visualize::as_image`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation, Visualize,
+    /// };
+    ///
+    /// let map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let bytes = map.as_rgba_bytes();
+    ///
+    /// assert_eq!(bytes.len(), 3 * 2 * 4);
+    /// let [r, g, b] = MapState::Unexplored.to_rgb().0;
+    /// assert_eq!(&bytes[..4], &[r, g, b, 255]);
+    /// ```
+    #[cfg(feature = "std")]
+    fn as_rgba_bytes(&self) -> Vec<u8>
+    where
+        Self::ImageType: Into<image::DynamicImage>,
+    {
+        self.as_image().into().into_rgba8().into_raw()
+    }
+    /// Render the map like [`Visualize::as_image`], but with each pixel
+    /// enlarged to a square of `pixels_per_cell` pixels.
+    ///
+    /// If `grid_lines` is given, the borders between cells are drawn in that
+    /// color, using the first row and column of pixels of each cell except
+    /// the ones at the image border. Grid lines are only drawn when a cell
+    /// is at least 3 pixels wide, since they would hide the cells otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scaled image would be wider or higher than [`u32::MAX`]
+    /// pixels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use image::Rgb;
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation, Visualize,
+    /// };
+    ///
+    /// let map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(30.0, 20.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let grid = Rgb([0, 0, 0]);
+    ///
+    /// let image = map.as_image_scaled(10, Some(grid));
+    ///
+    /// assert_eq!(image.dimensions(), (300, 200));
+    /// assert_eq!(*image.get_pixel(5, 5), MapState::Unexplored.to_rgb());
+    /// assert_eq!(*image.get_pixel(10, 5), grid);
+    /// ```
+    #[cfg(feature = "std")]
+    fn as_image_scaled(
+        &self,
+        pixels_per_cell: u32,
+        grid_lines: Option<image::Rgb<u8>>,
+    ) -> image::RgbImage
+    where
+        Self::ImageType: Into<image::DynamicImage>,
+    {
+        let image = self.as_image().into().into_rgb8();
+        let scale = |size: u32| {
+            size.checked_mul(pixels_per_cell)
+                .expect("Scaled image should fit into an image")
+        };
+        let grid_lines = grid_lines.filter(|_| pixels_per_cell >= 3);
+        image::RgbImage::from_fn(
+            scale(image.width()),
+            scale(image.height()),
+            |x, y| match grid_lines {
+                Some(color)
+                    if (x > 0 && x % pixels_per_cell == 0)
+                        || (y > 0 && y % pixels_per_cell == 0) =>
+                {
+                    color
+                }
+                _ => *image.get_pixel(x / pixels_per_cell, y / pixels_per_cell),
+            },
+        )
+    }
     /// Visualize the map using a GUI window.
     ///
+    /// [`CellMap`] and [`LocalMap`]s of [`CellMap`]s open an
+    /// `inspector::MapInspector` showing a snapshot of the map when the
+    /// `inspector` feature is enabled, blocking until the window is closed.
+    ///
     /// # Panics
     ///
-    /// As of now, **no implementation is given**. Attempting to call
-    /// the function will panic due to a `todo!` macro. Writing the image to a
-    /// file appears to be the more straigthforward way to do display it.
+    /// The default implementation panics due to a `todo!` macro. Writing the
+    /// image to a file appears to be the more straigthforward way to display
+    /// other maps.
     ///
-    /// Future note: this function should make use of [`Visualize::as_image`].
-    /// This allows providing a default implementation.
+    /// The implementation of [`CellMap`] panics if the window cannot be
+    /// opened, e.g. because no display is available.
     fn visualize(&self) {
         todo!("How to display images using a GUI window?");
     }
@@ -99,23 +344,196 @@ pub trait Visualize {
 /// The overarching idea was to allow multiple partitioning schemes to be
 /// implemented, which can be done by creating multiple crates/modules which
 /// each implement the partitioning in any way they see fit.
-pub trait Partition {
+///
+/// `F` is the type of the partitioning factors handed to the algorithm, see
+/// [`Algorithm`], which defaults to [`PartitionFactors`] as used by the
+/// algorithms of the [`partitioning`] module.
+///
+/// Nothing is stored in the map: both the algorithm and its factors are
+/// passed to each call, such that the same map can be partitioned by
+/// different algorithms, e.g. to compare them. The algorithm may be any
+/// function or closure with the signature of [`Algorithm`] (respectively
+/// [`OutcomeAlgorithm`] or [`ControlledAlgorithm`]), so additional parameters
+/// can simply be captured. [`LocalMap`] implements the trait for any `F`.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     partitioning::sweep_line, AxisResolution, CellMap, LocalMap, Partition,
+///     PartitionFactors, RealWorldLocation, Robot,
+/// };
+///
+/// let map = LocalMap::new_noexpand(
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     ),
+///     Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+///     vec![Robot::new(RealWorldLocation::from_xyz(9.5, 9.5, 0.0), ())],
+/// )
+/// .unwrap();
+/// let factors = PartitionFactors::builder()
+///     .speeds(vec![1.0, 2.0])
+///     .build()
+///     .unwrap();
+///
+/// let angle = 0.5;
+/// let map = map
+///     .partition(
+///         |map, factors| sweep_line(map, factors, angle),
+///         Some(factors),
+///     )
+///     .unwrap();
+/// ```
+pub trait Partition<F = PartitionFactors> {
     /// Consumes the map and returns the partitioned version thereof.
     fn partition(
         self,
-        partition_algorithm: Algorithm<Self>,
+        partition_algorithm: impl FnOnce(Self, Option<F>) -> Self,
+        factors: Option<F>,
     ) -> Result<Self, PartitionError>
     where
         Self: Sized,
     {
-        Ok(partition_algorithm(self))
+        Ok(partition_algorithm(self, factors))
+    }
+
+    /// Same as [`Partition::partition`], but also returns the
+    /// [`PartitionOutcome`] reported by the algorithm, e.g. the number of
+    /// cells each robot received.
+    ///
+    /// With the `std` feature, the runtime of the algorithm is measured and
+    /// stored in the outcome, unless the algorithm already did so.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     partitioning::weighted_voronoi_with_outcome, AxisResolution,
+    ///     CellMap, LocalMap, Partition, RealWorldLocation, Robot,
+    /// };
+    ///
+    /// let map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     Robot::new(RealWorldLocation::from_xyz(5.0, 5.0, 0.0), ()),
+    ///     vec![Robot::new(RealWorldLocation::from_xyz(15.0, 5.0, 0.0), ())],
+    /// )
+    /// .unwrap();
+    ///
+    /// let (map, outcome) = map
+    ///     .partition_with_outcome(weighted_voronoi_with_outcome, None)
+    ///     .unwrap();
+    ///
+    /// // Both robots' own cells are not partitioned.
+    /// assert_eq!(outcome.cell_counts(), [99, 99]);
+    /// assert_eq!(outcome.seeds()[1], *map.other_robots()[0].location());
+    /// assert!(outcome.runtime().is_some());
+    /// ```
+    fn partition_with_outcome<A>(
+        self,
+        partition_algorithm: A,
+        factors: Option<F>,
+    ) -> Result<(Self, PartitionOutcome), PartitionError>
+    where
+        Self: Sized,
+        A: FnOnce(Self, Option<F>) -> (Self, PartitionOutcome),
+    {
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+        let (map, outcome) = partition_algorithm(self, factors);
+        #[cfg(feature = "std")]
+        let outcome = match outcome.runtime() {
+            Some(_) => outcome,
+            None => outcome.with_runtime(start.elapsed()),
+        };
+        Ok((map, outcome))
+    }
+
+    /// Same as [`Partition::partition_with_outcome`], but the algorithm
+    /// reports its progress to `control` and stops early once `control` is
+    /// cancelled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PartitionCancelled`] holding the unchanged map if the run
+    /// was cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     partitioning::weighted_voronoi_with_control, AxisResolution,
+    ///     CellMap, LocalMap, Partition, PartitionControl, RealWorldLocation,
+    ///     Robot,
+    /// };
+    ///
+    /// let make_map = || {
+    ///     LocalMap::new_noexpand(
+    ///         CellMap::new(
+    ///             RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///             RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+    ///             AxisResolution::uniform(1.0),
+    ///         ),
+    ///         Robot::new(RealWorldLocation::from_xyz(5.0, 5.0, 0.0), ()),
+    ///         vec![Robot::new(
+    ///             RealWorldLocation::from_xyz(15.0, 5.0, 0.0),
+    ///             (),
+    ///         )],
+    ///     )
+    ///     .unwrap()
+    /// };
+    ///
+    /// let control = PartitionControl::with_progress(|progress| {
+    ///     println!("{:.0}%", progress.fraction() * 100.0);
+    /// });
+    /// let (_, outcome) = make_map()
+    ///     .partition_with_control(weighted_voronoi_with_control, None, &control)
+    ///     .unwrap();
+    /// assert_eq!(outcome.cell_counts(), [99, 99]);
+    ///
+    /// control.cancel();
+    /// let cancelled = make_map()
+    ///     .partition_with_control(weighted_voronoi_with_control, None, &control)
+    ///     .unwrap_err();
+    /// assert_eq!(cancelled.into_map().map(), make_map().map());
+    /// ```
+    fn partition_with_control<A>(
+        self,
+        partition_algorithm: A,
+        factors: Option<F>,
+        control: &PartitionControl,
+    ) -> Result<(Self, PartitionOutcome), PartitionCancelled<Self>>
+    where
+        Self: Sized,
+        A: FnOnce(
+            Self,
+            Option<F>,
+            &PartitionControl,
+        )
+            -> Result<(Self, PartitionOutcome), PartitionCancelled<Self>>,
+    {
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+        let (map, outcome) = partition_algorithm(self, factors, control)?;
+        #[cfg(feature = "std")]
+        let outcome = match outcome.runtime() {
+            Some(_) => outcome,
+            None => outcome.with_runtime(start.elapsed()),
+        };
+        Ok((map, outcome))
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub enum PartitionError {
     /// No (suitable) map was provided for partitioning.
-    /// See also [`PolygonMapError::NotEnoughVertices`]
+    /// See also `PolygonMapError::NotEnoughVertices`
     NoMap,
 }
 
@@ -125,8 +543,20 @@ pub trait Mask {
     /// condition.
     fn get_map_region(
         &self,
-        filter: impl Fn(LocationType) -> bool,
-    ) -> Vec<Cell>;
+        filter: &dyn Fn(LocationType) -> bool,
+    ) -> Vec<Cell<'_>>;
+
+    /// Same as [`Mask::get_map_region`], but the returned cells do not borrow
+    /// the map, which allows modifying the map while holding on to them.
+    fn get_map_region_owned(
+        &self,
+        filter: &dyn Fn(LocationType) -> bool,
+    ) -> Vec<CellValue> {
+        self.get_map_region(filter)
+            .into_iter()
+            .map(CellValue::from)
+            .collect()
+    }
 }
 
 /// Retrieve a subarea of the map based on a [`MapState`]
@@ -134,12 +564,100 @@ pub trait Mask {
 /// This trait is automatically implemented when [`Mask`] is implemented, and
 /// the type `T` can be compared to a [`MapState`].
 pub trait MaskMapState {
-    fn get_map_state(&self, state: MapState) -> Vec<Cell>;
+    fn get_map_state(&self, state: MapState) -> Vec<Cell<'_>>;
+    /// Same as [`MaskMapState::get_map_state`], but returns owned cells. See
+    /// [`Mask::get_map_region_owned`].
+    fn get_map_state_owned(&self, state: MapState) -> Vec<CellValue>;
 }
 
 impl<T: Mask> MaskMapState for T {
-    fn get_map_state(&self, state: MapState) -> Vec<Cell> {
-        self.get_map_region(|e| e == state)
+    fn get_map_state(&self, state: MapState) -> Vec<Cell<'_>> {
+        self.get_map_region(&|e| e == state)
+    }
+    fn get_map_state_owned(&self, state: MapState) -> Vec<CellValue> {
+        self.get_map_region_owned(&|e| e == state)
+    }
+}
+
+/// The part of a map a [`LocalMap`] relies on, usable as a trait object.
+///
+/// This trait is automatically implemented for every map implementing
+/// [`Location`], [`Mask`] and [`Debug`](core::fmt::Debug). A
+/// `LocalMap<Box<dyn MapView>, P>` thus allows choosing the map backend at
+/// runtime, e.g. from a configuration file, without the concrete type
+/// leaking into the code holding the [`LocalMap`].
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, LocalMap, MapState, MapView, MaskMapState,
+///     ObservedMap, RealWorldLocation, Robot,
+/// };
+///
+/// let cell_map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+/// let observed = true;
+/// let map: Box<dyn MapView> = if observed {
+///     Box::new(ObservedMap::new(cell_map))
+/// } else {
+///     Box::new(cell_map)
+/// };
+///
+/// let lmap = LocalMap::new_noexpand(
+///     map,
+///     Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+///     vec![],
+/// )
+/// .unwrap();
+///
+/// assert_eq!(lmap.map().get_map_state(MapState::MyRobot).len(), 1);
+/// ```
+pub trait MapView: Location + Mask + core::fmt::Debug {}
+
+impl<T: Location + Mask + core::fmt::Debug + ?Sized> MapView for T {}
+
+impl<T: Location + ?Sized> Location for Box<T> {
+    fn get_location(
+        &self,
+        coord: &RealWorldLocation,
+    ) -> Result<LocationType, LocationError> {
+        (**self).get_location(coord)
+    }
+    fn set_location(
+        &mut self,
+        coord: &RealWorldLocation,
+        value: LocationType,
+    ) -> Result<(), LocationError> {
+        (**self).set_location(coord, value)
+    }
+    fn locations_within(
+        &self,
+        center: &RealWorldLocation,
+        radius: f64,
+    ) -> Vec<RealWorldLocation> {
+        (**self).locations_within(center, radius)
+    }
+    fn same_cell(&self, a: &RealWorldLocation, b: &RealWorldLocation) -> bool {
+        (**self).same_cell(a, b)
+    }
+}
+
+impl<T: Mask + ?Sized> Mask for Box<T> {
+    fn get_map_region(
+        &self,
+        filter: &dyn Fn(LocationType) -> bool,
+    ) -> Vec<Cell<'_>> {
+        (**self).get_map_region(filter)
+    }
+    fn get_map_region_owned(
+        &self,
+        filter: &dyn Fn(LocationType) -> bool,
+    ) -> Vec<CellValue> {
+        (**self).get_map_region_owned(filter)
     }
 }
 
@@ -151,7 +669,7 @@ impl<T: Mask> MaskMapState for T {
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum MapState {
     /// Indicates the location is outside the map region (mostly relevant for
-    /// non-square maps such as those which can be produced by [`PolygonMap`])
+    /// non-square maps such as those which can be produced by `PolygonMap`)
     OutOfMap,
     /// Indicates the location is occupied by another robot
     OtherRobot,
@@ -166,6 +684,9 @@ pub enum MapState {
     Frontier,
     /// Indicates the location is assigned to the current robot
     Assigned,
+    /// Indicates the location is blocked, e.g. by a wall, and can neither be
+    /// traversed nor seen through
+    Obstacle,
 }
 
 impl MapState {
@@ -173,6 +694,7 @@ impl MapState {
     ///
     /// This is useful when trying to visualize the map. Can be
     /// used when implementing [`Visualize::as_image`].
+    #[cfg(feature = "std")]
     pub fn to_luma(&self) -> image::Luma<u8> {
         self.into()
     }
@@ -180,10 +702,56 @@ impl MapState {
     /// Returns a corresponding [`image::Rgb`] of the [`MapState`].
     ///
     /// This is useful when trying to visualize the map. Can be
-    /// used when implementing [`Visualize::as_image`].
+    /// used when implementing [`Visualize::as_image`]. Use a [`ColorMap`] to
+    /// render with other colors.
+    #[cfg(feature = "std")]
     pub fn to_rgb(&self) -> image::Rgb<u8> {
         self.into()
     }
+
+    /// Importance of the state, used to resolve conflicts whenever several
+    /// states compete for a single cell, e.g. when merging maps or showing
+    /// blocks of cells as a single character in [`CellMap::to_ascii`].
+    ///
+    /// From lowest to highest, the priorities are:
+    ///
+    /// 0. [`MapState::OutOfMap`]
+    /// 1. [`MapState::Unexplored`]
+    /// 2. [`MapState::Explored`]
+    /// 3. [`MapState::Assigned`]
+    /// 4. [`MapState::Frontier`]
+    /// 5. [`MapState::Obstacle`]
+    /// 6. [`MapState::OtherRobot`]
+    /// 7. [`MapState::MyRobot`]
+    ///
+    /// Any knowledge about a cell beats not knowing it, more specific states
+    /// beat more general ones, and states relevant to navigation (obstacles
+    /// and robots) beat everything else. No two states share a priority.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::MapState;
+    ///
+    /// let states = [MapState::Explored, MapState::Obstacle, MapState::Frontier];
+    ///
+    /// assert_eq!(
+    ///     states.into_iter().max_by_key(MapState::priority),
+    ///     Some(MapState::Obstacle)
+    /// );
+    /// ```
+    pub fn priority(&self) -> u8 {
+        match self {
+            MapState::OutOfMap => 0,
+            MapState::Unexplored => 1,
+            MapState::Explored => 2,
+            MapState::Assigned => 3,
+            MapState::Frontier => 4,
+            MapState::Obstacle => 5,
+            MapState::OtherRobot => 6,
+            MapState::MyRobot => 7,
+        }
+    }
 }
 
 impl From<&MapState> for &str {
@@ -196,10 +764,47 @@ impl From<&MapState> for &str {
             MapState::Unexplored => "Unexplored",
             MapState::Frontier => "Frontier",
             MapState::Assigned => "Assigned",
+            MapState::Obstacle => "Obstacle",
         }
     }
 }
 
+/// Byte representation of a [`MapState`], used for compact encodings such as
+/// [`CellMap::to_bytes`].
+impl From<&MapState> for u8 {
+    fn from(value: &MapState) -> Self {
+        match value {
+            MapState::OutOfMap => 0,
+            MapState::OtherRobot => 1,
+            MapState::MyRobot => 2,
+            MapState::Explored => 3,
+            MapState::Unexplored => 4,
+            MapState::Frontier => 5,
+            MapState::Assigned => 6,
+            MapState::Obstacle => 7,
+        }
+    }
+}
+
+impl TryFrom<u8> for MapState {
+    type Error = CellMapDecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MapState::OutOfMap),
+            1 => Ok(MapState::OtherRobot),
+            2 => Ok(MapState::MyRobot),
+            3 => Ok(MapState::Explored),
+            4 => Ok(MapState::Unexplored),
+            5 => Ok(MapState::Frontier),
+            6 => Ok(MapState::Assigned),
+            7 => Ok(MapState::Obstacle),
+            _ => Err(CellMapDecodeError::UnknownState(value)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl From<&MapState> for image::Luma<u8> {
     fn from(value: &MapState) -> Self {
         use image::Luma;
@@ -211,22 +816,16 @@ impl From<&MapState> for image::Luma<u8> {
             MapState::Unexplored => Luma([120]),
             MapState::Frontier => Luma([220]),
             MapState::Assigned => Luma([255]),
+            MapState::Obstacle => Luma([20]),
         }
     }
 }
 
+/// The colors of [`ColorMap::DEFAULT`].
+#[cfg(feature = "std")]
 impl From<&MapState> for image::Rgb<u8> {
     fn from(value: &MapState) -> Self {
-        use image::Rgb;
-        match value {
-            MapState::OutOfMap => Rgb([0, 0, 0]),
-            MapState::OtherRobot => Rgb([50, 255, 50]),
-            MapState::MyRobot => Rgb([255, 50, 50]),
-            MapState::Explored => Rgb([200, 200, 200]),
-            MapState::Unexplored => Rgb([100, 100, 100]),
-            MapState::Frontier => Rgb([255, 100, 255]),
-            MapState::Assigned => Rgb([255, 255, 0]),
-        }
+        ColorMap::DEFAULT.color(*value)
     }
 }
 
@@ -272,10 +871,107 @@ pub trait Location {
         coord: &RealWorldLocation,
         value: LocationType,
     ) -> Result<(), LocationError>;
+    /// One location per cell whose center lies within `radius` of `center`,
+    /// measured in the `x`/`y` plane, e.g. to mark the [`Footprint`] of a
+    /// robot.
+    ///
+    /// Locations outside the map are left out. The default implementation
+    /// returns `center` only, for maps without a notion of cells.
+    fn locations_within(
+        &self,
+        center: &RealWorldLocation,
+        radius: f64,
+    ) -> Vec<RealWorldLocation> {
+        let _ = radius;
+        alloc::vec![*center]
+    }
+    /// Whether `a` and `b` fall into the same cell of the map.
+    ///
+    /// The default implementation compares the coordinates exactly, for maps
+    /// without a notion of cells.
+    fn same_cell(&self, a: &RealWorldLocation, b: &RealWorldLocation) -> bool {
+        a == b
+    }
 }
 
 #[derive(Debug, PartialEq)]
 pub enum LocationError {
     /// The requested location is outside the map area and cannot be accessed.
-    OutOfMap,
+    ///
+    /// The details are boxed to keep the error, which is returned by every map
+    /// access, small.
+    OutOfMap(Box<OutOfMapError>),
+}
+
+impl From<OutOfMapError> for LocationError {
+    fn from(value: OutOfMapError) -> Self {
+        Self::OutOfMap(Box::new(value))
+    }
+}
+
+/// Details about a location which is outside of a map.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, Location, LocationError, RealWorldLocation,
+/// };
+///
+/// let map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+///
+/// let LocationError::OutOfMap(error) = map
+///     .get_location(&RealWorldLocation::from_xyz(1.0, 5.0, 0.0))
+///     .unwrap_err();
+/// assert_eq!(error.location(), &RealWorldLocation::from_xyz(1.0, 5.0, 0.0));
+/// assert_eq!(error.violated_axes(), [false, true, false]);
+/// assert_eq!(
+///     error.bounds(),
+///     Some(&[
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+///     ])
+/// );
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct OutOfMapError {
+    location: RealWorldLocation,
+    bounds: Option<[RealWorldLocation; 2]>,
+    axes: [bool; 3],
+}
+
+impl OutOfMapError {
+    /// Describe that `location` lies outside of the map along the `[x, y, z]`
+    /// `axes` set to `true`. The `bounds` are the bottom left and top right
+    /// corners of the map, if known.
+    pub fn new(
+        location: RealWorldLocation,
+        bounds: Option<[RealWorldLocation; 2]>,
+        axes: [bool; 3],
+    ) -> Self {
+        Self {
+            location,
+            bounds,
+            axes,
+        }
+    }
+
+    /// The location which was requested.
+    pub fn location(&self) -> &RealWorldLocation {
+        &self.location
+    }
+
+    /// The bottom left and top right corners of the map, if known.
+    pub fn bounds(&self) -> Option<&[RealWorldLocation; 2]> {
+        self.bounds.as_ref()
+    }
+
+    /// Whether the location is outside of the map along the `[x, y, z]` axes.
+    pub fn violated_axes(&self) -> [bool; 3] {
+        self.axes
+    }
 }
@@ -1,6 +1,10 @@
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
 use crate::{
-    Location, LocationError, MapState, MaskMapState, Partition,
-    RealWorldLocation, Visualize,
+    Footprint, IntoFactors, Location, LocationError, MapState, MaskMapState,
+    Partition, PartitionError, RealWorldLocation, Timestamp, Visualize,
 };
 
 /// Wrapper type to store robot's location **and** related parameters.
@@ -11,10 +15,19 @@ use crate::{
 ///
 /// One use case for the parameters could be to add identifiers to the robots,
 /// or to include factors that shall influence the partitioning.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Robot<P> {
     location: RealWorldLocation,
     parameters: P,
+    /// Positions recorded by the [`LocalMap`] holding the robot, see
+    /// [`LocalMap::record_trajectories`].
+    trajectory: Vec<(Timestamp, RealWorldLocation)>,
+    /// When the position of the robot was last updated, see
+    /// [`LocalMap::prune_stale`].
+    last_seen: Timestamp,
+    /// Yaw in radians, see [`Robot::heading`].
+    heading: Option<f64>,
+    footprint: Footprint,
 }
 
 impl<P> Robot<P> {
@@ -22,14 +35,104 @@ impl<P> Robot<P> {
         Self {
             location,
             parameters,
+            trajectory: Vec::new(),
+            last_seen: 0.0,
+            heading: None,
+            footprint: Footprint::Point,
         }
     }
+    /// Set the heading of the robot, see [`Robot::heading`].
+    pub fn with_heading(mut self, yaw: f64) -> Self {
+        self.heading = Some(yaw);
+        self
+    }
+    /// Set the heading of the robot from an orientation quaternion
+    /// `[w, x, y, z]`, e.g. as reported by an IMU or a ROS pose. Only the
+    /// rotation around the `z` axis is kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{RealWorldLocation, Robot};
+    ///
+    /// let half_turn = core::f64::consts::FRAC_PI_4.sin();
+    /// let robot = Robot::new(RealWorldLocation::from_xyz(0.0, 0.0, 0.0), ())
+    ///     .with_quaternion([half_turn, 0.0, 0.0, half_turn]);
+    ///
+    /// let yaw = robot.heading().unwrap();
+    /// assert!((yaw - core::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    /// ```
+    pub fn with_quaternion(self, [w, x, y, z]: [f64; 4]) -> Self {
+        let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+        self.with_heading(yaw)
+    }
+    /// Set the area occupied by the robot, see [`Robot::footprint`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Footprint, LocalMap, MapState,
+    ///     MaskMapState, RealWorldLocation, Robot,
+    /// };
+    ///
+    /// let robot = Robot::new(RealWorldLocation::from_xyz(2.0, 2.0, 0.0), ())
+    ///     .with_footprint(Footprint::Circle(1.0));
+    /// let map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     robot,
+    ///     vec![],
+    /// )
+    /// .unwrap();
+    ///
+    /// // The four cells around the robot are covered.
+    /// assert_eq!(map.map().get_map_state(MapState::MyRobot).len(), 4);
+    /// ```
+    pub fn with_footprint(mut self, footprint: Footprint) -> Self {
+        self.footprint = footprint;
+        self
+    }
     pub fn location(&self) -> &RealWorldLocation {
         &self.location
     }
     pub fn parameters(&self) -> &P {
         &self.parameters
     }
+    /// Mutable access to the parameters, e.g. to update a battery level
+    /// between partitioning cycles.
+    pub fn parameters_mut(&mut self) -> &mut P {
+        &mut self.parameters
+    }
+    /// The positions of the robot along with the time they were reached,
+    /// oldest first. Empty unless recorded, see
+    /// [`LocalMap::record_trajectories`].
+    pub fn trajectory(&self) -> &[(Timestamp, RealWorldLocation)] {
+        &self.trajectory
+    }
+    /// The [`LocalMap::clock`] at which the robot was last moved or seen.
+    /// Robots are considered seen at time `0.0` when created.
+    pub fn last_seen(&self) -> Timestamp {
+        self.last_seen
+    }
+    /// The direction the robot is facing, as a yaw angle in radians measured
+    /// counterclockwise from the `x` axis, or [`None`] if unknown.
+    ///
+    /// Partitioning algorithms may use it e.g. to align sweep directions or
+    /// sensor footprints with the robot.
+    pub fn heading(&self) -> Option<f64> {
+        self.heading
+    }
+    /// The area occupied by the robot, [`Footprint::Point`] by default.
+    ///
+    /// A [`LocalMap`] marks the cell containing the location of the robot as
+    /// well as all cells whose center is covered by the footprint.
+    pub fn footprint(&self) -> &Footprint {
+        &self.footprint
+    }
 }
 
 /// Type for map stored locally on a robot.
@@ -38,25 +141,33 @@ impl<P> Robot<P> {
 ///
 /// - `T` describes the type of the map that is used for partitioning. It is
 ///   restricted under certain traits which allows for a more uniform and
-///   standardized implementation.
+///   standardized implementation. Use `Box<dyn MapView>` to choose the map
+///   type at runtime, see [`MapView`](crate::MapView).
 /// - `F` is a type containing additional partitioning factors. They are passed
 ///   as arguments to the `partition_algorithm` function.
 ///
 /// Note that if you are not interested in additional partitioning factors, you
 /// can set `F` to be the empty type `()`. And then simply perform the
 /// partitioning by passing [`None`] as the partitioning factors.
+///
+/// Cloning a [`LocalMap`] allows trying out a partition on a copy, e.g. to
+/// compare several candidates, while keeping the original map.
+#[derive(Clone)]
 pub struct LocalMap<T, P>
 where
-    T: Location + MaskMapState + Visualize + std::fmt::Debug,
+    T: Location + MaskMapState + core::fmt::Debug,
 {
     map: T,
     my_robot: Robot<P>,
     other_robots: Vec<Robot<P>>,
+    /// The current time, see [`LocalMap::set_clock`].
+    clock: Timestamp,
+    record_trajectories: bool,
 }
 
 impl<T, P> LocalMap<T, P>
 where
-    T: Location + MaskMapState + Visualize + std::fmt::Debug,
+    T: Location + MaskMapState + core::fmt::Debug,
 {
     /// Create a [`LocalMap`] which does not allow out-of-map robots.
     ///
@@ -75,16 +186,16 @@ where
         other_robots: Vec<Robot<P>>,
     ) -> Result<Self, (LocationError, RealWorldLocation)> {
         if let Err(location_error) =
-            map.set_location(my_robot.location(), MapState::MyRobot)
+            Self::occupy(&mut map, &my_robot, MapState::MyRobot)
         {
             return Err((location_error, my_robot.location));
         };
 
         for pos in &other_robots {
             if let Err(location_error) =
-                map.set_location(pos.location(), MapState::OtherRobot)
+                Self::occupy(&mut map, pos, MapState::OtherRobot)
             {
-                return Err((location_error, pos.location().clone()));
+                return Err((location_error, *pos.location()));
             }
         }
 
@@ -92,6 +203,8 @@ where
             map,
             my_robot,
             other_robots,
+            clock: 0.0,
+            record_trajectories: false,
         })
     }
 
@@ -110,22 +223,22 @@ where
         my_robot: Robot<P>,
         other_robots: Vec<Robot<P>>,
     ) -> Result<Self, (LocationError, RealWorldLocation)> {
-        match map.set_location(my_robot.location(), MapState::MyRobot) {
+        match Self::occupy(&mut map, &my_robot, MapState::MyRobot) {
             Ok(_) => {}
             Err(e) => match e {
-                LocationError::OutOfMap => {}
+                LocationError::OutOfMap(_) => {}
                 #[allow(unreachable_patterns)]
-                _ => return Err((e, my_robot.location().clone())),
+                _ => return Err((e, *my_robot.location())),
             },
         }
 
         for pos in &other_robots {
-            match map.set_location(pos.location(), MapState::OtherRobot) {
+            match Self::occupy(&mut map, pos, MapState::OtherRobot) {
                 Ok(_) => {}
                 Err(e) => match e {
-                    LocationError::OutOfMap => {}
+                    LocationError::OutOfMap(_) => {}
                     #[allow(unreachable_patterns)]
-                    _ => return Err((e, my_robot.location().clone())),
+                    _ => return Err((e, *my_robot.location())),
                 },
             }
         }
@@ -134,6 +247,8 @@ where
             map,
             my_robot,
             other_robots,
+            clock: 0.0,
+            record_trajectories: false,
         })
     }
 
@@ -157,41 +272,434 @@ where
         &self.my_robot.location
     }
     pub fn other_positions(&self) -> Vec<RealWorldLocation> {
-        self.other_robots
-            .iter()
-            .map(|r| r.location().clone())
-            .collect()
+        self.other_robots.iter().map(|r| *r.location()).collect()
     }
     pub fn my_robot(&self) -> &Robot<P> {
         &self.my_robot
     }
+    /// Mutable access to my robot, mostly to update its parameters using
+    /// [`Robot::parameters_mut`].
+    ///
+    /// The robot is not marked on the map again, so replacing it as a whole
+    /// leaves the map outdated. Move it using [`LocalMap::move_my_robot`]
+    /// instead.
+    pub fn my_robot_mut(&mut self) -> &mut Robot<P> {
+        &mut self.my_robot
+    }
     pub fn other_robots(&self) -> &Vec<Robot<P>> {
         &self.other_robots
     }
+    /// Mutable access to the other robots, see [`LocalMap::my_robot_mut`].
+    ///
+    /// Robots can neither be added nor removed this way, since they are
+    /// marked on the map; see [`LocalMap::move_other_robot`] and
+    /// [`LocalMap::prune_stale`].
+    pub fn other_robots_mut(&mut self) -> &mut [Robot<P>] {
+        &mut self.other_robots
+    }
+
+    /// Set the current time, used to stamp the positions of the robots when
+    /// they move. Independent from the clock of the map, if it has any.
+    pub fn set_clock(&mut self, now: Timestamp) {
+        self.clock = now;
+    }
+    pub fn clock(&self) -> Timestamp {
+        self.clock
+    }
+
+    /// Whether to record the positions of all robots as they move, stamped
+    /// with the [`LocalMap::clock`]. Enabling it records the current
+    /// positions as the start of the trajectories; previously recorded
+    /// positions are kept either way.
+    ///
+    /// The trajectories are available using [`Robot::trajectory`] and
+    /// [`LocalMap::trajectory`].
+    pub fn record_trajectories(&mut self, enabled: bool) {
+        self.record_trajectories = enabled;
+        if enabled {
+            let now = self.clock;
+            for robot in self.robots_mut() {
+                robot.trajectory.push((now, robot.location));
+            }
+        }
+    }
+
+    /// The recorded trajectory of my robot, see
+    /// [`LocalMap::record_trajectories`].
+    pub fn trajectory(&self) -> &[(Timestamp, RealWorldLocation)] {
+        self.my_robot.trajectory()
+    }
+
+    /// Move my robot to a new location.
+    ///
+    /// The new location is marked as [`MapState::MyRobot`], while the previous
+    /// one is marked as [`MapState::Explored`], since the robot has been
+    /// there. The same goes for the cells covered by the
+    /// [`Robot::footprint`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new location cannot be set, see
+    /// [`Location::set_location`]. In that case, the robot is not moved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, LocalMap, Location, MapState,
+    ///     RealWorldLocation, Robot,
+    /// };
+    ///
+    /// let start = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+    /// let goal = RealWorldLocation::from_xyz(2.5, 0.5, 0.0);
+    /// let mut map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     Robot::new(start.clone(), ()),
+    ///     vec![],
+    /// )
+    /// .unwrap();
+    /// map.record_trajectories(true);
+    ///
+    /// map.set_clock(3.0);
+    /// map.move_my_robot(goal.clone()).unwrap();
+    ///
+    /// assert_eq!(map.map().get_location(&start), Ok(MapState::Explored));
+    /// assert_eq!(map.map().get_location(&goal), Ok(MapState::MyRobot));
+    /// assert_eq!(map.trajectory(), [(0.0, start), (3.0, goal)]);
+    /// ```
+    pub fn move_my_robot(
+        &mut self,
+        location: RealWorldLocation,
+    ) -> Result<(), LocationError> {
+        Self::move_robot(
+            &mut self.map,
+            &mut self.my_robot,
+            location,
+            MapState::MyRobot,
+            self.record_trajectories.then_some(self.clock),
+        )?;
+        self.my_robot.last_seen = self.clock;
+        Ok(())
+    }
+
+    /// Same as [`LocalMap::move_my_robot`], for the other robot at `index`
+    /// (see [`LocalMap::other_robots`]), e.g. when receiving its position.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`LocalMap::move_my_robot`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no other robot at `index`.
+    pub fn move_other_robot(
+        &mut self,
+        index: usize,
+        location: RealWorldLocation,
+    ) -> Result<(), LocationError> {
+        Self::move_robot(
+            &mut self.map,
+            &mut self.other_robots[index],
+            location,
+            MapState::OtherRobot,
+            self.record_trajectories.then_some(self.clock),
+        )?;
+        self.other_robots[index].last_seen = self.clock;
+        Ok(())
+    }
+
+    fn move_robot(
+        map: &mut T,
+        robot: &mut Robot<P>,
+        location: RealWorldLocation,
+        state: MapState,
+        stamp: Option<Timestamp>,
+    ) -> Result<(), LocationError> {
+        Self::place(map, robot, location, robot.heading, state)?;
+        if let Some(now) = stamp {
+            robot.trajectory.push((now, location));
+        }
+        Ok(())
+    }
+
+    /// Move the footprint of `robot` to `location` and `heading`. The cells
+    /// it no longer covers are marked as [`MapState::Explored`].
+    fn place(
+        map: &mut T,
+        robot: &mut Robot<P>,
+        location: RealWorldLocation,
+        heading: Option<f64>,
+        state: MapState,
+    ) -> Result<(), LocationError> {
+        // The previous cells may be outside the map (see
+        // `LocalMap::new_noexpand_nooutofmap`), or have been overwritten.
+        let previous: Vec<_> = Self::covered(map, robot)
+            .into_iter()
+            .filter(|covered| {
+                *covered != location
+                    && !robot.footprint.contains(&location, heading, covered)
+                    && map.get_location(covered) == Ok(state)
+            })
+            .collect();
+        map.set_location(&location, state)?;
+        for covered in previous {
+            let _ = map.set_location(&covered, MapState::Explored);
+        }
+        robot.location = location;
+        robot.heading = heading;
+        Self::occupy(map, robot, state)
+    }
+
+    /// Mark the cells covered by `robot` as `state`.
+    ///
+    /// Only an error for the location of the robot is returned; parts of the
+    /// footprint may stick out of the map.
+    fn occupy(
+        map: &mut T,
+        robot: &Robot<P>,
+        state: MapState,
+    ) -> Result<(), LocationError> {
+        let mut covered = Self::covered(map, robot).into_iter();
+        if let Some(location) = covered.next() {
+            map.set_location(&location, state)?;
+        }
+        for location in covered {
+            let _ = map.set_location(&location, state);
+        }
+        Ok(())
+    }
+
+    /// The location of `robot` followed by one location per cell covered by
+    /// its footprint.
+    fn covered(map: &T, robot: &Robot<P>) -> Vec<RealWorldLocation> {
+        let mut covered = alloc::vec![robot.location];
+        if robot.footprint != Footprint::Point {
+            let radius = robot.footprint.bounding_radius();
+            covered.extend(
+                map.locations_within(&robot.location, radius)
+                    .into_iter()
+                    .filter(|location| {
+                        robot.footprint.contains(
+                            &robot.location,
+                            robot.heading,
+                            location,
+                        )
+                    }),
+            );
+        }
+        covered
+    }
+
+    /// Change the heading of my robot, see [`Robot::heading`]. A
+    /// [`Footprint::Polygon`] is turned along with it.
+    pub fn set_my_heading(&mut self, yaw: Option<f64>) {
+        let location = self.my_robot.location;
+        // Only fails if the robot is outside the map.
+        let _ = Self::place(
+            &mut self.map,
+            &mut self.my_robot,
+            location,
+            yaw,
+            MapState::MyRobot,
+        );
+        self.my_robot.heading = yaw;
+    }
+
+    /// Change the heading of the other robot at `index`, see
+    /// [`Robot::heading`]. A [`Footprint::Polygon`] is turned along with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no other robot at `index`.
+    pub fn set_other_heading(&mut self, index: usize, yaw: Option<f64>) {
+        let robot = &mut self.other_robots[index];
+        let location = robot.location;
+        // Only fails if the robot is outside the map.
+        let _ = Self::place(
+            &mut self.map,
+            robot,
+            location,
+            yaw,
+            MapState::OtherRobot,
+        );
+        robot.heading = yaw;
+    }
+
+    /// Mark the other robot at `index` as seen at the current
+    /// [`LocalMap::clock`] without moving it, e.g. when it reported an
+    /// unchanged position. Moving a robot marks it as seen as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no other robot at `index`.
+    pub fn mark_seen(&mut self, index: usize) {
+        self.other_robots[index].last_seen = self.clock;
+    }
+
+    /// Remove the other robots which were not seen for longer than
+    /// `older_than`, relative to the [`LocalMap::clock`], and return them.
+    ///
+    /// Their cells, including the ones covered by their [`Robot::footprint`],
+    /// are marked as [`MapState::Explored`], unless another robot covers the
+    /// same cell or the cell was overwritten in the meantime. This prevents
+    /// partitioning the map around robots which failed or left.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, LocalMap, Location, MapState,
+    ///     RealWorldLocation, Robot,
+    /// };
+    ///
+    /// let lost = RealWorldLocation::from_xyz(3.5, 3.5, 0.0);
+    /// let mut map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), "me"),
+    ///     vec![
+    ///         Robot::new(lost.clone(), "lost"),
+    ///         Robot::new(RealWorldLocation::from_xyz(1.5, 3.5, 0.0), "alive"),
+    ///     ],
+    /// )
+    /// .unwrap();
+    ///
+    /// map.set_clock(600.0);
+    /// map.mark_seen(1);
+    /// let pruned = map.prune_stale(60.0);
+    ///
+    /// assert_eq!(pruned.len(), 1);
+    /// assert_eq!(*pruned[0].parameters(), "lost");
+    /// assert_eq!(map.other_robots().len(), 1);
+    /// assert_eq!(map.map().get_location(&lost), Ok(MapState::Explored));
+    /// ```
+    pub fn prune_stale(&mut self, older_than: Timestamp) -> Vec<Robot<P>> {
+        let now = self.clock;
+        let (stale, alive): (Vec<_>, Vec<_>) =
+            core::mem::take(&mut self.other_robots)
+                .into_iter()
+                .partition(|robot| now - robot.last_seen > older_than);
+        self.other_robots = alive;
+
+        let occupied: Vec<_> = self
+            .robots()
+            .flat_map(|robot| Self::covered(&self.map, robot))
+            .collect();
+        for robot in &stale {
+            for location in Self::covered(&self.map, robot) {
+                let state = self.map.get_location(&location);
+                let still_occupied = occupied
+                    .iter()
+                    .any(|other| self.map.same_cell(other, &location));
+                if !still_occupied && state == Ok(MapState::OtherRobot) {
+                    let _ =
+                        self.map.set_location(&location, MapState::Explored);
+                }
+            }
+        }
+        stale
+    }
+
+    /// My robot followed by the other robots.
+    fn robots(&self) -> impl Iterator<Item = &Robot<P>> {
+        core::iter::once(&self.my_robot).chain(&self.other_robots)
+    }
+
+    /// The partitioning factors derived from the parameters of all robots,
+    /// see [`IntoFactors`].
+    pub fn factors<F>(&self) -> F
+    where
+        P: IntoFactors<F>,
+    {
+        let robots: Vec<_> = core::iter::once(&self.my_robot)
+            .chain(&self.other_robots)
+            .collect();
+        P::into_factors(&robots)
+    }
+
+    /// Same as [`Partition::partition`], but passes the factors derived from
+    /// the parameters of the robots, see [`LocalMap::factors`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Partition::partition`].
+    pub fn partition_with_parameters<F, A>(
+        self,
+        partition_algorithm: A,
+    ) -> Result<Self, PartitionError>
+    where
+        P: IntoFactors<F>,
+        A: FnOnce(Self, Option<F>) -> Self,
+    {
+        let factors = self.factors();
+        self.partition(partition_algorithm, Some(factors))
+    }
+
+    /// My robot followed by the other robots.
+    fn robots_mut(&mut self) -> impl Iterator<Item = &mut Robot<P>> {
+        core::iter::once(&mut self.my_robot).chain(&mut self.other_robots)
+    }
+}
+
+impl<T, P, F> Partition<F> for LocalMap<T, P> where
+    T: Location + MaskMapState + core::fmt::Debug
+{
+}
+
+/// Robots are equal if they are at the same location with the same
+/// parameters, heading and footprint. When they were last seen and their
+/// trajectories are ignored.
+impl<P: PartialEq> PartialEq for Robot<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.location == other.location
+            && self.parameters == other.parameters
+            && self.heading == other.heading
+            && self.footprint == other.footprint
+    }
 }
 
-impl<T, P> Partition for LocalMap<T, P> where
-    T: Location + MaskMapState + Visualize + std::fmt::Debug
+/// Local maps are equal if their maps and robots are. Like the timestamps of
+/// a [`CellMap`](crate::CellMap), the clock is ignored.
+impl<T, P> PartialEq for LocalMap<T, P>
+where
+    T: Location + MaskMapState + core::fmt::Debug + PartialEq,
+    P: PartialEq,
 {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+            && self.my_robot == other.my_robot
+            && self.other_robots == other.other_robots
+    }
 }
 
 impl<T, P> Visualize for LocalMap<T, P>
 where
-    T: Location + MaskMapState + Visualize + std::fmt::Debug,
+    T: Location + MaskMapState + Visualize + core::fmt::Debug,
 {
     type ImageType = <T as Visualize>::ImageType;
 
     fn as_image(&self) -> Self::ImageType {
         self.map.as_image()
     }
+
+    fn visualize(&self) {
+        self.map.visualize()
+    }
 }
 
-impl<T, P> std::fmt::Debug for LocalMap<T, P>
+impl<T, P> core::fmt::Debug for LocalMap<T, P>
 where
-    T: Location + MaskMapState + Visualize + std::fmt::Debug,
-    P: std::fmt::Debug,
+    T: Location + MaskMapState + core::fmt::Debug,
+    P: core::fmt::Debug,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "LocalMap: map = {:?}, my_robot = {:?}, other_robots = {:?}",
@@ -249,7 +757,7 @@ mod tests {
     ) -> Vec<RealWorldLocation> {
         map.get_map_state(state)
             .iter()
-            .map(|cell| cell.location().clone())
+            .map(|cell| *cell.location())
             .collect()
     }
 
@@ -353,15 +861,14 @@ mod tests {
             )
         };
 
+        let (error, location) = lmap.unwrap_err();
+        assert!(matches!(error, LocationError::OutOfMap(_)));
         assert_eq!(
-            lmap.unwrap_err(),
-            (
-                LocationError::OutOfMap,
-                RealWorldLocation::from_xyz(
-                    11.0 - OFFSET,
-                    11.0 - OFFSET,
-                    11.0 - OFFSET
-                )
+            location,
+            RealWorldLocation::from_xyz(
+                11.0 - OFFSET,
+                11.0 - OFFSET,
+                11.0 - OFFSET
             )
         )
     }
@@ -414,15 +921,14 @@ mod tests {
             )
         };
 
+        let (error, location) = lmap.unwrap_err();
+        assert!(matches!(error, LocationError::OutOfMap(_)));
         assert_eq!(
-            lmap.unwrap_err(),
-            (
-                LocationError::OutOfMap,
-                RealWorldLocation::from_xyz(
-                    -1.0 - OFFSET,
-                    -1.0 - OFFSET,
-                    -1.0 - OFFSET
-                )
+            location,
+            RealWorldLocation::from_xyz(
+                -1.0 - OFFSET,
+                -1.0 - OFFSET,
+                -1.0 - OFFSET
             )
         )
     }
@@ -475,15 +981,14 @@ mod tests {
             )
         };
 
+        let (error, location) = lmap.unwrap_err();
+        assert!(matches!(error, LocationError::OutOfMap(_)));
         assert_eq!(
-            lmap.unwrap_err(),
-            (
-                LocationError::OutOfMap,
-                RealWorldLocation::from_xyz(
-                    12.0 - OFFSET,
-                    12.0 - OFFSET,
-                    12.0 - OFFSET
-                )
+            location,
+            RealWorldLocation::from_xyz(
+                12.0 - OFFSET,
+                12.0 - OFFSET,
+                12.0 - OFFSET
             )
         )
     }
@@ -987,8 +1492,137 @@ mod tests {
             vec![],
         );
 
-        let _partitioned_map =
-            lmap.partition(|map| map).expect("No error partitioning");
+        let _partitioned_map = lmap
+            .partition(|map, _: Option<()>| map, None)
+            .expect("No error partitioning");
+    }
+
+    #[test]
+    fn update_parameters_in_place() {
+        let (map, _) = make_map();
+        let mut lmap = LocalMap::new_noexpand(
+            map,
+            Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), 100),
+            vec![
+                Robot::new(RealWorldLocation::from_xyz(1.5, 0.5, 0.0), 80),
+                Robot::new(RealWorldLocation::from_xyz(2.5, 0.5, 0.0), 60),
+            ],
+        )
+        .expect("No error creating local map");
+
+        *lmap.my_robot_mut().parameters_mut() -= 10;
+        for robot in lmap.other_robots_mut() {
+            *robot.parameters_mut() /= 2;
+        }
+
+        assert_eq!(*lmap.my_robot().parameters(), 90);
+        let battery: Vec<_> = lmap
+            .other_robots()
+            .iter()
+            .map(|r| *r.parameters())
+            .collect();
+        assert_eq!(battery, [40, 30]);
+        assert_eq!(
+            lmap.other_positions(),
+            [
+                RealWorldLocation::from_xyz(1.5, 0.5, 0.0),
+                RealWorldLocation::from_xyz(2.5, 0.5, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn clone_and_compare() {
+        let lmap = make_random_local_map(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            vec![RealWorldLocation::from_xyz(1.0, 1.0, 0.0)],
+        );
+        let mut fork = lmap.clone();
+
+        assert_eq!(fork, lmap);
+        fork.map_mut()
+            .set_location(
+                &RealWorldLocation::from_xyz(2.5, 2.5, 0.0),
+                MapState::Assigned,
+            )
+            .unwrap();
+        assert_ne!(fork, lmap);
+
+        let mut later = lmap.clone();
+        later.set_clock(1.0);
+        later.record_trajectories(true);
+        later.map_mut().set_clock(1.0);
+        let location = RealWorldLocation::from_xyz(2.5, 2.5, 0.0);
+        let state = later.map().get_location(&location).unwrap();
+        later
+            .map_mut()
+            .set_location(&location, MapState::Obstacle)
+            .unwrap();
+        later.map_mut().set_location(&location, state).unwrap();
+        assert_eq!(later, lmap);
+        later
+            .move_my_robot(RealWorldLocation::from_xyz(0.5, 1.5, 0.0))
+            .unwrap();
+        assert_ne!(later, lmap);
+    }
+
+    #[test]
+    fn boxed_map_view() {
+        let map: Box<dyn crate::MapView> = Box::new(CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+            crate::AxisResolution::uniform(1.0),
+        ));
+
+        let mut lmap = LocalMap::new_noexpand(
+            map,
+            Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+            vec![Robot::new(RealWorldLocation::from_xyz(1.5, 0.5, 0.0), ())],
+        )
+        .expect("No error creating local map");
+        let location = RealWorldLocation::from_xyz(1.5, 1.5, 0.0);
+        lmap.map_mut()
+            .set_location(&location, MapState::Assigned)
+            .unwrap();
+
+        assert_eq!(lmap.map().get_location(&location), Ok(MapState::Assigned));
+        assert_eq!(lmap.map().get_map_state(MapState::OtherRobot).len(), 1);
+        assert_eq!(lmap.map().get_map_state_owned(MapState::MyRobot).len(), 1);
+    }
+
+    #[test]
+    fn partition_map_capturing_closure() {
+        let lmap = make_random_local_map(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            vec![],
+        );
+        let location = RealWorldLocation::from_xyz(1.5, 1.5, 0.0);
+        let mut calls = 0;
+
+        let partitioned_map = lmap
+            .partition(
+                |mut map, factors: Option<crate::PartitionFactors>| {
+                    calls += 1;
+                    assert_eq!(factors.and_then(|f| f.speed(0)), Some(2.0));
+                    map.map_mut()
+                        .set_location(&location, MapState::Assigned)
+                        .unwrap();
+                    map
+                },
+                Some(
+                    crate::PartitionFactors::builder()
+                        .speeds(vec![2.0])
+                        .build()
+                        .unwrap(),
+                ),
+            )
+            .expect("No error partitioning");
+
+        assert_eq!(calls, 1);
+        assert_eq!(
+            partitioned_map.map().get_location(&location),
+            Ok(MapState::Assigned)
+        );
     }
 
     #[test]
@@ -999,11 +1633,15 @@ mod tests {
         );
 
         // set dummy algorithm for the test
-        fn algorithm(map: LocalMap<CellMap, ()>) -> LocalMap<CellMap, ()> {
+        fn algorithm(
+            map: LocalMap<CellMap, ()>,
+            _: Option<()>,
+        ) -> LocalMap<CellMap, ()> {
             map
         }
-        let _partitioned_map =
-            lmap.partition(algorithm).expect("No error partitioning");
+        let _partitioned_map = lmap
+            .partition(algorithm, None)
+            .expect("No error partitioning");
     }
 
     #[test]
@@ -1014,18 +1652,23 @@ mod tests {
         );
 
         // set dummy algorithm for the test
-        fn algorithm(map: LocalMap<CellMap, ()>) -> LocalMap<CellMap, ()> {
+        fn algorithm(
+            map: LocalMap<CellMap, ()>,
+            _: Option<()>,
+        ) -> LocalMap<CellMap, ()> {
             map
         }
 
-        let _partitioned_map =
-            lmap.partition(algorithm).expect("No error partitioning");
+        let _partitioned_map = lmap
+            .partition(algorithm, None)
+            .expect("No error partitioning");
         let map_algorithm = algorithm;
         // function pointer equality: https://stackoverflow.com/a/57834304
         assert_eq!(map_algorithm as usize, algorithm as usize);
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn call_map_trait_function_visualize() {
         let lmap = make_random_local_map(
             RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
@@ -1035,15 +1678,16 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn call_map_trait_function_visualize_and_then_save() {
         let lmap = make_random_local_map(
             RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
             vec![],
         );
-        lmap.map()
-            .as_image()
-            .save("test_save_local_map.jpg")
-            .unwrap();
+        let path = std::env::temp_dir()
+            .join(format!("test_save_local_map_{}.jpg", std::process::id()));
+        lmap.map().as_image().save(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
     }
 
     #[test]
@@ -1054,4 +1698,153 @@ mod tests {
         );
         lmap.map().get_map_state(LocationType::Unexplored);
     }
+
+    #[test]
+    fn move_other_robot_records_trajectory() {
+        let start = RealWorldLocation::from_xyz(5.5, 5.5, 0.0);
+        let goal = RealWorldLocation::from_xyz(7.5, 5.5, 0.0);
+        let mut lmap = make_local_map(
+            RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+            vec![start],
+        );
+
+        lmap.move_other_robot(0, goal).unwrap();
+        assert!(lmap.other_robots()[0].trajectory().is_empty());
+        lmap.set_clock(2.0);
+        lmap.record_trajectories(true);
+        lmap.set_clock(4.0);
+        lmap.move_other_robot(0, start).unwrap();
+
+        assert_eq!(
+            lmap.other_robots()[0].trajectory(),
+            [(2.0, goal), (4.0, start)]
+        );
+        assert_eq!(lmap.trajectory().len(), 1);
+        assert_eq!(lmap.map().get_location(&goal), Ok(MapState::Explored));
+        assert_eq!(lmap.map().get_location(&start), Ok(MapState::OtherRobot));
+    }
+
+    #[test]
+    fn move_robot_outside_map_fails() {
+        let start = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        let mut lmap = make_local_map(start, vec![]);
+        lmap.record_trajectories(true);
+
+        let result =
+            lmap.move_my_robot(RealWorldLocation::from_xyz(20.0, 0.5, 0.0));
+
+        assert!(matches!(result, Err(LocationError::OutOfMap(_))));
+        assert_eq!(lmap.my_position(), &start);
+        assert_eq!(lmap.trajectory().len(), 1);
+        assert_eq!(lmap.map().get_location(&start), Ok(MapState::MyRobot));
+    }
+
+    #[test]
+    fn move_robot_keeps_overwritten_cell() {
+        let start = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        let mut lmap = make_local_map(start, vec![]);
+        lmap.map_mut()
+            .set_location(&start, MapState::Obstacle)
+            .unwrap();
+
+        lmap.move_my_robot(RealWorldLocation::from_xyz(1.5, 0.5, 0.0))
+            .unwrap();
+
+        assert_eq!(lmap.map().get_location(&start), Ok(MapState::Obstacle));
+    }
+
+    #[test]
+    fn prune_stale_keeps_shared_cells() {
+        let shared = RealWorldLocation::from_xyz(5.5, 5.5, 0.0);
+        let mut lmap = make_local_map(
+            RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+            vec![shared, shared],
+        );
+        lmap.set_clock(10.0);
+        lmap.move_other_robot(1, shared).unwrap();
+
+        assert!(lmap.prune_stale(10.0).is_empty());
+        assert_eq!(lmap.prune_stale(5.0).len(), 1);
+        assert_eq!(lmap.other_robots()[0].last_seen(), 10.0);
+        assert_eq!(lmap.map().get_location(&shared), Ok(MapState::OtherRobot));
+
+        lmap.set_clock(20.0);
+        assert_eq!(lmap.prune_stale(5.0).len(), 1);
+        assert_eq!(lmap.map().get_location(&shared), Ok(MapState::Explored));
+    }
+
+    #[test]
+    fn prune_stale_keeps_cell_shared_at_other_coordinates() {
+        let cell = RealWorldLocation::from_xyz(5.2, 5.2, 0.0);
+        let mut lmap = make_local_map(
+            RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+            vec![cell, RealWorldLocation::from_xyz(5.8, 5.8, 0.0)],
+        );
+        lmap.set_clock(10.0);
+        lmap.mark_seen(1);
+
+        assert_eq!(lmap.prune_stale(5.0).len(), 1);
+        assert_eq!(lmap.map().get_location(&cell), Ok(MapState::OtherRobot));
+    }
+
+    #[test]
+    fn move_robot_moves_footprint() {
+        let mut lmap = LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+                crate::AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(5.0, 5.0, 0.0), ())
+                .with_footprint(Footprint::Circle(1.0)),
+            vec![],
+        )
+        .unwrap();
+
+        lmap.move_my_robot(RealWorldLocation::from_xyz(6.0, 5.0, 0.0))
+            .unwrap();
+
+        assert_eq!(
+            get_mapstate_pos_from_map(lmap.map(), MapState::MyRobot),
+            [[5.0, 4.0], [6.0, 4.0], [5.0, 5.0], [6.0, 5.0]]
+                .map(|[x, y]| RealWorldLocation::from_xyz(x, y, 0.0))
+        );
+        for [x, y] in [[4.5, 4.5], [4.5, 5.5]] {
+            let location = RealWorldLocation::from_xyz(x, y, 0.0);
+            assert_eq!(
+                lmap.map().get_location(&location),
+                Ok(MapState::Explored)
+            );
+        }
+    }
+
+    #[test]
+    fn heading_turns_polygon_footprint() {
+        let center = RealWorldLocation::from_xyz(5.5, 5.5, 0.0);
+        let footprint = Footprint::Polygon(
+            [[-1.5, -0.5], [1.5, -0.5], [1.5, 0.5], [-1.5, 0.5]]
+                .map(|[x, y]| crate::Coords::new(x, y, 0.0))
+                .to_vec(),
+        );
+        let mut lmap = LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+                crate::AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+            vec![Robot::new(center, ()).with_footprint(footprint)],
+        )
+        .unwrap();
+        let ahead = RealWorldLocation::from_xyz(6.5, 5.5, 0.0);
+        let left = RealWorldLocation::from_xyz(5.5, 6.5, 0.0);
+        assert_eq!(lmap.map().get_location(&ahead), Ok(MapState::OtherRobot));
+        assert_eq!(lmap.map().get_location(&left), Ok(MapState::Unexplored));
+
+        lmap.set_other_heading(0, Some(core::f64::consts::FRAC_PI_2));
+
+        assert_eq!(lmap.map().get_location(&ahead), Ok(MapState::Explored));
+        assert_eq!(lmap.map().get_location(&left), Ok(MapState::OtherRobot));
+        assert_eq!(lmap.map().get_map_state(MapState::OtherRobot).len(), 3);
+    }
 }
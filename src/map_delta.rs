@@ -0,0 +1,224 @@
+use alloc::vec::Vec;
+
+use crate::{CellMap, LocationType};
+
+/// Compact description of the differences between two [`CellMap`]s.
+///
+/// A delta only lists the cells whose state differs, which makes it much
+/// cheaper to exchange than the whole map when only few cells changed. It is
+/// obtained using [`CellMap::diff`] and applied using [`CellMap::apply_delta`].
+///
+/// Transporting the delta to other robots is outside the scope of this crate.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+/// };
+///
+/// let make_map = || {
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     )
+/// };
+/// let mut old = make_map();
+/// let mut new = make_map();
+/// new.set_location(
+///     &RealWorldLocation::from_xyz(1.0, 2.0, 0.0),
+///     MapState::Explored,
+/// )
+/// .unwrap();
+///
+/// let delta = old.diff(&new).unwrap();
+/// assert_eq!(delta.len(), 1);
+///
+/// old.apply_delta(&delta).unwrap();
+/// assert_eq!(old.cells(), new.cells());
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct MapDelta {
+    /// Shape `(rows, columns)` of the maps the delta was computed from.
+    shape: (usize, usize),
+    /// The `[row, col]` index of each changed cell along with its new state.
+    changes: Vec<([usize; 2], LocationType)>,
+}
+
+impl MapDelta {
+    /// The `[row, col]` indices of the changed cells along with their new
+    /// state.
+    pub fn changes(&self) -> &Vec<([usize; 2], LocationType)> {
+        &self.changes
+    }
+    pub fn shape(&self) -> (usize, usize) {
+        self.shape
+    }
+    /// Number of changed cells.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+    /// Whether the delta contains no changes at all.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl CellMap {
+    /// Compute the [`MapDelta`] which turns `self` into `other`.
+    ///
+    /// Only the cell states are compared; both maps are expected to describe
+    /// the same region.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the maps do not share the same
+    /// dimensions, offset and resolution. See [`MapDeltaError`].
+    pub fn diff(&self, other: &CellMap) -> Result<MapDelta, MapDeltaError> {
+        if self.cells().dim() != other.cells().dim() {
+            return Err(MapDeltaError::ShapeMismatch);
+        }
+        if self.offset() != other.offset()
+            || self.resolution() != other.resolution()
+        {
+            return Err(MapDeltaError::FrameMismatch);
+        }
+
+        Ok(MapDelta {
+            shape: self.cells().dim(),
+            changes: self
+                .cells()
+                .indexed_iter()
+                .zip(other.cells().iter())
+                .filter(|((_, mine), theirs)| mine != theirs)
+                .map(|(((row, col), _), theirs)| ([row, col], *theirs))
+                .collect(),
+        })
+    }
+
+    /// Apply a [`MapDelta`] previously computed with [`CellMap::diff`].
+    ///
+    /// Changed cells are stamped with the map's clock, the same way
+    /// [`crate::Location::set_location`] does.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`MapDeltaError::ShapeMismatch`] if the delta
+    /// was computed for a map with different dimensions. In that case the map
+    /// is left untouched.
+    pub fn apply_delta(
+        &mut self,
+        delta: &MapDelta,
+    ) -> Result<(), MapDeltaError> {
+        if self.cells().dim() != delta.shape {
+            return Err(MapDeltaError::ShapeMismatch);
+        }
+
+        for (index, state) in &delta.changes {
+            self.set_cell(*index, *state);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MapDeltaError {
+    /// The maps (or the map and the delta) do not have the same number of rows
+    /// and columns.
+    ShapeMismatch,
+    /// The maps do not share the same offset and resolution, meaning the same
+    /// cell index describes different real-world locations.
+    FrameMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cell_map::tests::make_map, AxisResolution, Coords, RealWorldLocation,
+    };
+
+    #[test]
+    fn diff_identical_maps_is_empty() {
+        let (map, _) = make_map();
+        let (other, _) = make_map();
+
+        let delta = map.diff(&other).unwrap();
+
+        assert!(delta.is_empty());
+        assert_eq!(delta.shape(), (5, 3));
+    }
+
+    #[test]
+    fn diff_lists_changed_cells() {
+        let (map, _) = make_map();
+        let (mut other, _) = make_map();
+        other.set_cell([0, 0], LocationType::Explored);
+        other.set_cell([4, 2], LocationType::Assigned);
+
+        let delta = map.diff(&other).unwrap();
+
+        assert_eq!(
+            delta.changes(),
+            &vec![
+                ([0, 0], LocationType::Explored),
+                ([4, 2], LocationType::Assigned)
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_delta_reproduces_other_map() {
+        let (mut map, _) = make_map();
+        let (mut other, _) = make_map();
+        other.set_cell([1, 1], LocationType::Frontier);
+        other.set_cell([3, 0], LocationType::OutOfMap);
+
+        let delta = map.diff(&other).unwrap();
+        map.apply_delta(&delta).unwrap();
+
+        assert_eq!(map.cells(), other.cells());
+    }
+
+    #[test]
+    fn diff_shape_mismatch() {
+        let (map, _) = make_map();
+        let other = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        assert_eq!(map.diff(&other), Err(MapDeltaError::ShapeMismatch));
+    }
+
+    #[test]
+    fn diff_frame_mismatch() {
+        let (map, _) = make_map();
+        let other = CellMap::from_raster(
+            map.cells().clone(),
+            *map.resolution(),
+            Coords::new(1.0, 0.0, 0.0),
+        );
+
+        assert_eq!(map.diff(&other), Err(MapDeltaError::FrameMismatch));
+    }
+
+    #[test]
+    fn apply_delta_shape_mismatch() {
+        let (map, _) = make_map();
+        let (other, _) = make_map();
+        let delta = map.diff(&other).unwrap();
+        let mut small = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        assert_eq!(
+            small.apply_delta(&delta),
+            Err(MapDeltaError::ShapeMismatch)
+        );
+    }
+}
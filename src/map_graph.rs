@@ -0,0 +1,307 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+use ndarray::Array2;
+use num::ToPrimitive;
+
+use crate::{CellMap, Connectivity, GridIndex, RealWorldLocation};
+
+/// Topological graph of a map, whose nodes are areas such as rooms and whose
+/// edges are the passages between them.
+///
+/// See [`CellMap::topological_graph`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapGraph {
+    nodes: Vec<MapNode>,
+    edges: Vec<MapEdge>,
+}
+
+impl MapGraph {
+    /// The areas of the map, which are referred to by their position in this
+    /// list.
+    pub fn nodes(&self) -> &[MapNode] {
+        &self.nodes
+    }
+    /// The passages between areas, sorted by the nodes they connect.
+    pub fn edges(&self) -> &[MapEdge] {
+        &self.edges
+    }
+
+    /// The nodes connected to the given one by an edge.
+    pub fn neighbors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges.iter().filter_map(move |edge| match edge.nodes {
+            [a, b] if a == node => Some(b),
+            [a, b] if b == node => Some(a),
+            _ => None,
+        })
+    }
+}
+
+/// An area of a [`MapGraph`], e.g. a room.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapNode {
+    cells: Vec<GridIndex>,
+    area: f64,
+    centroid: RealWorldLocation,
+}
+
+impl MapNode {
+    /// The cells making up the area, sorted by row and column.
+    pub fn cells(&self) -> &[GridIndex] {
+        &self.cells
+    }
+    /// The size of the area in real-world units.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+    /// The mean of the cell centers, which does not necessarily lie within
+    /// the area.
+    pub fn centroid(&self) -> &RealWorldLocation {
+        &self.centroid
+    }
+}
+
+/// A passage between two areas of a [`MapGraph`], e.g. a door.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapEdge {
+    nodes: [usize; 2],
+    width: f64,
+}
+
+impl MapEdge {
+    /// The indices of the connected nodes, the lower one first.
+    pub fn nodes(&self) -> [usize; 2] {
+        self.nodes
+    }
+    /// The length of the border shared by both areas in real-world units.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+}
+
+impl CellMap {
+    /// Segment the free space of the map into areas, such as rooms and
+    /// corridors, and connect them wherever they touch.
+    ///
+    /// Cells with a clearance (see [`CellMap::brushfire`]) above
+    /// `min_clearance` make up the cores of the areas, i.e. every group of
+    /// such cells sharing edges becomes one area. Passages which are
+    /// narrower than about twice `min_clearance`, such as doors, thus
+    /// separate areas. All other free cells are then added to the closest
+    /// area they can reach, while groups of free cells which cannot reach any
+    /// area form areas of their own. Cells which are
+    /// [`MapState::Obstacle`](crate::MapState::Obstacle) or
+    /// [`MapState::OutOfMap`](crate::MapState::OutOfMap) do not belong to any
+    /// area.
+    ///
+    /// This allows e.g. partitioning an indoor environment room by room
+    /// rather than cell by cell.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// // Two rooms separated by a wall with a door in its middle.
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(11.0, 5.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(5.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(6.0, 5.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(5.0, 2.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(6.0, 3.0, 0.0),
+    ///     MapState::Unexplored,
+    /// );
+    ///
+    /// let graph = map.topological_graph(1.0);
+    ///
+    /// assert_eq!(graph.nodes().len(), 2);
+    /// assert_eq!(graph.nodes()[0].area() + graph.nodes()[1].area(), 51.0);
+    /// assert_eq!(graph.edges().len(), 1);
+    /// assert_eq!(graph.edges()[0].width(), 1.0);
+    /// ```
+    pub fn topological_graph(&self, min_clearance: f64) -> MapGraph {
+        let clearance = self.brushfire();
+        let (rows, columns) = self.cells().dim();
+        let mut labels: Array2<Option<usize>> =
+            Array2::from_elem((rows, columns), None);
+        let mut count = 0;
+
+        // Label every group of unlabeled cells matching `predicate`, which
+        // may only grow into unlabeled, passable cells.
+        let mut label_components =
+            |labels: &mut Array2<Option<usize>>,
+             predicate: &dyn Fn([usize; 2]) -> bool| {
+                let components = self
+                    .connected_components(Connectivity::Four, |index| {
+                        labels[index].is_none() && predicate(index)
+                    });
+                for component in components {
+                    for index in component {
+                        labels[index] = Some(count);
+                    }
+                    count += 1;
+                }
+            };
+
+        label_components(&mut labels, &|index| {
+            self.is_passable(index) && clearance[index] > min_clearance
+        });
+        // Grow the cores into the remaining free space, breadth first.
+        let mut queue: VecDeque<[usize; 2]> = labels
+            .indexed_iter()
+            .filter(|(_, label)| label.is_some())
+            .map(|((row, col), _)| [row, col])
+            .collect();
+        while let Some(index) = queue.pop_front() {
+            for neighbor in self.connected_neighbors(index, Connectivity::Four)
+            {
+                if labels[neighbor].is_none() && self.is_passable(neighbor) {
+                    labels[neighbor] = labels[index];
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        label_components(&mut labels, &|index| self.is_passable(index));
+
+        let cell_area = 1.0 / (self.resolution().x * self.resolution().y);
+        let mut cells = vec![Vec::new(); count];
+        let mut widths = BTreeMap::new();
+        for ((row, col), label) in labels.indexed_iter() {
+            let Some(label) = *label else {
+                continue;
+            };
+            cells[label].push(GridIndex::new(row, col));
+            // Each shared border is counted once, from its lower left cell.
+            let above = (row + 1 < rows).then(|| labels[[row + 1, col]]);
+            let right = (col + 1 < columns).then(|| labels[[row, col + 1]]);
+            for (other, length) in [
+                (above.flatten(), 1.0 / self.resolution().x),
+                (right.flatten(), 1.0 / self.resolution().y),
+            ] {
+                match other {
+                    Some(other) if other != label => {
+                        let nodes = [label.min(other), label.max(other)];
+                        *widths.entry(nodes).or_insert(0.0) += length;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let nodes = cells
+            .into_iter()
+            .map(|cells| {
+                let n = cells.len().to_f64().expect("usize to f64 should work");
+                let (x, y) = cells.iter().fold((0.0, 0.0), |(x, y), index| {
+                    let center = self.index_center::<f64>((*index).into());
+                    (x + center.x, y + center.y)
+                });
+                MapNode {
+                    area: n * cell_area,
+                    centroid: RealWorldLocation::from_xyz(
+                        x / n,
+                        y / n,
+                        self.offset().z,
+                    ),
+                    cells,
+                }
+            })
+            .collect();
+        let edges = widths
+            .into_iter()
+            .map(|(nodes, width)| MapEdge { nodes, width })
+            .collect();
+        MapGraph { nodes, edges }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, MapState};
+
+    /// Three rooms in a row, connected by doors of different widths.
+    fn make_map() -> CellMap {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(17.0, 6.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        for (wall, door) in [(5.0, 1.0), (11.0, 2.0)] {
+            map.set_rect(
+                &RealWorldLocation::from_xyz(wall, 0.0, 0.0),
+                &RealWorldLocation::from_xyz(wall + 1.0, 6.0, 0.0),
+                MapState::Obstacle,
+            );
+            map.set_rect(
+                &RealWorldLocation::from_xyz(wall, 2.0, 0.0),
+                &RealWorldLocation::from_xyz(wall + 1.0, 2.0 + door, 0.0),
+                MapState::Explored,
+            );
+        }
+        map
+    }
+
+    #[test]
+    fn rooms_and_doors() {
+        let map = make_map();
+
+        let graph = map.topological_graph(1.0);
+
+        assert_eq!(graph.nodes().len(), 3);
+        let widths: Vec<_> =
+            graph.edges().iter().map(|edge| edge.width()).collect();
+        assert_eq!(widths, [1.0, 2.0]);
+        assert_eq!(graph.edges()[0].nodes(), [0, 1]);
+        assert_eq!(graph.neighbors(1).collect::<Vec<_>>(), [0, 2]);
+        let area: f64 = graph.nodes().iter().map(MapNode::area).sum();
+        assert_eq!(area, 17.0 * 6.0 - 12.0 + 3.0);
+        for node in graph.nodes() {
+            assert!(node
+                .cells()
+                .iter()
+                .all(|index| map[*index] != MapState::Obstacle));
+        }
+    }
+
+    #[test]
+    fn isolated_pockets_form_own_nodes() {
+        let mut map = make_map();
+        // Close the first door.
+        map.set_cell([2, 5], MapState::Obstacle);
+
+        let graph = map.topological_graph(1.0);
+
+        assert_eq!(graph.nodes().len(), 3);
+        assert_eq!(graph.edges().len(), 1);
+        assert_eq!(graph.neighbors(0).count(), 0);
+    }
+
+    #[test]
+    fn whole_map_without_walls() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            AxisResolution::uniform(2.0),
+        );
+
+        let graph = map.topological_graph(100.0);
+
+        assert_eq!(graph.nodes().len(), 1);
+        assert_eq!(graph.nodes()[0].area(), 16.0);
+        assert_eq!(
+            graph.nodes()[0].centroid(),
+            &RealWorldLocation::from_xyz(2.0, 2.0, 0.0)
+        );
+        assert!(graph.edges().is_empty());
+    }
+}
@@ -0,0 +1,504 @@
+//! Merging of maps, e.g. when robots exchange what they explored.
+//!
+//! When two maps disagree on the state of a cell, a [`MergePolicy`] decides
+//! which state to keep. Which one is right depends on the application, so
+//! several policies are provided:
+//!
+//! - [`PreferExplored`]: knowledge wins over ignorance.
+//! - [`PreferNewer`]: the most recent observation wins, which suits dynamic
+//!   environments.
+//! - [`PreferMine`]: only fill in what the current robot does not know yet.
+//! - [`PreferPriority`]: the state with the higher [`MapState::priority`]
+//!   wins, consistent with other conflicts resolved by this crate.
+//!
+//! Custom policies can be written as closures, see [`MergePolicy`].
+//!
+//! # Example
+//!
+//! ```
+//! use local_robot_map::{
+//!     merge::PreferNewer, AxisResolution, CellMap, Location, MapState,
+//!     RealWorldLocation,
+//! };
+//!
+//! let make_map = || {
+//!     CellMap::new(
+//!         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+//!         RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+//!         AxisResolution::uniform(1.0),
+//!     )
+//! };
+//! let door = RealWorldLocation::from_xyz(1.5, 1.5, 0.0);
+//! let mut mine = make_map();
+//! mine.set_location(&door, MapState::Obstacle).unwrap();
+//! // The other robot saw the door open later on.
+//! let mut theirs = make_map();
+//! theirs.set_clock(10.0);
+//! theirs.set_location(&door, MapState::Explored).unwrap();
+//!
+//! let changed = mine.merge(&theirs, &PreferNewer);
+//!
+//! assert_eq!(changed, 1);
+//! assert_eq!(mine.get_location(&door), Ok(MapState::Explored));
+//! assert_eq!(mine.last_update(&door), Ok(10.0));
+//! ```
+
+use ndarray::Array2;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+use num::ToPrimitive;
+
+use crate::{
+    CellMap, Coords, MapState, MapStateMatrix, RealWorldLocation, Timestamp,
+};
+
+/// The state of a cell in one of the maps being merged, along with the time
+/// it was last updated (see [`CellMap::last_update`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellVersion {
+    pub state: MapState,
+    pub updated: Timestamp,
+}
+
+/// Decides the state of a cell when merging maps, see [`CellMap::merge`].
+///
+/// Besides the provided policies, any closure taking the current robot's
+/// version and the other version of a cell can be used.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     merge::CellVersion, AxisResolution, CellMap, Location, MapState,
+///     RealWorldLocation,
+/// };
+///
+/// let make_map = || {
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     )
+/// };
+/// let mut mine = make_map();
+/// let mut theirs = make_map();
+/// theirs
+///     .set_location(
+///         &RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+///         MapState::Obstacle,
+///     )
+///     .unwrap();
+///
+/// // Only take over obstacles, to be on the safe side.
+/// let changed =
+///     mine.merge(&theirs, &|mine: CellVersion, theirs: CellVersion| {
+///         if theirs.state == MapState::Obstacle {
+///             theirs.state
+///         } else {
+///             mine.state
+///         }
+///     });
+///
+/// assert_eq!(changed, 1);
+/// ```
+pub trait MergePolicy {
+    /// The state the merged cell should have, given the current robot's
+    /// version and the other version of it.
+    fn resolve(&self, mine: CellVersion, theirs: CellVersion) -> MapState;
+}
+
+impl<F> MergePolicy for F
+where
+    F: Fn(CellVersion, CellVersion) -> MapState,
+{
+    fn resolve(&self, mine: CellVersion, theirs: CellVersion) -> MapState {
+        self(mine, theirs)
+    }
+}
+
+/// Take the other state only if the current robot has not explored the cell,
+/// i.e. if it is [`MapState::Unexplored`]. Otherwise, the current state is
+/// kept.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferExplored;
+
+impl MergePolicy for PreferExplored {
+    fn resolve(&self, mine: CellVersion, theirs: CellVersion) -> MapState {
+        if mine.state == MapState::Unexplored {
+            theirs.state
+        } else {
+            mine.state
+        }
+    }
+}
+
+/// Take the state which was updated most recently. On a tie, the current
+/// state is kept.
+///
+/// This only makes sense if the clocks of both maps (see
+/// [`CellMap::set_clock`]) refer to the same time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferNewer;
+
+impl MergePolicy for PreferNewer {
+    fn resolve(&self, mine: CellVersion, theirs: CellVersion) -> MapState {
+        if theirs.updated > mine.updated {
+            theirs.state
+        } else {
+            mine.state
+        }
+    }
+}
+
+/// Keep the current state, unless the cell is [`MapState::Unexplored`] or
+/// [`MapState::OutOfMap`], i.e. unknown to the current robot.
+///
+/// Unlike [`PreferExplored`], this also extends the current robot's region
+/// into cells it considered to be outside the map.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferMine;
+
+impl MergePolicy for PreferMine {
+    fn resolve(&self, mine: CellVersion, theirs: CellVersion) -> MapState {
+        match mine.state {
+            MapState::Unexplored | MapState::OutOfMap => theirs.state,
+            _ => mine.state,
+        }
+    }
+}
+
+/// Take the state with the higher [`MapState::priority`]. Since no two
+/// states share a priority, the result does not depend on which map is
+/// merged into which.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferPriority;
+
+impl MergePolicy for PreferPriority {
+    fn resolve(&self, mine: CellVersion, theirs: CellVersion) -> MapState {
+        if theirs.state.priority() > mine.state.priority() {
+            theirs.state
+        } else {
+            mine.state
+        }
+    }
+}
+
+impl CellMap {
+    /// Merge the cells of `other` into this map, resolving conflicting
+    /// states with the given `policy`.
+    ///
+    /// Each cell of `other` is matched with the cell of this map containing
+    /// its center, so the maps are expected to share the same resolution,
+    /// but not necessarily the same offset or size. Cells lying outside this
+    /// map are ignored, as are the [`MapState::OutOfMap`] cells of `other`.
+    ///
+    /// Changed cells are marked as dirty (see [`CellMap::dirty_cells`]). A
+    /// cell which takes over the other state also takes over its timestamp,
+    /// while any other new state is stamped with this map's clock.
+    ///
+    /// Returns the number of cells which changed.
+    pub fn merge(
+        &mut self,
+        other: &CellMap,
+        policy: &impl MergePolicy,
+    ) -> usize {
+        let mut changed = 0;
+        for ((row, col), theirs) in other.cells().indexed_iter() {
+            if *theirs == MapState::OutOfMap {
+                continue;
+            }
+            let center =
+                RealWorldLocation::new(other.index_center::<f64>([row, col]));
+            let Ok(index) = self.location_to_map_index(&center) else {
+                continue;
+            };
+            let theirs = CellVersion {
+                state: *theirs,
+                updated: other.index_last_update([row, col]),
+            };
+            if self.merge_cell(index, theirs, policy) {
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Combine several maps, e.g. the partial maps received from other
+    /// robots, into a single one covering all of them.
+    ///
+    /// The result has the resolution of the first map and is aligned with
+    /// its cells, and extends just far enough to cover all maps. It is only
+    /// allocated once, which makes this cheaper than growing a map by merging
+    /// the maps one after the other. Cells are matched like in
+    /// [`CellMap::merge`], so all maps are expected to share the same
+    /// resolution (see [`CellMap::resampled`]).
+    ///
+    /// A cell covered by a single map takes over its state, while states of
+    /// cells covered by several maps are resolved by `policy`, in the order
+    /// of `maps`. Cells not covered by any map are [`MapState::OutOfMap`].
+    /// The clock of the result is the latest clock of all maps.
+    ///
+    /// Returns [`None`] if there are no maps, or if the stitched map would
+    /// contain more than [`CellMap::MAX_CELLS`] cells, e.g. because the maps
+    /// lie far apart.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     merge::PreferExplored, AxisResolution, CellMap, Location, MapState,
+    ///     RealWorldLocation,
+    /// };
+    ///
+    /// let make_map = |x: f64, y: f64| {
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(x, y, 0.0),
+    ///         RealWorldLocation::from_xyz(x + 2.0, y + 2.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     )
+    /// };
+    /// let left = make_map(0.0, 0.0);
+    /// let mut right = make_map(3.0, 1.0);
+    /// let cell = RealWorldLocation::from_xyz(3.5, 2.5, 0.0);
+    /// right.set_location(&cell, MapState::Explored).unwrap();
+    ///
+    /// let map = CellMap::stitch(&[left, right], &PreferExplored).unwrap();
+    ///
+    /// assert_eq!(map.cells().dim(), (3, 5));
+    /// assert_eq!(map.get_location(&cell), Ok(MapState::Explored));
+    /// assert_eq!(
+    ///     map.get_location(&RealWorldLocation::from_xyz(2.5, 0.5, 0.0)),
+    ///     Ok(MapState::OutOfMap)
+    /// );
+    /// ```
+    pub fn stitch(
+        maps: &[CellMap],
+        policy: &impl MergePolicy,
+    ) -> Option<CellMap> {
+        let first = maps.first()?;
+        let resolution = *first.resolution();
+        let bounds = maps.iter().map(CellMap::bounds);
+        let fold = |pick: fn(f64, f64) -> f64, init: f64, corner: usize| {
+            let coords = bounds.clone().map(move |bounds| *bounds[corner]);
+            (
+                coords.clone().map(|c| c.x).fold(init, pick),
+                coords.map(|c| c.y).fold(init, pick),
+            )
+        };
+        let (min_x, min_y) = fold(f64::min, f64::INFINITY, 0);
+        let (max_x, max_y) = fold(f64::max, f64::NEG_INFINITY, 1);
+        let min_z = maps
+            .iter()
+            .map(|map| map.offset().z)
+            .fold(f64::INFINITY, f64::min);
+
+        // Whole cells of the first map, allowing for rounding errors.
+        let cells = |distance: f64, resolution: f64| {
+            (distance * resolution - 1e-9).ceil().max(0.0)
+        };
+        let offset = Coords::new(
+            first.offset().x
+                - cells(first.offset().x - min_x, resolution.x) / resolution.x,
+            first.offset().y
+                - cells(first.offset().y - min_y, resolution.y) / resolution.y,
+            min_z,
+        );
+        let rows = cells(max_y - offset.y, resolution.y).to_usize()?;
+        let columns = cells(max_x - offset.x, resolution.x).to_usize()?;
+        CellMap::check_size(rows, columns).ok()?;
+
+        let mut stitched = CellMap::from_raster(
+            MapStateMatrix::from_elem((rows, columns), MapState::OutOfMap),
+            resolution,
+            offset,
+        );
+        stitched.set_clock(
+            maps.iter()
+                .map(CellMap::clock)
+                .fold(f64::NEG_INFINITY, f64::max),
+        );
+        let mut covered = Array2::from_elem((rows, columns), false);
+        for map in maps {
+            for ((row, col), state) in map.cells().indexed_iter() {
+                if *state == MapState::OutOfMap {
+                    continue;
+                }
+                let center =
+                    RealWorldLocation::new(map.index_center::<f64>([row, col]));
+                let Ok(index) = stitched.location_to_map_index(&center) else {
+                    continue;
+                };
+                let theirs = CellVersion {
+                    state: *state,
+                    updated: map.index_last_update([row, col]),
+                };
+                if covered[index] {
+                    stitched.merge_cell(index, theirs, policy);
+                } else {
+                    covered[index] = true;
+                    stitched.set_cell_stamped(
+                        index,
+                        theirs.state,
+                        theirs.updated,
+                    );
+                }
+            }
+        }
+        stitched.clear_dirty();
+        Some(stitched)
+    }
+
+    /// Internal helper resolving the state of a cell with the state of
+    /// another map, see [`CellMap::merge`]. Returns whether the cell changed.
+    fn merge_cell(
+        &mut self,
+        index: [usize; 2],
+        theirs: CellVersion,
+        policy: &impl MergePolicy,
+    ) -> bool {
+        let mine = CellVersion {
+            state: self.cells()[index],
+            updated: self.index_last_update(index),
+        };
+        let state = policy.resolve(mine, theirs);
+        if state == mine.state {
+            return false;
+        }
+        let stamp = if state == theirs.state {
+            theirs.updated
+        } else {
+            self.clock()
+        };
+        self.set_cell_stamped(index, state, stamp);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location};
+
+    fn make_map(from: (f64, f64), to: (f64, f64)) -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(from.0, from.1, 0.0),
+            RealWorldLocation::from_xyz(to.0, to.1, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    fn set(map: &mut CellMap, x: f64, y: f64, state: MapState) {
+        map.set_location(&RealWorldLocation::from_xyz(x, y, 0.0), state)
+            .unwrap();
+    }
+
+    fn get(map: &CellMap, x: f64, y: f64) -> MapState {
+        map.get_location(&RealWorldLocation::from_xyz(x, y, 0.0))
+            .unwrap()
+    }
+
+    #[test]
+    fn policies_resolve_conflicts() {
+        let version = |state, updated| CellVersion { state, updated };
+        let explored = version(MapState::Explored, 1.0);
+        let obstacle = version(MapState::Obstacle, 2.0);
+        let unexplored = version(MapState::Unexplored, 3.0);
+        let outside = version(MapState::OutOfMap, 0.0);
+
+        assert_eq!(
+            PreferExplored.resolve(unexplored, obstacle),
+            obstacle.state
+        );
+        assert_eq!(PreferExplored.resolve(explored, obstacle), explored.state);
+        assert_eq!(PreferExplored.resolve(outside, obstacle), outside.state);
+        assert_eq!(PreferNewer.resolve(explored, obstacle), obstacle.state);
+        assert_eq!(PreferNewer.resolve(obstacle, explored), obstacle.state);
+        assert_eq!(PreferNewer.resolve(explored, explored), explored.state);
+        assert_eq!(PreferMine.resolve(outside, obstacle), obstacle.state);
+        assert_eq!(PreferMine.resolve(explored, obstacle), explored.state);
+        assert_eq!(PreferPriority.resolve(explored, obstacle), obstacle.state);
+        assert_eq!(PreferPriority.resolve(obstacle, explored), obstacle.state);
+        assert_eq!(
+            PreferPriority.resolve(unexplored, outside),
+            unexplored.state
+        );
+    }
+
+    #[test]
+    fn merge_overlapping_maps_with_different_offsets() {
+        let mut mine = make_map((0.0, 0.0), (4.0, 2.0));
+        set(&mut mine, 2.5, 0.5, MapState::Obstacle);
+        mine.clear_dirty();
+        let mut theirs = make_map((2.0, 0.0), (6.0, 2.0));
+        set(&mut theirs, 2.5, 0.5, MapState::Explored);
+        set(&mut theirs, 3.5, 1.5, MapState::Explored);
+        set(&mut theirs, 5.5, 0.5, MapState::Explored);
+        set(&mut theirs, 2.5, 1.5, MapState::OutOfMap);
+
+        let changed = mine.merge(&theirs, &PreferExplored);
+
+        assert_eq!(changed, 1);
+        assert_eq!(mine.dirty_cells(), [[1, 3]]);
+        assert_eq!(get(&mine, 2.5, 0.5), MapState::Obstacle);
+        assert_eq!(get(&mine, 3.5, 1.5), MapState::Explored);
+        assert_eq!(get(&mine, 2.5, 1.5), MapState::Unexplored);
+    }
+
+    #[test]
+    fn new_states_are_stamped_with_own_clock() {
+        let mut mine = make_map((0.0, 0.0), (2.0, 2.0));
+        mine.set_clock(5.0);
+        let theirs = make_map((0.0, 0.0), (2.0, 2.0));
+
+        let changed = mine.merge(&theirs, &|_: CellVersion, _: CellVersion| {
+            MapState::Frontier
+        });
+
+        assert_eq!(changed, 4);
+        assert_eq!(
+            mine.last_update(&RealWorldLocation::from_xyz(0.5, 0.5, 0.0)),
+            Ok(5.0)
+        );
+    }
+
+    #[test]
+    fn stitch_maps_with_overlap() {
+        let mut a = make_map((0.0, 0.0), (2.0, 2.0));
+        a.set_clock(1.0);
+        set(&mut a, 1.5, 1.5, MapState::Obstacle);
+        let mut b = make_map((1.0, -1.0), (3.0, 1.0));
+        b.set_clock(4.0);
+        set(&mut b, 1.5, 0.5, MapState::Explored);
+        set(&mut b, 2.5, -0.5, MapState::Frontier);
+        // Not aligned with the cells of the first map.
+        let mut c = make_map((-1.25, 0.0), (-0.25, 1.0));
+        set(&mut c, -0.75, 0.5, MapState::Obstacle);
+
+        let map = CellMap::stitch(&[a, b, c], &PreferExplored).unwrap();
+
+        assert_eq!(map.offset(), &Coords::new(-2.0, -1.0, 0.0));
+        assert_eq!(map.cells().dim(), (3, 5));
+        assert_eq!(map.clock(), 4.0);
+        assert!(map.dirty_cells().is_empty());
+        assert_eq!(get(&map, 1.5, 1.5), MapState::Obstacle);
+        assert_eq!(get(&map, 1.5, 0.5), MapState::Explored);
+        assert_eq!(get(&map, 0.5, 0.5), MapState::Unexplored);
+        assert_eq!(get(&map, 2.5, -0.5), MapState::Frontier);
+        assert_eq!(get(&map, -0.5, 0.5), MapState::Obstacle);
+        assert_eq!(get(&map, -1.5, 0.5), MapState::OutOfMap);
+        assert_eq!(get(&map, 0.5, -0.5), MapState::OutOfMap);
+        assert_eq!(
+            map.last_update(&RealWorldLocation::from_xyz(2.5, -0.5, 0.0)),
+            Ok(4.0)
+        );
+        assert_eq!(CellMap::stitch(&[], &PreferExplored), None);
+    }
+
+    #[test]
+    fn stitch_maps_far_apart() {
+        let a = make_map((0.0, 0.0), (2.0, 2.0));
+        let b = make_map((1e5, 1e5), (1e5 + 2.0, 1e5 + 2.0));
+
+        assert_eq!(CellMap::stitch(&[a, b], &PreferExplored), None);
+    }
+}
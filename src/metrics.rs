@@ -0,0 +1,343 @@
+//! Logging of experiment metrics.
+//!
+//! To compare partitioning and exploration strategies across runs, the same
+//! metrics need to be recorded in the same way. [`StepMetrics`] captures the
+//! state of a run after each step: how much of the map is covered, how evenly
+//! the last partitioning split the work, and how far each robot has traveled.
+//! A [`MetricsSink`] stores them, e.g. as CSV using [`CsvSink`] or as JSON
+//! lines using [`JsonLinesSink`].
+//!
+//! Requires the `std` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use local_robot_map::{
+//!     metrics::{CsvSink, MetricsSink, StepMetrics},
+//!     partitioning::weighted_voronoi_with_outcome,
+//!     AxisResolution, CellMap, LocalMap, Partition, RealWorldLocation, Robot,
+//! };
+//!
+//! let mut map = LocalMap::new_noexpand(
+//!     CellMap::new(
+//!         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+//!         RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+//!         AxisResolution::uniform(1.0),
+//!     ),
+//!     Robot::new(RealWorldLocation::from_xyz(2.5, 2.5, 0.0), ()),
+//!     vec![Robot::new(RealWorldLocation::from_xyz(7.5, 7.5, 0.0), ())],
+//! )
+//! .unwrap();
+//! map.record_trajectories(true);
+//! let mut sink = CsvSink::new(Vec::new());
+//!
+//! for step in 0..3 {
+//!     let (partitioned, outcome) = map
+//!         .partition_with_outcome(weighted_voronoi_with_outcome, None)
+//!         .unwrap();
+//!     map = partitioned;
+//!     // ... move the robots and record what they observed.
+//!     sink.record(&StepMetrics::from_map(step, &map, Some(&outcome)))
+//!         .unwrap();
+//! }
+//!
+//! let csv = String::from_utf8(sink.into_inner()).unwrap();
+//! assert_eq!(csv.lines().count(), 4);
+//! assert!(csv.starts_with("step,time,coverage,balance,travel_distances\n"));
+//! ```
+
+use std::io::{self, Write};
+
+use crate::{CellMap, LocalMap, MapState, PartitionOutcome, Timestamp};
+
+/// Metrics of a run after one step, see the [module](self) documentation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepMetrics {
+    /// Number of the step, e.g. the iteration of the control loop.
+    pub step: usize,
+    /// When the step happened, see [`LocalMap::clock`].
+    pub time: Timestamp,
+    /// Fraction of the traversable cells which are covered, from `0.0` to
+    /// `1.0`.
+    pub coverage: f64,
+    /// Area of the smallest region divided by the area of the largest region
+    /// of the last partitioning, from `0.0` to `1.0` for perfectly balanced
+    /// regions. [`None`] if no partitioning took place.
+    pub balance: Option<f64>,
+    /// Distance traveled by each robot, starting with the current one.
+    pub travel_distances: Vec<f64>,
+}
+
+impl StepMetrics {
+    /// Compute the metrics of a map at `step`.
+    ///
+    /// Cells which are [`MapState::Explored`] or occupied by a robot count as
+    /// covered, while [`MapState::Obstacle`] and [`MapState::OutOfMap`] cells
+    /// do not need to be covered. The balance is taken from the `outcome` of
+    /// the last partitioning, if any. The travel distances are the lengths of
+    /// the robots' trajectories, which are only recorded if enabled, see
+    /// [`LocalMap::record_trajectories`].
+    pub fn from_map<P>(
+        step: usize,
+        map: &LocalMap<CellMap, P>,
+        outcome: Option<&PartitionOutcome>,
+    ) -> Self {
+        let (covered, total) =
+            map.map()
+                .cells()
+                .iter()
+                .fold((0, 0), |(covered, total), value| match value {
+                    MapState::Obstacle | MapState::OutOfMap => (covered, total),
+                    MapState::Explored
+                    | MapState::MyRobot
+                    | MapState::OtherRobot => (covered + 1, total + 1),
+                    _ => (covered, total + 1),
+                });
+        let balance = outcome.and_then(|outcome| {
+            let areas = outcome.areas();
+            let min = areas.iter().copied().reduce(f64::min)?;
+            let max = areas.iter().copied().reduce(f64::max)?;
+            Some(if max > 0.0 { min / max } else { 1.0 })
+        });
+        let travel_distances = core::iter::once(map.my_robot())
+            .chain(map.other_robots())
+            .map(|robot| {
+                robot
+                    .trajectory()
+                    .windows(2)
+                    .map(|pair| {
+                        let (a, b) = (&pair[0].1, &pair[1].1);
+                        ((a.x() - b.x()).powi(2) + (a.y() - b.y()).powi(2))
+                            .sqrt()
+                    })
+                    // Unlike `sum`, which gives `-0.0` without any segments.
+                    .fold(0.0, |total, length| total + length)
+            })
+            .collect();
+
+        Self {
+            step,
+            time: map.clock(),
+            coverage: if total > 0 {
+                covered as f64 / total as f64
+            } else {
+                1.0
+            },
+            balance,
+            travel_distances,
+        }
+    }
+}
+
+/// Destination of [`StepMetrics`], e.g. a file or a database.
+pub trait MetricsSink {
+    /// Store the metrics of one step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metrics could not be stored.
+    fn record(&mut self, metrics: &StepMetrics) -> io::Result<()>;
+}
+
+/// Writes [`StepMetrics`] as CSV, one row per step.
+///
+/// The columns are `step`, `time`, `coverage`, `balance` and
+/// `travel_distances`. A missing balance is left empty, and the travel
+/// distances are separated by semicolons, such that the number of columns
+/// does not depend on the number of robots. The header is written along with
+/// the first row.
+#[derive(Debug)]
+pub struct CsvSink<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl<W: Write> CsvSink<W> {
+    /// Write the rows to `writer`, starting with the header.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header_written: false,
+        }
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> MetricsSink for CsvSink<W> {
+    fn record(&mut self, metrics: &StepMetrics) -> io::Result<()> {
+        if !self.header_written {
+            writeln!(
+                self.writer,
+                "step,time,coverage,balance,travel_distances"
+            )?;
+            self.header_written = true;
+        }
+        let balance =
+            metrics.balance.map(|b| b.to_string()).unwrap_or_default();
+        let distances: Vec<String> = metrics
+            .travel_distances
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        writeln!(
+            self.writer,
+            "{},{},{},{},{}",
+            metrics.step,
+            metrics.time,
+            metrics.coverage,
+            balance,
+            distances.join(";")
+        )
+    }
+}
+
+/// Writes [`StepMetrics`] as JSON lines, i.e. one JSON object per step and
+/// line.
+///
+/// The objects have the keys `step`, `time`, `coverage`, `balance` (`null`
+/// if missing) and `travel_distances` (an array). Non-finite numbers, which
+/// JSON cannot represent, are written as `null`.
+#[derive(Debug)]
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    /// Write the lines to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> MetricsSink for JsonLinesSink<W> {
+    fn record(&mut self, metrics: &StepMetrics) -> io::Result<()> {
+        let distances: Vec<String> =
+            metrics.travel_distances.iter().map(|d| json(*d)).collect();
+        writeln!(
+            self.writer,
+            r#"{{"step":{},"time":{},"coverage":{},"balance":{},"travel_distances":[{}]}}"#,
+            metrics.step,
+            json(metrics.time),
+            json(metrics.coverage),
+            metrics.balance.map_or("null".to_owned(), json),
+            distances.join(",")
+        )
+    }
+}
+
+/// A number as JSON, which does not support NaN and infinity.
+fn json(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location, RealWorldLocation, Robot};
+
+    fn make_local_map() -> LocalMap<CellMap, ()> {
+        let mut map = LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+            vec![Robot::new(RealWorldLocation::from_xyz(3.5, 1.5, 0.0), ())],
+        )
+        .unwrap();
+        map.record_trajectories(true);
+        map.map_mut()
+            .set_location(
+                &RealWorldLocation::from_xyz(3.5, 0.5, 0.0),
+                MapState::Obstacle,
+            )
+            .unwrap();
+        map.set_clock(2.0);
+        map.move_my_robot(RealWorldLocation::from_xyz(2.5, 0.5, 0.0))
+            .unwrap();
+        map
+    }
+
+    #[test]
+    fn metrics_of_map() {
+        let map = make_local_map();
+        let outcome = PartitionOutcome::new(
+            vec![*map.my_position(), map.other_positions()[0]],
+            vec![2, 4],
+            1.0,
+        );
+
+        let metrics = StepMetrics::from_map(3, &map, Some(&outcome));
+
+        // Both robots and the start of my robot out of 7 cells.
+        assert_eq!(
+            metrics,
+            StepMetrics {
+                step: 3,
+                time: 2.0,
+                coverage: 3.0 / 7.0,
+                balance: Some(0.5),
+                travel_distances: vec![2.0, 0.0],
+            }
+        );
+    }
+
+    #[test]
+    fn csv_rows() {
+        let mut sink = CsvSink::new(Vec::new());
+        let metrics = StepMetrics::from_map(0, &make_local_map(), None);
+
+        sink.record(&metrics).unwrap();
+        sink.record(&StepMetrics { step: 1, ..metrics }).unwrap();
+
+        let csv = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], format!("0,2,{},,2;0", 3.0 / 7.0));
+        assert!(lines[2].starts_with("1,"));
+    }
+
+    #[test]
+    fn json_lines() {
+        let mut sink = JsonLinesSink::new(Vec::new());
+        let metrics = StepMetrics {
+            step: 4,
+            time: f64::NAN,
+            coverage: 0.25,
+            balance: Some(1.0),
+            travel_distances: vec![1.5, 3.0],
+        };
+
+        sink.record(&metrics).unwrap();
+        sink.record(&StepMetrics {
+            balance: None,
+            ..metrics
+        })
+        .unwrap();
+
+        let json = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"step":4,"time":null,"coverage":0.25,"balance":1,"travel_distances":[1.5,3]}"#,
+                "\n",
+                r#"{"step":4,"time":null,"coverage":0.25,"balance":null,"travel_distances":[1.5,3]}"#,
+                "\n",
+            )
+        );
+    }
+}
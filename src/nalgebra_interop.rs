@@ -0,0 +1,122 @@
+//! Conversions between the coordinates of this crate and [`nalgebra`] types,
+//! e.g. the output of a state estimator.
+//!
+//! [`Coords`] convert from and to both [`Point3`] and [`Vector3`], since they
+//! are used for both locations and offsets. [`RealWorldLocation`] only
+//! converts from and to [`Point3`], and can be moved to another frame using
+//! [`RealWorldLocation::transform`].
+//!
+//! Requires the `nalgebra` feature.
+
+use nalgebra::{Isometry3, Point3, Scalar, Vector3};
+use num::Float;
+
+use crate::{Coords, RealWorldLocation};
+
+impl<S: Scalar> From<Coords<S>> for Point3<S> {
+    fn from(value: Coords<S>) -> Self {
+        Point3::new(value.x, value.y, value.z)
+    }
+}
+
+impl<S: Scalar> From<Point3<S>> for Coords<S> {
+    fn from(value: Point3<S>) -> Self {
+        let [x, y, z] = value.coords.into();
+        Self { x, y, z }
+    }
+}
+
+impl<S: Scalar> From<Coords<S>> for Vector3<S> {
+    fn from(value: Coords<S>) -> Self {
+        Vector3::new(value.x, value.y, value.z)
+    }
+}
+
+impl<S: Scalar> From<Vector3<S>> for Coords<S> {
+    fn from(value: Vector3<S>) -> Self {
+        let [x, y, z] = value.into();
+        Self { x, y, z }
+    }
+}
+
+impl<S: Scalar + Float> From<RealWorldLocation<S>> for Point3<S> {
+    fn from(value: RealWorldLocation<S>) -> Self {
+        (*value.location()).into()
+    }
+}
+
+impl<S: Scalar + Float> From<Point3<S>> for RealWorldLocation<S> {
+    fn from(value: Point3<S>) -> Self {
+        Self::new(value.into())
+    }
+}
+
+impl RealWorldLocation {
+    /// Apply a rigid transformation to the location, e.g. to move it from the
+    /// frame of a robot into the frame of the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::RealWorldLocation;
+    /// use nalgebra::{Isometry3, Vector3};
+    ///
+    /// // The robot is at (10, 5), facing along the `y` axis of the map.
+    /// let robot_to_map = Isometry3::new(
+    ///     Vector3::new(10.0, 5.0, 0.0),
+    ///     Vector3::z() * std::f64::consts::FRAC_PI_2,
+    /// );
+    /// // An obstacle 2 meters ahead of the robot.
+    /// let obstacle = RealWorldLocation::from_xyz(2.0, 0.0, 0.0);
+    ///
+    /// let obstacle = obstacle.transform(&robot_to_map);
+    ///
+    /// assert!((obstacle.x() - 10.0).abs() < 1e-9);
+    /// assert!((obstacle.y() - 7.0).abs() < 1e-9);
+    /// ```
+    pub fn transform(&self, isometry: &Isometry3<f64>) -> Self {
+        isometry
+            .transform_point(&Point3::from(*self.location()))
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coords_roundtrip() {
+        let coords = Coords::new(1.0, -2.0, 3.5);
+
+        assert_eq!(Point3::from(coords), Point3::new(1.0, -2.0, 3.5));
+        assert_eq!(Vector3::from(coords), Vector3::new(1.0, -2.0, 3.5));
+        assert_eq!(Coords::from(Point3::from(coords)), coords);
+        assert_eq!(Coords::from(Vector3::from(coords)), coords);
+    }
+
+    #[test]
+    fn location_roundtrip() {
+        let location = RealWorldLocation::from_xyz(0.5_f32, 1.5, -2.5);
+
+        let point = Point3::from(location);
+
+        assert_eq!(point, Point3::new(0.5, 1.5, -2.5));
+        assert_eq!(RealWorldLocation::from(point), location);
+    }
+
+    #[test]
+    fn transform_translates_and_rotates() {
+        let isometry = Isometry3::new(
+            Vector3::new(1.0, 2.0, 3.0),
+            Vector3::z() * core::f64::consts::PI,
+        );
+
+        let location =
+            RealWorldLocation::from_xyz(1.0, 0.0, 0.0).transform(&isometry);
+
+        assert!((location.x() - 0.0).abs() < 1e-9);
+        assert!((location.y() - 2.0).abs() < 1e-9);
+        assert_eq!(location.z(), 3.0);
+    }
+}
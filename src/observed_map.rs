@@ -0,0 +1,244 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{
+    Cell, Location, LocationError, LocationType, Mask, RealWorldLocation,
+    Visualize,
+};
+
+/// Callback invoked with the location, the old and the new state of a cell.
+type Observer =
+    Box<dyn FnMut(&RealWorldLocation, LocationType, LocationType) + Send>;
+
+/// Identifies an observer registered with [`ObservedMap::add_observer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(usize);
+
+/// A map which notifies observers whenever a location changes its state.
+///
+/// Observers are called by [`Location::set_location`] with the location
+/// passed to it, the previous and the new state. Writing a state which is
+/// already stored does not notify them, so observers only see actual
+/// changes. This allows e.g. synchronizing maps between robots without
+/// comparing whole maps every cycle (see [`crate::MapDelta`] for that).
+///
+/// Only changes made through the [`Location`] trait are observed, which is
+/// why the wrapped map is not accessible mutably.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::mpsc;
+///
+/// use local_robot_map::{
+///     AxisResolution, CellMap, Location, MapState, ObservedMap,
+///     RealWorldLocation,
+/// };
+///
+/// let mut map = ObservedMap::new(CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// ));
+/// let (sender, receiver) = mpsc::channel();
+/// map.add_observer(move |location, old, new| {
+///     sender.send((location.clone(), old, new)).unwrap();
+/// });
+/// let cell = RealWorldLocation::from_xyz(1.5, 2.5, 0.0);
+///
+/// map.set_location(&cell, MapState::Explored).unwrap();
+/// map.set_location(&cell, MapState::Explored).unwrap();
+///
+/// let changes: Vec<_> = receiver.try_iter().collect();
+/// assert_eq!(
+///     changes,
+///     [(cell, MapState::Unexplored, MapState::Explored)]
+/// );
+/// ```
+pub struct ObservedMap<T> {
+    map: T,
+    observers: Vec<(ObserverId, Observer)>,
+    next_id: usize,
+}
+
+impl<T> ObservedMap<T> {
+    /// Observe changes of `map`. No observer is registered initially.
+    pub fn new(map: T) -> Self {
+        Self {
+            map,
+            observers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Register a callback called with `(location, old_state, new_state)`
+    /// whenever a location changes. Observers are called in the order they
+    /// were added.
+    pub fn add_observer(
+        &mut self,
+        observer: impl FnMut(&RealWorldLocation, LocationType, LocationType)
+            + Send
+            + 'static,
+    ) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+        self.observers.push((id, Box::new(observer)));
+        id
+    }
+
+    /// Unregister an observer. Returns whether it was registered.
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        let count = self.observers.len();
+        self.observers.retain(|(other, _)| *other != id);
+        self.observers.len() != count
+    }
+
+    /// The observed map.
+    pub fn map(&self) -> &T {
+        &self.map
+    }
+
+    /// Stop observing the map and return it.
+    pub fn into_inner(self) -> T {
+        self.map
+    }
+}
+
+impl<T: Location> Location for ObservedMap<T> {
+    fn get_location(
+        &self,
+        coord: &RealWorldLocation,
+    ) -> Result<LocationType, LocationError> {
+        self.map.get_location(coord)
+    }
+
+    fn set_location(
+        &mut self,
+        coord: &RealWorldLocation,
+        value: LocationType,
+    ) -> Result<(), LocationError> {
+        let old = self.map.get_location(coord)?;
+        self.map.set_location(coord, value)?;
+        if old != value {
+            for (_, observer) in &mut self.observers {
+                observer(coord, old, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn locations_within(
+        &self,
+        center: &RealWorldLocation,
+        radius: f64,
+    ) -> Vec<RealWorldLocation> {
+        self.map.locations_within(center, radius)
+    }
+
+    fn same_cell(&self, a: &RealWorldLocation, b: &RealWorldLocation) -> bool {
+        self.map.same_cell(a, b)
+    }
+}
+
+impl<T: Mask> Mask for ObservedMap<T> {
+    fn get_map_region(
+        &self,
+        filter: &dyn Fn(LocationType) -> bool,
+    ) -> Vec<Cell<'_>> {
+        self.map.get_map_region(filter)
+    }
+}
+
+impl<T: Visualize> Visualize for ObservedMap<T> {
+    type ImageType = T::ImageType;
+
+    fn as_image(&self) -> Self::ImageType {
+        self.map.as_image()
+    }
+
+    fn visualize(&self) {
+        self.map.visualize()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ObservedMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservedMap")
+            .field("map", &self.map)
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{AxisResolution, CellMap, LocalMap, MapState, Robot};
+
+    fn make_map() -> ObservedMap<CellMap> {
+        ObservedMap::new(CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+            AxisResolution::uniform(1.0),
+        ))
+    }
+
+    #[test]
+    fn removed_observers_are_not_called() {
+        let mut map = make_map();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let ids = [0, 1].map(|i| {
+            let calls = Arc::clone(&calls);
+            map.add_observer(move |_, _, new| {
+                calls.lock().unwrap().push((i, new));
+            })
+        });
+        let cell = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+
+        map.set_location(&cell, MapState::Explored).unwrap();
+        assert!(map.remove_observer(ids[0]));
+        assert!(!map.remove_observer(ids[0]));
+        map.set_location(&cell, MapState::Obstacle).unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                (0, MapState::Explored),
+                (1, MapState::Explored),
+                (1, MapState::Obstacle)
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_writes_are_not_observed() {
+        let mut map = make_map();
+        let calls = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&calls);
+        map.add_observer(move |_, _, _| *counter.lock().unwrap() += 1);
+        let outside = RealWorldLocation::from_xyz(5.0, 0.5, 0.0);
+
+        assert!(map.set_location(&outside, MapState::Explored).is_err());
+        assert_eq!(*calls.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn observes_local_map_updates() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut map = make_map();
+        let recorded = Arc::clone(&calls);
+        map.add_observer(move |location, _, new| {
+            recorded.lock().unwrap().push((*location, new));
+        });
+        let robot = RealWorldLocation::from_xyz(1.5, 1.5, 0.0);
+
+        let local_map =
+            LocalMap::new_noexpand(map, Robot::new(robot, ()), vec![]);
+
+        assert!(local_map.is_ok());
+        assert_eq!(*calls.lock().unwrap(), [(robot, MapState::MyRobot)]);
+    }
+}
@@ -0,0 +1,398 @@
+use image::{Rgb, RgbImage};
+
+use crate::visibility::grid_line;
+use crate::{CellMap, ColorMap, LocalMap, MapState, RealWorldLocation};
+
+/// Additional information drawn on top of a map image by
+/// [`LocalMap::as_image_with_overlay`].
+///
+/// By default, robots are drawn as markers along with their heading and
+/// their recorded trajectories (see [`LocalMap::record_trajectories`]), and
+/// the [`MapState::Assigned`] region is shown as a translucent tint. All
+/// states are drawn in the colors of [`ColorMap::DEFAULT`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOverlay {
+    colors: ColorMap,
+    robot_markers: bool,
+    heading_arrows: Option<u32>,
+    recorded_trajectories: bool,
+    assigned_tint: Option<(Rgb<u8>, f32)>,
+    trajectories: Vec<(Vec<RealWorldLocation>, Rgb<u8>)>,
+}
+
+impl Default for ImageOverlay {
+    fn default() -> Self {
+        Self {
+            colors: ColorMap::DEFAULT,
+            robot_markers: true,
+            heading_arrows: Some(4),
+            recorded_trajectories: true,
+            assigned_tint: Some((MapState::Assigned.to_rgb(), 0.4)),
+            trajectories: Vec::new(),
+        }
+    }
+}
+
+impl ImageOverlay {
+    /// Colors of the map and of the robot markers.
+    pub fn colors(mut self, colors: ColorMap) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Whether to draw each robot as a cross-shaped marker, in the color of
+    /// [`MapState::MyRobot`] or [`MapState::OtherRobot`] respectively.
+    pub fn robot_markers(mut self, enabled: bool) -> Self {
+        self.robot_markers = enabled;
+        self
+    }
+
+    /// Draw the heading of each robot (see [`crate::Robot::heading`]) as an
+    /// arrow of the given length in cells, in the color of its marker.
+    ///
+    /// Passing [`None`] disables the arrows.
+    pub fn heading_arrows(mut self, length: Option<u32>) -> Self {
+        self.heading_arrows = length;
+        self
+    }
+
+    /// Whether to draw the trajectories recorded by the [`LocalMap`], in the
+    /// color of [`MapState::MyRobot`] or [`MapState::OtherRobot`]
+    /// respectively.
+    pub fn recorded_trajectories(mut self, enabled: bool) -> Self {
+        self.recorded_trajectories = enabled;
+        self
+    }
+
+    /// Draw [`MapState::Assigned`] cells like [`MapState::Unexplored`] ones,
+    /// blended with `color` by `opacity` (from `0.0` to `1.0`). This keeps the
+    /// assignment visible while showing that the cells still need to be
+    /// covered.
+    ///
+    /// Passing [`None`] draws them in their regular color.
+    pub fn assigned_tint(mut self, tint: Option<(Rgb<u8>, f32)>) -> Self {
+        self.assigned_tint =
+            tint.map(|(color, opacity)| (color, opacity.clamp(0.0, 1.0)));
+        self
+    }
+
+    /// Draw a path through the given locations as connected lines.
+    ///
+    /// Segments with an end outside the map are skipped.
+    pub fn trajectory(
+        mut self,
+        path: Vec<RealWorldLocation>,
+        color: Rgb<u8>,
+    ) -> Self {
+        self.trajectories.push((path, color));
+        self
+    }
+}
+
+impl<P> LocalMap<CellMap, P> {
+    /// Render the map like [`CellMap::as_image_with`], with additional
+    /// information drawn on top of it.
+    ///
+    /// Drawing happens in the order tint, recorded trajectories, additional
+    /// trajectories, heading arrows and robot markers, such that robots are
+    /// never hidden.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use image::Rgb;
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, ImageOverlay, LocalMap, MapState,
+    ///     RealWorldLocation, Robot,
+    /// };
+    ///
+    /// let map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     Robot::new(RealWorldLocation::from_xyz(2.5, 2.5, 0.0), ()),
+    ///     vec![],
+    /// )
+    /// .unwrap();
+    /// let path = vec![
+    ///     RealWorldLocation::from_xyz(2.5, 2.5, 0.0),
+    ///     RealWorldLocation::from_xyz(8.5, 2.5, 0.0),
+    /// ];
+    ///
+    /// let image = map.as_image_with_overlay(
+    ///     &ImageOverlay::default().trajectory(path, Rgb([0, 0, 255])),
+    /// );
+    ///
+    /// // The marker extends one pixel around the robot's cell.
+    /// assert_eq!(*image.get_pixel(3, 2), MapState::MyRobot.to_rgb());
+    /// assert_eq!(*image.get_pixel(6, 2), Rgb([0, 0, 255]));
+    /// ```
+    pub fn as_image_with_overlay(&self, overlay: &ImageOverlay) -> RgbImage {
+        let map = self.map();
+        let mut image = map.as_image_with(&overlay.colors);
+        let mut paint = |[row, col]: [usize; 2], color: Rgb<u8>| {
+            if let (Ok(x), Ok(y)) = (u32::try_from(col), u32::try_from(row)) {
+                if x < image.width() && y < image.height() {
+                    image.put_pixel(x, y, color);
+                }
+            }
+        };
+
+        if let Some((tint, opacity)) = overlay.assigned_tint {
+            let base = overlay.colors.color(MapState::Unexplored);
+            let blended = Rgb([0, 1, 2].map(|i| {
+                let (base, tint) = (f32::from(base[i]), f32::from(tint[i]));
+                (base + (tint - base) * opacity).round() as u8
+            }));
+            for ((row, col), value) in map.cells().indexed_iter() {
+                if *value == MapState::Assigned {
+                    paint([row, col], blended);
+                }
+            }
+        }
+
+        // Segments with an end outside the map are skipped.
+        let mut draw_path = |path: Vec<Option<[usize; 2]>>, color: Rgb<u8>| {
+            for segment in path.windows(2) {
+                if let [Some(from), Some(to)] = segment {
+                    for index in grid_line(*from, *to) {
+                        paint(index, color);
+                    }
+                }
+            }
+        };
+        let to_index = |location: &RealWorldLocation| {
+            map.location_to_map_index(location).ok()
+        };
+        if overlay.recorded_trajectories {
+            let robots = self
+                .other_robots()
+                .iter()
+                .map(|robot| (robot, MapState::OtherRobot))
+                .chain([(self.my_robot(), MapState::MyRobot)]);
+            for (robot, state) in robots {
+                let path = robot.trajectory().iter();
+                draw_path(
+                    path.map(|(_, location)| to_index(location)).collect(),
+                    overlay.colors.color(state),
+                );
+            }
+        }
+        for (path, color) in &overlay.trajectories {
+            draw_path(path.iter().map(to_index).collect(), *color);
+        }
+
+        let robots = || {
+            self.other_robots()
+                .iter()
+                .map(|robot| (robot, MapState::OtherRobot))
+                .chain([(self.my_robot(), MapState::MyRobot)])
+        };
+        if let Some(length) = overlay.heading_arrows {
+            for (robot, state) in robots() {
+                let (Ok(index), Some(yaw)) = (
+                    map.location_to_map_index(robot.location()),
+                    robot.heading(),
+                ) else {
+                    continue;
+                };
+                let length = f64::from(length);
+                let mut arrow = ray(index, yaw, length);
+                if let Some(tip) = arrow.last().copied() {
+                    for angle in [ARROW_BARB, -ARROW_BARB] {
+                        arrow.extend(ray(tip, yaw + angle, length / 3.0));
+                    }
+                }
+                for index in arrow {
+                    paint(index, overlay.colors.color(state));
+                }
+            }
+        }
+
+        if overlay.robot_markers {
+            for (robot, state) in robots() {
+                let Ok([row, col]) =
+                    map.location_to_map_index(robot.location())
+                else {
+                    continue;
+                };
+                let marker = [
+                    Some([row, col]),
+                    row.checked_sub(1).map(|row| [row, col]),
+                    Some([row + 1, col]),
+                    col.checked_sub(1).map(|col| [row, col]),
+                    Some([row, col + 1]),
+                ];
+                for index in marker.into_iter().flatten() {
+                    paint(index, overlay.colors.color(state));
+                }
+            }
+        }
+
+        image
+    }
+}
+
+/// Angle between the shaft and the barbs of heading arrows, in radians.
+const ARROW_BARB: f64 = 5.0 * core::f64::consts::FRAC_PI_6;
+
+/// The indices of the cells along a ray from the cell at `start`, with an
+/// angle of `yaw` counterclockwise from the `x` axis (i.e. the column axis)
+/// and a length in cells. Cells with negative indices are skipped.
+fn ray([row, col]: [usize; 2], yaw: f64, length: f64) -> Vec<[usize; 2]> {
+    let (sin, cos) = yaw.sin_cos();
+    let steps = length.max(0.0).ceil();
+    let mut cells: Vec<[usize; 2]> = (1..=steps as usize)
+        .filter_map(|step| {
+            let distance = step as f64 * length / steps;
+            let row = row as f64 + (sin * distance).round();
+            let col = col as f64 + (cos * distance).round();
+            (row >= 0.0 && col >= 0.0).then_some([row as usize, col as usize])
+        })
+        .collect();
+    cells.dedup();
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location, Robot};
+
+    fn make_local_map() -> LocalMap<CellMap, ()> {
+        LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(6.0, 4.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+            vec![Robot::new(RealWorldLocation::from_xyz(4.5, 2.5, 0.0), ())],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn markers_are_clipped_at_border() {
+        let map = make_local_map();
+
+        let image = map.as_image_with_overlay(&ImageOverlay::default());
+
+        let red = MapState::MyRobot.to_rgb();
+        let green = MapState::OtherRobot.to_rgb();
+        assert_eq!(*image.get_pixel(0, 0), red);
+        assert_eq!(*image.get_pixel(1, 0), red);
+        assert_eq!(*image.get_pixel(0, 1), red);
+        assert_eq!(*image.get_pixel(1, 1), MapState::Unexplored.to_rgb());
+        for (x, y) in [(4, 2), (3, 2), (5, 2), (4, 1), (4, 3)] {
+            assert_eq!(*image.get_pixel(x, y), green);
+        }
+    }
+
+    #[test]
+    fn assigned_tint() {
+        let mut map = make_local_map();
+        let cell = RealWorldLocation::from_xyz(2.5, 0.5, 0.0);
+        map.map_mut()
+            .set_location(&cell, MapState::Assigned)
+            .unwrap();
+
+        let plain = map.as_image_with_overlay(
+            &ImageOverlay::default().assigned_tint(None),
+        );
+        let full = map.as_image_with_overlay(
+            &ImageOverlay::default().assigned_tint(Some((Rgb([0, 0, 0]), 1.0))),
+        );
+        let half = map.as_image_with_overlay(
+            &ImageOverlay::default().assigned_tint(Some((Rgb([0, 0, 0]), 0.5))),
+        );
+
+        assert_eq!(*plain.get_pixel(2, 0), MapState::Assigned.to_rgb());
+        assert_eq!(*full.get_pixel(2, 0), Rgb([0, 0, 0]));
+        assert_eq!(*half.get_pixel(2, 0), Rgb([50, 50, 50]));
+    }
+
+    #[test]
+    fn recorded_trajectories() {
+        let mut map = make_local_map();
+        map.record_trajectories(true);
+        map.move_other_robot(0, RealWorldLocation::from_xyz(4.5, 0.5, 0.0))
+            .unwrap();
+        let overlay = ImageOverlay::default().robot_markers(false);
+
+        let image = map.as_image_with_overlay(&overlay);
+        let hidden =
+            map.as_image_with_overlay(&overlay.recorded_trajectories(false));
+
+        let green = MapState::OtherRobot.to_rgb();
+        assert_eq!(*image.get_pixel(4, 1), green);
+        assert_eq!(*hidden.get_pixel(4, 1), MapState::Unexplored.to_rgb());
+    }
+
+    #[test]
+    fn heading_arrow_points_along_yaw() {
+        let mut map = make_local_map();
+        map.set_my_heading(Some(0.0));
+        map.set_other_heading(0, Some(core::f64::consts::FRAC_PI_2));
+        let overlay = ImageOverlay::default().robot_markers(false);
+
+        let image = map.as_image_with_overlay(&overlay);
+        let plain = map.as_image_with_overlay(&overlay.heading_arrows(None));
+
+        let (red, green) =
+            (MapState::MyRobot.to_rgb(), MapState::OtherRobot.to_rgb());
+        // My robot at [0, 0] points along the columns.
+        for x in 1..=4 {
+            assert_eq!(*image.get_pixel(x, 0), red);
+            assert_ne!(*plain.get_pixel(x, 0), red);
+        }
+        assert_eq!(*image.get_pixel(3, 1), red);
+        // The other robot at [2, 4] points along the rows.
+        assert_eq!(*image.get_pixel(4, 3), green);
+        assert_eq!(*plain.get_pixel(4, 3), MapState::Unexplored.to_rgb());
+    }
+
+    #[test]
+    fn ray_skips_negative_indices() {
+        let cells = ray([1, 1], core::f64::consts::PI, 3.0);
+        let diagonal = ray([0, 0], core::f64::consts::FRAC_PI_4, 3.0);
+
+        assert_eq!(cells, [[1, 0]]);
+        assert_eq!(diagonal, [[1, 1], [2, 2]]);
+    }
+
+    #[test]
+    fn custom_colors() {
+        let map = make_local_map();
+        let colors = ColorMap::colorblind_safe();
+
+        let image =
+            map.as_image_with_overlay(&ImageOverlay::default().colors(colors));
+
+        assert_eq!(*image.get_pixel(0, 0), colors.color(MapState::MyRobot));
+        assert_eq!(*image.get_pixel(4, 2), colors.color(MapState::OtherRobot));
+        assert_eq!(*image.get_pixel(2, 3), colors.color(MapState::Unexplored));
+    }
+
+    #[test]
+    fn trajectory_outside_map_is_skipped() {
+        let map = make_local_map();
+        let color = Rgb([1, 2, 3]);
+        let path = vec![
+            RealWorldLocation::from_xyz(20.5, 0.5, 0.0),
+            RealWorldLocation::from_xyz(2.5, 3.5, 0.0),
+            RealWorldLocation::from_xyz(5.5, 3.5, 0.0),
+        ];
+
+        let image = map.as_image_with_overlay(
+            &ImageOverlay::default()
+                .robot_markers(false)
+                .trajectory(path, color),
+        );
+
+        let painted = image.pixels().filter(|pixel| **pixel == color).count();
+        assert_eq!(painted, 4);
+    }
+}
@@ -0,0 +1,165 @@
+use alloc::boxed::Box;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Callback invoked with the progress of a partitioning run.
+type ProgressCallback = Box<dyn Fn(PartitionProgress) + Send + Sync>;
+
+/// Progress reports and cancellation for a partitioning run.
+///
+/// Passed to [`Partition`](crate::Partition)`::partition_with_control`, which
+/// hands it to the algorithm, see
+/// [`ControlledAlgorithm`](crate::ControlledAlgorithm). Algorithms call
+/// [`PartitionControl::report`] as they make progress and regularly check
+/// [`PartitionControl::is_cancelled`], returning the map unchanged as a
+/// [`PartitionCancelled`] once it is set.
+///
+/// The control is [`Sync`], so a long partitioning run can be cancelled from
+/// another thread, e.g. a control loop which has to meet a planning deadline.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// use local_robot_map::PartitionControl;
+///
+/// let reports = Arc::new(AtomicUsize::new(0));
+/// let counter = Arc::clone(&reports);
+/// let control = PartitionControl::with_progress(move |progress| {
+///     assert!(progress.fraction() <= 1.0);
+///     counter.fetch_add(1, Ordering::Relaxed);
+/// });
+///
+/// control.report(Default::default());
+/// assert!(!control.is_cancelled());
+/// control.cancel();
+///
+/// assert!(control.is_cancelled());
+/// assert_eq!(reports.load(Ordering::Relaxed), 1);
+/// ```
+#[derive(Default)]
+pub struct PartitionControl {
+    cancelled: AtomicBool,
+    progress: Option<ProgressCallback>,
+}
+
+impl PartitionControl {
+    /// A control which is not cancelled and ignores progress reports.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A control which calls `callback` on every progress report.
+    ///
+    /// The callback is called from the thread running the algorithm and
+    /// should return quickly, as the algorithm waits for it.
+    pub fn with_progress(
+        callback: impl Fn(PartitionProgress) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+            progress: Some(Box::new(callback)),
+        }
+    }
+
+    /// Request the algorithm to stop as soon as possible.
+    ///
+    /// Cancelling cannot be undone, create a new control for the next run.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`PartitionControl::cancel`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Report the progress of the algorithm to the callback, if any.
+    pub fn report(&self, progress: PartitionProgress) {
+        if let Some(callback) = &self.progress {
+            callback(progress);
+        }
+    }
+}
+
+impl fmt::Debug for PartitionControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartitionControl")
+            .field("cancelled", &self.is_cancelled())
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// How far a partitioning run has come, see [`PartitionControl::report`].
+///
+/// Iterative algorithms report every iteration, within which `done` out of
+/// `total` steps were completed. Algorithms which are not iterative only run
+/// iteration `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PartitionProgress {
+    iteration: usize,
+    done: usize,
+    total: usize,
+}
+
+impl PartitionProgress {
+    /// Progress of `done` out of `total` steps within `iteration`.
+    pub fn new(iteration: usize, done: usize, total: usize) -> Self {
+        Self {
+            iteration,
+            done,
+            total,
+        }
+    }
+
+    /// The current iteration, starting at `1`.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+    /// Number of steps completed within the current iteration.
+    pub fn done(&self) -> usize {
+        self.done
+    }
+    /// Number of steps of the current iteration.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+    /// Fraction of the current iteration which is completed, from `0.0` to
+    /// `1.0`. An iteration without steps counts as completed.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f64 / self.total as f64
+        }
+    }
+}
+
+/// A partitioning run which was cancelled through
+/// [`PartitionControl::cancel`].
+///
+/// Holds the map which was passed to the algorithm, unchanged, so that it is
+/// not lost when the run is aborted. The map is boxed to keep the error, and
+/// thus the result of every partitioning run, small.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionCancelled<T>(Box<T>);
+
+impl<T> PartitionCancelled<T> {
+    /// Wrap the unchanged `map` of a cancelled run.
+    pub fn new(map: T) -> Self {
+        Self(Box::new(map))
+    }
+
+    /// The unchanged map.
+    pub fn map(&self) -> &T {
+        &self.0
+    }
+
+    /// Take the unchanged map back.
+    pub fn into_map(self) -> T {
+        *self.0
+    }
+}
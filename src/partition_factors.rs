@@ -0,0 +1,367 @@
+use alloc::vec::Vec;
+
+use crate::Robot;
+
+/// Common partitioning factors describing the capabilities of each robot.
+///
+/// Intended to be used as the `F` generic of [`Partition`](crate::Partition)
+/// and [`Algorithm`](crate::Algorithm), such that different algorithms share
+/// the same vocabulary. Every factor is optional and given per robot, where
+/// robot `0` is [`LocalMap::my_robot`](crate::LocalMap::my_robot), followed by
+/// [`LocalMap::other_robots`](crate::LocalMap::other_robots) in order.
+///
+/// Use [`PartitionFactors::builder`] to create the factors.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::PartitionFactors;
+///
+/// let factors = PartitionFactors::builder()
+///     .speeds(vec![1.0, 2.0])
+///     .battery_levels(vec![0.9, 0.4])
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(factors.robot_count(), 2);
+/// assert_eq!(factors.speed(1), Some(2.0));
+/// assert_eq!(factors.sensor_radius(1), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PartitionFactors {
+    speeds: Vec<f64>,
+    battery_levels: Vec<f64>,
+    sensor_radii: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl PartitionFactors {
+    /// Start building [`PartitionFactors`].
+    pub fn builder() -> PartitionFactorsBuilder {
+        PartitionFactorsBuilder::default()
+    }
+
+    /// Number of robots the factors describe, or `0` if no factor is set.
+    pub fn robot_count(&self) -> usize {
+        [
+            &self.speeds,
+            &self.battery_levels,
+            &self.sensor_radii,
+            &self.weights,
+        ]
+        .iter()
+        .map(|factor| factor.len())
+        .max()
+        .unwrap_or(0)
+    }
+
+    /// Speed of the given robot, e.g. in meters per second.
+    pub fn speed(&self, robot: usize) -> Option<f64> {
+        self.speeds.get(robot).copied()
+    }
+    /// Battery level of the given robot, as a fraction from `0.0` (empty) to
+    /// `1.0` (full).
+    pub fn battery_level(&self, robot: usize) -> Option<f64> {
+        self.battery_levels.get(robot).copied()
+    }
+    /// Sensor radius of the given robot, in the map's real-world units.
+    pub fn sensor_radius(&self, robot: usize) -> Option<f64> {
+        self.sensor_radii.get(robot).copied()
+    }
+    /// Arbitrary, user-defined weight of the given robot.
+    pub fn weight(&self, robot: usize) -> Option<f64> {
+        self.weights.get(robot).copied()
+    }
+
+    /// Overall capability of the given robot, i.e. how much work it can take
+    /// on relative to the others.
+    ///
+    /// Computed as the product of the robot's weight, speed and battery level.
+    /// Factors which are not set count as `1.0`.
+    ///
+    /// ```
+    /// use local_robot_map::PartitionFactors;
+    ///
+    /// let factors = PartitionFactors::builder()
+    ///     .speeds(vec![2.0, 1.0])
+    ///     .battery_levels(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(factors.capability(0), 1.0);
+    /// assert_eq!(factors.capability(1), 1.0);
+    /// assert_eq!(factors.capability(2), 1.0);
+    /// ```
+    pub fn capability(&self, robot: usize) -> f64 {
+        self.weight(robot).unwrap_or(1.0)
+            * self.speed(robot).unwrap_or(1.0)
+            * self.battery_level(robot).unwrap_or(1.0)
+    }
+
+    pub fn speeds(&self) -> &[f64] {
+        &self.speeds
+    }
+    pub fn battery_levels(&self) -> &[f64] {
+        &self.battery_levels
+    }
+    pub fn sensor_radii(&self) -> &[f64] {
+        &self.sensor_radii
+    }
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+}
+
+/// Builder for [`PartitionFactors`], see [`PartitionFactors::builder`].
+///
+/// Factors which are not set are left empty.
+#[derive(Debug, Clone, Default)]
+pub struct PartitionFactorsBuilder {
+    factors: PartitionFactors,
+}
+
+impl PartitionFactorsBuilder {
+    /// Set the speed of each robot. Speeds must be non-negative.
+    pub fn speeds(mut self, speeds: Vec<f64>) -> Self {
+        self.factors.speeds = speeds;
+        self
+    }
+    /// Set the battery level of each robot. Levels must be within
+    /// `0.0..=1.0`.
+    pub fn battery_levels(mut self, battery_levels: Vec<f64>) -> Self {
+        self.factors.battery_levels = battery_levels;
+        self
+    }
+    /// Set the sensor radius of each robot. Radii must be non-negative.
+    pub fn sensor_radii(mut self, sensor_radii: Vec<f64>) -> Self {
+        self.factors.sensor_radii = sensor_radii;
+        self
+    }
+    /// Set the weight of each robot. Weights must be non-negative.
+    pub fn weights(mut self, weights: Vec<f64>) -> Self {
+        self.factors.weights = weights;
+        self
+    }
+
+    /// Validate and create the [`PartitionFactors`].
+    ///
+    /// # Errors
+    ///
+    /// - [`PartitionFactorsError::LengthMismatch`] if the factors which are set
+    ///   do not all describe the same number of robots.
+    /// - [`PartitionFactorsError::InvalidValue`] if a value is NaN, infinite or
+    ///   outside its allowed range.
+    pub fn build(self) -> Result<PartitionFactors, PartitionFactorsError> {
+        let factors = self.factors;
+        let robot_count = factors.robot_count();
+        let check = |values: &[f64], is_valid: fn(f64) -> bool| {
+            if !values.is_empty() && values.len() != robot_count {
+                return Err(PartitionFactorsError::LengthMismatch);
+            }
+            match values.iter().position(|v| !v.is_finite() || !is_valid(*v)) {
+                Some(robot) => {
+                    Err(PartitionFactorsError::InvalidValue { robot })
+                }
+                None => Ok(()),
+            }
+        };
+        check(&factors.speeds, |v| v >= 0.0)?;
+        check(&factors.battery_levels, |v| (0.0..=1.0).contains(&v))?;
+        check(&factors.sensor_radii, |v| v >= 0.0)?;
+        check(&factors.weights, |v| v >= 0.0)?;
+        Ok(factors)
+    }
+}
+
+/// Assemble partitioning factors from the parameters of the robots.
+///
+/// Implement it for the parameters `P` of [`Robot<P>`] to derive the factors
+/// from the robots stored in a [`LocalMap`] when partitioning, see
+/// [`LocalMap::factors`] and [`LocalMap::partition_with_parameters`].
+/// This keeps the factors from drifting apart from the robots, unlike
+/// maintaining both separately.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, IntoFactors, LocalMap, PartitionFactors,
+///     RealWorldLocation, Robot,
+/// };
+///
+/// #[derive(Debug)]
+/// struct Parameters {
+///     speed: f64,
+///     battery_level: f64,
+/// }
+///
+/// impl IntoFactors for Parameters {
+///     fn into_factors(robots: &[&Robot<Self>]) -> PartitionFactors {
+///         PartitionFactors::builder()
+///             .speeds(robots.iter().map(|r| r.parameters().speed).collect())
+///             .battery_levels(
+///                 robots
+///                     .iter()
+///                     .map(|r| r.parameters().battery_level)
+///                     .collect(),
+///             )
+///             .build()
+///             .expect("Parameters should be valid")
+///     }
+/// }
+///
+/// let mut map = LocalMap::new_noexpand(
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     ),
+///     Robot::new(
+///         RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+///         Parameters { speed: 1.0, battery_level: 0.9 },
+///     ),
+///     vec![Robot::new(
+///         RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+///         Parameters { speed: 2.0, battery_level: 0.5 },
+///     )],
+/// )
+/// .unwrap();
+///
+/// map.other_robots_mut()[0].parameters_mut().battery_level = 0.4;
+/// let factors: PartitionFactors = map.factors();
+///
+/// assert_eq!(factors.speed(1), Some(2.0));
+/// assert_eq!(factors.battery_level(1), Some(0.4));
+/// ```
+///
+/// [`LocalMap`]: crate::LocalMap
+/// [`LocalMap::factors`]: crate::LocalMap::factors
+/// [`LocalMap::partition_with_parameters`]:
+///     crate::LocalMap::partition_with_parameters
+pub trait IntoFactors<F = PartitionFactors>: Sized {
+    /// Assemble the factors of the given robots, where robot `0` is
+    /// [`LocalMap::my_robot`](crate::LocalMap::my_robot), followed by
+    /// [`LocalMap::other_robots`](crate::LocalMap::other_robots) in order.
+    fn into_factors(robots: &[&Robot<Self>]) -> F;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PartitionFactorsError {
+    /// The factors describe different numbers of robots.
+    LengthMismatch,
+    /// A factor of the given robot is NaN, infinite or out of range.
+    InvalidValue { robot: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CellMap, LocalMap, Partition, RealWorldLocation, Robot};
+
+    #[test]
+    fn empty_factors() {
+        let factors = PartitionFactors::builder().build().unwrap();
+
+        assert_eq!(factors, PartitionFactors::default());
+        assert_eq!(factors.robot_count(), 0);
+        assert_eq!(factors.weight(0), None);
+    }
+
+    #[test]
+    fn length_mismatch() {
+        assert_eq!(
+            PartitionFactors::builder()
+                .speeds(vec![1.0, 2.0])
+                .weights(vec![1.0])
+                .build(),
+            Err(PartitionFactorsError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert_eq!(
+            PartitionFactors::builder()
+                .battery_levels(vec![0.5, 1.5])
+                .build(),
+            Err(PartitionFactorsError::InvalidValue { robot: 1 })
+        );
+        assert_eq!(
+            PartitionFactors::builder()
+                .sensor_radii(vec![f64::NAN])
+                .build(),
+            Err(PartitionFactorsError::InvalidValue { robot: 0 })
+        );
+        assert_eq!(
+            PartitionFactors::builder().speeds(vec![-1.0]).build(),
+            Err(PartitionFactorsError::InvalidValue { robot: 0 })
+        );
+    }
+
+    #[test]
+    fn factors_from_parameters() {
+        struct Speed(f64);
+        impl IntoFactors for Speed {
+            fn into_factors(robots: &[&Robot<Self>]) -> PartitionFactors {
+                PartitionFactors::builder()
+                    .speeds(robots.iter().map(|r| r.parameters().0).collect())
+                    .build()
+                    .unwrap()
+            }
+        }
+        let mut lmap = LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+                crate::AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(1.0, 1.0, 0.0), Speed(1.0)),
+            vec![Robot::new(
+                RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+                Speed(2.0),
+            )],
+        )
+        .unwrap();
+        lmap.my_robot_mut().parameters_mut().0 = 3.0;
+
+        let mut calls = 0;
+        lmap.partition_with_parameters(
+            |map, factors: Option<PartitionFactors>| {
+                calls += 1;
+                assert_eq!(factors.unwrap().speeds(), [3.0, 2.0]);
+                map
+            },
+        )
+        .unwrap();
+
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn factors_are_passed_to_algorithm() {
+        let lmap = LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+                crate::AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(1.0, 1.0, 0.0), ()),
+            vec![],
+        )
+        .unwrap();
+        fn algorithm(
+            map: LocalMap<CellMap, ()>,
+            factors: Option<PartitionFactors>,
+        ) -> LocalMap<CellMap, ()> {
+            let factors = factors.unwrap();
+            assert_eq!(factors.speed(0), Some(3.0));
+            map
+        }
+
+        let factors = PartitionFactors::builder()
+            .speeds(vec![3.0])
+            .build()
+            .unwrap();
+        lmap.partition(algorithm, Some(factors)).unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::RealWorldLocation;
+
+/// Per-robot result of a partitioning run, alongside the partitioned map.
+///
+/// The map itself only encodes the current robot's region, as
+/// [`MapState::Assigned`](crate::MapState::Assigned) cells. This struct
+/// additionally records how many cells every robot received, which is needed
+/// to evaluate and log partitioning algorithms. Robots are indexed like in
+/// [`PartitionFactors`](crate::PartitionFactors), i.e. robot `0` is the
+/// current robot, followed by the other robots in order.
+///
+/// Returned by [`Partition`](crate::Partition)`::partition_with_outcome`,
+/// see [`OutcomeAlgorithm`](crate::OutcomeAlgorithm).
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{PartitionOutcome, RealWorldLocation};
+///
+/// let seeds = vec![
+///     RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+///     RealWorldLocation::from_xyz(3.0, 1.0, 0.0),
+/// ];
+/// // Cells of half by half a unit.
+/// let outcome = PartitionOutcome::new(seeds, vec![6, 2], 0.25);
+///
+/// assert_eq!(outcome.robot_count(), 2);
+/// assert_eq!(outcome.cells(1), 2);
+/// assert_eq!(outcome.areas(), [1.5, 0.5]);
+/// assert_eq!(outcome.iterations(), 1);
+/// assert_eq!(outcome.runtime(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionOutcome {
+    seeds: Vec<RealWorldLocation>,
+    cells: Vec<usize>,
+    cell_area: f64,
+    iterations: usize,
+    runtime: Option<Duration>,
+}
+
+impl PartitionOutcome {
+    /// Create an outcome from the seed location of each robot and the number
+    /// of cells each one received, with cells covering `cell_area` square
+    /// units each.
+    ///
+    /// The number of iterations defaults to `1`, the runtime to [`None`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `seeds` and `cells` differ in length.
+    pub fn new(
+        seeds: Vec<RealWorldLocation>,
+        cells: Vec<usize>,
+        cell_area: f64,
+    ) -> Self {
+        assert_eq!(
+            seeds.len(),
+            cells.len(),
+            "Every robot needs a seed and a cell count"
+        );
+        Self {
+            seeds,
+            cells,
+            cell_area,
+            iterations: 1,
+            runtime: None,
+        }
+    }
+
+    /// Set the number of iterations an iterative algorithm needed.
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Set the time the algorithm took.
+    pub fn with_runtime(mut self, runtime: Duration) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Number of robots taking part in the partitioning.
+    pub fn robot_count(&self) -> usize {
+        self.seeds.len()
+    }
+    /// Number of cells the given robot received.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such robot.
+    pub fn cells(&self, robot: usize) -> usize {
+        self.cells[robot]
+    }
+    /// Number of cells each robot received.
+    pub fn cell_counts(&self) -> &[usize] {
+        &self.cells
+    }
+    /// Area each robot received, in square real-world units.
+    pub fn areas(&self) -> Vec<f64> {
+        self.cells
+            .iter()
+            .map(|cells| *cells as f64 * self.cell_area)
+            .collect()
+    }
+    /// The locations the regions were grown from, usually the robots'
+    /// positions.
+    pub fn seeds(&self) -> &[RealWorldLocation] {
+        &self.seeds
+    }
+    /// Number of iterations the algorithm needed, `1` for algorithms which
+    /// are not iterative.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+    /// Time the algorithm took, if measured.
+    ///
+    /// Set by [`Partition`](crate::Partition)`::partition_with_outcome` when
+    /// the `std` feature is enabled.
+    pub fn runtime(&self) -> Option<Duration> {
+        self.runtime
+    }
+}
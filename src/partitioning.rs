@@ -0,0 +1,1058 @@
+//! Built-in partitioning algorithms.
+//!
+//! The algorithms are meant to be passed to
+//! [`Partition::partition`](crate::Partition::partition) on a [`LocalMap`]
+//! holding a [`CellMap`]. They take [`PartitionFactors`] into account, and
+//! mark the cells which end up in the current robot's region as
+//! [`MapState::Assigned`].
+//!
+//! Only cells which still need to be covered take part in the partitioning,
+//! i.e. [`MapState::Unexplored`], [`MapState::Frontier`] and previously
+//! [`MapState::Assigned`] cells. Previously assigned cells which now belong to
+//! another robot are reset to [`MapState::Unexplored`]. Frontier cells keep
+//! their state so as not to lose the frontiers, but are counted towards the
+//! region of the robot owning them.
+//!
+//! The algorithms are deterministic: ties are broken by robot index, so every
+//! robot computes the same partition from the same map and factors, and runs
+//! can be repeated exactly.
+//!
+//! Cells are only assigned to robots which can reach them, see
+//! [`CellMap::reachable_from`]. Cells no robot can reach, e.g. behind a closed
+//! wall, are left [`MapState::Unexplored`].
+//!
+//! Every algorithm has a `_with_outcome` variant, which also reports how many
+//! cells each robot received as a [`PartitionOutcome`], see
+//! [`Partition::partition_with_outcome`](crate::Partition::partition_with_outcome).
+//! The `_with_control` variants additionally report their progress and can be
+//! cancelled, see
+//! [`Partition::partition_with_control`](crate::Partition::partition_with_control).
+
+use alloc::vec::Vec;
+use ndarray::Array2;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{
+    CellMap, Coords, LocalMap, MapState, PartitionCancelled, PartitionControl,
+    PartitionFactors, PartitionOutcome, PartitionProgress, RealWorldLocation,
+};
+
+/// Result of the `_with_control` variants of the algorithms.
+type Controlled<P> = Result<
+    (LocalMap<CellMap, P>, PartitionOutcome),
+    PartitionCancelled<LocalMap<CellMap, P>>,
+>;
+
+/// Partition the map into multiplicatively weighted Voronoi regions.
+///
+/// Each cell is assigned to the robot minimizing its distance to the cell's
+/// center divided by the robot's weight. The weight of a robot is the square
+/// root of its [`PartitionFactors::capability`], such that, away from the map
+/// borders, the area of a robot's region grows proportionally to its
+/// capability. Without factors, all robots are weighted equally, resulting in
+/// a regular Voronoi partition.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     partitioning::weighted_voronoi, AxisResolution, CellMap, LocalMap,
+///     MapState, MaskMapState, Partition, PartitionFactors, RealWorldLocation,
+///     Robot,
+/// };
+///
+/// let map = LocalMap::new_noexpand(
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     ),
+///     Robot::new(RealWorldLocation::from_xyz(5.0, 5.0, 0.0), ()),
+///     vec![Robot::new(RealWorldLocation::from_xyz(15.0, 5.0, 0.0), ())],
+/// )
+/// .unwrap();
+/// // The current robot is twice as fast as the other one.
+/// let factors = PartitionFactors::builder()
+///     .speeds(vec![2.0, 1.0])
+///     .build()
+///     .unwrap();
+///
+/// let map = map.partition(weighted_voronoi, Some(factors)).unwrap();
+///
+/// let mine = map.map().get_map_state(MapState::Assigned).len();
+/// assert!(mine > 200 / 2);
+/// ```
+pub fn weighted_voronoi<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+) -> LocalMap<CellMap, P> {
+    weighted_voronoi_with_outcome(map, factors).0
+}
+
+/// Same as [`weighted_voronoi`], but also reports the number of cells each
+/// robot received, see [`crate::Partition::partition_with_outcome`].
+pub fn weighted_voronoi_with_outcome<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+) -> (LocalMap<CellMap, P>, PartitionOutcome) {
+    uncontrolled(weighted_voronoi_with_control(
+        map,
+        factors,
+        &PartitionControl::new(),
+    ))
+}
+
+/// Same as [`weighted_voronoi_with_outcome`], but reports its progress to
+/// and can be cancelled through `control`, see
+/// [`crate::Partition::partition_with_control`].
+pub fn weighted_voronoi_with_control<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    control: &PartitionControl,
+) -> Controlled<P> {
+    voronoi(map, factors, 0.0, control)
+}
+
+/// Re-partition the map like [`weighted_voronoi`], while keeping the
+/// previous assignment where the robots have barely moved.
+///
+/// Cells which are still [`MapState::Assigned`] from the previous
+/// partitioning remain with the current robot, unless another robot's
+/// weighted distance to them is shorter by more than `stability`, given in
+/// real-world units. This avoids robots thrashing between goals when small
+/// movements would otherwise shift the boundaries back and forth every
+/// cycle. A `stability` of `0.0` behaves like [`weighted_voronoi`].
+///
+/// Since each robot only knows its own previous region, the regions of
+/// different robots may overlap by up to `stability` along their borders.
+///
+/// Like the sweep angle of [`sweep_line`], `stability` is passed by wrapping
+/// this function in a closure, which may capture it from its surroundings.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     partitioning::{repartition, weighted_voronoi},
+///     AxisResolution, CellMap, LocalMap, Location, MapState, MaskMapState,
+///     Partition, RealWorldLocation, Robot,
+/// };
+///
+/// let map = LocalMap::new_noexpand(
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     ),
+///     Robot::new(RealWorldLocation::from_xyz(5.5, 5.5, 0.0), ()),
+///     vec![Robot::new(RealWorldLocation::from_xyz(14.5, 5.5, 0.0), ())],
+/// )
+/// .unwrap();
+/// let mut map = map.partition(weighted_voronoi, None).unwrap();
+/// let before = map.map().get_map_state(MapState::Assigned).len();
+///
+/// // My robot moves away from the other one, which would shift the border.
+/// map.move_my_robot(RealWorldLocation::from_xyz(3.5, 5.5, 0.0))
+///     .unwrap();
+/// let map = map
+///     .partition(|map, factors| repartition(map, factors, 2.0), None)
+///     .unwrap();
+///
+/// // My robot keeps its region, except for the cell it now occupies.
+/// let after = map.map().get_map_state(MapState::Assigned).len();
+/// assert_eq!(after, before - 1);
+/// ```
+pub fn repartition<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    stability: f64,
+) -> LocalMap<CellMap, P> {
+    repartition_with_outcome(map, factors, stability).0
+}
+
+/// Same as [`repartition`], but also reports the number of cells each
+/// robot received, see [`crate::Partition::partition_with_outcome`].
+pub fn repartition_with_outcome<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    stability: f64,
+) -> (LocalMap<CellMap, P>, PartitionOutcome) {
+    uncontrolled(repartition_with_control(
+        map,
+        factors,
+        stability,
+        &PartitionControl::new(),
+    ))
+}
+
+/// Same as [`repartition_with_outcome`], but reports its progress to and can
+/// be cancelled through `control`, see
+/// [`crate::Partition::partition_with_control`].
+pub fn repartition_with_control<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    stability: f64,
+    control: &PartitionControl,
+) -> Controlled<P> {
+    voronoi(map, factors, stability, control)
+}
+
+/// Weighted Voronoi partitioning, favoring the current robot by `stability`
+/// for cells it was previously assigned.
+fn voronoi<P>(
+    mut map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    stability: f64,
+    control: &PartitionControl,
+) -> Controlled<P> {
+    let factors = factors.unwrap_or_default();
+    let seeds = positions(&map);
+    let weights: Vec<f64> = (0..seeds.len())
+        .map(|i| factors.capability(i).sqrt())
+        .collect();
+    let reachable = reachability(map.map(), seeds.iter());
+
+    let outcome =
+        assign(map.map_mut(), seeds.clone(), control, |cells, index| {
+            let center = cells.index_center::<f64>(index);
+            seeds
+                .iter()
+                .zip(&weights)
+                .enumerate()
+                .filter(|(i, _)| can_reach(&reachable[*i], index))
+                .map(|(i, (location, weight))| {
+                    let distance = ((center.x - location.x).powi(2)
+                        + (center.y - location.y).powi(2))
+                    .sqrt();
+                    let cost = distance / weight;
+                    if i == 0 && cells.cells()[index] == MapState::Assigned {
+                        (i, cost - stability)
+                    } else {
+                        (i, cost)
+                    }
+                })
+                .filter(|(_, cost)| !cost.is_nan())
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        });
+
+    finish(map, outcome)
+}
+
+/// Partition the map by travel distance rather than straight-line distance.
+///
+/// Works like [`weighted_voronoi`], except that the distance from a robot to
+/// a cell is measured around obstacles, see [`CellMap::wavefront`]. This
+/// keeps regions from extending through walls, which matters indoors, where
+/// a room behind a wall may be far away for a robot right next to it. Robots
+/// located outside the map do not receive any cells.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     partitioning::geodesic_voronoi, AxisResolution, CellMap, LocalMap,
+///     Location, MapState, Partition, RealWorldLocation, Robot,
+/// };
+///
+/// let mut cells = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+/// // A wall with a door at the top, right next to my robot.
+/// cells.set_rect(
+///     &RealWorldLocation::from_xyz(6.0, 0.0, 0.0),
+///     &RealWorldLocation::from_xyz(7.0, 9.0, 0.0),
+///     MapState::Obstacle,
+/// );
+/// let map = LocalMap::new_noexpand(
+///     cells,
+///     Robot::new(RealWorldLocation::from_xyz(5.5, 0.5, 0.0), ()),
+///     vec![Robot::new(RealWorldLocation::from_xyz(15.5, 0.5, 0.0), ())],
+/// )
+/// .unwrap();
+///
+/// let map = map.partition(geodesic_voronoi, None).unwrap();
+///
+/// // Close in a straight line, but far for my robot.
+/// let behind_wall = RealWorldLocation::from_xyz(7.5, 0.5, 0.0);
+/// assert_eq!(map.map().get_location(&behind_wall), Ok(MapState::Unexplored));
+/// ```
+pub fn geodesic_voronoi<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+) -> LocalMap<CellMap, P> {
+    geodesic_voronoi_with_outcome(map, factors).0
+}
+
+/// Same as [`geodesic_voronoi`], but also reports the number of cells each
+/// robot received, see [`crate::Partition::partition_with_outcome`].
+pub fn geodesic_voronoi_with_outcome<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+) -> (LocalMap<CellMap, P>, PartitionOutcome) {
+    uncontrolled(geodesic_voronoi_with_control(
+        map,
+        factors,
+        &PartitionControl::new(),
+    ))
+}
+
+/// Same as [`geodesic_voronoi_with_outcome`], but reports its progress to
+/// and can be cancelled through `control`, see
+/// [`crate::Partition::partition_with_control`].
+pub fn geodesic_voronoi_with_control<P>(
+    mut map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    control: &PartitionControl,
+) -> Controlled<P> {
+    let factors = factors.unwrap_or_default();
+    let seeds = positions(&map);
+    // Robots outside the map have no distances.
+    let mut robots: Vec<Option<(Array2<f64>, f64)>> = Vec::new();
+    for (i, location) in seeds.iter().enumerate() {
+        // Computing the distances is the expensive part, check in between.
+        if control.is_cancelled() {
+            return Err(PartitionCancelled::new(map));
+        }
+        let distances =
+            map.map().wavefront(core::slice::from_ref(location)).ok();
+        robots.push(
+            distances
+                .map(|distances| (distances, factors.capability(i).sqrt())),
+        );
+    }
+
+    let outcome = assign(map.map_mut(), seeds, control, |_, index| {
+        robots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, robot)| {
+                let (distances, weight) = robot.as_ref()?;
+                Some((i, distances[index] / weight))
+            })
+            .filter(|(_, cost)| cost.is_finite())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    });
+
+    finish(map, outcome)
+}
+
+/// Split the map into one stripe per robot, as a baseline for comparisons.
+///
+/// The partitionable cells are ordered column by column along the longer
+/// side of the map, and cut into consecutive stripes holding a number of
+/// cells proportional to the robots' [`PartitionFactors::capability`], i.e.
+/// the same number without factors. The robots are ranked by their location
+/// along the same side, and each one receives the stripe of its rank. This
+/// way every robot computes the same partition without having to agree on
+/// identifiers. Obstacles are ignored, except that cells a robot cannot
+/// reach are left unassigned.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     partitioning::grid_split, AxisResolution, CellMap, LocalMap, Location,
+///     MapState, MaskMapState, Partition, RealWorldLocation, Robot,
+/// };
+///
+/// let map = LocalMap::new_noexpand(
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(30.0, 10.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     ),
+///     Robot::new(RealWorldLocation::from_xyz(12.5, 2.5, 0.0), ()),
+///     vec![
+///         Robot::new(RealWorldLocation::from_xyz(0.5, 9.5, 0.0), ()),
+///         Robot::new(RealWorldLocation::from_xyz(29.5, 0.5, 0.0), ()),
+///     ],
+/// )
+/// .unwrap();
+///
+/// let map = map.partition(grid_split, None).unwrap();
+///
+/// // My robot is second from the left, and gets the middle third.
+/// assert_eq!(map.map().get_map_state(MapState::Assigned).len(), 99);
+/// let left = RealWorldLocation::from_xyz(9.5, 5.5, 0.0);
+/// let middle = RealWorldLocation::from_xyz(10.5, 5.5, 0.0);
+/// assert_eq!(map.map().get_location(&left), Ok(MapState::Unexplored));
+/// assert_eq!(map.map().get_location(&middle), Ok(MapState::Assigned));
+/// ```
+pub fn grid_split<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+) -> LocalMap<CellMap, P> {
+    grid_split_with_outcome(map, factors).0
+}
+
+/// Same as [`grid_split`], but also reports the number of cells each
+/// robot received, see [`crate::Partition::partition_with_outcome`].
+pub fn grid_split_with_outcome<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+) -> (LocalMap<CellMap, P>, PartitionOutcome) {
+    uncontrolled(grid_split_with_control(
+        map,
+        factors,
+        &PartitionControl::new(),
+    ))
+}
+
+/// Same as [`grid_split_with_outcome`], but reports its progress to and can
+/// be cancelled through `control`, see
+/// [`crate::Partition::partition_with_control`].
+pub fn grid_split_with_control<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    control: &PartitionControl,
+) -> Controlled<P> {
+    let cells = map.map();
+    let resolution = cells.resolution();
+    let along_x = cells.width() as f64 / resolution.x
+        >= cells.height() as f64 / resolution.y;
+    split_along(map, factors, control, |location| {
+        if along_x {
+            (location.x, location.y)
+        } else {
+            (location.y, location.x)
+        }
+    })
+}
+
+/// Divide the map into bands perpendicular to a sweep direction.
+///
+/// Works like [`grid_split`], except that the cells are ordered along the
+/// direction given by `angle`, in radians counter-clockwise from the x-axis.
+/// This yields contiguous bands, e.g. following the rows of a field, whose
+/// sizes are proportional to the robots' [`PartitionFactors::capability`].
+/// The robots are ranked along the same direction.
+///
+/// The angle is passed by wrapping this function in a closure, see the
+/// example.
+///
+/// # Example
+///
+/// ```
+/// use core::f64::consts::FRAC_PI_4;
+/// use local_robot_map::{
+///     partitioning::sweep_line, AxisResolution, CellMap, LocalMap, Location,
+///     MapState, Partition, RealWorldLocation, Robot,
+/// };
+///
+/// let map = LocalMap::new_noexpand(
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     ),
+///     Robot::new(RealWorldLocation::from_xyz(0.5, 0.5, 0.0), ()),
+///     vec![Robot::new(RealWorldLocation::from_xyz(9.5, 9.5, 0.0), ())],
+/// )
+/// .unwrap();
+///
+/// // Bands along the diagonal from the bottom left to the top right.
+/// let angle = FRAC_PI_4;
+/// let map = map
+///     .partition(|map, factors| sweep_line(map, factors, angle), None)
+///     .unwrap();
+///
+/// let below = RealWorldLocation::from_xyz(0.5, 7.5, 0.0);
+/// let above = RealWorldLocation::from_xyz(9.5, 1.5, 0.0);
+/// assert_eq!(map.map().get_location(&below), Ok(MapState::Assigned));
+/// assert_eq!(map.map().get_location(&above), Ok(MapState::Unexplored));
+/// ```
+pub fn sweep_line<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    angle: f64,
+) -> LocalMap<CellMap, P> {
+    sweep_line_with_outcome(map, factors, angle).0
+}
+
+/// Same as [`sweep_line`], but also reports the number of cells each
+/// robot received, see [`crate::Partition::partition_with_outcome`].
+pub fn sweep_line_with_outcome<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    angle: f64,
+) -> (LocalMap<CellMap, P>, PartitionOutcome) {
+    uncontrolled(sweep_line_with_control(
+        map,
+        factors,
+        angle,
+        &PartitionControl::new(),
+    ))
+}
+
+/// Same as [`sweep_line_with_outcome`], but reports its progress to and can
+/// be cancelled through `control`, see
+/// [`crate::Partition::partition_with_control`].
+pub fn sweep_line_with_control<P>(
+    map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    angle: f64,
+    control: &PartitionControl,
+) -> Controlled<P> {
+    let (sin, cos) = angle.sin_cos();
+    split_along(map, factors, control, |location| {
+        (
+            location.x * cos + location.y * sin,
+            location.y * cos - location.x * sin,
+        )
+    })
+}
+
+/// Order the partitionable cells and the robots by `key`, and assign the
+/// cells to the robots in consecutive chunks proportional to their
+/// capability. Cells a robot cannot reach are left unassigned.
+fn split_along<P, K>(
+    mut map: LocalMap<CellMap, P>,
+    factors: Option<PartitionFactors>,
+    control: &PartitionControl,
+    key: K,
+) -> Controlled<P>
+where
+    K: Fn(&Coords) -> (f64, f64),
+{
+    let factors = factors.unwrap_or_default();
+    let cmp = |a: (f64, f64), b: (f64, f64)| {
+        a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+    };
+    let seeds = positions(&map);
+
+    // Robot indices ordered by rank.
+    let mut robots: Vec<(usize, (f64, f64))> = seeds
+        .iter()
+        .map(|location| key(location))
+        .enumerate()
+        .collect();
+    robots.sort_by(|(i, a), (j, b)| cmp(*a, *b).then(i.cmp(j)));
+    let shares: Vec<f64> =
+        robots.iter().map(|(i, _)| factors.capability(*i)).collect();
+
+    let cells = map.map();
+    let mut order: Vec<([usize; 2], (f64, f64))> = cells
+        .cells()
+        .indexed_iter()
+        .filter(|(_, value)| is_partitionable(**value))
+        .map(|((row, col), _)| {
+            let index = [row, col];
+            (index, key(&cells.index_center(index)))
+        })
+        .collect();
+    order.sort_by(|(_, a), (_, b)| cmp(*a, *b));
+    let total: f64 = shares.iter().sum();
+    let bound = |rank: usize| {
+        let share = if total > 0.0 {
+            shares[..rank].iter().sum::<f64>() / total
+        } else {
+            rank as f64 / shares.len() as f64
+        };
+        (share * order.len() as f64).round() as usize
+    };
+    let reachable = reachability(cells, seeds.iter());
+    let mut owners = Array2::from_elem(cells.cells().dim(), None);
+    for (rank, (robot, _)) in robots.iter().enumerate() {
+        for (index, _) in &order[bound(rank)..bound(rank + 1)] {
+            if can_reach(&reachable[*robot], *index) {
+                owners[*index] = Some(*robot);
+            }
+        }
+    }
+
+    let outcome =
+        assign(map.map_mut(), seeds, control, |_, index| owners[index]);
+
+    finish(map, outcome)
+}
+
+/// Mark the [`MapState::Unexplored`] cells owned by robot `0`, i.e. the
+/// current robot, as [`MapState::Assigned`] and the [`MapState::Assigned`]
+/// cells owned by other robots as [`MapState::Unexplored`]. All other cells,
+/// including [`MapState::Frontier`] cells, keep their state.
+///
+/// Returns the number of cells owned by each robot. The owners are determined
+/// row by row, reporting the progress to `control`. If `control` is cancelled
+/// in the meantime, [`None`] is returned and the cells are left unchanged.
+fn assign<F>(
+    cells: &mut CellMap,
+    seeds: Vec<RealWorldLocation>,
+    control: &PartitionControl,
+    owner: F,
+) -> Option<PartitionOutcome>
+where
+    F: Fn(&CellMap, [usize; 2]) -> Option<usize>,
+{
+    let (rows, cols) = cells.cells().dim();
+    let mut owners: Vec<([usize; 2], Option<usize>)> = Vec::new();
+    for row in 0..rows {
+        if control.is_cancelled() {
+            return None;
+        }
+        for col in 0..cols {
+            let index = [row, col];
+            if is_partitionable(cells.cells()[index]) {
+                owners.push((index, owner(cells, index)));
+            }
+        }
+        control.report(PartitionProgress::new(1, row + 1, rows));
+    }
+
+    let mut counts = alloc::vec![0; seeds.len()];
+    for (index, owner) in owners {
+        if let Some(count) = owner.and_then(|i| counts.get_mut(i)) {
+            *count += 1;
+        }
+        let state = match (cells.cells()[index], owner == Some(0)) {
+            (MapState::Unexplored, true) => MapState::Assigned,
+            (MapState::Assigned, false) => MapState::Unexplored,
+            _ => continue,
+        };
+        cells.set_cell(index, state);
+    }
+    let resolution = cells.resolution();
+    Some(PartitionOutcome::new(
+        seeds,
+        counts,
+        1.0 / (resolution.x * resolution.y),
+    ))
+}
+
+/// Return the partitioned map with its `outcome`, or the map as cancelled if
+/// [`assign`] did not produce an outcome.
+fn finish<P>(
+    map: LocalMap<CellMap, P>,
+    outcome: Option<PartitionOutcome>,
+) -> Controlled<P> {
+    match outcome {
+        Some(outcome) => Ok((map, outcome)),
+        None => Err(PartitionCancelled::new(map)),
+    }
+}
+
+/// Unwrap the result of an algorithm run with a control which is never
+/// cancelled.
+fn uncontrolled<T>(result: Result<T, PartitionCancelled<impl Sized>>) -> T {
+    match result {
+        Ok(result) => result,
+        Err(_) => unreachable!("The control is never cancelled"),
+    }
+}
+
+/// The locations of all robots, starting with the current one.
+fn positions<P>(map: &LocalMap<CellMap, P>) -> Vec<RealWorldLocation> {
+    core::iter::once(*map.my_position())
+        .chain(map.other_positions())
+        .collect()
+}
+
+/// The cells each robot can reach, or [`None`] for robots outside the map
+/// which are not restricted.
+fn reachability<'a>(
+    map: &CellMap,
+    robots: impl Iterator<Item = &'a RealWorldLocation>,
+) -> Vec<Option<Array2<bool>>> {
+    robots
+        .map(|location| map.reachable_from(location).ok())
+        .collect()
+}
+
+fn can_reach(reachable: &Option<Array2<bool>>, index: [usize; 2]) -> bool {
+    reachable
+        .as_ref()
+        .map_or(true, |reachable| reachable[index])
+}
+
+/// Whether a cell still needs to be covered and thus takes part in the
+/// partitioning.
+fn is_partitionable(value: MapState) -> bool {
+    matches!(
+        value,
+        MapState::Unexplored | MapState::Frontier | MapState::Assigned
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Algorithm, AxisResolution, Location, MaskMapState, Partition, Robot,
+    };
+
+    fn make_local_map(others: Vec<(f64, f64)>) -> LocalMap<CellMap, ()> {
+        LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(40.0, 40.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(10.5, 20.5, 0.0), ()),
+            others
+                .into_iter()
+                .map(|(x, y)| {
+                    Robot::new(RealWorldLocation::from_xyz(x, y, 0.0), ())
+                })
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn assigned(map: &LocalMap<CellMap, ()>) -> usize {
+        map.map().get_map_state(MapState::Assigned).len()
+    }
+
+    #[test]
+    fn equal_weights_split_evenly() {
+        let map = make_local_map(vec![(29.5, 20.5)]);
+
+        let map = map.partition(weighted_voronoi, None).unwrap();
+
+        // Half of the map minus the current robot's own cell.
+        assert_eq!(assigned(&map), 40 * 40 / 2 - 1);
+    }
+
+    #[test]
+    fn larger_capability_larger_region() {
+        let map = make_local_map(vec![(30.5, 20.5)]);
+        let equal = assigned(&map.partition(weighted_voronoi, None).unwrap());
+
+        for (speeds, larger) in [([2.0, 1.0], true), ([1.0, 3.0], false)] {
+            let map = make_local_map(vec![(30.5, 20.5)]);
+            let factors = PartitionFactors::builder()
+                .speeds(speeds.to_vec())
+                .build()
+                .unwrap();
+
+            let weighted = assigned(
+                &map.partition(weighted_voronoi, Some(factors)).unwrap(),
+            );
+
+            assert_eq!(weighted > equal, larger);
+        }
+    }
+
+    #[test]
+    fn obstacles_and_explored_cells_are_kept() {
+        let mut map = make_local_map(vec![]);
+        let wall = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        let explored = RealWorldLocation::from_xyz(1.5, 0.5, 0.0);
+        map.map_mut()
+            .set_location(&wall, MapState::Obstacle)
+            .unwrap();
+        map.map_mut()
+            .set_location(&explored, MapState::Explored)
+            .unwrap();
+
+        let map = map.partition(weighted_voronoi, None).unwrap();
+
+        assert_eq!(map.map().get_location(&wall), Ok(MapState::Obstacle));
+        assert_eq!(map.map().get_location(&explored), Ok(MapState::Explored));
+        assert_eq!(assigned(&map), 40 * 40 - 3);
+    }
+
+    #[test]
+    fn frontier_cells_are_kept() {
+        let mut map = make_local_map(vec![(29.5, 20.5)]);
+        let mine = RealWorldLocation::from_xyz(5.5, 20.5, 0.0);
+        let theirs = RealWorldLocation::from_xyz(35.5, 20.5, 0.0);
+        for location in [&mine, &theirs] {
+            map.map_mut()
+                .set_location(location, MapState::Frontier)
+                .unwrap();
+        }
+
+        let (map, outcome) = map
+            .partition_with_outcome(weighted_voronoi_with_outcome, None)
+            .unwrap();
+
+        assert_eq!(map.map().get_location(&mine), Ok(MapState::Frontier));
+        assert_eq!(map.map().get_location(&theirs), Ok(MapState::Frontier));
+        // The frontier cell counts towards the region without being marked.
+        assert_eq!(assigned(&map), 40 * 40 / 2 - 2);
+        assert_eq!(outcome.cell_counts(), [40 * 40 / 2 - 1; 2]);
+    }
+
+    #[test]
+    fn unreachable_cells_are_not_assigned() {
+        let mut map = make_local_map(vec![(30.5, 20.5)]);
+        // Wall in the other robot's region, closing off the top right corner.
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(35.0, 35.0, 0.0),
+            &RealWorldLocation::from_xyz(36.0, 40.0, 0.0),
+            MapState::Obstacle,
+        );
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(35.0, 35.0, 0.0),
+            &RealWorldLocation::from_xyz(40.0, 36.0, 0.0),
+            MapState::Obstacle,
+        );
+        // Wall around my robot, leaving it a 3x3 room.
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(8.0, 18.0, 0.0),
+            &RealWorldLocation::from_xyz(13.0, 23.0, 0.0),
+            MapState::Obstacle,
+        );
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(9.0, 19.0, 0.0),
+            &RealWorldLocation::from_xyz(12.0, 22.0, 0.0),
+            MapState::Unexplored,
+        );
+        map.map_mut()
+            .set_location(
+                &RealWorldLocation::from_xyz(10.5, 20.5, 0.0),
+                MapState::MyRobot,
+            )
+            .unwrap();
+
+        let map = map.partition(weighted_voronoi, None).unwrap();
+
+        assert_eq!(assigned(&map), 3 * 3 - 1);
+        let corner = RealWorldLocation::from_xyz(39.5, 39.5, 0.0);
+        assert_eq!(map.map().get_location(&corner), Ok(MapState::Unexplored));
+    }
+
+    #[test]
+    fn geodesic_regions_do_not_cross_walls() {
+        // A wall between the robots with a door at the top, far from the
+        // other robot.
+        let make_walled_map = || {
+            let mut map = make_local_map(vec![(24.5, 2.5)]);
+            map.map_mut().set_rect(
+                &RealWorldLocation::from_xyz(20.0, 0.0, 0.0),
+                &RealWorldLocation::from_xyz(21.0, 39.0, 0.0),
+                MapState::Obstacle,
+            );
+            map
+        };
+        let straight =
+            make_walled_map().partition(weighted_voronoi, None).unwrap();
+
+        let map = make_walled_map().partition(geodesic_voronoi, None).unwrap();
+
+        let left = map.map().cells().slice(ndarray::s![.., ..20]);
+        assert!(left.iter().all(|value| matches!(
+            value,
+            MapState::Assigned | MapState::MyRobot
+        )));
+        let behind_wall = RealWorldLocation::from_xyz(18.5, 2.5, 0.0);
+        assert_eq!(
+            straight.map().get_location(&behind_wall),
+            Ok(MapState::Unexplored)
+        );
+        let near_other = RealWorldLocation::from_xyz(21.5, 0.5, 0.0);
+        assert_eq!(
+            map.map().get_location(&near_other),
+            Ok(MapState::Unexplored)
+        );
+    }
+
+    #[test]
+    fn geodesic_with_outside_robot() {
+        let map = LocalMap::new_noexpand_nooutofmap(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(-5.0, 5.0, 0.0), ()),
+            vec![Robot::new(RealWorldLocation::from_xyz(5.5, 5.5, 0.0), ())],
+        )
+        .unwrap();
+
+        let map = map.partition(geodesic_voronoi, None).unwrap();
+
+        assert_eq!(assigned(&map), 0);
+    }
+
+    #[test]
+    fn grid_split_follows_rank_and_capability() {
+        let map = make_local_map(vec![(5.5, 20.5)]);
+        let factors = PartitionFactors::builder()
+            .speeds(vec![3.0, 1.0])
+            .build()
+            .unwrap();
+
+        let map = map.partition(grid_split, Some(factors)).unwrap();
+
+        // My robot is ranked second and receives three quarters of the cells.
+        assert_eq!(assigned(&map), 1198);
+        for (x, state) in
+            [(9.5, MapState::Unexplored), (11.5, MapState::Assigned)]
+        {
+            let location = RealWorldLocation::from_xyz(x, 0.5, 0.0);
+            assert_eq!(map.map().get_location(&location), Ok(state));
+        }
+    }
+
+    #[test]
+    fn sweep_line_bands() {
+        let map = make_local_map(vec![(30.5, 30.5)]);
+
+        let map = map
+            .partition(
+                |map, factors| {
+                    sweep_line(map, factors, core::f64::consts::FRAC_PI_2)
+                },
+                None,
+            )
+            .unwrap();
+
+        // Horizontal bands, the lower one for my robot. Row 19 lacks the
+        // cell which went to the other robot instead of my robot's own cell.
+        assert_eq!(assigned(&map), 40 * 40 / 2 - 1);
+        let rows = map.map().cells().rows().into_iter();
+        for (row, values) in rows.enumerate().filter(|(row, _)| *row != 19) {
+            let expected = if row < 19 {
+                MapState::Assigned
+            } else {
+                MapState::Unexplored
+            };
+            assert!(values.iter().all(|value| matches!(
+                *value,
+                MapState::MyRobot | MapState::OtherRobot
+            ) || *value == expected));
+        }
+    }
+
+    #[test]
+    fn repartition_keeps_region_within_stability() {
+        type Map = LocalMap<CellMap, ()>;
+        let partitioned = |algorithm: Algorithm<Map, _>, moved_to: f64| {
+            let map = make_local_map(vec![(29.5, 20.5)]);
+            let mut map = map.partition(weighted_voronoi, None).unwrap();
+            map.move_my_robot(RealWorldLocation::from_xyz(moved_to, 20.5, 0.0))
+                .unwrap();
+            assigned(&map.partition(algorithm, None).unwrap())
+        };
+        let stable: Algorithm<Map, _> =
+            |map, factors| repartition(map, factors, 2.5);
+
+        // Moving 2 units to the left moves the border by 1 unit, i.e. one
+        // column of 40 cells.
+        let half = 40 * 40 / 2 - 2;
+        assert_eq!(
+            partitioned(|map, f| repartition(map, f, 0.0), 8.5),
+            half - 40
+        );
+        assert_eq!(partitioned(stable, 8.5), half);
+        // Moving far away releases the cells anyway.
+        assert!(partitioned(stable, 0.5) < half - 40);
+    }
+
+    #[test]
+    fn previous_assignment_is_released() {
+        let mut map = make_local_map(vec![(30.5, 20.5)]);
+        let far = RealWorldLocation::from_xyz(39.5, 39.5, 0.0);
+        map.map_mut()
+            .set_location(&far, MapState::Assigned)
+            .unwrap();
+
+        let map = map.partition(weighted_voronoi, None).unwrap();
+
+        assert_eq!(map.map().get_location(&far), Ok(MapState::Unexplored));
+    }
+
+    #[test]
+    fn outcome_counts_cells_of_every_robot() {
+        let map = make_local_map(vec![(29.5, 20.5)]);
+
+        let (map, outcome) = map
+            .partition_with_outcome(weighted_voronoi_with_outcome, None)
+            .unwrap();
+
+        let half = 40 * 40 / 2 - 1;
+        assert_eq!(outcome.cell_counts(), [half, half]);
+        assert_eq!(outcome.cells(0), assigned(&map));
+        assert_eq!(outcome.areas(), [half as f64, half as f64]);
+        assert_eq!(outcome.seeds()[0], *map.my_position());
+    }
+
+    #[test]
+    fn outcome_skips_robots_outside_the_map() {
+        let map = LocalMap::new_noexpand_nooutofmap(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(-5.0, 5.0, 0.0), ()),
+            vec![Robot::new(RealWorldLocation::from_xyz(5.5, 5.5, 0.0), ())],
+        )
+        .unwrap();
+
+        let (_, outcome) = map
+            .partition_with_outcome(geodesic_voronoi_with_outcome, None)
+            .unwrap();
+
+        assert_eq!(outcome.cell_counts(), [0, 10 * 10 - 1]);
+    }
+
+    #[test]
+    fn split_outcome_sums_to_partitionable_cells() {
+        let map = make_local_map(vec![(5.5, 20.5)]);
+        let factors = PartitionFactors::builder()
+            .speeds(vec![3.0, 1.0])
+            .build()
+            .unwrap();
+
+        let (map, outcome) = map
+            .partition_with_outcome(grid_split_with_outcome, Some(factors))
+            .unwrap();
+
+        assert_eq!(outcome.cells(0), assigned(&map));
+        assert_eq!(outcome.cell_counts().iter().sum::<usize>(), 40 * 40 - 2);
+    }
+
+    #[test]
+    fn progress_is_reported_row_by_row() {
+        use std::sync::{Arc, Mutex};
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let control = PartitionControl::with_progress(move |progress| {
+            sink.lock().unwrap().push(progress);
+        });
+
+        make_local_map(vec![(29.5, 20.5)])
+            .partition_with_control(
+                geodesic_voronoi_with_control,
+                None,
+                &control,
+            )
+            .unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 40);
+        assert_eq!(reports.last(), Some(&PartitionProgress::new(1, 40, 40)));
+    }
+
+    #[test]
+    fn cancelled_partition_leaves_map_unchanged() {
+        let mut map = make_local_map(vec![(29.5, 20.5)]);
+        let far = RealWorldLocation::from_xyz(39.5, 39.5, 0.0);
+        map.map_mut()
+            .set_location(&far, MapState::Assigned)
+            .unwrap();
+        let control = PartitionControl::new();
+        control.cancel();
+
+        let cancelled = map
+            .partition_with_control(
+                |map, factors, control| {
+                    sweep_line_with_control(map, factors, 0.0, control)
+                },
+                None,
+                &control,
+            )
+            .unwrap_err();
+
+        let map = cancelled.into_map();
+        assert_eq!(map.map().get_location(&far), Ok(MapState::Assigned));
+        assert_eq!(assigned(&map), 1);
+    }
+}
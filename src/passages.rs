@@ -0,0 +1,270 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use ndarray::Array2;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{CellMap, GridIndex};
+
+/// Directions of the lines along which the width of the free space is
+/// measured: horizontal, vertical and both diagonals, as `[rows, columns]`.
+const DIRECTIONS: [[isize; 2]; 4] = [[0, 1], [1, 0], [1, 1], [1, -1]];
+
+/// A narrow passage of the free space, e.g. a door or a tight corridor.
+///
+/// See [`CellMap::narrow_passages`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passage {
+    cells: Vec<GridIndex>,
+    width: f64,
+    narrowest: GridIndex,
+}
+
+impl Passage {
+    /// The cells making up the passage, sorted by row and column.
+    pub fn cells(&self) -> &[GridIndex] {
+        &self.cells
+    }
+    /// The width of the passage at its narrowest cell in real-world units.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+    /// The cell at which the passage is narrowest, e.g. to cut a map there.
+    pub fn narrowest(&self) -> GridIndex {
+        self.narrowest
+    }
+}
+
+impl CellMap {
+    /// Find the passages of the free space which are narrower than
+    /// `min_width`, e.g. to see where a robot of that size cannot fit or to
+    /// prefer cutting partitions at chokepoints.
+    ///
+    /// The width at a cell is measured along horizontal, vertical and
+    /// diagonal lines, as the length of the run of free cells through it.
+    /// Runs end at [`MapState::Obstacle`](crate::MapState::Obstacle) and
+    /// [`MapState::OutOfMap`](crate::MapState::OutOfMap) cells as well as at
+    /// the edges of the map. A cell lies in a narrow passage if one of its
+    /// runs is shorter than `min_width`, and the runs of both cells next to
+    /// it across that line are free and at least as long. The latter
+    /// excludes the corners of rooms, where diagonal runs are short as well.
+    /// Cells sharing an edge or a corner form one passage.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, GridIndex, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// // Two rooms separated by a wall with a door in its middle.
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(11.0, 5.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(5.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(6.0, 5.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(5.0, 2.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(6.0, 3.0, 0.0),
+    ///     MapState::Unexplored,
+    /// );
+    ///
+    /// let passages = map.narrow_passages(2.0);
+    ///
+    /// assert_eq!(passages.len(), 1);
+    /// assert_eq!(passages[0].width(), 1.0);
+    /// assert_eq!(passages[0].narrowest(), GridIndex::new(2, 5));
+    /// ```
+    pub fn narrow_passages(&self, min_width: f64) -> Vec<Passage> {
+        let (rows, columns) = self.cells().dim();
+        let runs = DIRECTIONS.map(|direction| self.free_runs(direction));
+        let step = |[d_row, d_col]: [isize; 2]| {
+            (d_col as f64 / self.resolution().x)
+                .hypot(d_row as f64 / self.resolution().y)
+        };
+        let shifted = |[row, col]: [usize; 2], [d_row, d_col]: [isize; 2]| {
+            let row = row.checked_add_signed(d_row)?;
+            let col = col.checked_add_signed(d_col)?;
+            (row < rows && col < columns).then_some([row, col])
+        };
+
+        let mut widths = Array2::from_elem((rows, columns), f64::INFINITY);
+        for ((row, col), _) in self.cells().indexed_iter() {
+            let index = [row, col];
+            for (direction, runs) in DIRECTIONS.iter().zip(&runs) {
+                let width = runs[index] as f64 * step(*direction);
+                let [d_row, d_col] = *direction;
+                // Both cells next to this one across the line.
+                let is_minimum = [[d_col, -d_row], [-d_col, d_row]]
+                    .into_iter()
+                    .all(|across| {
+                        shifted(index, across)
+                            .is_some_and(|other| runs[other] >= runs[index])
+                    });
+                if runs[index] > 0 && width < min_width && is_minimum {
+                    widths[index] = widths[index].min(width);
+                }
+            }
+        }
+
+        let mut visited = Array2::from_elem((rows, columns), false);
+        let mut passages = Vec::new();
+        for ((row, col), width) in widths.indexed_iter() {
+            if visited[[row, col]] || width.is_infinite() {
+                continue;
+            }
+            visited[[row, col]] = true;
+            let mut queue = VecDeque::from([[row, col]]);
+            let mut cells = Vec::new();
+            while let Some(index) = queue.pop_front() {
+                cells.push(GridIndex::from(index));
+                for neighbor in self.neighbor_indices(index) {
+                    if !visited[neighbor] && widths[neighbor].is_finite() {
+                        visited[neighbor] = true;
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            cells.sort();
+            let narrowest = *cells
+                .iter()
+                .min_by(|a, b| {
+                    widths[<[usize; 2]>::from(**a)]
+                        .total_cmp(&widths[<[usize; 2]>::from(**b)])
+                })
+                .expect("Passages contain at least one cell");
+            passages.push(Passage {
+                width: widths[<[usize; 2]>::from(narrowest)],
+                cells,
+                narrowest,
+            });
+        }
+        passages
+    }
+
+    /// Internal helper returning for each cell the number of free cells in
+    /// the run through it along `direction`, or `0` for blocked cells.
+    fn free_runs(&self, [d_row, d_col]: [isize; 2]) -> Array2<usize> {
+        let (rows, columns) = self.cells().dim();
+        let shifted = |[row, col]: [usize; 2], sign: isize| {
+            let row = row.checked_add_signed(sign * d_row)?;
+            let col = col.checked_add_signed(sign * d_col)?;
+            (row < rows && col < columns && self.is_passable([row, col]))
+                .then_some([row, col])
+        };
+
+        let mut runs = Array2::zeros((rows, columns));
+        for ((row, col), _) in self.cells().indexed_iter() {
+            if runs[[row, col]] > 0 || !self.is_passable([row, col]) {
+                continue;
+            }
+            let mut start = [row, col];
+            while let Some(previous) = shifted(start, -1) {
+                start = previous;
+            }
+            let mut run = Vec::from([start]);
+            while let Some(next) = shifted(run[run.len() - 1], 1) {
+                run.push(next);
+            }
+            for index in &run {
+                runs[*index] = run.len();
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, MapState, RealWorldLocation};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(20.0, 9.0, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    /// Adds a wall across the map at column `col` with a door starting at
+    /// row 3.
+    fn add_wall(map: &mut CellMap, col: f64, door: f64) {
+        map.set_rect(
+            &RealWorldLocation::from_xyz(col, 0.0, 0.0),
+            &RealWorldLocation::from_xyz(col + 1.0, 9.0, 0.0),
+            MapState::Obstacle,
+        );
+        map.set_rect(
+            &RealWorldLocation::from_xyz(col, 3.0, 0.0),
+            &RealWorldLocation::from_xyz(col + 1.0, 3.0 + door, 0.0),
+            MapState::Explored,
+        );
+    }
+
+    #[test]
+    fn doors_of_different_widths() {
+        let mut map = make_map();
+        add_wall(&mut map, 6.0, 1.0);
+        add_wall(&mut map, 13.0, 2.0);
+
+        let passages = map.narrow_passages(3.0);
+
+        assert_eq!(passages.len(), 2);
+        assert_eq!(passages[0].cells(), [GridIndex::new(3, 6)]);
+        assert_eq!(passages[0].width(), 1.0);
+        assert_eq!(
+            passages[1].cells(),
+            [GridIndex::new(3, 13), GridIndex::new(4, 13)]
+        );
+        assert_eq!(passages[1].width(), 2.0);
+        assert_eq!(map.narrow_passages(1.5).len(), 1);
+    }
+
+    #[test]
+    fn no_passages_in_open_space() {
+        let mut map = make_map();
+
+        assert!(map.narrow_passages(5.0).is_empty());
+
+        // The gap between a pillar and the edge of the map.
+        map.set_rect(
+            &RealWorldLocation::from_xyz(8.0, 2.0, 0.0),
+            &RealWorldLocation::from_xyz(10.0, 9.0, 0.0),
+            MapState::Obstacle,
+        );
+        let passages = map.narrow_passages(5.0);
+
+        assert_eq!(passages.len(), 1);
+        assert_eq!(passages[0].width(), 2.0);
+        for index in [[0, 8], [0, 9], [1, 8], [1, 9]] {
+            assert!(passages[0].cells().contains(&index.into()));
+        }
+    }
+
+    #[test]
+    fn width_follows_resolution() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+            AxisResolution::uniform(2.0),
+        );
+        // A corridor 1.5 wide.
+        map.set_rect(
+            &RealWorldLocation::from_xyz(0.0, 1.5, 0.0),
+            &RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+            MapState::OutOfMap,
+        );
+
+        let passages = map.narrow_passages(2.0);
+
+        assert_eq!(passages.len(), 1);
+        assert_eq!(passages[0].width(), 1.5);
+        assert_eq!(passages[0].cells().len(), 3 * 18);
+    }
+}
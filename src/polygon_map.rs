@@ -1,14 +1,20 @@
-use geo::{BoundingRect, MapCoords};
-use geo_rasterize::BinaryBuilder;
+use geo::line_intersection::{line_intersection, LineIntersection};
+use geo::{
+    Area, BoundingRect, Centroid, Contains, EuclideanLength, HasKernel,
+    Intersects, Kernel, Orientation,
+};
 use num::ToPrimitive;
+use std::f64::consts::TAU;
 
-use crate::cell_map::CellMap;
-use crate::coords::{AxisResolution, Coords, InternalLocation};
-use crate::{Location, LocationType, RealWorldLocation};
+use crate::cell_map::{index_to_internal, CellMap};
+use crate::coords::{AxisResolution, Coords};
+use crate::{Cell, LocalMap, LocationType, MapState, RealWorldLocation};
 
 /// Describe a map using a polygon.
 ///
 /// The polygon is described using a set of coordinates making up its vertices.
+/// A map may also consist of several disjoint polygons, e.g. two fields
+/// separated by a road, see [`PolygonMap::new_multi`].
 ///
 /// # Examples
 /// ```
@@ -21,19 +27,22 @@ use crate::{Location, LocationType, RealWorldLocation};
 /// let polygon = PolygonMap::new(vec![p1, p2, p3]).unwrap();
 ///
 /// assert_eq!(
-///     polygon.vertices(),
-///     &vec![
+///     polygon.areas(),
+///     &[vec![
 ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
 ///         RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
 ///         RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
-///     ]
+///     ]]
 /// );
 /// ```
 pub struct PolygonMap {
-    /// Vertices of the polygon describing the region to be explored.
-    vertices: Vec<RealWorldLocation>,
+    /// Vertices of the disjoint polygons describing the regions to be
+    /// explored, of which there is at least one.
+    areas: Vec<Vec<RealWorldLocation>>,
     /// List of vertices describing polygons of the already explored regions.
-    explored: Option<Vec<Vec<RealWorldLocation>>>,
+    explored: Vec<Vec<RealWorldLocation>>,
+    /// List of vertices describing polygons of known obstacles.
+    obstacles: Vec<Vec<RealWorldLocation>>,
 }
 
 impl PolygonMap {
@@ -45,15 +54,49 @@ impl PolygonMap {
     ///
     /// # Errors
     ///
-    /// This function will return an error if the polygon has not enough
-    /// vertices (strictly less than 3 vertices) and thus describes an invalid
-    /// polygon.
+    /// This function will return an error if the vertices do not describe a
+    /// valid polygon, rather than failing later on when working with it:
+    ///
+    /// - [`PolygonMapError::NotEnoughVertices`] if there are strictly less
+    ///   than 3 vertices.
+    /// - [`PolygonMapError::NonFiniteCoordinates`] if a coordinate is NaN or
+    ///   infinite.
+    /// - [`PolygonMapError::RepeatedVertex`] if two consecutive vertices
+    ///   share the same `x` and `y` coordinates.
+    /// - [`PolygonMapError::ZeroArea`] if all vertices lie on a line.
+    /// - [`PolygonMapError::SelfIntersecting`] if edges of the polygon cross
+    ///   or touch each other, apart from edges sharing a vertex. The polygon
+    ///   may pass through the same vertex several times, e.g. where a region
+    ///   is pinched, as long as it does not cross itself there.
+    ///
+    /// The polygon is closed implicitly, but may also repeat the first vertex
+    /// at the end, as e.g. in GeoJSON. That vertex is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{PolygonMap, PolygonMapError, RealWorldLocation};
+    ///
+    /// // A bow tie, whose second and last edge cross each other.
+    /// let bow_tie = vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 2.0, 0.0),
+    /// ];
+    ///
+    /// assert_eq!(
+    ///     PolygonMap::new(bow_tie).err(),
+    ///     Some(PolygonMapError::SelfIntersecting)
+    /// );
+    /// ```
     pub fn new(
         vertices: Vec<RealWorldLocation>,
     ) -> Result<Self, PolygonMapError> {
         Ok(Self {
-            vertices: Self::verify_polygon(vertices)?,
-            explored: None,
+            areas: vec![Self::verify_polygon(vertices)?],
+            explored: Vec::new(),
+            obstacles: Vec::new(),
         })
     }
 
@@ -66,22 +109,61 @@ impl PolygonMap {
         vertices: Vec<RealWorldLocation>,
         explored: Option<Vec<Vec<RealWorldLocation>>>,
     ) -> Result<Self, PolygonMapError> {
-        // TODO: find a better way to make this check in-place when creating the
-        // struct? The [`Self::verify_polygon`] function was made such that it
-        // returns the polygon itself for this particular situation, in case
-        // there was no error. The issue with using `.map()` is that a
-        // `return` inside the closure will not return from the parent
-        // function. Also the following expression (equally with a `match`) will
-        // partially move the value, hence a clone is necessary.
-        if let Some(e) = explored.clone() {
-            for polygon in e {
-                Self::verify_polygon(polygon)?;
-            }
-        }
+        let explored = explored
+            .unwrap_or_default()
+            .into_iter()
+            .map(Self::verify_polygon)
+            .collect::<Result<_, _>>()?;
 
         Ok(Self {
-            vertices: Self::verify_polygon(vertices)?,
+            areas: vec![Self::verify_polygon(vertices)?],
             explored,
+            obstacles: Vec::new(),
+        })
+    }
+
+    /// Same as [`PolygonMap::new`], but the region to be explored consists of
+    /// several disjoint polygons, which are rasterized into a single
+    /// [`CellMap`] covering all of them.
+    ///
+    /// # Errors
+    ///
+    /// - [`PolygonMapError::NotEnoughVertices`] if there are no polygons.
+    /// - [`PolygonMapError::OverlappingAreas`] if polygons overlap or touch
+    ///   each other.
+    /// - Same errors as [`PolygonMap::new`] for each polygon.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, MapState, MaskMapState, PolygonMap,
+    ///     RealWorldLocation,
+    /// };
+    ///
+    /// let field = |x: f64| {
+    ///     vec![
+    ///         RealWorldLocation::from_xyz(x, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(x + 2.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(x + 2.0, 3.0, 0.0),
+    ///         RealWorldLocation::from_xyz(x, 3.0, 0.0),
+    ///     ]
+    /// };
+    /// // Two fields separated by a road.
+    /// let map = PolygonMap::new_multi(vec![field(0.0), field(3.0)]).unwrap();
+    ///
+    /// assert_eq!(map.area(), 12.0);
+    /// let cellmap = map.to_cell_map(AxisResolution::uniform(1.0));
+    /// assert_eq!(cellmap.cells().dim(), (3, 5));
+    /// assert_eq!(cellmap.get_map_state(MapState::OutOfMap).len(), 3);
+    /// ```
+    pub fn new_multi(
+        areas: Vec<Vec<RealWorldLocation>>,
+    ) -> Result<Self, PolygonMapError> {
+        Ok(Self {
+            areas: Self::verify_areas(areas)?,
+            explored: Vec::new(),
+            obstacles: Vec::new(),
         })
     }
 
@@ -89,16 +171,45 @@ impl PolygonMap {
     ///
     /// # Errors
     ///
-    /// This function will return an error if the polygon has too few vertices
-    /// (less than 3) to describe a valid shape.
+    /// Same as [`PolygonMap::new`].
     fn verify_polygon(
         vertices: Vec<RealWorldLocation>,
     ) -> Result<Vec<RealWorldLocation>, PolygonMapError> {
-        if vertices.len() < 3 {
-            Err(PolygonMapError::NotEnoughVertices)
-        } else {
-            Ok(vertices)
+        let len = check_vertices(&vertices)?.len();
+        let mut vertices = vertices;
+        vertices.truncate(len);
+        Ok(vertices)
+    }
+
+    /// Internal function to verify validity of the polygons of the regions
+    /// to be explored.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PolygonMap::new_multi`].
+    fn verify_areas(
+        areas: Vec<Vec<RealWorldLocation>>,
+    ) -> Result<Vec<Vec<RealWorldLocation>>, PolygonMapError> {
+        if areas.is_empty() {
+            return Err(PolygonMapError::NotEnoughVertices);
+        }
+        let areas: Vec<_> = areas
+            .into_iter()
+            .map(Self::verify_polygon)
+            .collect::<Result<_, _>>()?;
+        let polygons: Vec<_> = areas
+            .iter()
+            .map(|vertices| to_geo_polygon(vertices))
+            .collect();
+        for (i, polygon) in polygons.iter().enumerate() {
+            if polygons[i + 1..]
+                .iter()
+                .any(|other| polygon.intersects(other))
+            {
+                return Err(PolygonMapError::OverlappingAreas);
+            }
         }
+        Ok(areas)
     }
 
     /// Convert this map to a [`CellMap`].
@@ -108,10 +219,57 @@ impl PolygonMap {
     /// mostly interesting for specifying a map region.
     ///
     /// The `resolution` is used to impact the size/dimension of the
-    /// [`CellMap`]. See also [`AxisResolution`].
+    /// [`CellMap`]. See also [`AxisResolution`]. Explored regions and
+    /// obstacles are set to [`MapState::Explored`] and [`MapState::Obstacle`]
+    /// respectively. The cells are rasterized
+    /// with the default [`RasterOptions`], see
+    /// [`PolygonMap::to_cell_map_with`] to choose another convention.
     pub fn to_cell_map(self, resolution: AxisResolution) -> CellMap {
-        let (cells, offset) =
-            self.rasterize_polygon(&self.vertices, &resolution);
+        self.to_cell_map_with(resolution, RasterOptions::default())
+    }
+
+    /// Same as [`PolygonMap::to_cell_map`], but rasterizes the polygons with
+    /// the given [`RasterOptions`].
+    ///
+    /// The map starts at the bottom left corner of the polygon's bounding box
+    /// and has just enough cells to cover it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, MapState, MaskMapState, PixelSampling, PolygonMap,
+    ///     RasterOptions, RealWorldLocation,
+    /// };
+    ///
+    /// let square = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 2.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// // The corners of the cells in the bottom row and the left column lie
+    /// // on the edges of the polygon.
+    /// let map = square.to_cell_map_with(
+    ///     AxisResolution::uniform(2.0),
+    ///     RasterOptions {
+    ///         sampling: PixelSampling::Corner,
+    ///         inclusive_edges: false,
+    ///         ..RasterOptions::default()
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(map.cells().dim(), (4, 4));
+    /// assert_eq!(map.get_map_state(MapState::Unexplored).len(), 9);
+    /// ```
+    pub fn to_cell_map_with(
+        self,
+        resolution: AxisResolution,
+        options: RasterOptions,
+    ) -> CellMap {
+        let (cells, offset) = self.rasterize_areas(&resolution, options);
         let cells = cells.map(|e| match e {
             true => LocationType::Unexplored,
             false => LocationType::OutOfMap,
@@ -119,39 +277,31 @@ impl PolygonMap {
         let mut cellmap = CellMap::from_raster(cells, resolution, offset);
 
         // Set already-explored cells in `cellmap`
-        if let Some(explored) = &self.explored {
-            for polygon in explored {
-                let (cells_explored, offset_explored) =
-                    self.rasterize_polygon(polygon, &resolution);
-                let explored_locations: Vec<RealWorldLocation> = cells_explored
-                    .indexed_iter()
-                    .filter(|((_, _), e)| **e)
-                    .map(|((row, col), _)| {
-                        InternalLocation::new(
-                            Coords::new(
-                                col.to_f64().expect(
-                                    "No overflow converting usize to f64",
-                                ),
-                                row.to_f64().expect(
-                                    "No overflow converting usize to f64",
-                                ),
-                                0.0,
-                            ),
-                            offset_explored,
-                            resolution,
-                        )
-                        .expect(
-                            "indexed_iter() will not return negative indexes",
-                        )
-                        .into_real_world()
-                    })
-                    .filter(|location| cellmap.get_location(location).is_ok())
-                    .collect();
-
-                for loc in &explored_locations {
-                    cellmap
-                        .set_location(loc, LocationType::Explored)
-                        .expect("Invalid locations were filtered out");
+        for polygon in &self.explored {
+            let covered = rasterize_onto_grid(
+                &to_geo_polygon(polygon),
+                offset,
+                &resolution,
+                cellmap.cells().dim(),
+                options,
+            );
+            for ((row, col), _) in covered.indexed_iter().filter(|(_, e)| **e) {
+                cellmap.set_cell([row, col], LocationType::Explored);
+            }
+        }
+
+        // Set obstacle cells within the map, even if explored
+        for polygon in &self.obstacles {
+            let covered = rasterize_onto_grid(
+                &to_geo_polygon(polygon),
+                offset,
+                &resolution,
+                cellmap.cells().dim(),
+                options,
+            );
+            for ((row, col), _) in covered.indexed_iter().filter(|(_, e)| **e) {
+                if cellmap.cells()[[row, col]] != LocationType::OutOfMap {
+                    cellmap.set_cell([row, col], LocationType::Obstacle);
                 }
             }
         }
@@ -159,177 +309,2006 @@ impl PolygonMap {
         cellmap
     }
 
-    /// Internal helper function to convert the polygon to a corresponding
-    /// matrix [`MapStateMatrix`] for use with [`CellMap`]. The function should
-    /// be used by [`PolygonMap::to_cell_map`].
+    /// Internal helper function to convert the polygons to a corresponding
+    /// matrix [`MapStateMatrix`](crate::MapStateMatrix) for use with
+    /// [`CellMap`]. The function should be used by
+    /// [`PolygonMap::to_cell_map_with`].
     ///
-    /// # Panics
-    ///
-    /// The [`geo`] crate allows to obtain the polygon's *bounding box* as well
-    /// *rasterizing* it. Both of these procedures can cause panics, which
-    /// is not expected to happen. They boil down to **invalid polygon shapes**
-    /// and **NaN or infinite values** in the vertex coordinates.
-    ///
-    /// - Extract the Bounding Box: will panic if no bounding box can be made. A
-    ///   properly formed polygon should always have a properly defined bounding
-    ///   box. It should be checked elsewhere that the polygons have a valid
-    ///   shape.
-    /// - The *BinaryBuilder* used to rasterize the polygon can panic as well if
-    ///   there are NaN of infinite values.
-    /// - The rasterization itself can panic as well if there are NaN of
-    ///   infinite values.
-    fn rasterize_polygon(
+    /// The grid starts at the bottom left corner of the polygons' bounding
+    /// box, which is returned alongside the matrix, and covers all of it.
+    fn rasterize_areas(
         &self,
-        vertices: &[RealWorldLocation],
         resolution: &AxisResolution,
+        options: RasterOptions,
     ) -> (ndarray::Array2<bool>, Coords) {
-        let polygon = geo::Polygon::new(
-            geo::LineString::from(
-                vertices.iter().map(|e| (e.x(), e.y())).collect::<Vec<_>>(),
-            ),
-            vec![],
-        );
-
-        let bbox = match polygon.bounding_rect() {
-            Some(b) => b,
-            None => panic!("No bounding box for polygon"),
-        };
-        let offset = Coords::new(bbox.min().x, bbox.min().y, 0.0);
-        // convert to pixels
-        let width = bbox.width() * resolution.x;
-        let height = bbox.height() * resolution.y;
-        let polygon = polygon.map_coords(|geo::Coord { x, y }| {
-            let internal_location =
-                RealWorldLocation::new(Coords::new(x, y, 0.0))
-                    .into_internal(offset, *resolution)
-                    .expect("Coordinates should be valid");
-            geo::Coord {
-                x: internal_location.x(),
-                y: internal_location.y(),
-            }
-        });
+        let areas = self.as_geo();
+        let (offset, dim) = bounding_grid(&areas, resolution);
 
-        let mut rasterizer = BinaryBuilder::new()
-            .width(width.to_usize().expect("No conversion issues"))
-            .height(height.to_usize().expect("No conversion issues"))
-            .build()
-            .expect("There should be no NaN or infinite values among the polygon vertices");
+        let mut cells = ndarray::Array2::from_elem(dim, false);
+        for polygon in &areas {
+            let covered =
+                rasterize_onto_grid(polygon, offset, resolution, dim, options);
+            cells.zip_mut_with(&covered, |cell, covered| *cell |= *covered);
+        }
+        (cells, offset)
+    }
 
-        rasterizer
-            .rasterize(&polygon)
-            .expect("There should be no NaN of infinite values");
+    /// The fraction of each cell covered by the polygon, from `0.0` for cells
+    /// outside of it to `1.0` for cells entirely within it, e.g. to weigh the
+    /// cells along the border when balancing the areas of partitions at
+    /// coarse resolutions.
+    ///
+    /// The cells are the same as the ones of [`PolygonMap::to_cell_map`] with
+    /// the given `resolution`, so the coverage can be used alongside the
+    /// resulting map. The explored regions and obstacles do not affect the
+    /// coverage.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, PolygonMap, RealWorldLocation};
+    ///
+    /// let triangle = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 2.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// let coverage = triangle.coverage(AxisResolution::uniform(1.0));
+    ///
+    /// assert_eq!(coverage, ndarray::array![[1.0, 0.5], [0.5, 0.0]]);
+    /// assert_eq!(coverage.sum(), 2.0);
+    /// ```
+    pub fn coverage(&self, resolution: AxisResolution) -> ndarray::Array2<f64> {
+        let areas = self.as_geo();
+        let (offset, dim) = bounding_grid(&areas, &resolution);
 
-        (rasterizer.finish(), offset)
+        let mut coverage = ndarray::Array2::zeros(dim);
+        for polygon in &areas {
+            coverage += &coverage_onto_grid(polygon, offset, &resolution, dim);
+        }
+        // Disjoint polygons may still share cells, but not their area.
+        coverage.mapv_inplace(|fraction: f64| fraction.min(1.0));
+        coverage
     }
 
+    /// The vertices of the polygon describing the region to be explored. If
+    /// there are several regions, these are the vertices of the first one,
+    /// see [`PolygonMap::areas`].
+    #[deprecated(
+        note = "only returns the first region, use `PolygonMap::areas` instead"
+    )]
     pub fn vertices(&self) -> &Vec<RealWorldLocation> {
-        &self.vertices
+        &self.areas[0]
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub enum PolygonMapError {
-    /// At least 3 vertices are needed to form a proper polygon on which
-    /// anything meaningful can be done.
-    NotEnoughVertices,
+    /// The vertices of the disjoint polygons describing the regions to be
+    /// explored, of which there is at least one.
+    pub fn areas(&self) -> &[Vec<RealWorldLocation>] {
+        &self.areas
+    }
+
+    /// The regions to be explored as a [`geo::MultiPolygon`], e.g. to compute
+    /// their area or centroid, or to combine them with other geometries.
+    ///
+    /// The `z` component of the vertices is dropped. The explored regions are
+    /// not part of the polygons, see [`PolygonMap::explored_as_geo`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{Area, Centroid};
+    /// use local_robot_map::{PolygonMap, RealWorldLocation};
+    ///
+    /// let map = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 2.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// let polygons = map.as_geo();
+    ///
+    /// assert_eq!(polygons.0.len(), 1);
+    /// assert_eq!(polygons.unsigned_area(), 8.0);
+    /// assert_eq!(polygons.centroid(), Some(geo::point!(x: 2.0, y: 1.0)));
+    /// ```
+    pub fn as_geo(&self) -> geo::MultiPolygon {
+        self.areas
+            .iter()
+            .map(|vertices| to_geo_polygon(vertices))
+            .collect()
+    }
+
+    /// The vertices of the polygons describing the already explored regions,
+    /// which is empty if there are none.
+    pub fn explored(&self) -> &[Vec<RealWorldLocation>] {
+        &self.explored
+    }
+
+    /// Add an already explored region, e.g. as reports of the mission come
+    /// in. It is rasterized to [`MapState::Explored`] by
+    /// [`PolygonMap::to_cell_map`], like the ones given to
+    /// [`PolygonMap::new_explored`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PolygonMap::new`], in which case the map is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, MapState, MaskMapState, PolygonMap,
+    ///     RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 4.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// map.add_explored_region(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 2.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// assert_eq!(map.explored().len(), 1);
+    /// let cellmap = map.to_cell_map(AxisResolution::uniform(1.0));
+    /// assert_eq!(cellmap.get_map_state(MapState::Explored).len(), 4);
+    /// ```
+    pub fn add_explored_region(
+        &mut self,
+        vertices: Vec<RealWorldLocation>,
+    ) -> Result<(), PolygonMapError> {
+        self.explored.push(Self::verify_polygon(vertices)?);
+        Ok(())
+    }
+
+    /// The vertices of the polygons describing the known obstacles, which is
+    /// empty if there are none.
+    pub fn obstacles(&self) -> &[Vec<RealWorldLocation>] {
+        &self.obstacles
+    }
+
+    /// Add a known obstacle, e.g. a building or a body of water, which must
+    /// not be covered but still lies within the map.
+    ///
+    /// [`PolygonMap::to_cell_map`] sets the cells of obstacles to
+    /// [`MapState::Obstacle`], even if they are explored. Cells outside the
+    /// regions to be explored stay [`MapState::OutOfMap`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PolygonMap::new`], in which case the map is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, MapState, MaskMapState, PolygonMap,
+    ///     RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 4.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// // A building reaching beyond the map.
+    /// map.add_obstacle(vec![
+    ///     RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+    ///     RealWorldLocation::from_xyz(6.0, 3.0, 0.0),
+    ///     RealWorldLocation::from_xyz(6.0, 6.0, 0.0),
+    ///     RealWorldLocation::from_xyz(3.0, 6.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// let cellmap = map.to_cell_map(AxisResolution::uniform(1.0));
+    /// assert_eq!(cellmap.get_map_state(MapState::Obstacle).len(), 1);
+    /// assert_eq!(cellmap.get_map_state(MapState::Unexplored).len(), 15);
+    /// ```
+    pub fn add_obstacle(
+        &mut self,
+        vertices: Vec<RealWorldLocation>,
+    ) -> Result<(), PolygonMapError> {
+        self.obstacles.push(Self::verify_polygon(vertices)?);
+        Ok(())
+    }
+
+    /// The known obstacles as a [`geo::MultiPolygon`], which is empty if
+    /// there are none. See [`PolygonMap::as_geo`].
+    pub fn obstacles_as_geo(&self) -> geo::MultiPolygon {
+        self.obstacles
+            .iter()
+            .map(|vertices| to_geo_polygon(vertices))
+            .collect()
+    }
+
+    /// The already explored regions as a [`geo::MultiPolygon`], which is
+    /// empty if there are none. See [`PolygonMap::as_geo`].
+    pub fn explored_as_geo(&self) -> geo::MultiPolygon {
+        self.explored
+            .iter()
+            .map(|vertices| to_geo_polygon(vertices))
+            .collect()
+    }
+
+    /// Whether the location lies within any region to be explored or on its
+    /// outline, e.g. to check the start positions of the robots without
+    /// rasterizing the polygon first.
+    ///
+    /// The `z` component of the location is ignored, as are the explored
+    /// regions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{PolygonMap, RealWorldLocation};
+    ///
+    /// let map = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// assert!(map.contains(&RealWorldLocation::from_xyz(3.0, 1.0, 5.0)));
+    /// assert!(map.contains(&RealWorldLocation::from_xyz(2.0, 2.0, 0.0)));
+    /// assert!(!map.contains(&RealWorldLocation::from_xyz(1.0, 3.0, 0.0)));
+    /// ```
+    pub fn contains(&self, location: &RealWorldLocation) -> bool {
+        self.as_geo().intersects(&geo::Coord::from(location))
+    }
+
+    /// Whether all locations lie within the region to be explored, see
+    /// [`PolygonMap::contains`]. This is `true` if there are no locations.
+    pub fn contains_all(&self, locations: &[RealWorldLocation]) -> bool {
+        let areas = self.as_geo();
+        locations
+            .iter()
+            .all(|location| areas.intersects(&geo::Coord::from(location)))
+    }
+
+    /// The area of the regions to be explored in real-world units, e.g. to
+    /// choose the resolution or the number of robots before rasterizing them.
+    /// Explored regions are not subtracted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{PolygonMap, RealWorldLocation};
+    ///
+    /// let map = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 3.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// assert_eq!(map.area(), 6.0);
+    /// assert_eq!(map.perimeter(), 12.0);
+    /// assert_eq!(
+    ///     map.bounding_box(),
+    ///     [
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(4.0, 3.0, 0.0)
+    ///     ]
+    /// );
+    /// ```
+    pub fn area(&self) -> f64 {
+        self.as_geo().unsigned_area()
+    }
+
+    /// The total length of the outlines of the regions to be explored in
+    /// real-world units.
+    pub fn perimeter(&self) -> f64 {
+        self.as_geo()
+            .iter()
+            .map(|polygon| polygon.exterior().euclidean_length())
+            .sum()
+    }
+
+    /// The centroid of the regions to be explored, i.e. their center of mass,
+    /// which does not necessarily lie within them.
+    ///
+    /// As for [`PolygonMap::as_geo`], the `z` component is dropped and set to
+    /// `0.0`.
+    pub fn centroid(&self) -> RealWorldLocation {
+        let centroid = self
+            .as_geo()
+            .centroid()
+            .expect("A polygon with at least 3 vertices has a centroid");
+        RealWorldLocation::from_xyz(centroid.x(), centroid.y(), 0.0)
+    }
+
+    /// The bottom left and top right corners of the bounding box of the
+    /// regions to be explored, like [`CellMap::bounds`].
+    ///
+    /// As for [`PolygonMap::as_geo`], the `z` component is dropped and set to
+    /// `0.0`.
+    pub fn bounding_box(&self) -> [RealWorldLocation; 2] {
+        let bbox = self
+            .as_geo()
+            .bounding_rect()
+            .expect("A polygon with at least 3 vertices has a bounding box");
+        [
+            RealWorldLocation::from_xyz(bbox.min().x, bbox.min().y, 0.0),
+            RealWorldLocation::from_xyz(bbox.max().x, bbox.max().y, 0.0),
+        ]
+    }
+
+    /// A copy of the map whose regions to be explored are expanded by
+    /// `distance` in real-world units, or shrunk if it is negative, e.g. to
+    /// keep robots of a given radius away from the mission boundary.
+    ///
+    /// Each edge is moved along its normal by `distance`, and neighboring
+    /// edges are joined where they meet. Sharp corners which stick out would
+    /// thereby reach much further than `distance`, so they are cut off once
+    /// they reach twice as far. The explored regions are kept as they are.
+    ///
+    /// # Errors
+    ///
+    /// - [`PolygonMapError::Collapsed`] if an edge vanishes, i.e. parts of
+    ///   the region are narrower than twice the distance it is shrunk by.
+    /// - Same as [`PolygonMap::new_multi`] for the resulting polygons, e.g.
+    ///   [`PolygonMapError::SelfIntersecting`] if moved edges cross or
+    ///   [`PolygonMapError::OverlappingAreas`] if expanded regions meet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{PolygonMap, RealWorldLocation};
+    ///
+    /// let map = PolygonMap::new(vec![
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 6.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 6.0, 0.0),
+    /// ])
+    /// .unwrap();
+    ///
+    /// let inset = map.buffer(-0.5).unwrap();
+    ///
+    /// assert_eq!(inset.area(), 9.0 * 5.0);
+    /// assert!(inset.areas()[0]
+    ///     .contains(&RealWorldLocation::from_xyz(0.5, 0.5, 0.0)));
+    /// assert!(map.buffer(-3.0).is_err());
+    /// ```
+    pub fn buffer(&self, distance: f64) -> Result<Self, PolygonMapError> {
+        let areas = self
+            .areas
+            .iter()
+            .map(|vertices| buffer_vertices(vertices, distance))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            areas: Self::verify_areas(areas)?,
+            explored: self.explored.clone(),
+            obstacles: self.obstacles.clone(),
+        })
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::LocationType;
+impl From<&PolygonMap> for geo::MultiPolygon {
+    fn from(value: &PolygonMap) -> Self {
+        value.as_geo()
+    }
+}
 
-    const OOM: LocationType = LocationType::OutOfMap;
-    const UNE: LocationType = LocationType::Unexplored;
+impl From<PolygonMap> for geo::MultiPolygon {
+    fn from(value: PolygonMap) -> Self {
+        value.as_geo()
+    }
+}
 
-    /// Note how the rasterized polygon seems tilted to the right and not
-    /// perfectly centered/symmetric. I assume this is an artifact from the
-    /// [`geo_rasterize`] crate, but I could not find any relevant information
-    /// thereon. It should not pose too big of an issue though.
-    #[test]
-    fn polygon_map_to_cell_map_positive() {
-        let p1 = RealWorldLocation::from_xyz(0.0, 0.0, 0.0);
-        let p2 = RealWorldLocation::from_xyz(4.0, 4.0, 0.0);
-        let p3 = RealWorldLocation::from_xyz(8.0, 0.0, 0.0);
+impl CellMap {
+    /// Set all cells covered by the given polygon to the given state.
+    ///
+    /// Unlike [`PolygonMap::to_cell_map`], this keeps the existing content of
+    /// the map and only updates the covered cells. Cells are covered
+    /// according to the default [`RasterOptions`], i.e. if their center lies
+    /// within the polygon or on its edges. Parts of the polygon lying outside
+    /// the map are ignored. Changed cells are stamped with the map's clock
+    /// (see [`CellMap::set_clock`]).
+    ///
+    /// Returns the number of cells which were set.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PolygonMap::new`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, MaskMapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let square = vec![
+    ///     RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     RealWorldLocation::from_xyz(5.0, 2.0, 0.0),
+    ///     RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+    ///     RealWorldLocation::from_xyz(2.0, 5.0, 0.0),
+    /// ];
+    ///
+    /// let count = map.set_polygon(&square, MapState::Explored).unwrap();
+    ///
+    /// assert_eq!(count, map.get_map_state(MapState::Explored).len());
+    /// assert_eq!(count, 9);
+    /// ```
+    pub fn set_polygon(
+        &mut self,
+        vertices: &[RealWorldLocation],
+        value: LocationType,
+    ) -> Result<usize, PolygonMapError> {
+        let vertices = check_vertices(vertices)?;
 
-        let resolution = AxisResolution::uniform(1.0);
-        let cellmap: CellMap = PolygonMap::new(vec![p1, p2, p3])
-            .unwrap()
-            .to_cell_map(resolution);
+        let covered = rasterize_onto_grid(
+            &to_geo_polygon(vertices),
+            *self.offset(),
+            self.resolution(),
+            self.cells().dim(),
+            RasterOptions::default(),
+        );
+        let mut count = 0;
+        for ((row, col), _) in covered.indexed_iter().filter(|(_, e)| **e) {
+            self.set_cell([row, col], value);
+            count += 1;
+        }
+        Ok(count)
+    }
 
-        assert_eq!(cellmap.width(), 8);
-        assert_eq!(cellmap.height(), 4);
+    /// Retrieve the cells covered by the given polygon whose state matches
+    /// the `filter`, e.g. to count the unexplored cells in a region claimed
+    /// by another robot.
+    ///
+    /// The polygon is rasterized once, the same way as in
+    /// [`CellMap::set_polygon`], so both cover the same cells. Parts of the
+    /// polygon lying outside the map are ignored. The cells are ordered by
+    /// row, then by column.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PolygonMap::new`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(10.0, 3.0, 0.0),
+    ///     MapState::Explored,
+    /// );
+    /// let claimed = [
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(5.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+    ///     RealWorldLocation::from_xyz(0.0, 5.0, 0.0),
+    /// ];
+    ///
+    /// let unexplored = map
+    ///     .cells_in_polygon(&claimed, |state| state == MapState::Unexplored)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(unexplored.len(), 5 * 2);
+    /// assert!(unexplored.iter().all(|cell| *cell.y() >= 3.0));
+    /// ```
+    pub fn cells_in_polygon(
+        &self,
+        vertices: &[RealWorldLocation],
+        filter: impl Fn(LocationType) -> bool,
+    ) -> Result<Vec<Cell<'_>>, PolygonMapError> {
+        let vertices = check_vertices(vertices)?;
 
-        assert_eq!(
-            cellmap.cells(),
-            MapStateMatrix::from_shape_vec(
-                (cellmap.nrows(), cellmap.ncols()),
-                vec![
-                    UNE, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
-                    OOM, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
-                    OOM, OOM, UNE, UNE, UNE, UNE, UNE, OOM, //
-                    OOM, OOM, OOM, UNE, UNE, UNE, OOM, OOM, //
-                ]
-            )
-            .unwrap()
-        )
+        let covered = rasterize_onto_grid(
+            &to_geo_polygon(vertices),
+            *self.offset(),
+            self.resolution(),
+            self.cells().dim(),
+            RasterOptions::default(),
+        );
+        Ok(covered
+            .indexed_iter()
+            .filter(|((row, col), inside)| {
+                **inside && filter(self.cells()[[*row, *col]])
+            })
+            .map(|((row, col), _)| {
+                Cell::new(
+                    index_to_internal(
+                        [row, col],
+                        *self.offset(),
+                        *self.resolution(),
+                    ),
+                    &self.cells()[[row, col]],
+                )
+            })
+            .collect())
     }
 
-    #[test]
-    fn polygon_map_to_cell_map_negative() {
-        let p1 = RealWorldLocation::from_xyz(0.0, -2.0, 0.0);
-        let p2 = RealWorldLocation::from_xyz(-2.0, 0.0, 0.0);
-        let p3 = RealWorldLocation::from_xyz(-4.0, -2.0, 0.0);
+    /// The fraction of each cell covered by the given polygon, from `0.0` to
+    /// `1.0`, e.g. to weigh the cells of a region claimed by another robot
+    /// by how much of them it actually covers. See
+    /// [`PolygonMap::coverage`].
+    ///
+    /// The returned matrix has the same dimensions as the map. Parts of the
+    /// polygon lying outside the map are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PolygonMap::new`].
+    pub fn polygon_coverage(
+        &self,
+        vertices: &[RealWorldLocation],
+    ) -> Result<ndarray::Array2<f64>, PolygonMapError> {
+        let vertices = check_vertices(vertices)?;
 
-        let resolution = AxisResolution::uniform(2.0);
-        let cellmap: CellMap = PolygonMap::new(vec![p1, p2, p3])
-            .unwrap()
-            .to_cell_map(resolution);
+        Ok(coverage_onto_grid(
+            &to_geo_polygon(vertices),
+            *self.offset(),
+            self.resolution(),
+            self.cells().dim(),
+        ))
+    }
+}
 
-        assert_eq!(cellmap.width(), 8);
-        assert_eq!(cellmap.height(), 4);
+impl<P> LocalMap<CellMap, P> {
+    /// Vectorize the region assigned to the current robot into a
+    /// [`PolygonMap`].
+    ///
+    /// The outline of the [`MapState::Assigned`] cells is traced along the
+    /// cell edges, resulting in a compact representation of the partition which
+    /// can be exchanged with other robots or a ground station. If the assigned
+    /// cells form several disconnected regions, only the largest one is
+    /// exported. Holes in the region are not represented.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PolygonMapError::EmptyRegion`] if no cell is assigned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, LocalMap, MapState, RealWorldLocation,
+    ///     Robot,
+    /// };
+    ///
+    /// let mut map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     Robot::new(RealWorldLocation::from_xyz(1.5, 1.5, 0.0), ()),
+    ///     vec![],
+    /// )
+    /// .unwrap();
+    /// map.map_mut().set_rect(
+    ///     &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(5.0, 3.0, 0.0),
+    ///     MapState::Assigned,
+    /// );
+    ///
+    /// let polygon = map.partition_polygon().unwrap();
+    ///
+    /// assert_eq!(polygon.areas()[0].len(), 4);
+    /// assert!(polygon.areas()[0]
+    ///     .contains(&RealWorldLocation::from_xyz(5.0, 3.0, 0.0)));
+    /// ```
+    pub fn partition_polygon(&self) -> Result<PolygonMap, PolygonMapError> {
+        let map = self.map();
+        let largest = map
+            .components(|value| *value == MapState::Assigned)
+            .into_iter()
+            .max_by_key(|cells| cells.len())
+            .ok_or(PolygonMapError::EmptyRegion)?;
 
-        assert_eq!(
-            cellmap.cells(),
-            MapStateMatrix::from_shape_vec(
-                (cellmap.nrows(), cellmap.ncols()),
-                vec![
-                    UNE, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
-                    OOM, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
-                    OOM, OOM, UNE, UNE, UNE, UNE, UNE, OOM, //
-                    OOM, OOM, OOM, UNE, UNE, UNE, OOM, OOM, //
-                ]
-            )
-            .unwrap()
+        PolygonMap::new(
+            map.outline(&largest)
+                .into_iter()
+                .map(RealWorldLocation::new)
+                .collect(),
         )
     }
+}
 
-    #[test]
-    fn polygon_map_to_cell_map_partly_negative() {
-        let p1 = RealWorldLocation::from_xyz(-2.0, 0.0, 0.0);
-        let p2 = RealWorldLocation::from_xyz(0.0, 2.0, 0.0);
-        let p3 = RealWorldLocation::from_xyz(2.0, 0.0, 0.0);
-
-        let resolution = AxisResolution::uniform(2.0);
-        let cellmap: CellMap = PolygonMap::new(vec![p1, p2, p3])
-            .unwrap()
-            .to_cell_map(resolution);
+/// Internal helper moving the edges of a polygon by `distance`, see
+/// [`PolygonMap::buffer`].
+///
+/// # Errors
+///
+/// [`PolygonMapError::Collapsed`] if an edge vanishes.
+fn buffer_vertices(
+    vertices: &[RealWorldLocation],
+    distance: f64,
+) -> Result<Vec<RealWorldLocation>, PolygonMapError> {
+    // How much further than `distance` a corner may reach.
+    const MITER_LIMIT: f64 = 2.0;
 
-        assert_eq!(cellmap.width(), 8);
-        assert_eq!(cellmap.height(), 4);
+    let count = vertices.len();
+    let vertex = |i: usize| vertices[i % count];
+    // Normals pointing outwards, whichever way the polygon winds.
+    let sign = to_geo_polygon(vertices).signed_area().signum();
+    let normal = |from: RealWorldLocation, to: RealWorldLocation| {
+        let (dx, dy) = (to.x() - from.x(), to.y() - from.y());
+        let length = dx.hypot(dy);
+        (sign * dy / length, -sign * dx / length)
+    };
 
-        assert_eq!(
-            cellmap.cells(),
-            MapStateMatrix::from_shape_vec(
-                (cellmap.nrows(), cellmap.ncols()),
-                vec![
-                    UNE, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
-                    OOM, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
-                    OOM, OOM, UNE, UNE, UNE, UNE, UNE, OOM, //
-                    OOM, OOM, OOM, UNE, UNE, UNE, OOM, OOM, //
-                ]
+    let mut moved = Vec::with_capacity(count);
+    // The first and last new vertex of each corner, between which the
+    // moved edges run.
+    let mut corners = Vec::with_capacity(count);
+    for i in 0..count {
+        let (previous, current) = (vertex(i + count - 1), vertex(i));
+        let next = vertex(i + 1);
+        let (n1, n2) = (normal(previous, current), normal(current, next));
+        let at = |(x, y): (f64, f64)| {
+            RealWorldLocation::from_xyz(
+                current.x() + x * distance,
+                current.y() + y * distance,
+                current.z(),
+            )
+        };
+        // The joint lies along the bisector of both normals, far enough
+        // for both edges to be moved by `distance`.
+        let cos = n1.0 * n2.0 + n1.1 * n2.1;
+        let miter = 2.0 / (1.0 + cos);
+        let bisector =
+            ((n1.0 + n2.0) * miter / 2.0, (n1.1 + n2.1) * miter / 2.0);
+        let cross = n1.0 * n2.1 - n1.1 * n2.0;
+        // The edges move apart at corners turning towards the side they
+        // are moved to, which are cut off if they are too sharp.
+        let sticks_out = cross * sign * distance > 0.0 || cross == 0.0;
+        if sticks_out && bisector.0.hypot(bisector.1) > MITER_LIMIT {
+            corners.push([moved.len(), moved.len() + 1]);
+            moved.push(at(n1));
+            moved.push(at(n2));
+        } else {
+            corners.push([moved.len(); 2]);
+            moved.push(at(bisector));
+        }
+    }
+    // Edges which turned around were shrunk by more than their length.
+    for i in 0..count {
+        let (start, end) =
+            (moved[corners[i][1]], moved[corners[(i + 1) % count][0]]);
+        let (from, to) = (vertex(i), vertex(i + 1));
+        let dot = (end.x() - start.x()) * (to.x() - from.x())
+            + (end.y() - start.y()) * (to.y() - from.y());
+        if dot <= 0.0 {
+            return Err(PolygonMapError::Collapsed);
+        }
+    }
+
+    Ok(moved)
+}
+
+/// Internal helper checking that the vertices describe a valid polygon, see
+/// [`PolygonMap::new`] for the checks which are made.
+fn check_vertices(
+    vertices: &[RealWorldLocation],
+) -> Result<&[RealWorldLocation], PolygonMapError> {
+    let same = |a: &RealWorldLocation, b: &RealWorldLocation| {
+        a.x() == b.x() && a.y() == b.y()
+    };
+    let vertices = match vertices {
+        [first, rest @ .., last] if same(first, last) => {
+            &vertices[..rest.len() + 1]
+        }
+        _ => vertices,
+    };
+    if vertices.len() < 3 {
+        return Err(PolygonMapError::NotEnoughVertices);
+    }
+    if vertices.iter().any(|vertex| {
+        !(vertex.x().is_finite()
+            && vertex.y().is_finite()
+            && vertex.z().is_finite())
+    }) {
+        return Err(PolygonMapError::NonFiniteCoordinates);
+    }
+    let count = vertices.len();
+    if (0..count).any(|i| same(&vertices[i], &vertices[(i + 1) % count])) {
+        return Err(PolygonMapError::RepeatedVertex);
+    }
+
+    let polygon = to_geo_polygon(vertices);
+    let coords: Vec<_> = polygon.exterior().coords().copied().collect();
+    if coords.iter().all(|coord| {
+        <f64 as HasKernel>::Ker::orient2d(coords[0], coords[1], *coord)
+            == Orientation::Collinear
+    }) {
+        return Err(PolygonMapError::ZeroArea);
+    }
+
+    let edges: Vec<geo::Line> = polygon.exterior().lines().collect();
+    for (i, edge) in edges.iter().enumerate() {
+        for other in &edges[i + 1..] {
+            let shared = [edge.start, edge.end]
+                .iter()
+                .any(|end| *end == other.start || *end == other.end);
+            match line_intersection(*edge, *other) {
+                None => {}
+                // Edges sharing a vertex, but not overlapping beyond it.
+                Some(LineIntersection::SinglePoint { .. }) if shared => {}
+                Some(_) => return Err(PolygonMapError::SelfIntersecting),
+            }
+        }
+    }
+    // Where the polygon passes through a vertex several times, the passes
+    // must not cross each other.
+    let pass = |i: usize| {
+        [vertices[(i + count - 1) % count], vertices[(i + 1) % count]]
+            .map(|vertex| geo::Coord::from(&vertex))
+    };
+    for i in 0..count {
+        for j in i + 1..count {
+            if same(&vertices[i], &vertices[j])
+                && passes_cross((&vertices[i]).into(), pass(i), pass(j))
+            {
+                return Err(PolygonMapError::SelfIntersecting);
+            }
+        }
+    }
+    Ok(vertices)
+}
+
+/// Internal helper returning whether two passes of a polygon through the same
+/// `vertex` cross each other, given the previous and next vertex of each.
+fn passes_cross(
+    vertex: geo::Coord,
+    [from, to]: [geo::Coord; 2],
+    other: [geo::Coord; 2],
+) -> bool {
+    let angle =
+        |coord: geo::Coord| (coord.y - vertex.y).atan2(coord.x - vertex.x);
+    let (start, end) = (angle(from), angle(to));
+    // Whether a neighbor lies on the counter-clockwise arc from `from` to
+    // `to`, i.e. on one side of the first pass.
+    let within = |coord: geo::Coord| {
+        (angle(coord) - start).rem_euclid(TAU) < (end - start).rem_euclid(TAU)
+    };
+    within(other[0]) != within(other[1])
+}
+
+/// Internal helper to convert vertices into a [`geo::Polygon`], ignoring the
+/// `z` component.
+fn to_geo_polygon(vertices: &[RealWorldLocation]) -> geo::Polygon {
+    geo::Polygon::new(vertices.iter().map(geo::Coord::from).collect(), vec![])
+}
+
+/// Where a cell is sampled when rasterizing polygons, see [`RasterOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelSampling {
+    /// Sample the center of the cell, such that a cell is covered if most of
+    /// it usually is. The number of covered cells thus matches the area of
+    /// the polygon closely.
+    #[default]
+    Center,
+    /// Sample the bottom left corner of the cell, i.e. the corner with the
+    /// lowest coordinates, like the cell's location in the map.
+    Corner,
+}
+
+/// The convention used to decide which cells a polygon covers when it is
+/// rasterized, e.g. by [`PolygonMap::to_cell_map_with`].
+///
+/// Each cell is covered if its sample point, see [`PixelSampling`], lies
+/// within the polygon. The default samples the centers of the cells and
+/// includes sample points lying exactly on the edges of the polygon, such
+/// that polygons aligned with the cells cover exactly the cells within them.
+/// It takes a single sample per cell, which is the cheapest, but parts of
+/// the polygon narrower than a cell may be missed, see
+/// [`RasterOptions::supersampling`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterOptions {
+    /// The point of each cell which is checked against the polygon.
+    pub sampling: PixelSampling,
+    /// Whether sample points lying exactly on an edge of the polygon are
+    /// covered. Otherwise they are not, which e.g. drops the cells sampled
+    /// along the border of a polygon aligned with the cell corners.
+    pub inclusive_edges: bool,
+    /// The number of samples per cell along each axis, such that a cell is
+    /// covered if any of them lies within the polygon. The cell is split into
+    /// as many equal parts, each of which is sampled according to
+    /// [`RasterOptions::sampling`]. Parts of the polygon narrower than a
+    /// cell, such as a narrow corridor at a low resolution, are kept this way
+    /// as long as they are wider than one such part. The cost grows with the
+    /// square of the factor. `0` is treated like `1`, i.e. no supersampling.
+    pub supersampling: usize,
+}
+
+/// Samples the centers of the cells once, with inclusive edges.
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            sampling: PixelSampling::Center,
+            inclusive_edges: true,
+            supersampling: 1,
+        }
+    }
+}
+
+/// Internal helper to rasterize a polygon onto a grid of `(rows, columns)`
+/// cells, whose bottom left corner sits at `offset` in real-world coordinates,
+/// following the convention given by `options`.
+///
+/// Parts of the polygon lying outside the grid are clipped.
+fn rasterize_onto_grid(
+    polygon: &geo::Polygon,
+    offset: Coords,
+    resolution: &AxisResolution,
+    (rows, columns): (usize, usize),
+    options: RasterOptions,
+) -> ndarray::Array2<bool> {
+    let shift = match options.sampling {
+        PixelSampling::Center => 0.5,
+        PixelSampling::Corner => 0.0,
+    };
+    let samples = options.supersampling.max(1);
+    let to_f64 =
+        |value: usize| value.to_f64().expect("usize to f64 should work");
+    let sample = |index: usize, part: usize, resolution: f64| {
+        (to_f64(index) + (to_f64(part) + shift) / to_f64(samples)) / resolution
+    };
+
+    let mut grid = ndarray::Array2::from_elem((rows, columns), false);
+    let Some(bbox) = polygon.bounding_rect() else {
+        return grid;
+    };
+    // The samples of cell `i` lie within `[i, i + 1)` in grid units, so only
+    // the cells from the one containing the minimum up to the one containing
+    // the maximum of the bounding box can overlap the polygon.
+    let range = |min: f64, max: f64, offset: f64, resolution: f64, len| {
+        let clamp = |value: f64| {
+            value.max(0.0).min(to_f64(len)).to_usize().unwrap_or(0)
+        };
+        let start = ((min - offset) * resolution).floor();
+        let end = ((max - offset) * resolution).floor() + 1.0;
+        clamp(start)..clamp(end)
+    };
+    let row_range =
+        range(bbox.min().y, bbox.max().y, offset.y, resolution.y, rows);
+    let col_range =
+        range(bbox.min().x, bbox.max().x, offset.x, resolution.x, columns);
+
+    for row in row_range {
+        for col in col_range.clone() {
+            grid[[row, col]] = (0..samples)
+                .flat_map(|y| (0..samples).map(move |x| (x, y)))
+                .any(|(x, y)| {
+                    let point = geo::Coord {
+                        x: offset.x + sample(col, x, resolution.x),
+                        y: offset.y + sample(row, y, resolution.y),
+                    };
+                    if options.inclusive_edges {
+                        polygon.intersects(&point)
+                    } else {
+                        polygon.contains(&point)
+                    }
+                });
+        }
+    }
+    grid
+}
+
+/// Internal helper returning the bottom left corner and the `(rows,
+/// columns)` of the smallest grid covering the bounding box of the polygons.
+///
+/// # Panics
+///
+/// If the polygons have no bounding box, which cannot happen for the areas of
+/// a [`PolygonMap`] as they are validated by [`PolygonMap::new`].
+fn bounding_grid(
+    polygons: &geo::MultiPolygon,
+    resolution: &AxisResolution,
+) -> (Coords, (usize, usize)) {
+    let bbox = match polygons.bounding_rect() {
+        Some(b) => b,
+        None => panic!("No bounding box for polygon"),
+    };
+    let offset = Coords::new(bbox.min().x, bbox.min().y, 0.0);
+    // convert to pixels, allowing for rounding errors
+    let cells = |length: f64| {
+        (length - 1e-9)
+            .ceil()
+            .max(0.0)
+            .to_usize()
+            .expect("No conversion issues")
+    };
+    (
+        offset,
+        (
+            cells(bbox.height() * resolution.y),
+            cells(bbox.width() * resolution.x),
+        ),
+    )
+}
+
+/// Internal helper computing the fraction of each cell of a grid of `(rows,
+/// columns)` cells covered by a polygon, see [`rasterize_onto_grid`].
+///
+/// The polygon is clipped to each row of the grid, and then to each cell of
+/// the row, whose remaining area is the covered one.
+fn coverage_onto_grid(
+    polygon: &geo::Polygon,
+    offset: Coords,
+    resolution: &AxisResolution,
+    (rows, columns): (usize, usize),
+) -> ndarray::Array2<f64> {
+    // Work in cells, such that each cell has an area of 1.
+    let mut vertices: Vec<[f64; 2]> = polygon
+        .exterior()
+        .coords()
+        .map(|coord| {
+            [
+                (coord.x - offset.x) * resolution.x,
+                (coord.y - offset.y) * resolution.y,
+            ]
+        })
+        .collect();
+    // The ring is closed, but clipping does not need the last vertex again.
+    vertices.pop();
+
+    let mut coverage = ndarray::Array2::zeros((rows, columns));
+    for row in 0..rows {
+        let bottom = row.to_f64().expect("usize to f64 should work");
+        let strip =
+            clip(&clip(&vertices, 1, bottom, true), 1, bottom + 1.0, false);
+        if strip.is_empty() {
+            continue;
+        }
+        for col in 0..columns {
+            let left = col.to_f64().expect("usize to f64 should work");
+            let cell = clip(&clip(&strip, 0, left, true), 0, left + 1.0, false);
+            coverage[[row, col]] = shoelace_area(&cell).min(1.0);
+        }
+    }
+    coverage
+}
+
+/// Internal helper clipping a polygon to the half-plane on one side of an
+/// axis-parallel line, following Sutherland-Hodgman. The `axis` is `0` for
+/// `x` and `1` for `y`, and `above` keeps the part with larger coordinates.
+fn clip(
+    vertices: &[[f64; 2]],
+    axis: usize,
+    bound: f64,
+    above: bool,
+) -> Vec<[f64; 2]> {
+    let inside = |vertex: &[f64; 2]| {
+        if above {
+            vertex[axis] >= bound
+        } else {
+            vertex[axis] <= bound
+        }
+    };
+    let mut clipped = Vec::new();
+    for (i, current) in vertices.iter().enumerate() {
+        let previous = &vertices[(i + vertices.len() - 1) % vertices.len()];
+        if inside(current) != inside(previous) {
+            let t = (bound - previous[axis]) / (current[axis] - previous[axis]);
+            let mut crossing = [
+                previous[0] + t * (current[0] - previous[0]),
+                previous[1] + t * (current[1] - previous[1]),
+            ];
+            crossing[axis] = bound;
+            clipped.push(crossing);
+        }
+        if inside(current) {
+            clipped.push(*current);
+        }
+    }
+    clipped
+}
+
+/// Internal helper computing the unsigned area of a polygon.
+fn shoelace_area(vertices: &[[f64; 2]]) -> f64 {
+    let twice: f64 = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+        .sum();
+    twice.abs() / 2.0
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PolygonMapError {
+    /// At least 3 vertices are needed to form a proper polygon on which
+    /// anything meaningful can be done.
+    NotEnoughVertices,
+    /// A vertex has a NaN or infinite coordinate.
+    NonFiniteCoordinates,
+    /// Consecutive vertices lie at the same location, ignoring the `z`
+    /// component, such that an edge has no length.
+    RepeatedVertex,
+    /// All vertices lie on a line, such that the polygon does not cover
+    /// anything.
+    ZeroArea,
+    /// Edges of the polygon cross or touch each other, apart from at shared
+    /// vertices, such that its inside is ambiguous.
+    SelfIntersecting,
+    /// Several polygons of a [`PolygonMap`] overlap or touch each other.
+    OverlappingAreas,
+    /// Shrinking the polygon made edges vanish, see [`PolygonMap::buffer`].
+    Collapsed,
+    /// The region to be converted into a polygon does not contain any cells.
+    EmptyRegion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Location, LocationType, MapState, MapStateMatrix, MaskMapState,
+    };
+
+    const OOM: LocationType = LocationType::OutOfMap;
+    const UNE: LocationType = LocationType::Unexplored;
+    const EXP: LocationType = LocationType::Explored;
+    const FNT: LocationType = LocationType::Frontier;
+    const OBS: LocationType = LocationType::Obstacle;
+
+    #[test]
+    fn polygon_map_to_cell_map_positive() {
+        let p1 = RealWorldLocation::from_xyz(0.0, 0.0, 0.0);
+        let p2 = RealWorldLocation::from_xyz(4.0, 4.0, 0.0);
+        let p3 = RealWorldLocation::from_xyz(8.0, 0.0, 0.0);
+
+        let resolution = AxisResolution::uniform(1.0);
+        let cellmap: CellMap = PolygonMap::new(vec![p1, p2, p3])
+            .unwrap()
+            .to_cell_map(resolution);
+
+        assert_eq!(cellmap.width(), 8);
+        assert_eq!(cellmap.height(), 4);
+
+        assert_eq!(
+            cellmap.cells(),
+            MapStateMatrix::from_shape_vec(
+                (cellmap.nrows(), cellmap.ncols()),
+                vec![
+                    UNE, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
+                    OOM, UNE, UNE, UNE, UNE, UNE, UNE, OOM, //
+                    OOM, OOM, UNE, UNE, UNE, UNE, OOM, OOM, //
+                    OOM, OOM, OOM, UNE, UNE, OOM, OOM, OOM, //
+                ]
             )
             .unwrap()
         )
     }
+
+    #[test]
+    fn polygon_map_to_cell_map_negative() {
+        let p1 = RealWorldLocation::from_xyz(0.0, -2.0, 0.0);
+        let p2 = RealWorldLocation::from_xyz(-2.0, 0.0, 0.0);
+        let p3 = RealWorldLocation::from_xyz(-4.0, -2.0, 0.0);
+
+        let resolution = AxisResolution::uniform(2.0);
+        let cellmap: CellMap = PolygonMap::new(vec![p1, p2, p3])
+            .unwrap()
+            .to_cell_map(resolution);
+
+        assert_eq!(cellmap.width(), 8);
+        assert_eq!(cellmap.height(), 4);
+
+        assert_eq!(
+            cellmap.cells(),
+            MapStateMatrix::from_shape_vec(
+                (cellmap.nrows(), cellmap.ncols()),
+                vec![
+                    UNE, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
+                    OOM, UNE, UNE, UNE, UNE, UNE, UNE, OOM, //
+                    OOM, OOM, UNE, UNE, UNE, UNE, OOM, OOM, //
+                    OOM, OOM, OOM, UNE, UNE, OOM, OOM, OOM, //
+                ]
+            )
+            .unwrap()
+        )
+    }
+
+    #[test]
+    fn polygon_map_to_cell_map_partly_negative() {
+        let p1 = RealWorldLocation::from_xyz(-2.0, 0.0, 0.0);
+        let p2 = RealWorldLocation::from_xyz(0.0, 2.0, 0.0);
+        let p3 = RealWorldLocation::from_xyz(2.0, 0.0, 0.0);
+
+        let resolution = AxisResolution::uniform(2.0);
+        let cellmap: CellMap = PolygonMap::new(vec![p1, p2, p3])
+            .unwrap()
+            .to_cell_map(resolution);
+
+        assert_eq!(cellmap.width(), 8);
+        assert_eq!(cellmap.height(), 4);
+
+        assert_eq!(
+            cellmap.cells(),
+            MapStateMatrix::from_shape_vec(
+                (cellmap.nrows(), cellmap.ncols()),
+                vec![
+                    UNE, UNE, UNE, UNE, UNE, UNE, UNE, UNE, //
+                    OOM, UNE, UNE, UNE, UNE, UNE, UNE, OOM, //
+                    OOM, OOM, UNE, UNE, UNE, UNE, OOM, OOM, //
+                    OOM, OOM, OOM, UNE, UNE, OOM, OOM, OOM, //
+                ]
+            )
+            .unwrap()
+        )
+    }
+
+    #[test]
+    fn raster_conventions() {
+        let triangle = || {
+            PolygonMap::new(vec![
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+                RealWorldLocation::from_xyz(8.0, 0.0, 0.0),
+            ])
+            .unwrap()
+        };
+        let count = |sampling, inclusive_edges| {
+            triangle()
+                .to_cell_map_with(
+                    AxisResolution::uniform(1.0),
+                    RasterOptions {
+                        sampling,
+                        inclusive_edges,
+                        ..RasterOptions::default()
+                    },
+                )
+                .get_map_state(MapState::Unexplored)
+                .len()
+        };
+
+        // The centers of the outermost cells of each row lie on the edges.
+        assert_eq!(count(PixelSampling::Center, true), 8 + 6 + 4 + 2);
+        assert_eq!(count(PixelSampling::Center, false), 6 + 4 + 2);
+        // Corners along the bottom edge and both sloped edges.
+        assert_eq!(count(PixelSampling::Corner, true), 8 + 7 + 5 + 3);
+        assert_eq!(count(PixelSampling::Corner, false), 5 + 3 + 1);
+    }
+
+    #[test]
+    fn rasterize_clips_to_bounding_box() {
+        let square = geo::Polygon::new(
+            geo::LineString::from(vec![
+                (2.0, 2.0),
+                (4.0, 2.0),
+                (4.0, 4.0),
+                (2.0, 4.0),
+            ]),
+            vec![],
+        );
+        let options = RasterOptions {
+            sampling: PixelSampling::Corner,
+            inclusive_edges: true,
+            ..RasterOptions::default()
+        };
+
+        let covered = rasterize_onto_grid(
+            &square,
+            Coords::new(0.0, 0.0, 0.0),
+            &AxisResolution::uniform(1.0),
+            (8, 8),
+            options,
+        );
+
+        // The corners on the upper and right edges are still included.
+        let expected = ndarray::Array2::from_shape_fn((8, 8), |(row, col)| {
+            (2..=4).contains(&row) && (2..=4).contains(&col)
+        });
+        assert_eq!(covered, expected);
+    }
+
+    #[test]
+    fn cell_map_covers_partial_cells() {
+        let cellmap = PolygonMap::new(vec![
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.5, 0.0, 0.0),
+            RealWorldLocation::from_xyz(2.5, 1.0, 0.0),
+            RealWorldLocation::from_xyz(0.0, 1.0, 0.0),
+        ])
+        .unwrap()
+        .to_cell_map(AxisResolution::uniform(1.0));
+
+        assert_eq!(cellmap.cells().dim(), (1, 3));
+        assert_eq!(cellmap.cells()[[0, 2]], UNE);
+    }
+
+    #[test]
+    fn supersampling_keeps_narrow_corridors() {
+        // An L-shaped area, whose corridor is a quarter of a cell wide.
+        let map = || {
+            PolygonMap::new(vec![
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(8.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(8.0, 0.25, 0.0),
+                RealWorldLocation::from_xyz(2.0, 0.25, 0.0),
+                RealWorldLocation::from_xyz(2.0, 4.0, 0.0),
+                RealWorldLocation::from_xyz(0.0, 4.0, 0.0),
+            ])
+            .unwrap()
+        };
+        let count = |supersampling| {
+            map()
+                .to_cell_map_with(
+                    AxisResolution::uniform(1.0),
+                    RasterOptions {
+                        supersampling,
+                        ..RasterOptions::default()
+                    },
+                )
+                .get_map_state(MapState::Unexplored)
+                .len()
+        };
+
+        assert_eq!(count(0), 4 * 2);
+        assert_eq!(count(1), 4 * 2);
+        // Samples at a quarter of a cell lie on the edge of the corridor.
+        assert_eq!(count(2), 4 * 2 + 6);
+        assert_eq!(count(4), 4 * 2 + 6);
+        assert_eq!(count(8), 4 * 2 + 6);
+    }
+
+    #[test]
+    fn coverage_of_boundary_cells() {
+        let map = PolygonMap::new(vec![
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            RealWorldLocation::from_xyz(8.0, 0.0, 0.0),
+        ])
+        .unwrap();
+
+        let coverage = map.coverage(AxisResolution::uniform(1.0));
+
+        assert_eq!(coverage.dim(), (4, 8));
+        assert_eq!(coverage.sum(), 16.0);
+        assert_eq!(
+            coverage.row(3).to_vec(),
+            [0., 0., 0., 0.5, 0.5, 0., 0., 0.]
+        );
+        assert_eq!(coverage[[0, 0]], 0.5);
+        assert_eq!(coverage[[0, 1]], 1.0);
+        assert!(coverage.iter().all(|c| (0.0..=1.0).contains(c)));
+    }
+
+    #[test]
+    fn polygon_coverage_on_map() {
+        let cellmap = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        // A square rotated by 45 degrees, reaching beyond the map.
+        let diamond = [
+            RealWorldLocation::from_xyz(4.0, -1.0, 0.0),
+            RealWorldLocation::from_xyz(6.0, 1.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 3.0, 0.0),
+            RealWorldLocation::from_xyz(2.0, 1.0, 0.0),
+        ];
+
+        let coverage = cellmap.polygon_coverage(&diamond).unwrap();
+
+        assert_eq!(coverage.dim(), (4, 4));
+        assert_eq!(coverage[[0, 2]], 0.5);
+        assert_eq!(coverage[[0, 3]], 1.0);
+        assert_eq!(coverage[[1, 3]], 1.0);
+        assert_eq!(coverage[[2, 3]], 0.5);
+        // Half of the diamond, without the corner below the map.
+        assert_eq!(coverage.sum(), 4.0 - 0.5);
+        assert_eq!(
+            cellmap.polygon_coverage(&diamond[..2]),
+            Err(PolygonMapError::NotEnoughVertices)
+        );
+    }
+
+    #[test]
+    fn set_polygon_keeps_existing_content() {
+        let mut cellmap = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(8.0, 4.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        cellmap
+            .set_location(
+                &RealWorldLocation::from_xyz(0.5, 3.5, 0.0),
+                MapState::Frontier,
+            )
+            .unwrap();
+
+        let count = cellmap
+            .set_polygon(
+                &[
+                    RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                    RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+                    RealWorldLocation::from_xyz(8.0, 0.0, 0.0),
+                ],
+                MapState::Explored,
+            )
+            .unwrap();
+
+        assert_eq!(count, 8 + 6 + 4 + 2);
+        assert_eq!(
+            cellmap.cells(),
+            MapStateMatrix::from_shape_vec(
+                (cellmap.nrows(), cellmap.ncols()),
+                vec![
+                    EXP, EXP, EXP, EXP, EXP, EXP, EXP, EXP, //
+                    UNE, EXP, EXP, EXP, EXP, EXP, EXP, UNE, //
+                    UNE, UNE, EXP, EXP, EXP, EXP, UNE, UNE, //
+                    FNT, UNE, UNE, EXP, EXP, UNE, UNE, UNE, //
+                ]
+            )
+            .unwrap()
+        )
+    }
+
+    #[test]
+    fn set_polygon_partly_outside_map() {
+        let mut cellmap = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        let count = cellmap
+            .set_polygon(
+                &[
+                    RealWorldLocation::from_xyz(-10.0, -10.0, 0.0),
+                    RealWorldLocation::from_xyz(2.0, -10.0, 0.0),
+                    RealWorldLocation::from_xyz(2.0, 10.0, 0.0),
+                    RealWorldLocation::from_xyz(-10.0, 10.0, 0.0),
+                ],
+                MapState::Assigned,
+            )
+            .unwrap();
+
+        assert_eq!(count, cellmap.get_map_state(MapState::Assigned).len());
+        assert_eq!(count, 8);
+    }
+
+    #[test]
+    fn cells_in_polygon_match_set_polygon() {
+        let mut cellmap = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(8.0, 4.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        let triangle = [
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            RealWorldLocation::from_xyz(8.0, 0.0, 0.0),
+        ];
+        cellmap
+            .set_location(
+                &RealWorldLocation::from_xyz(3.5, 1.5, 0.0),
+                MapState::Frontier,
+            )
+            .unwrap();
+
+        let all: Vec<_> = cellmap
+            .cells_in_polygon(&triangle, |_| true)
+            .unwrap()
+            .iter()
+            .map(|cell| *cell.location())
+            .collect();
+        let frontiers = cellmap
+            .cells_in_polygon(&triangle, |state| state == MapState::Frontier)
+            .unwrap();
+        assert_eq!(frontiers.len(), 1);
+        cellmap.set_polygon(&triangle, MapState::Explored).unwrap();
+
+        assert_eq!(
+            all,
+            cellmap
+                .get_map_state(MapState::Explored)
+                .iter()
+                .map(|cell| *cell.location())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            cellmap.cells_in_polygon(&triangle[..2], |_| true).err(),
+            Some(PolygonMapError::NotEnoughVertices)
+        );
+    }
+
+    #[test]
+    fn set_polygon_not_enough_vertices() {
+        let mut cellmap = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        assert_eq!(
+            cellmap.set_polygon(
+                &[
+                    RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                    RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+                ],
+                MapState::Explored
+            ),
+            Err(PolygonMapError::NotEnoughVertices)
+        );
+    }
+
+    fn local_map() -> LocalMap<CellMap, ()> {
+        LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(8.0, 8.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            crate::Robot::new(RealWorldLocation::from_xyz(7.5, 7.5, 0.0), ()),
+            vec![],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn partition_polygon_largest_region() {
+        let mut map = local_map();
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            &RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+            MapState::Assigned,
+        );
+        // L-shaped region.
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+            &RealWorldLocation::from_xyz(6.0, 4.0, 0.0),
+            MapState::Assigned,
+        );
+        map.map_mut().set_rect(
+            &RealWorldLocation::from_xyz(3.0, 4.0, 0.0),
+            &RealWorldLocation::from_xyz(4.0, 6.0, 0.0),
+            MapState::Assigned,
+        );
+
+        let polygon = map.partition_polygon().unwrap();
+
+        let mut vertices: Vec<_> =
+            polygon.areas()[0].iter().map(|v| (v.x(), v.y())).collect();
+        vertices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            vertices,
+            [
+                (3.0, 3.0),
+                (3.0, 6.0),
+                (4.0, 4.0),
+                (4.0, 6.0),
+                (6.0, 3.0),
+                (6.0, 4.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn geo_polygon_of_map() {
+        let map = PolygonMap::new_explored(
+            vec![
+                RealWorldLocation::from_xyz(0.0, 0.0, 1.0),
+                RealWorldLocation::from_xyz(2.0, 0.0, 1.0),
+                RealWorldLocation::from_xyz(0.0, 2.0, 1.0),
+            ],
+            Some(vec![vec![
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(1.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(0.0, 1.0, 0.0),
+            ]]),
+        )
+        .unwrap();
+
+        let polygons = geo::MultiPolygon::from(&map);
+        let polygon = &polygons.0[0];
+
+        assert_eq!(
+            polygon
+                .exterior()
+                .points()
+                .map(|p| p.x_y())
+                .collect::<Vec<_>>(),
+            [(0.0, 0.0), (2.0, 0.0), (0.0, 2.0), (0.0, 0.0)]
+        );
+        assert!(polygon.interiors().is_empty());
+        assert_eq!(polygons.0.len(), 1);
+        assert_eq!(map.explored_as_geo().0.len(), 1);
+        assert!(PolygonMap::new(map.areas()[0].clone())
+            .unwrap()
+            .explored_as_geo()
+            .0
+            .is_empty());
+    }
+
+    #[test]
+    fn invalid_polygons() {
+        let polygon = |vertices: &[(f64, f64)]| {
+            PolygonMap::new(
+                vertices
+                    .iter()
+                    .map(|(x, y)| RealWorldLocation::from_xyz(*x, *y, 0.0))
+                    .collect(),
+            )
+            .err()
+        };
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+
+        assert_eq!(polygon(&square), None);
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (f64::NAN, 0.0), (1.0, 1.0)]),
+            Some(PolygonMapError::NonFiniteCoordinates)
+        );
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (1.0, f64::INFINITY), (1.0, 1.0)]),
+            Some(PolygonMapError::NonFiniteCoordinates)
+        );
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (2.0, 0.0), (2.0, 0.0), (0.0, 2.0)]),
+            Some(PolygonMapError::RepeatedVertex)
+        );
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (2.0, 0.0), (0.0, 0.0)]),
+            Some(PolygonMapError::NotEnoughVertices)
+        );
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (1.0, 1.0), (3.0, 3.0)]),
+            Some(PolygonMapError::ZeroArea)
+        );
+        // Touching, crossing and overlapping edges.
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (4.0, 0.0), (2.0, 0.0), (2.0, 2.0)]),
+            Some(PolygonMapError::SelfIntersecting)
+        );
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]),
+            Some(PolygonMapError::SelfIntersecting)
+        );
+        assert_eq!(
+            polygon(&[
+                (0.0, 0.0),
+                (4.0, 0.0),
+                (4.0, 4.0),
+                (2.0, 0.0),
+                (0.0, 4.0)
+            ]),
+            Some(PolygonMapError::SelfIntersecting)
+        );
+        // Collinear neighbors are fine.
+        assert_eq!(
+            polygon(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (1.0, 1.0)]),
+            None
+        );
+        assert_eq!(
+            PolygonMap::new_explored(
+                square
+                    .iter()
+                    .map(|(x, y)| RealWorldLocation::from_xyz(*x, *y, 0.0))
+                    .collect(),
+                Some(vec![vec![
+                    RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                    RealWorldLocation::from_xyz(1.0, 1.0, f64::NAN),
+                    RealWorldLocation::from_xyz(1.0, 0.0, 0.0),
+                ]])
+            )
+            .err(),
+            Some(PolygonMapError::NonFiniteCoordinates)
+        );
+    }
+
+    #[test]
+    fn buffer_expands_and_shrinks() {
+        // An L-shaped area with one reflex corner, wound clockwise.
+        let map = PolygonMap::new(vec![
+            RealWorldLocation::from_xyz(0.0, 0.0, 1.0),
+            RealWorldLocation::from_xyz(0.0, 4.0, 1.0),
+            RealWorldLocation::from_xyz(2.0, 4.0, 1.0),
+            RealWorldLocation::from_xyz(2.0, 2.0, 1.0),
+            RealWorldLocation::from_xyz(4.0, 2.0, 1.0),
+            RealWorldLocation::from_xyz(4.0, 0.0, 1.0),
+        ])
+        .unwrap();
+
+        let expanded = map.buffer(1.0).unwrap();
+        let shrunk = map.buffer(-0.5).unwrap();
+
+        assert_eq!(expanded.areas()[0].len(), 6);
+        assert_eq!(expanded.area(), 6.0 * 6.0 - 2.0 * 2.0);
+        assert_eq!(
+            expanded.areas()[0][3],
+            RealWorldLocation::from_xyz(3.0, 3.0, 1.0)
+        );
+        assert_eq!(shrunk.area(), 3.0 * 3.0 - 2.0 * 2.0);
+        assert!(map.contains_all(&shrunk.areas()[0]));
+        assert_eq!(map.buffer(0.0).unwrap().areas()[0], map.areas()[0]);
+        assert_eq!(map.buffer(-1.5).err(), Some(PolygonMapError::Collapsed));
+    }
+
+    #[test]
+    fn buffer_cuts_off_sharp_corners() {
+        let map = PolygonMap::new(vec![
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(0.0, 1.0, 0.0),
+        ])
+        .unwrap();
+
+        let expanded = map.buffer(0.5).unwrap();
+
+        // Only the sharp corner at the right is cut off.
+        assert_eq!(expanded.areas()[0].len(), 4);
+        let [min, max] = expanded.bounding_box();
+        assert!(max.x() < 10.0 + 2.0 * 0.5);
+        assert_eq!(min.x(), -0.5);
+        assert!(expanded.contains_all(&map.areas()[0]));
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<RealWorldLocation> {
+        vec![
+            RealWorldLocation::from_xyz(x, y, 0.0),
+            RealWorldLocation::from_xyz(x + size, y, 0.0),
+            RealWorldLocation::from_xyz(x + size, y + size, 0.0),
+            RealWorldLocation::from_xyz(x, y + size, 0.0),
+        ]
+    }
+
+    #[test]
+    fn multiple_areas() {
+        let map = PolygonMap::new_multi(vec![
+            square(0.0, 0.0, 2.0),
+            square(3.0, 1.0, 1.0),
+            square(0.0, 3.0, 1.0),
+        ])
+        .unwrap();
+
+        assert_eq!(map.areas().len(), 3);
+        assert_eq!(map.areas()[0], square(0.0, 0.0, 2.0));
+        assert_eq!(geo::MultiPolygon::from(&map).0.len(), 3);
+        assert_eq!(map.area(), 6.0);
+        assert_eq!(map.perimeter(), 8.0 + 4.0 + 4.0);
+        assert_eq!(
+            map.bounding_box(),
+            [
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            ]
+        );
+        assert_eq!(
+            map.centroid(),
+            RealWorldLocation::from_xyz(
+                (4.0 + 3.5 + 0.5) / 6.0,
+                (4.0 + 1.5 + 3.5) / 6.0,
+                0.0
+            )
+        );
+        assert!(map.contains(&RealWorldLocation::from_xyz(3.5, 1.5, 0.0)));
+        assert!(!map.contains(&RealWorldLocation::from_xyz(2.5, 1.5, 0.0)));
+
+        let coverage = map.coverage(AxisResolution::uniform(2.0));
+        assert_eq!(coverage.dim(), (8, 8));
+        assert_eq!(coverage.sum(), 6.0 * 4.0);
+
+        let cellmap = map.to_cell_map(AxisResolution::uniform(1.0));
+        assert_eq!(cellmap.cells().dim(), (4, 4));
+        assert_eq!(
+            cellmap.cells(),
+            MapStateMatrix::from_shape_vec(
+                (4, 4),
+                vec![
+                    UNE, UNE, OOM, OOM, //
+                    UNE, UNE, OOM, UNE, //
+                    OOM, OOM, OOM, OOM, //
+                    UNE, OOM, OOM, OOM, //
+                ]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn overlapping_areas() {
+        assert_eq!(
+            PolygonMap::new_multi(vec![]).err(),
+            Some(PolygonMapError::NotEnoughVertices)
+        );
+        assert_eq!(
+            PolygonMap::new_multi(vec![
+                square(0.0, 0.0, 2.0),
+                square(1.0, 1.0, 2.0)
+            ])
+            .err(),
+            Some(PolygonMapError::OverlappingAreas)
+        );
+        // One area within the other.
+        assert_eq!(
+            PolygonMap::new_multi(vec![
+                square(0.0, 0.0, 4.0),
+                square(1.0, 1.0, 1.0)
+            ])
+            .err(),
+            Some(PolygonMapError::OverlappingAreas)
+        );
+        assert_eq!(
+            PolygonMap::new_multi(vec![
+                square(0.0, 0.0, 1.0),
+                square(0.0, 1.0, 1.0)
+            ])
+            .err(),
+            Some(PolygonMapError::OverlappingAreas)
+        );
+
+        let map = PolygonMap::new_multi(vec![
+            square(0.0, 0.0, 1.0),
+            square(2.0, 0.0, 1.0),
+        ])
+        .unwrap();
+        assert_eq!(map.buffer(0.25).unwrap().area(), 2.0 * 1.5 * 1.5);
+        assert_eq!(
+            map.buffer(0.5).err(),
+            Some(PolygonMapError::OverlappingAreas)
+        );
+    }
+
+    #[test]
+    fn add_explored_regions() {
+        let mut map = PolygonMap::new_explored(
+            square(0.0, 0.0, 4.0),
+            Some(vec![square(0.0, 0.0, 1.0)]),
+        )
+        .unwrap();
+
+        map.add_explored_region(square(2.0, 2.0, 1.0)).unwrap();
+        // Only the part within the map matters.
+        map.add_explored_region(square(3.0, 3.0, 2.0)).unwrap();
+
+        assert_eq!(
+            map.add_explored_region(square(0.0, 0.0, 1.0)[..2].to_vec()),
+            Err(PolygonMapError::NotEnoughVertices)
+        );
+        assert_eq!(map.explored().len(), 3);
+        assert_eq!(map.explored()[1], square(2.0, 2.0, 1.0));
+        assert_eq!(map.explored_as_geo().0.len(), 3);
+        let cellmap = map.to_cell_map(AxisResolution::uniform(1.0));
+        let explored: Vec<_> = cellmap
+            .get_map_state(MapState::Explored)
+            .iter()
+            .map(|cell| (*cell.x(), *cell.y()))
+            .collect();
+        assert_eq!(explored, [(0.0, 0.0), (2.0, 2.0), (3.0, 3.0)]);
+        assert!(PolygonMap::new(square(0.0, 0.0, 1.0))
+            .unwrap()
+            .explored()
+            .is_empty());
+    }
+
+    #[test]
+    fn obstacles_within_map() {
+        let mut map = PolygonMap::new_multi(vec![
+            square(0.0, 0.0, 3.0),
+            square(4.0, 0.0, 2.0),
+        ])
+        .unwrap();
+        map.add_explored_region(square(0.0, 0.0, 2.0)).unwrap();
+        // Between both areas, overlapping the explored region.
+        map.add_obstacle(square(1.0, 1.0, 4.0)).unwrap();
+
+        assert_eq!(map.obstacles(), [square(1.0, 1.0, 4.0)]);
+        assert_eq!(map.obstacles_as_geo().0.len(), 1);
+        assert_eq!(
+            map.add_obstacle(vec![]),
+            Err(PolygonMapError::NotEnoughVertices)
+        );
+        assert_eq!(map.obstacles().len(), 1);
+        let buffered = map.buffer(0.0).unwrap();
+        assert_eq!(buffered.obstacles(), map.obstacles());
+
+        let cellmap = map.to_cell_map(AxisResolution::uniform(1.0));
+        assert_eq!(
+            cellmap.cells(),
+            MapStateMatrix::from_shape_vec(
+                (3, 6),
+                vec![
+                    EXP, EXP, UNE, OOM, UNE, UNE, //
+                    EXP, OBS, OBS, OOM, OBS, UNE, //
+                    UNE, OBS, OBS, OOM, OOM, OOM, //
+                ]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn contains_locations() {
+        let map = PolygonMap::new_explored(
+            vec![
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+                RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+                RealWorldLocation::from_xyz(2.0, 4.0, 0.0),
+                RealWorldLocation::from_xyz(0.0, 4.0, 0.0),
+            ],
+            Some(vec![vec![
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(1.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(0.0, 1.0, 0.0),
+            ]]),
+        )
+        .unwrap();
+        let inside = [
+            RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+            RealWorldLocation::from_xyz(3.0, 1.0, 0.0),
+            RealWorldLocation::from_xyz(1.0, 3.0, -1.0),
+            RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+        ];
+        let outside = RealWorldLocation::from_xyz(3.0, 3.0, 0.0);
+
+        assert!(inside.iter().all(|location| map.contains(location)));
+        assert!(!map.contains(&outside));
+        assert!(!map.contains(&RealWorldLocation::from_xyz(-0.1, 1.0, 0.0)));
+        assert!(map.contains_all(&inside));
+        assert!(!map.contains_all(&[inside[0], outside]));
+        assert!(map.contains_all(&[]));
+    }
+
+    #[test]
+    fn geometric_properties() {
+        // An L-shaped area.
+        let map = PolygonMap::new(vec![
+            RealWorldLocation::from_xyz(-1.0, 0.0, 2.0),
+            RealWorldLocation::from_xyz(3.0, 0.0, 2.0),
+            RealWorldLocation::from_xyz(3.0, 2.0, 2.0),
+            RealWorldLocation::from_xyz(1.0, 2.0, 2.0),
+            RealWorldLocation::from_xyz(1.0, 4.0, 2.0),
+            RealWorldLocation::from_xyz(-1.0, 4.0, 2.0),
+        ])
+        .unwrap();
+
+        assert_eq!(map.area(), 12.0);
+        assert_eq!(map.perimeter(), 16.0);
+        let centroid = map.centroid();
+        assert!((centroid.x() - 2.0 / 3.0).abs() < 1e-12);
+        assert!((centroid.y() - 5.0 / 3.0).abs() < 1e-12);
+        assert_eq!(centroid.z(), 0.0);
+        assert_eq!(
+            map.bounding_box(),
+            [
+                RealWorldLocation::from_xyz(-1.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(3.0, 4.0, 0.0),
+            ]
+        );
+        // The area matches the number of cells of the map.
+        assert_eq!(
+            map.to_cell_map(AxisResolution::uniform(2.0))
+                .get_map_state(MapState::Unexplored)
+                .len(),
+            12 * 4
+        );
+    }
+
+    #[test]
+    fn closed_rings() {
+        let mut closed = square(0.0, 0.0, 2.0);
+        closed.push(closed[0]);
+
+        let map = PolygonMap::new(closed.clone()).unwrap();
+
+        assert_eq!(map.areas(), &[square(0.0, 0.0, 2.0)]);
+        assert_eq!(map.area(), 4.0);
+        let mut cells = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        assert_eq!(cells.cells_in_polygon(&closed, |_| true).unwrap().len(), 4);
+        assert_eq!(cells.set_polygon(&closed, MapState::Explored), Ok(4));
+        assert!(
+            PolygonMap::new_multi(vec![closed, square(3.0, 0.0, 1.0)]).is_ok()
+        );
+    }
+
+    #[test]
+    fn pinched_polygons() {
+        let polygon = |vertices: &[(f64, f64)]| {
+            PolygonMap::new(
+                vertices
+                    .iter()
+                    .map(|(x, y)| RealWorldLocation::from_xyz(*x, *y, 0.0))
+                    .collect(),
+            )
+            .err()
+        };
+
+        // A square whose hole touches its outline at (2, 2).
+        assert_eq!(
+            polygon(&[
+                (0.0, 0.0),
+                (3.0, 0.0),
+                (3.0, 2.0),
+                (2.0, 2.0),
+                (2.0, 1.0),
+                (1.0, 1.0),
+                (1.0, 2.0),
+                (2.0, 2.0),
+                (2.0, 3.0),
+                (0.0, 3.0),
+            ]),
+            None
+        );
+        // Two triangles touching at (2, 2).
+        assert_eq!(
+            polygon(&[
+                (0.0, 0.0),
+                (2.0, 2.0),
+                (4.0, 0.0),
+                (4.0, 4.0),
+                (2.0, 2.0),
+                (0.0, 4.0),
+            ]),
+            None
+        );
+        // Passing through (2, 2) twice, crossing itself there.
+        assert_eq!(
+            polygon(&[
+                (0.0, 0.0),
+                (2.0, 2.0),
+                (4.0, 4.0),
+                (4.0, 0.0),
+                (2.0, 2.0),
+                (0.0, 4.0),
+            ]),
+            Some(PolygonMapError::SelfIntersecting)
+        );
+    }
+
+    #[test]
+    fn partition_polygon_pinched_region() {
+        let mut map = local_map();
+        // A 3x3 ring whose hole touches the outline at a corner.
+        for index in [[0, 0], [0, 1], [0, 2], [1, 0], [1, 2], [2, 0], [2, 1]] {
+            map.map_mut().set_cell(index, MapState::Assigned);
+        }
+
+        let polygon = map.partition_polygon().unwrap();
+
+        assert_eq!(polygon.areas()[0].len(), 6);
+        assert_eq!(polygon.area(), 8.0);
+        assert!(polygon.contains(&RealWorldLocation::from_xyz(2.5, 0.5, 0.0)));
+        assert!(!polygon.contains(&RealWorldLocation::from_xyz(2.5, 2.5, 0.0)));
+    }
+
+    #[test]
+    fn partition_polygon_empty() {
+        let map = local_map();
+
+        assert_eq!(
+            map.partition_polygon().err(),
+            Some(PolygonMapError::EmptyRegion)
+        );
+    }
 }
@@ -0,0 +1,240 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{imageops, Delay, DynamicImage, Frame, ImageResult, RgbImage};
+
+use crate::{MapState, Visualize};
+
+/// Record the evolution of a map as a sequence of images.
+///
+/// Each call to [`MapRecorder::record`] stores a snapshot of the map, as
+/// rendered by [`Visualize::as_image`]. The snapshots can then be written as
+/// an animated GIF or as numbered PNG files.
+///
+/// Maps may change their size in between snapshots, e.g. a [`LocalMap`]
+/// expanding to fit a robot. In that case, all frames are placed in the top
+/// left corner of a canvas large enough to fit the largest one, with the
+/// remaining pixels colored as [`MapState::OutOfMap`].
+///
+/// [`LocalMap`]: crate::LocalMap
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use local_robot_map::{
+///     AxisResolution, CellMap, Location, MapRecorder, MapState,
+///     RealWorldLocation,
+/// };
+///
+/// let mut map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+/// let mut recorder = MapRecorder::new(Duration::from_millis(200));
+///
+/// for x in 0..4 {
+///     let location = RealWorldLocation::from_xyz(x as f64 + 0.5, 0.5, 0.0);
+///     map.set_location(&location, MapState::Explored).unwrap();
+///     recorder.record(&map);
+/// }
+///
+/// let mut gif = Vec::new();
+/// recorder.write_gif(&mut gif).unwrap();
+/// assert_eq!(recorder.len(), 4);
+/// assert_eq!(&gif[..6], b"GIF89a");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MapRecorder {
+    frames: Vec<RgbImage>,
+    frame_duration: Duration,
+}
+
+impl MapRecorder {
+    /// Create a recorder showing each frame for `frame_duration` when
+    /// written as a GIF.
+    pub fn new(frame_duration: Duration) -> Self {
+        Self {
+            frames: Vec::new(),
+            frame_duration,
+        }
+    }
+
+    /// Store a snapshot of the map.
+    pub fn record<M>(&mut self, map: &M)
+    where
+        M: Visualize<ImageType = RgbImage>,
+    {
+        self.frames.push(map.as_image());
+    }
+
+    /// Store an already rendered snapshot, e.g. one drawn with custom colors
+    /// by [`CellMap::as_image_with`] or with an overlay by
+    /// [`LocalMap::as_image_with_overlay`].
+    ///
+    /// [`CellMap::as_image_with`]: crate::CellMap::as_image_with
+    /// [`LocalMap::as_image_with_overlay`]: crate::LocalMap::as_image_with_overlay
+    pub fn record_image(&mut self, image: RgbImage) {
+        self.frames.push(image);
+    }
+
+    /// The recorded snapshots, in the order they were recorded.
+    pub fn frames(&self) -> &[RgbImage] {
+        &self.frames
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        self.frame_duration
+    }
+
+    /// Number of recorded snapshots.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no snapshot was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Discard all recorded snapshots.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Write all snapshots as an endlessly looping animated GIF.
+    ///
+    /// GIF only supports 256 colors per frame, which is plenty for the
+    /// colors of the [`MapState`]s.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding or writing the GIF fails.
+    pub fn write_gif<W: Write>(&self, writer: W) -> ImageResult<()> {
+        let mut encoder = GifEncoder::new(writer);
+        encoder.set_repeat(Repeat::Infinite)?;
+        let delay = Delay::from_saturating_duration(self.frame_duration);
+        encoder.encode_frames(self.canvas_frames().map(|frame| {
+            let rgba = DynamicImage::ImageRgb8(frame).into_rgba8();
+            Frame::from_parts(rgba, 0, 0, delay)
+        }))
+    }
+
+    /// Save each snapshot as a PNG file in `directory`, named after `prefix`
+    /// followed by the zero-padded index of the snapshot, e.g.
+    /// `prefix0007.png`.
+    ///
+    /// Returns the paths of the written files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding or writing a file fails. Files written up
+    /// to that point are kept.
+    pub fn save_png_sequence(
+        &self,
+        directory: impl AsRef<Path>,
+        prefix: &str,
+    ) -> ImageResult<Vec<PathBuf>> {
+        let digits = self.frames.len().max(1).ilog10() as usize + 1;
+        let digits = digits.max(4);
+        self.canvas_frames()
+            .enumerate()
+            .map(|(i, frame)| {
+                let path = directory
+                    .as_ref()
+                    .join(format!("{prefix}{i:0digits$}.png"));
+                frame.save(&path)?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// The snapshots, all enlarged to the size of the largest one.
+    fn canvas_frames(&self) -> impl Iterator<Item = RgbImage> + '_ {
+        let width = self.frames.iter().map(RgbImage::width).max();
+        let height = self.frames.iter().map(RgbImage::height).max();
+        let (width, height) = (width.unwrap_or(0), height.unwrap_or(0));
+        self.frames.iter().map(move |frame| {
+            if frame.dimensions() == (width, height) {
+                return frame.clone();
+            }
+            let mut canvas = RgbImage::from_pixel(
+                width,
+                height,
+                MapState::OutOfMap.to_rgb(),
+            );
+            imageops::replace(&mut canvas, frame, 0, 0);
+            canvas
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, CellMap, RealWorldLocation};
+
+    fn map(width: f64, height: f64) -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(width, height, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    #[test]
+    fn frames_are_padded_to_largest() {
+        let mut recorder = MapRecorder::new(Duration::from_millis(100));
+        recorder.record(&map(2.0, 3.0));
+        recorder.record(&map(4.0, 1.0));
+
+        let frames: Vec<RgbImage> = recorder.canvas_frames().collect();
+
+        assert!(frames.iter().all(|frame| frame.dimensions() == (4, 3)));
+        assert_eq!(*frames[0].get_pixel(3, 0), MapState::OutOfMap.to_rgb());
+        assert_eq!(*frames[0].get_pixel(1, 2), MapState::Unexplored.to_rgb());
+        assert_eq!(*frames[1].get_pixel(0, 2), MapState::OutOfMap.to_rgb());
+    }
+
+    #[test]
+    fn png_sequence() {
+        let directory = std::env::temp_dir()
+            .join(format!("map_recorder_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut recorder = MapRecorder::new(Duration::from_millis(100));
+        for _ in 0..3 {
+            recorder.record(&map(2.0, 2.0));
+        }
+
+        let paths = recorder.save_png_sequence(&directory, "step").unwrap();
+
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[2], directory.join("step0002.png"));
+        let image = image::open(&paths[1]).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn gif_decodes_all_frames() {
+        use image::AnimationDecoder;
+
+        let mut recorder = MapRecorder::new(Duration::from_millis(50));
+        recorder.record(&map(3.0, 2.0));
+        recorder.record(&map(3.0, 2.0));
+        let mut gif = Vec::new();
+
+        recorder.write_gif(&mut gif).unwrap();
+
+        let decoder =
+            image::codecs::gif::GifDecoder::new(std::io::Cursor::new(gif))
+                .unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].buffer().dimensions(), (3, 2));
+    }
+}
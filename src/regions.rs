@@ -0,0 +1,1215 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::ops::Range;
+use ndarray::Array2;
+use num::{Float, ToPrimitive};
+
+use crate::cell_map::index_to_internal;
+use crate::{
+    Cell, CellMap, Coords, LocationError, LocationType, MapState,
+    RealWorldLocation,
+};
+
+impl CellMap {
+    /// Set all cells overlapping the axis-aligned rectangle spanned by two
+    /// corners to the given state.
+    ///
+    /// The corners can be given in any order. Parts of the rectangle lying
+    /// outside the map are ignored. Changed cells are stamped with the map's
+    /// clock (see [`CellMap::set_clock`]).
+    ///
+    /// Returns the number of cells which were set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, MaskMapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let count = map.set_rect(
+    ///     &RealWorldLocation::from_xyz(5.0, 4.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     MapState::Explored,
+    /// );
+    ///
+    /// assert_eq!(count, 3 * 2);
+    /// assert_eq!(map.get_map_state(MapState::Explored).len(), 3 * 2);
+    /// ```
+    pub fn set_rect(
+        &mut self,
+        corner_a: &RealWorldLocation,
+        corner_b: &RealWorldLocation,
+        value: LocationType,
+    ) -> usize {
+        let (rows, cols) = self.index_ranges(corner_a, corner_b);
+        let mut count = 0;
+        for row in rows {
+            for col in cols.clone() {
+                self.set_cell([row, col], value);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Set all cells whose center lies within `radius` of `center` to the given
+    /// state.
+    ///
+    /// Only the `x` and `y` components are considered, making this a disc
+    /// rather than a sphere. Only the cells within the disc's bounding box are
+    /// visited. Changed cells are stamped with the map's clock (see
+    /// [`CellMap::set_clock`]).
+    ///
+    /// Returns the number of cells which were set.
+    ///
+    /// # Example
+    ///
+    /// Marking a circular sensor footprint as explored:
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, MaskMapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let count = map.set_disc(
+    ///     &RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+    ///     1.0,
+    ///     MapState::Explored,
+    /// );
+    ///
+    /// assert_eq!(count, 4);
+    /// assert_eq!(map.get_map_state(MapState::Explored).len(), 4);
+    /// ```
+    pub fn set_disc(
+        &mut self,
+        center: &RealWorldLocation,
+        radius: f64,
+        value: LocationType,
+    ) -> usize {
+        let indices: Vec<[usize; 2]> =
+            self.disc_indices(center, radius).collect();
+        for index in &indices {
+            self.set_cell(*index, value);
+        }
+        indices.len()
+    }
+
+    /// Retrieve the cells whose center lies within `radius` of `center` and
+    /// whose state matches the `filter`, e.g. the cells in a sensor's
+    /// footprint.
+    ///
+    /// Like [`CellMap::set_disc`], only the `x` and `y` components are
+    /// considered and only the cells within the disc's bounding box are
+    /// visited, so the cost depends on the radius rather than the size of the
+    /// map. The cells are ordered by row, then by column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(100.0, 100.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(50.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(100.0, 100.0, 0.0),
+    ///     MapState::Explored,
+    /// );
+    /// let robot = RealWorldLocation::from_xyz(50.0, 50.0, 0.0);
+    ///
+    /// let unexplored =
+    ///     map.cells_within(&robot, 2.0, |state| state == MapState::Unexplored);
+    ///
+    /// assert_eq!(unexplored.len(), 6);
+    /// assert!(unexplored.iter().all(|cell| *cell.x() < 50.0));
+    /// ```
+    pub fn cells_within(
+        &self,
+        center: &RealWorldLocation,
+        radius: f64,
+        filter: impl Fn(LocationType) -> bool,
+    ) -> Vec<Cell<'_>> {
+        self.disc_indices(center, radius)
+            .filter(|index| filter(self.cells()[*index]))
+            .map(|index| {
+                Cell::new(
+                    index_to_internal(
+                        index,
+                        *self.offset(),
+                        *self.resolution(),
+                    ),
+                    &self.cells()[index],
+                )
+            })
+            .collect()
+    }
+
+    /// Find the cell with the given state which is closest to a location.
+    ///
+    /// Distances are measured between `from` and the cells' centers,
+    /// considering only the `x` and `y` components. Rather than scanning the
+    /// whole map, rings of cells of increasing size are searched around
+    /// `from`, stopping as soon as no closer cell can be found. If `from` lies
+    /// outside the map, the search starts at the closest cell on the map's
+    /// border.
+    ///
+    /// Returns [`None`] if no cell has the requested state. If several cells
+    /// are equally close, any one of them is returned.
+    ///
+    /// # Example
+    ///
+    /// "Go to the nearest frontier":
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// for (x, y) in [(8.5, 8.5), (1.5, 6.5), (9.5, 0.5)] {
+    ///     map.set_location(
+    ///         &RealWorldLocation::from_xyz(x, y, 0.0),
+    ///         MapState::Frontier,
+    ///     )
+    ///     .unwrap();
+    /// }
+    ///
+    /// let nearest = map
+    ///     .nearest_cell(
+    ///         &RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///         MapState::Frontier,
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!((*nearest.x(), *nearest.y()), (1.0, 6.0));
+    /// assert!(map
+    ///     .nearest_cell(
+    ///         &RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///         MapState::Assigned
+    ///     )
+    ///     .is_none());
+    /// ```
+    pub fn nearest_cell(
+        &self,
+        from: &RealWorldLocation,
+        state: LocationType,
+    ) -> Option<Cell<'_>> {
+        if self.width() == 0 || self.height() == 0 {
+            return None;
+        }
+        let (rows, cols) = self.index_ranges(from, from);
+        let start_row = rows.start.min(self.height() - 1);
+        let start_col = cols.start.min(self.width() - 1);
+        let cell_size =
+            (1.0 / self.resolution().x).min(1.0 / self.resolution().y);
+        let distance = |index: [usize; 2]| {
+            let center = self.index_center::<f64>(index);
+            ((center.x - from.x).powi(2) + (center.y - from.y).powi(2)).sqrt()
+        };
+
+        let max_ring = start_row
+            .max(self.height() - 1 - start_row)
+            .max(start_col)
+            .max(self.width() - 1 - start_col);
+        let mut best: Option<([usize; 2], f64)> = None;
+        for ring in 0..=max_ring {
+            if let Some((_, best_distance)) = best {
+                if (ring as f64 - 0.5) * cell_size > best_distance {
+                    break;
+                }
+            }
+            let row_range = start_row.saturating_sub(ring)
+                ..=(start_row + ring).min(self.height() - 1);
+            let col_range = start_col.saturating_sub(ring)
+                ..=(start_col + ring).min(self.width() - 1);
+            for row in row_range.clone() {
+                for col in col_range.clone() {
+                    let on_ring = row.abs_diff(start_row) == ring
+                        || col.abs_diff(start_col) == ring;
+                    if !on_ring || self.cells()[[row, col]] != state {
+                        continue;
+                    }
+                    let d = distance([row, col]);
+                    if best.map_or(true, |(_, best_distance)| d < best_distance)
+                    {
+                        best = Some(([row, col], d));
+                    }
+                }
+            }
+        }
+
+        best.map(|(index, _)| {
+            Cell::new(
+                index_to_internal(index, *self.offset(), *self.resolution()),
+                &self.cells()[index],
+            )
+        })
+    }
+
+    /// Retrieve the cells of the given state which touch a cell of a different
+    /// state, i.e. the outline of the region(s) in that state.
+    ///
+    /// Cells are considered touching if they share an edge. Cells at the edge
+    /// of the map are part of the boundary as well, such that the returned
+    /// cells always form a closed outline.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, CellMap, MapState, RealWorldLocation};
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(6.0, 5.0, 0.0),
+    ///     MapState::Assigned,
+    /// );
+    ///
+    /// // Out of the 4x3 region only the two inner cells are not on the outline.
+    /// assert_eq!(map.boundary_of(MapState::Assigned).len(), 4 * 3 - 2);
+    /// ```
+    pub fn boundary_of(&self, state: LocationType) -> Vec<Cell<'_>> {
+        let (height, width) = (self.height(), self.width());
+        self.cells()
+            .indexed_iter()
+            .filter(|(_, value)| **value == state)
+            .filter(|((row, col), _)| {
+                let (row, col) = (*row, *col);
+                row == 0
+                    || col == 0
+                    || row + 1 == height
+                    || col + 1 == width
+                    || [
+                        [row - 1, col],
+                        [row + 1, col],
+                        [row, col - 1],
+                        [row, col + 1],
+                    ]
+                    .iter()
+                    .any(|index| self.cells()[*index] != state)
+            })
+            .map(|((row, col), value)| {
+                Cell::new(
+                    index_to_internal(
+                        [row, col],
+                        *self.offset(),
+                        *self.resolution(),
+                    ),
+                    value,
+                )
+            })
+            .collect()
+    }
+
+    /// Change the state of the region of `from_state` cells connected to
+    /// `seed` to `to_state`.
+    ///
+    /// Starting at the cell containing `seed`, all cells in `from_state`
+    /// which can be reached through neighbors in `from_state` (see
+    /// [`Connectivity`]) are set to `to_state` and stamped with the map's
+    /// clock (see [`CellMap::set_clock`]). Nothing happens if the seed cell
+    /// is not in `from_state`, or if both states are the same.
+    ///
+    /// Returns the number of cells which were changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocationError::OutOfMap`] if `seed` lies outside the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Connectivity, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// // A wall splitting the map in two.
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(5.0, 10.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    ///
+    /// let filled = map.flood_fill(
+    ///     &RealWorldLocation::from_xyz(1.5, 1.5, 0.0),
+    ///     MapState::Unexplored,
+    ///     MapState::Assigned,
+    ///     Connectivity::Four,
+    /// );
+    ///
+    /// assert_eq!(filled, Ok(4 * 10));
+    /// ```
+    pub fn flood_fill(
+        &mut self,
+        seed: &RealWorldLocation,
+        from_state: LocationType,
+        to_state: LocationType,
+        connectivity: Connectivity,
+    ) -> Result<usize, LocationError> {
+        let start = self.location_to_map_index(seed)?;
+        if from_state == to_state {
+            return Ok(0);
+        }
+        let region = self.connected_region(start, connectivity, |value| {
+            *value == from_state
+        });
+        for index in &region {
+            self.set_cell(*index, to_state);
+        }
+        Ok(region.len())
+    }
+
+    /// Determine which cells a robot at `location` can reach.
+    ///
+    /// Returns a mask of the map's shape which is `true` for all cells
+    /// connected to the cell containing `location` through cells sharing an
+    /// edge (see [`Connectivity::Four`]), without crossing a
+    /// [`MapState::Obstacle`] or [`MapState::OutOfMap`] cell. Robots are not
+    /// considered obstacles, since they move. The cell containing `location`
+    /// is reachable unless it is blocked itself.
+    ///
+    /// Partitioning with it avoids assigning cells behind a wall a robot
+    /// cannot cross, see [`crate::partitioning`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocationError::OutOfMap`] if `location` lies outside the
+    /// map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(4.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(5.0, 10.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    ///
+    /// let reachable = map
+    ///     .reachable_from(&RealWorldLocation::from_xyz(1.5, 1.5, 0.0))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(reachable.iter().filter(|cell| **cell).count(), 4 * 10);
+    /// assert!(!reachable[[0, 5]]);
+    /// ```
+    pub fn reachable_from(
+        &self,
+        location: &RealWorldLocation,
+    ) -> Result<Array2<bool>, LocationError> {
+        let start = self.location_to_map_index(location)?;
+        let mut reachable = Array2::from_elem(self.cells().dim(), false);
+        let region =
+            self.connected_region(start, Connectivity::Four, |value| {
+                !matches!(value, MapState::Obstacle | MapState::OutOfMap)
+            });
+        for index in region {
+            reachable[index] = true;
+        }
+        Ok(reachable)
+    }
+
+    /// Internal helper returning the indices of the cells matching
+    /// `predicate` which are connected to `start`, starting with `start`
+    /// itself. Returns no cells if `start` does not match.
+    pub(crate) fn connected_region<F>(
+        &self,
+        start: [usize; 2],
+        connectivity: Connectivity,
+        predicate: F,
+    ) -> Vec<[usize; 2]>
+    where
+        F: Fn(&LocationType) -> bool,
+    {
+        if !predicate(&self.cells()[start]) {
+            return Vec::new();
+        }
+        let mut visited = Array2::from_elem(self.cells().dim(), false);
+        visited[start] = true;
+        let mut region = alloc::vec![start];
+        let mut next = 0;
+        while let Some(index) = region.get(next).copied() {
+            next += 1;
+            for neighbor in self.connected_neighbors(index, connectivity) {
+                if !visited[neighbor] && predicate(&self.cells()[neighbor]) {
+                    visited[neighbor] = true;
+                    region.push(neighbor);
+                }
+            }
+        }
+        region
+    }
+
+    /// Internal helper returning the indices of the neighbors of a cell
+    /// which lie inside the map, see [`Connectivity`].
+    pub(crate) fn connected_neighbors(
+        &self,
+        [row, col]: [usize; 2],
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = [usize; 2]> {
+        grid_neighbors([row, col], [self.height(), self.width()], connectivity)
+    }
+
+    /// Internal helper returning the indices of all cells whose center lies
+    /// within `radius` of `center`, considering only the `x` and `y`
+    /// components.
+    pub(crate) fn disc_indices<'a>(
+        &'a self,
+        center: &'a RealWorldLocation,
+        radius: f64,
+    ) -> impl Iterator<Item = [usize; 2]> + 'a {
+        let delta = Coords::new(radius, radius, 0.0);
+        let (rows, cols) = self.index_ranges(
+            &RealWorldLocation::new(*center.location() - delta),
+            &RealWorldLocation::new(*center.location() + delta),
+        );
+        rows.flat_map(move |row| cols.clone().map(move |col| [row, col]))
+            .filter(move |index| {
+                let cell = self.index_center::<f64>(*index);
+                (cell.x - center.x).powi(2) + (cell.y - center.y).powi(2)
+                    <= radius.powi(2)
+            })
+    }
+
+    /// Internal helper returning the indices of the 8-connected neighbors of a
+    /// cell which lie inside the map.
+    pub(crate) fn neighbor_indices(
+        &self,
+        [row, col]: [usize; 2],
+    ) -> impl Iterator<Item = [usize; 2]> {
+        grid_neighbors(
+            [row, col],
+            [self.height(), self.width()],
+            Connectivity::Eight,
+        )
+    }
+
+    /// Internal helper grouping all cells matching `predicate` into
+    /// 8-connected components, each given as a list of cell indices.
+    pub(crate) fn components<F>(&self, predicate: F) -> Vec<Vec<[usize; 2]>>
+    where
+        F: Fn(&LocationType) -> bool,
+    {
+        self.connected_components(Connectivity::Eight, |index| {
+            predicate(&self.cells()[index])
+        })
+    }
+
+    /// Internal helper grouping the indices of all cells matching
+    /// `predicate` into components, see [`Connectivity`]. Components are
+    /// ordered by their first cell in the order of [`CellMap::cells`].
+    pub(crate) fn connected_components<F>(
+        &self,
+        connectivity: Connectivity,
+        predicate: F,
+    ) -> Vec<Vec<[usize; 2]>>
+    where
+        F: Fn([usize; 2]) -> bool,
+    {
+        let mut visited = Array2::from_elem(self.cells().dim(), false);
+        let mut components = Vec::new();
+        for ((row, col), _) in self.cells().indexed_iter() {
+            if visited[[row, col]] || !predicate([row, col]) {
+                continue;
+            }
+            visited[[row, col]] = true;
+            let mut component = Vec::new();
+            let mut stack = alloc::vec![[row, col]];
+            while let Some(index) = stack.pop() {
+                component.push(index);
+                for neighbor in self.connected_neighbors(index, connectivity) {
+                    if !visited[neighbor] && predicate(neighbor) {
+                        visited[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Retrieve the contours separating the cells in the given state from all
+    /// other cells, in real-world coordinates.
+    ///
+    /// Each contour is a closed polygon along the cell edges, given by its
+    /// corners without repeating the first one and without collinear
+    /// intermediate vertices. Outer contours are counter-clockwise, while the
+    /// contours of holes are clockwise, so that the cells in the state are
+    /// always to the left. The edge of the map counts as a separation as
+    /// well.
+    ///
+    /// A few contours summarize a region much more compactly than its cells,
+    /// e.g. to share the explored area with other robots.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// // An explored square with an obstacle in its middle.
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(2.0, 2.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+    ///     MapState::Explored,
+    /// );
+    /// map.set_location(
+    ///     &RealWorldLocation::from_xyz(3.5, 3.5, 0.0),
+    ///     MapState::Obstacle,
+    /// )
+    /// .unwrap();
+    ///
+    /// let contours = map.contours(MapState::Explored);
+    ///
+    /// assert_eq!(contours.len(), 2);
+    /// assert_eq!(contours[0][0], RealWorldLocation::from_xyz(2.0, 2.0, 0.0));
+    /// assert_eq!(contours[1][0], RealWorldLocation::from_xyz(3.0, 3.0, 0.0));
+    /// ```
+    pub fn contours(&self, state: LocationType) -> Vec<Vec<RealWorldLocation>> {
+        let cells: Vec<[usize; 2]> = self
+            .cells()
+            .indexed_iter()
+            .filter(|(_, value)| **value == state)
+            .map(|((row, col), _)| [row, col])
+            .collect();
+        trace_rings(&cells)
+            .iter()
+            .map(|ring| {
+                self.ring_corners(ring)
+                    .into_iter()
+                    .map(RealWorldLocation::new)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Internal helper tracing the outer outline of a group of cells along the
+    /// cell edges.
+    ///
+    /// Returns the corners of the outline in counter-clockwise order, without
+    /// repeating the first one and without collinear intermediate vertices.
+    /// Holes are not represented, including holes touching the outline at a
+    /// corner, so every corner appears once. If the cells form several
+    /// outlines (e.g. cells touching only diagonally), the one enclosing the
+    /// largest area is returned.
+    #[cfg(any(feature = "std", test))]
+    pub(crate) fn outline(&self, cells: &[[usize; 2]]) -> Vec<Coords> {
+        trace_rings(cells)
+            .iter()
+            .flat_map(|ring| split_pinches(ring))
+            .max_by_key(|ring| doubled_area(ring))
+            .map(|ring| self.ring_corners(&ring))
+            .unwrap_or_default()
+    }
+
+    /// Internal helper converting a ring of cell corners given as
+    /// `(col, row)` to real-world coordinates, leaving out collinear
+    /// vertices.
+    fn ring_corners(&self, ring: &[(usize, usize)]) -> Vec<Coords> {
+        let n = ring.len();
+        (0..n)
+            .filter(|&i| {
+                let (prev, here, next) =
+                    (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+                direction(prev, here) != direction(here, next)
+            })
+            .map(|i| {
+                let (col, row) = ring[i];
+                Coords::new(
+                    col.to_f64().expect("usize to f64 should work")
+                        / self.resolution().x
+                        + self.offset().x,
+                    row.to_f64().expect("usize to f64 should work")
+                        / self.resolution().y
+                        + self.offset().y,
+                    self.offset().z,
+                )
+            })
+            .collect()
+    }
+
+    /// Internal helper returning the real-world center of a cell.
+    ///
+    /// Like [`CellMap::location_to_map_index`], the computation is carried out
+    /// in the requested scalar type.
+    pub(crate) fn index_center<S: Float>(
+        &self,
+        [row, col]: [usize; 2],
+    ) -> Coords<S> {
+        let scalar =
+            |value: f64| S::from(value).expect("f64 to float should work");
+        let index =
+            |value: usize| S::from(value).expect("usize to float should work");
+        let half = S::from(0.5).expect("f64 to float should work");
+        Coords::new(
+            (index(col) + half) / scalar(self.resolution().x)
+                + scalar(self.offset().x),
+            (index(row) + half) / scalar(self.resolution().y)
+                + scalar(self.offset().y),
+            scalar(self.offset().z),
+        )
+    }
+
+    /// Internal helper returning the range of rows and columns of the cells
+    /// overlapping the rectangle spanned by two corners, clamped to the map.
+    pub(crate) fn index_ranges(
+        &self,
+        corner_a: &RealWorldLocation,
+        corner_b: &RealWorldLocation,
+    ) -> (Range<usize>, Range<usize>) {
+        let range = |a: f64, b: f64, offset: f64, resolution: f64, len| {
+            let clamp = |value: f64| {
+                value
+                    .max(0.0)
+                    .min(len as f64)
+                    .to_usize()
+                    .expect("Value was clamped to the map dimensions")
+            };
+            let start = (a.min(b) - offset) * resolution;
+            let end = (a.max(b) - offset) * resolution;
+            clamp(start.floor())..clamp(end.ceil())
+        };
+        (
+            range(
+                corner_a.y,
+                corner_b.y,
+                self.offset().y,
+                self.resolution().y,
+                self.height(),
+            ),
+            range(
+                corner_a.x,
+                corner_b.x,
+                self.offset().x,
+                self.resolution().x,
+                self.width(),
+            ),
+        )
+    }
+}
+
+/// Which cells count as neighbors when growing regions, e.g. in
+/// [`CellMap::flood_fill`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Cells sharing an edge.
+    Four,
+    /// Cells sharing an edge or a corner.
+    #[default]
+    Eight,
+}
+
+/// Internal helper returning the indices of the neighbors of a cell which lie
+/// within a grid of `[rows, columns]` cells, see [`Connectivity`].
+pub(crate) fn grid_neighbors(
+    [row, col]: [usize; 2],
+    [rows, columns]: [usize; 2],
+    connectivity: Connectivity,
+) -> impl Iterator<Item = [usize; 2]> {
+    (-1isize..=1)
+        .flat_map(|dr| (-1isize..=1).map(move |dc| (dr, dc)))
+        .filter(move |&(dr, dc)| match connectivity {
+            Connectivity::Four => (dr == 0) != (dc == 0),
+            Connectivity::Eight => (dr, dc) != (0, 0),
+        })
+        .filter_map(move |(dr, dc)| {
+            let r = row.checked_add_signed(dr)?;
+            let c = col.checked_add_signed(dc)?;
+            (r < rows && c < columns).then_some([r, c])
+        })
+}
+
+/// Direction of the step between two cell corners given as `(col, row)`.
+fn direction(from: (usize, usize), to: (usize, usize)) -> (isize, isize) {
+    (
+        to.0 as isize - from.0 as isize,
+        to.1 as isize - from.1 as isize,
+    )
+}
+
+/// Twice the signed area enclosed by a ring of cell corners, positive for
+/// counter-clockwise rings.
+#[cfg(any(feature = "std", test))]
+fn doubled_area(ring: &[(usize, usize)]) -> isize {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 as isize * b.1 as isize - b.0 as isize * a.1 as isize)
+        .sum()
+}
+
+/// Split a ring at the corners it passes through several times, e.g. where
+/// an outline touches one of its holes, into rings passing through each
+/// corner once.
+#[cfg(any(feature = "std", test))]
+fn split_pinches(ring: &[(usize, usize)]) -> Vec<Vec<(usize, usize)>> {
+    let mut rings = Vec::new();
+    let mut path: Vec<(usize, usize)> = Vec::new();
+    let mut positions = BTreeMap::new();
+    for corner in ring {
+        match positions.get(corner) {
+            Some(&position) => {
+                let closed = path.split_off(position + 1);
+                for corner in &closed {
+                    positions.remove(corner);
+                }
+                let mut closed_ring = alloc::vec![*corner];
+                closed_ring.extend(closed);
+                rings.push(closed_ring);
+            }
+            None => {
+                positions.insert(*corner, path.len());
+                path.push(*corner);
+            }
+        }
+    }
+    rings.push(path);
+    rings
+}
+
+/// Trace the closed outlines of a group of cells along the cell edges, as
+/// rings of cell corners given as `(col, row)`, keeping the cells to the left.
+/// Outer outlines are counter-clockwise, the ones of holes clockwise.
+fn trace_rings(cells: &[[usize; 2]]) -> Vec<Vec<(usize, usize)>> {
+    let inside: BTreeSet<[usize; 2]> = cells.iter().copied().collect();
+    let contains = |row: usize, col: usize| inside.contains(&[row, col]);
+
+    // Directed edges between cell corners, with the cell to their left.
+    let mut edges: BTreeMap<(usize, usize), Vec<(usize, usize)>> =
+        BTreeMap::new();
+    let mut add = |from, to| edges.entry(from).or_default().push(to);
+    for &[row, col] in &inside {
+        if row == 0 || !contains(row - 1, col) {
+            add((col, row), (col + 1, row));
+        }
+        if !contains(row, col + 1) {
+            add((col + 1, row), (col + 1, row + 1));
+        }
+        if !contains(row + 1, col) {
+            add((col + 1, row + 1), (col, row + 1));
+        }
+        if col == 0 || !contains(row, col - 1) {
+            add((col, row + 1), (col, row));
+        }
+    }
+
+    // Corners are removed once all their edges are used, so the first one
+    // left starts the next ring.
+    let mut rings = Vec::new();
+    while let Some((&start, _)) = edges.first_key_value() {
+        let mut ring = alloc::vec![start];
+        let mut current = start;
+        let mut heading = (0, 0);
+        loop {
+            // Prefer turning left, i.e. towards the cell being outlined,
+            // which keeps outlines touching at a single corner apart.
+            let outgoing = edges.get_mut(&current).expect("Edges are closed");
+            let (position, _) = outgoing
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, to)| {
+                    let (dx, dy) = direction(current, **to);
+                    heading.0 * dy - heading.1 * dx
+                })
+                .expect("Every corner on an outline has an outgoing edge");
+            let next = outgoing.swap_remove(position);
+            if outgoing.is_empty() {
+                edges.remove(&current);
+            }
+            heading = direction(current, next);
+            current = next;
+            if current == start {
+                break;
+            }
+            ring.push(current);
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location, MaskMapState};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(-5.0, -5.0, 0.0),
+            RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+            AxisResolution::uniform(2.0),
+        )
+    }
+
+    #[test]
+    fn set_rect_high_resolution() {
+        let mut map = make_map();
+
+        let count = map.set_rect(
+            &RealWorldLocation::from_xyz(-1.0, -1.0, 0.0),
+            &RealWorldLocation::from_xyz(1.0, 0.5, 0.0),
+            MapState::Assigned,
+        );
+
+        assert_eq!(count, 4 * 3);
+        assert_eq!(map.get_map_state(MapState::Assigned).len(), count);
+        assert_eq!(
+            map.get_location(&RealWorldLocation::from_xyz(0.9, 0.4, 0.0)),
+            Ok(MapState::Assigned)
+        );
+        assert_eq!(
+            map.get_location(&RealWorldLocation::from_xyz(1.1, 0.4, 0.0)),
+            Ok(MapState::Unexplored)
+        );
+    }
+
+    #[test]
+    fn set_rect_clamped_to_map() {
+        let mut map = make_map();
+
+        let count = map.set_rect(
+            &RealWorldLocation::from_xyz(-100.0, -100.0, 0.0),
+            &RealWorldLocation::from_xyz(100.0, -4.0, 0.0),
+            MapState::Explored,
+        );
+
+        assert_eq!(count, 20 * 2);
+    }
+
+    #[test]
+    fn set_rect_outside_map() {
+        let mut map = make_map();
+
+        let count = map.set_rect(
+            &RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+            &RealWorldLocation::from_xyz(20.0, 20.0, 0.0),
+            MapState::Explored,
+        );
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn set_disc_is_round() {
+        let mut map = make_map();
+
+        let count = map.set_disc(
+            &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            2.0,
+            MapState::Explored,
+        );
+
+        assert_eq!(count, map.get_map_state(MapState::Explored).len());
+        // corners of the bounding box are not part of the disc
+        assert_eq!(
+            map.get_location(&RealWorldLocation::from_xyz(1.9, 1.9, 0.0)),
+            Ok(MapState::Unexplored)
+        );
+        assert_eq!(
+            map.get_location(&RealWorldLocation::from_xyz(1.9, 0.1, 0.0)),
+            Ok(MapState::Explored)
+        );
+        // a disc of radius 2 covers about 4 * pi square meters, i.e. 4 cells
+        // per square meter
+        assert!((44..=56).contains(&count));
+    }
+
+    #[test]
+    fn cells_within_matches_set_disc() {
+        let mut map = make_map();
+        let center = RealWorldLocation::from_xyz(4.3, -0.6, 0.0);
+        map.set_rect(
+            &RealWorldLocation::from_xyz(3.0, -5.0, 0.0),
+            &RealWorldLocation::from_xyz(5.0, 0.0, 0.0),
+            MapState::Frontier,
+        );
+
+        let all = map.cells_within(&center, 1.5, |_| true).len();
+        let frontiers = map
+            .cells_within(&center, 1.5, |state| state == MapState::Frontier)
+            .len();
+        let count = map.set_disc(&center, 1.5, MapState::Explored);
+
+        assert_eq!(all, count);
+        assert!(frontiers > 0 && frontiers < all);
+        assert!(map
+            .cells_within(
+                &RealWorldLocation::from_xyz(50.0, 0.0, 0.0),
+                1.0,
+                |_| { true }
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn nearest_cell_matches_exhaustive_search() {
+        let mut map = make_map();
+        for (x, y) in [(-4.2, 3.1), (2.7, -0.4), (0.3, 4.8), (-1.1, -3.9)] {
+            map.set_location(
+                &RealWorldLocation::from_xyz(x, y, 0.0),
+                MapState::Frontier,
+            )
+            .unwrap();
+        }
+
+        for (x, y) in [(0.0, 0.0), (-4.9, 4.9), (4.0, -4.0), (-2.0, 1.0)] {
+            let from = RealWorldLocation::from_xyz(x, y, 0.0);
+            let nearest = map.nearest_cell(&from, MapState::Frontier).unwrap();
+            let expected = map
+                .get_map_state(MapState::Frontier)
+                .into_iter()
+                .map(|cell| {
+                    let index =
+                        map.location_to_map_index(cell.location()).unwrap();
+                    let center = map.index_center::<f64>(index);
+                    (center.x - x).powi(2) + (center.y - y).powi(2)
+                })
+                .fold(f64::INFINITY, f64::min);
+            let index = map.location_to_map_index(nearest.location()).unwrap();
+            let center = map.index_center::<f64>(index);
+
+            assert_eq!(
+                (center.x - x).powi(2) + (center.y - y).powi(2),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_cell_from_outside_map() {
+        let mut map = make_map();
+        let target = RealWorldLocation::from_xyz(4.5, 0.0, 0.0);
+        map.set_location(&target, MapState::Frontier).unwrap();
+
+        let nearest = map
+            .nearest_cell(
+                &RealWorldLocation::from_xyz(50.0, 0.0, 0.0),
+                MapState::Frontier,
+            )
+            .unwrap();
+
+        assert_eq!(
+            nearest.location(),
+            &RealWorldLocation::from_xyz(4.5, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn nearest_cell_none() {
+        let map = make_map();
+
+        assert!(map
+            .nearest_cell(
+                &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                MapState::Frontier
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn boundary_includes_map_edge() {
+        let mut map = make_map();
+        map.set_rect(
+            &RealWorldLocation::from_xyz(-5.0, -5.0, 0.0),
+            &RealWorldLocation::from_xyz(-3.0, -3.0, 0.0),
+            MapState::Assigned,
+        );
+
+        let boundary: Vec<_> = map
+            .boundary_of(MapState::Assigned)
+            .iter()
+            .map(|cell| map.location_to_map_index(cell.location()).unwrap())
+            .collect();
+
+        // 4x4 cells in the corner of the map; all but the inner 2x2 cells lie
+        // on the outline.
+        assert_eq!(boundary.len(), 4 * 4 - 4);
+        assert!(!boundary.contains(&[1, 1]));
+        assert!(!boundary.contains(&[2, 2]));
+        assert!(boundary.contains(&[0, 1]));
+        assert!(boundary.contains(&[3, 3]));
+    }
+
+    #[test]
+    fn boundary_of_absent_state() {
+        let map = make_map();
+
+        assert!(map.boundary_of(MapState::Assigned).is_empty());
+        assert_eq!(
+            map.boundary_of(MapState::Unexplored).len(),
+            2 * map.width() + 2 * map.height() - 4
+        );
+    }
+
+    #[test]
+    fn outline_of_l_shape() {
+        let map = make_map();
+        let cells = [[0, 0], [0, 1], [0, 2], [1, 0], [2, 0]];
+
+        let outline = map.outline(&cells);
+
+        let corners: Vec<_> = outline.iter().map(|c| (c.x, c.y)).collect();
+        assert_eq!(corners.len(), 6);
+        for corner in [
+            (-5.0, -5.0),
+            (-3.5, -5.0),
+            (-3.5, -4.5),
+            (-4.5, -4.5),
+            (-4.5, -3.5),
+            (-5.0, -3.5),
+        ] {
+            assert!(corners.contains(&corner));
+        }
+        // Counter-clockwise: the corner after the origin lies along +x.
+        let origin = corners.iter().position(|c| *c == (-5.0, -5.0)).unwrap();
+        assert_eq!(corners[(origin + 1) % 6], (-3.5, -5.0));
+    }
+
+    #[test]
+    fn outline_ignores_holes_and_picks_largest() {
+        let map = make_map();
+        // A 3x3 ring with a hole, and a single cell touching it diagonally.
+        let mut cells: Vec<_> = (0..3)
+            .flat_map(|row| (0..3).map(move |col| [row, col]))
+            .filter(|index| *index != [1, 1])
+            .collect();
+        cells.push([3, 3]);
+
+        let outline = map.outline(&cells);
+
+        assert_eq!(outline.len(), 4);
+        assert!(outline.iter().all(|c| c.x <= -3.5 && c.y <= -3.5));
+        assert!(map.outline(&[]).is_empty());
+    }
+
+    #[test]
+    fn outline_of_region_pinched_at_hole() {
+        let map = make_map();
+        // A 3x3 ring whose hole touches the outline at a corner.
+        let cells = [[0, 0], [0, 1], [0, 2], [1, 0], [1, 2], [2, 0], [2, 1]];
+
+        let outline = map.outline(&cells);
+
+        let corners: Vec<_> = outline.iter().map(|c| (c.x, c.y)).collect();
+        assert_eq!(
+            corners,
+            [
+                (-5.0, -5.0),
+                (-3.5, -5.0),
+                (-3.5, -4.0),
+                (-4.0, -4.0),
+                (-4.0, -3.5),
+                (-5.0, -3.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn contours_of_touching_regions() {
+        let mut map = make_map();
+        // Two squares touching at a corner, and one along the map edge.
+        for [x, y] in [[-4.0, -4.0], [-3.0, -3.0], [4.0, -1.0]] {
+            map.set_rect(
+                &RealWorldLocation::from_xyz(x, y, 0.0),
+                &RealWorldLocation::from_xyz(x + 1.0, y + 1.0, 0.0),
+                MapState::Explored,
+            );
+        }
+
+        let contours = map.contours(MapState::Explored);
+
+        assert_eq!(contours.len(), 3);
+        assert!(contours.iter().all(|contour| contour.len() == 4));
+        assert!(contours[2]
+            .iter()
+            .all(|corner| corner.x >= 4.0 && corner.x <= 5.0));
+        assert!(map.contours(MapState::Assigned).is_empty());
+    }
+
+    #[test]
+    fn flood_fill_connectivity() {
+        let mut map = make_map();
+        // A diagonal wall, which only blocks 4-connected regions.
+        for i in 0..map.width() {
+            map.set_cell([i, map.width() - 1 - i], MapState::Obstacle);
+        }
+        let seed = RealWorldLocation::from_xyz(-4.75, -4.75, 0.0);
+        let half = (map.width() * map.height() - map.width()) / 2;
+
+        let fill = |map: &mut CellMap, connectivity| {
+            map.flood_fill(
+                &seed,
+                MapState::Unexplored,
+                MapState::Explored,
+                connectivity,
+            )
+        };
+        assert_eq!(fill(&mut map.clone(), Connectivity::Four), Ok(half));
+        assert_eq!(fill(&mut map, Connectivity::Eight), Ok(2 * half));
+        assert_eq!(fill(&mut map, Connectivity::Eight), Ok(0));
+        assert!(map
+            .flood_fill(
+                &RealWorldLocation::from_xyz(6.0, 0.0, 0.0),
+                MapState::Explored,
+                MapState::Frontier,
+                Connectivity::Four,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn set_disc_at_map_edge() {
+        let mut map = make_map();
+
+        let count = map.set_disc(
+            &RealWorldLocation::from_xyz(-5.0, -5.0, 0.0),
+            0.5,
+            MapState::Explored,
+        );
+
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,525 @@
+//! Alignment of maps which do not share a common frame.
+//!
+//! Robots without a shared global frame, e.g. without GNSS indoors, build
+//! their maps in frames of their own. Before their maps can be merged (see
+//! [`CellMap::merge`]), the rigid transform between both frames needs to be
+//! known. [`register`] estimates it by trying rotations and translations and
+//! keeping the one under which the known cells of both maps agree best.
+//!
+//! This is a coarse, brute-force search: its accuracy is limited by the step
+//! sizes given in [`RegistrationParams`], and its run time grows with the
+//! search window. It is meant to provide an initial guess, e.g. for a finer
+//! registration such as ICP.
+
+use alloc::vec::Vec;
+use core::f64::consts::TAU;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+use num::ToPrimitive;
+
+use crate::{CellMap, Coords, MapState, MapStateMatrix, RealWorldLocation};
+
+/// Parameters of the search of [`register`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegistrationParams {
+    /// Largest translation tried along each axis, in real-world units.
+    pub max_translation: f64,
+    /// Step between the translations tried, in real-world units. Usually the
+    /// size of a cell.
+    pub translation_step: f64,
+    /// Step between the rotations tried, in radians. All rotations of a full
+    /// turn are tried.
+    pub angle_step: f64,
+}
+
+impl RegistrationParams {
+    /// Largest number of steps [`register`] takes along the translation
+    /// window in each direction, or around a full turn, to bound its run time.
+    pub const MAX_STEPS: u32 = 1 << 12;
+}
+
+impl Default for RegistrationParams {
+    /// Translations of up to `10.0` in steps of `1.0`, and rotations in steps
+    /// of 5 degrees.
+    fn default() -> Self {
+        Self {
+            max_translation: 10.0,
+            translation_step: 1.0,
+            angle_step: 5f64.to_radians(),
+        }
+    }
+}
+
+/// A rigid transform from the frame of one map into the frame of another one,
+/// see [`register`].
+///
+/// A location `p` is transformed into `R p + t`, where `R` is the rotation
+/// about the `z` axis and `t` the translation. The `z` value is left as is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Registration {
+    rotation: f64,
+    translation: Coords,
+    score: f64,
+}
+
+impl Registration {
+    /// The rotation about the `z` axis in radians, counterclockwise.
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+    /// The translation applied after the rotation, with a `z` value of `0.0`.
+    pub fn translation(&self) -> &Coords {
+        &self.translation
+    }
+    /// How well both maps agree, from `-1.0` to `1.0` if all known cells of
+    /// the transformed map agree with the reference map.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// Transform a location into the frame of the reference map.
+    pub fn transform(&self, location: &RealWorldLocation) -> RealWorldLocation {
+        let (sin, cos) = self.rotation.sin_cos();
+        RealWorldLocation::from_xyz(
+            cos * location.x() - sin * location.y() + self.translation.x,
+            sin * location.x() + cos * location.y() + self.translation.y,
+            location.z(),
+        )
+    }
+
+    /// Transform a location of the reference map back into the frame of the
+    /// transformed map.
+    pub fn inverse_transform(
+        &self,
+        location: &RealWorldLocation,
+    ) -> RealWorldLocation {
+        let (sin, cos) = self.rotation.sin_cos();
+        let x = location.x() - self.translation.x;
+        let y = location.y() - self.translation.y;
+        RealWorldLocation::from_xyz(
+            cos * x + sin * y,
+            -sin * x + cos * y,
+            location.z(),
+        )
+    }
+
+    /// A copy of `map` in the frame of the reference map, with the same
+    /// resolution, such that it can be merged into the reference map.
+    ///
+    /// The copy covers the transformed bounds of `map`. Each of its cells
+    /// takes the state and timestamp of the cell of `map` containing its
+    /// center, while cells whose center lies outside `map` are
+    /// [`MapState::OutOfMap`], which [`CellMap::merge`] ignores.
+    pub fn apply(&self, map: &CellMap) -> CellMap {
+        let [min, max] = map.bounds();
+        let corners = [
+            RealWorldLocation::from_xyz(min.x(), min.y(), min.z()),
+            RealWorldLocation::from_xyz(max.x(), min.y(), min.z()),
+            RealWorldLocation::from_xyz(min.x(), max.y(), min.z()),
+            RealWorldLocation::from_xyz(max.x(), max.y(), min.z()),
+        ]
+        .map(|corner| self.transform(&corner));
+        let extreme = |axis: fn(&RealWorldLocation) -> f64,
+                       pick: fn(f64, f64) -> f64| {
+            corners
+                .iter()
+                .map(axis)
+                .reduce(pick)
+                .expect("There are four corners")
+        };
+        let offset = Coords::new(
+            extreme(RealWorldLocation::x, f64::min),
+            extreme(RealWorldLocation::y, f64::min),
+            min.z(),
+        );
+        let resolution = *map.resolution();
+        // Enough cells to cover the transformed bounds, allowing for rounding
+        // errors.
+        let cells = |extent: f64, resolution: f64| {
+            (extent * resolution - 1e-9)
+                .ceil()
+                .max(1.0)
+                .to_usize()
+                .expect("The bounds of a map are finite")
+        };
+        let rows = cells(
+            extreme(RealWorldLocation::y, f64::max) - offset.y,
+            resolution.y,
+        );
+        let columns = cells(
+            extreme(RealWorldLocation::x, f64::max) - offset.x,
+            resolution.x,
+        );
+
+        let mut aligned = CellMap::from_raster(
+            MapStateMatrix::from_elem((rows, columns), MapState::OutOfMap),
+            resolution,
+            offset,
+        );
+        aligned.set_clock(map.clock());
+        for row in 0..rows {
+            for col in 0..columns {
+                let center =
+                    RealWorldLocation::new(aligned.index_center([row, col]));
+                if let Ok(index) =
+                    map.location_to_map_index(&self.inverse_transform(&center))
+                {
+                    aligned.set_cell_stamped(
+                        [row, col],
+                        map.cells()[index],
+                        map.index_last_update(index),
+                    );
+                }
+            }
+        }
+        aligned.clear_dirty();
+        aligned
+    }
+}
+
+/// Estimate the rigid transform from the frame of `map` into the frame of
+/// `reference` under which both maps agree best.
+///
+/// Rotations are tried about the center of `map`, followed by translations
+/// within the window given by `params`. For every candidate, the known cells
+/// of `map` are transformed into `reference`: a cell counts as agreeing if
+/// both maps consider it an obstacle, or both consider it free, and as
+/// disagreeing if one considers it an obstacle and the other free. Free cells
+/// are all cells which are neither [`MapState::Obstacle`],
+/// [`MapState::Unexplored`] nor [`MapState::OutOfMap`]. The score of a
+/// candidate is the number of agreeing minus disagreeing cells, relative to
+/// the number of known cells of `map`.
+///
+/// Returns [`None`] if `map` does not contain any known cell, if `params`
+/// contain non-finite values, non-positive steps or a negative window, or if
+/// they would take more than [`RegistrationParams::MAX_STEPS`] steps.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     registration::{register, RegistrationParams},
+///     AxisResolution, CellMap, MapState, RealWorldLocation,
+/// };
+///
+/// let make_map = |wall_x: f64| {
+///     let mut map = CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+///         AxisResolution::uniform(1.0),
+///     );
+///     map.set_rect(
+///         &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///         &RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+///         MapState::Explored,
+///     );
+///     // An L-shaped wall.
+///     map.set_rect(
+///         &RealWorldLocation::from_xyz(wall_x, 2.0, 0.0),
+///         &RealWorldLocation::from_xyz(wall_x + 1.0, 8.0, 0.0),
+///         MapState::Obstacle,
+///     );
+///     map.set_rect(
+///         &RealWorldLocation::from_xyz(wall_x, 2.0, 0.0),
+///         &RealWorldLocation::from_xyz(wall_x + 4.0, 3.0, 0.0),
+///         MapState::Obstacle,
+///     );
+///     map
+/// };
+/// // The same wall, seen 2.0 further to the left by another robot.
+/// let reference = make_map(3.0);
+/// let map = make_map(5.0);
+///
+/// let registration =
+///     register(&reference, &map, &RegistrationParams::default()).unwrap();
+///
+/// assert_eq!(registration.rotation(), 0.0);
+/// assert_eq!(registration.translation().x, -2.0);
+/// assert_eq!(registration.translation().y, 0.0);
+/// ```
+pub fn register(
+    reference: &CellMap,
+    map: &CellMap,
+    params: &RegistrationParams,
+) -> Option<Registration> {
+    let finite = [
+        params.translation_step,
+        params.angle_step,
+        params.max_translation,
+    ]
+    .iter()
+    .all(|value| value.is_finite());
+    if !(finite
+        && params.translation_step > 0.0
+        && params.angle_step > 0.0
+        && params.max_translation >= 0.0)
+    {
+        return None;
+    }
+    let steps = |range: f64, step: f64| {
+        (range / step + 1e-9)
+            .floor()
+            .to_i64()
+            .filter(|steps| *steps <= i64::from(RegistrationParams::MAX_STEPS))
+    };
+    let angles = steps(TAU, params.angle_step)?.max(1);
+    let translations = steps(params.max_translation, params.translation_step)?;
+
+    let [min, max] = map.bounds();
+    let center = RealWorldLocation::new(min.lerp(&max, 0.5));
+    // Known cells of `map` relative to its center, and whether they are
+    // obstacles.
+    let known: Vec<(f64, f64, bool)> = map
+        .cells()
+        .indexed_iter()
+        .filter_map(|((row, col), state)| {
+            let obstacle = occupancy(*state)?;
+            let cell = map.index_center::<f64>([row, col]);
+            Some((cell.x - center.x(), cell.y - center.y(), obstacle))
+        })
+        .collect();
+    if known.is_empty() {
+        return None;
+    }
+    let total = known.len().to_f64().expect("usize to f64 should work");
+
+    // The state of the cell of `reference` containing a location, and the
+    // squared distance to its center in cells.
+    let state_at = |x: f64, y: f64| {
+        let x = (x - reference.offset().x) * reference.resolution().x;
+        let y = (y - reference.offset().y) * reference.resolution().y;
+        let state = reference
+            .cells()
+            .get([y.floor().to_usize()?, x.floor().to_usize()?])?;
+        let residual =
+            (x - x.floor() - 0.5).powi(2) + (y - y.floor() - 0.5).powi(2);
+        Some((*state, residual))
+    };
+
+    let mut best: Option<(Registration, f64)> = None;
+    for angle in 0..angles {
+        let rotation = angle as f64 * params.angle_step;
+        let (sin, cos) = rotation.sin_cos();
+        let rotated: Vec<(f64, f64, bool)> = known
+            .iter()
+            .map(|(x, y, obstacle)| {
+                (
+                    cos * x - sin * y + center.x(),
+                    sin * x + cos * y + center.y(),
+                    *obstacle,
+                )
+            })
+            .collect();
+        for dy in -translations..=translations {
+            for dx in -translations..=translations {
+                let dx = dx as f64 * params.translation_step;
+                let dy = dy as f64 * params.translation_step;
+                let (agreement, residual) = rotated.iter().fold(
+                    (0, 0.0),
+                    |(agreement, residual), (x, y, obstacle)| {
+                        let Some((state, distance)) = state_at(x + dx, y + dy)
+                        else {
+                            return (agreement, residual);
+                        };
+                        match occupancy(state) {
+                            Some(other) if other == *obstacle => {
+                                (agreement + 1, residual + distance)
+                            }
+                            Some(_) => (agreement - 1, residual + distance),
+                            None => (agreement, residual),
+                        }
+                    },
+                );
+                let score = agreement as f64 / total;
+                // Among equally good candidates, which are common since every
+                // cell is matched with the closest one, prefer the one whose
+                // cells line up best with those of `reference`.
+                if best.map_or(true, |(best, best_residual)| {
+                    score > best.score
+                        || (score == best.score && residual < best_residual)
+                }) {
+                    // `R (p - c) + c + d` is `R p + (c - R c + d)`.
+                    best = Some((
+                        Registration {
+                            rotation,
+                            translation: Coords::new(
+                                center.x()
+                                    - (cos * center.x() - sin * center.y())
+                                    + dx,
+                                center.y()
+                                    - (sin * center.x() + cos * center.y())
+                                    + dy,
+                                0.0,
+                            ),
+                            score,
+                        },
+                        residual,
+                    ));
+                }
+            }
+        }
+    }
+    best.map(|(best, _)| best)
+}
+
+/// Whether a state is an obstacle, or [`None`] if it is unknown.
+fn occupancy(state: MapState) -> Option<bool> {
+    match state {
+        MapState::Unexplored | MapState::OutOfMap => None,
+        MapState::Obstacle => Some(true),
+        _ => Some(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AxisResolution;
+    use core::f64::consts::FRAC_PI_2;
+
+    /// A room with two walls, in the frame of the reference map.
+    fn scene(x: f64, y: f64) -> MapState {
+        if !(-10.0..10.0).contains(&x) || !(-10.0..10.0).contains(&y) {
+            MapState::Unexplored
+        } else if (2.0..3.0).contains(&x) && (-8.0..4.0).contains(&y)
+            || (5.0..6.0).contains(&y) && (-7.0..0.0).contains(&x)
+        {
+            MapState::Obstacle
+        } else {
+            MapState::Explored
+        }
+    }
+
+    fn sample(
+        map: &mut CellMap,
+        to_scene: impl Fn(&RealWorldLocation) -> RealWorldLocation,
+    ) {
+        for row in 0..map.height() {
+            for col in 0..map.width() {
+                let center = to_scene(&RealWorldLocation::new(
+                    map.index_center([row, col]),
+                ));
+                map.set_cell([row, col], scene(center.x(), center.y()));
+            }
+        }
+    }
+
+    #[test]
+    fn recovers_rotation_and_translation() {
+        let mut reference = CellMap::new(
+            RealWorldLocation::from_xyz(-10.0, -10.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        sample(&mut reference, |location| *location);
+        let truth = Registration {
+            rotation: FRAC_PI_2,
+            translation: Coords::new(3.0, 1.0, 0.0),
+            score: 1.0,
+        };
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 8.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        sample(&mut map, |location| truth.transform(location));
+
+        let registration =
+            register(&reference, &map, &RegistrationParams::default()).unwrap();
+
+        assert!((registration.rotation() - FRAC_PI_2).abs() < 1e-9);
+        assert!((registration.translation().x - 3.0).abs() < 1e-9);
+        assert!((registration.translation().y - 1.0).abs() < 1e-9);
+        let location = RealWorldLocation::from_xyz(1.5, 2.5, 0.0);
+        let back =
+            registration.inverse_transform(&registration.transform(&location));
+        assert!((back.x() - 1.5).abs() < 1e-9);
+        assert!((back.y() - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn apply_moves_cells_into_reference_frame() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_cell([0, 2], MapState::Obstacle);
+        let registration = Registration {
+            rotation: FRAC_PI_2,
+            translation: Coords::new(5.0, 0.0, 0.0),
+            score: 1.0,
+        };
+
+        let aligned = registration.apply(&map);
+
+        assert_eq!(aligned.cells().dim(), (3, 2));
+        assert_eq!(aligned.offset(), &Coords::new(3.0, 0.0, 0.0));
+        let obstacle =
+            registration.transform(&RealWorldLocation::from_xyz(2.5, 0.5, 0.0));
+        assert_eq!(aligned.get(&obstacle), Some(&MapState::Obstacle));
+        assert_eq!(
+            aligned
+                .cells()
+                .iter()
+                .filter(|state| **state == MapState::Unexplored)
+                .count(),
+            5
+        );
+    }
+
+    #[test]
+    fn nothing_to_register() {
+        let map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+
+        assert_eq!(register(&map, &map, &RegistrationParams::default()), None);
+        let mut explored = map.clone();
+        explored.set_cell([0, 0], MapState::Explored);
+        let params = RegistrationParams {
+            angle_step: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(register(&map, &explored, &params), None);
+    }
+
+    #[test]
+    fn invalid_params() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_cell([0, 0], MapState::Explored);
+        let register = |params| register(&map, &map, &params);
+
+        for params in [
+            RegistrationParams {
+                max_translation: f64::INFINITY,
+                ..Default::default()
+            },
+            RegistrationParams {
+                translation_step: f64::INFINITY,
+                ..Default::default()
+            },
+            RegistrationParams {
+                angle_step: f64::NAN,
+                ..Default::default()
+            },
+            RegistrationParams {
+                translation_step: 1e-9,
+                ..Default::default()
+            },
+            RegistrationParams {
+                angle_step: 1e-9,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(register(params), None, "{params:?}");
+        }
+        assert!(register(RegistrationParams::default()).is_some());
+    }
+}
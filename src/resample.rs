@@ -0,0 +1,265 @@
+use core::ops::Range;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+use num::ToPrimitive;
+
+use crate::merge::{CellVersion, MergePolicy};
+use crate::{AxisResolution, CellMap, CellMapError, MapState, MapStateMatrix};
+
+impl CellMap {
+    /// A copy of the map with another resolution, e.g. to use the map of a
+    /// robot running a different resolution.
+    ///
+    /// The new map starts at the same offset and covers at least the same
+    /// area. When down-sampling, each new cell combines all old cells whose
+    /// centers lie within it, with conflicting states resolved pairwise by
+    /// `policy` (see [`MergePolicy`]). When up-sampling, each new cell takes
+    /// the state of the old cell containing its center. New cells lying
+    /// beyond the old map, since its size is not a multiple of the new cells,
+    /// are [`MapState::OutOfMap`].
+    ///
+    /// Cells keep the timestamp of the state they took over, while states
+    /// made up by the policy are stamped with the map's clock, just like in
+    /// [`CellMap::merge`].
+    ///
+    /// # Errors
+    ///
+    /// - [`CellMapError::InvalidResolution`] if the `x` or `y` resolution is
+    ///   zero, negative, NaN or infinite.
+    /// - [`CellMapError::TooLarge`] if the resampled map would contain more
+    ///   than [`CellMap::MAX_CELLS`] cells.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     merge::PreferPriority, AxisResolution, CellMap, MapState,
+    ///     RealWorldLocation,
+    /// };
+    /// use ndarray::array;
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(4.0, 2.0, 0.0),
+    ///     AxisResolution::uniform(2.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    ///
+    /// let coarse = map
+    ///     .resampled(AxisResolution::uniform(1.0), &PreferPriority)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     coarse.cells(),
+    ///     array![
+    ///         [
+    ///             MapState::Obstacle,
+    ///             MapState::Unexplored,
+    ///             MapState::Unexplored,
+    ///             MapState::Unexplored,
+    ///         ],
+    ///         [MapState::Unexplored; 4],
+    ///     ]
+    /// );
+    /// ```
+    pub fn resampled(
+        &self,
+        resolution: AxisResolution,
+        policy: &impl MergePolicy,
+    ) -> Result<CellMap, CellMapError> {
+        let valid = |value: f64| value.is_finite() && value > 0.0;
+        if !valid(resolution.x) || !valid(resolution.y) {
+            return Err(CellMapError::InvalidResolution);
+        }
+        // Enough cells to cover the whole map, allowing for rounding errors.
+        let cells = |len: usize, old: f64, new: f64| {
+            (len.to_f64().expect("usize to f64 should work") / old * new - 1e-9)
+                .ceil()
+                .to_usize()
+                .ok_or(CellMapError::TooLarge)
+        };
+        let rows = cells(self.height(), self.resolution().y, resolution.y)?;
+        let columns = cells(self.width(), self.resolution().x, resolution.x)?;
+        CellMap::check_size(rows, columns)?;
+        let mut map = CellMap::from_raster(
+            MapStateMatrix::from_elem((rows, columns), MapState::OutOfMap),
+            resolution,
+            *self.offset(),
+        );
+        map.set_clock(self.clock());
+
+        for row in 0..map.height() {
+            for col in 0..map.width() {
+                let index = [row, col];
+                let rows = centers_within(
+                    row,
+                    self.resolution().y,
+                    resolution.y,
+                    self.height(),
+                );
+                let cols = centers_within(
+                    col,
+                    self.resolution().x,
+                    resolution.x,
+                    self.width(),
+                );
+                let version = |index: [usize; 2]| CellVersion {
+                    state: self.cells()[index],
+                    updated: self.index_last_update(index),
+                };
+                let merged = rows
+                    .flat_map(|row| cols.clone().map(move |col| [row, col]))
+                    .map(version)
+                    .reduce(|mine, theirs| {
+                        let state = policy.resolve(mine, theirs);
+                        if state == mine.state {
+                            mine
+                        } else if state == theirs.state {
+                            theirs
+                        } else {
+                            CellVersion {
+                                state,
+                                updated: self.clock(),
+                            }
+                        }
+                    })
+                    .or_else(|| {
+                        let center = crate::RealWorldLocation::new(
+                            map.index_center::<f64>(index),
+                        );
+                        self.location_to_map_index(&center).ok().map(version)
+                    })
+                    .unwrap_or(CellVersion {
+                        state: MapState::OutOfMap,
+                        updated: self.clock(),
+                    });
+                map.set_cell_stamped(index, merged.state, merged.updated);
+            }
+        }
+        map.clear_dirty();
+        Ok(map)
+    }
+}
+
+/// The range of rows or columns of a map with `resolution` whose centers lie
+/// within row or column `index` of a map with the same offset and the
+/// resolution `other`, along one axis. The range is clamped to `len`.
+fn centers_within(
+    index: usize,
+    resolution: f64,
+    other: f64,
+    len: usize,
+) -> Range<usize> {
+    let index = index.to_f64().expect("usize to f64 should work");
+    // The center of cell `i` lies at `(i + 0.5) / resolution`.
+    let first = |bound: f64| {
+        (bound / other * resolution - 0.5)
+            .ceil()
+            .max(0.0)
+            .min(len as f64)
+            .to_usize()
+            .expect("Value was clamped to the map dimensions")
+    };
+    first(index)..first(index + 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge::{PreferMine, PreferPriority};
+    use crate::{Location, RealWorldLocation};
+
+    fn make_map() -> CellMap {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(-1.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_clock(2.0);
+        map.set_location(
+            &RealWorldLocation::from_xyz(0.5, 1.5, 0.0),
+            MapState::Obstacle,
+        )
+        .unwrap();
+        map.set_clock(3.0);
+        map
+    }
+
+    #[test]
+    fn upsampling_copies_cells() {
+        let map = make_map();
+
+        let fine = map
+            .resampled(AxisResolution::uniform(2.0), &PreferPriority)
+            .unwrap();
+
+        assert_eq!(fine.cells().dim(), (4, 8));
+        assert_eq!(fine.offset(), map.offset());
+        assert_eq!(fine.clock(), 3.0);
+        for (index, state) in fine.cells().indexed_iter() {
+            let center =
+                RealWorldLocation::new(fine.index_center(index.into()));
+            assert_eq!(map.get_location(&center), Ok(*state));
+        }
+        assert_eq!(
+            fine.last_update(&RealWorldLocation::from_xyz(0.25, 1.75, 0.0)),
+            Ok(2.0)
+        );
+        assert!(fine.dirty_cells().is_empty());
+    }
+
+    #[test]
+    fn downsampling_resolves_conflicts() {
+        let map = make_map();
+
+        let coarse = map
+            .resampled(AxisResolution::uniform(0.5), &PreferPriority)
+            .unwrap();
+        let mine = map
+            .resampled(AxisResolution::uniform(0.5), &PreferMine)
+            .unwrap();
+
+        assert_eq!(coarse.cells().dim(), (1, 2));
+        assert_eq!(coarse.cells()[[0, 0]], MapState::Obstacle);
+        assert_eq!(coarse.cells()[[0, 1]], MapState::Unexplored);
+        // The first cell of the block is unexplored, so any other state wins.
+        assert_eq!(mine.cells()[[0, 0]], MapState::Obstacle);
+        assert_eq!(
+            coarse.last_update(&RealWorldLocation::from_xyz(-0.5, 0.5, 0.0)),
+            Ok(2.0)
+        );
+    }
+
+    #[test]
+    fn cells_beyond_old_map() {
+        let map = make_map();
+
+        // Cells are 0.625 wide, so 7 of them extend 0.375 beyond the map.
+        let fine = map
+            .resampled(AxisResolution::new(1.6, 1.0, 1.0), &PreferPriority)
+            .unwrap();
+
+        assert_eq!(fine.cells().dim(), (2, 7));
+        assert_eq!(fine.cells()[[0, 5]], MapState::Unexplored);
+        assert_eq!(fine.cells()[[0, 6]], MapState::OutOfMap);
+        assert_eq!(fine.cells()[[1, 6]], MapState::OutOfMap);
+        let coarse = map
+            .resampled(AxisResolution::uniform(1.0 / 3.0), &PreferPriority)
+            .unwrap();
+
+        assert_eq!(coarse.cells().dim(), (1, 2));
+        assert_eq!(coarse.cells()[[0, 1]], MapState::Unexplored);
+        assert_eq!(
+            map.resampled(AxisResolution::uniform(0.0), &PreferPriority),
+            Err(CellMapError::InvalidResolution)
+        );
+        assert_eq!(
+            map.resampled(AxisResolution::uniform(1e4), &PreferPriority),
+            Err(CellMapError::TooLarge)
+        );
+    }
+}
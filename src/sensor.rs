@@ -0,0 +1,198 @@
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{CellMap, Coords, MapState, RealWorldLocation};
+
+/// Describes which part of a map a sensor covers, in order to record the
+/// exploration progress of a robot.
+///
+/// Implementations mark the cells observed from a pose as
+/// [`MapState::Explored`]. This way, robots with different sensors update
+/// their maps in the same way, and the partitioning only sees the result.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, CircularSensor, ConeSensor, MapState,
+///     MaskMapState, RealWorldLocation, SensorModel,
+/// };
+///
+/// let mut map = CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// );
+/// let sensors: Vec<Box<dyn SensorModel>> = vec![
+///     Box::new(CircularSensor { radius: 1.0 }),
+///     Box::new(ConeSensor {
+///         range: 3.0,
+///         field_of_view: 1.0,
+///         heading: 0.0,
+///     }),
+/// ];
+///
+/// let pose = RealWorldLocation::from_xyz(5.0, 5.0, 0.0);
+/// let observed: usize =
+///     sensors.iter().map(|sensor| sensor.observe(&mut map, &pose)).sum();
+///
+/// assert_eq!(map.get_map_state(MapState::Explored).len(), observed);
+/// ```
+pub trait SensorModel {
+    /// Mark the cells observed from `pose` as [`MapState::Explored`].
+    ///
+    /// Returns the number of cells which were newly explored.
+    fn observe(&self, map: &mut CellMap, pose: &RealWorldLocation) -> usize;
+}
+
+/// A sensor seeing all around the robot, e.g. a 360° lidar.
+///
+/// Observes all cells whose center lies within `radius` of the pose and is
+/// in sight of it, see [`CellMap::line_of_sight`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircularSensor {
+    pub radius: f64,
+}
+
+impl SensorModel for CircularSensor {
+    fn observe(&self, map: &mut CellMap, pose: &RealWorldLocation) -> usize {
+        map.observe_visible(pose, self.radius, |_| true)
+    }
+}
+
+/// A sensor with a limited field of view, e.g. a camera.
+///
+/// Observes all cells whose center lies within `range` of the pose, at most
+/// `field_of_view / 2` radians away from the direction the sensor is facing,
+/// and in sight of the pose (see [`CellMap::line_of_sight`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConeSensor {
+    pub range: f64,
+    /// Opening angle in radians.
+    pub field_of_view: f64,
+    /// Direction the sensor is facing, as a yaw angle in radians like
+    /// [`crate::Robot::heading`]. Needs to be updated as the robot turns.
+    pub heading: f64,
+}
+
+impl SensorModel for ConeSensor {
+    fn observe(&self, map: &mut CellMap, pose: &RealWorldLocation) -> usize {
+        let (sin, cos) = self.heading.sin_cos();
+        let min_cos = (self.field_of_view / 2.0).cos();
+        map.observe_visible(pose, self.range, |center| {
+            let (dx, dy) = (center.x - pose.x, center.y - pose.y);
+            let distance = dx.hypot(dy);
+            distance == 0.0 || dx * cos + dy * sin >= min_cos * distance
+        })
+    }
+}
+
+impl CellMap {
+    /// Internal helper marking the cells within `radius` of `pose` whose
+    /// center is in sight and satisfies `filter` as explored.
+    fn observe_visible(
+        &mut self,
+        pose: &RealWorldLocation,
+        radius: f64,
+        filter: impl Fn(&Coords) -> bool,
+    ) -> usize {
+        let observed: Vec<[usize; 2]> = self
+            .disc_indices(pose, radius)
+            .filter(|index| {
+                matches!(
+                    self.cells()[*index],
+                    MapState::Unexplored
+                        | MapState::Frontier
+                        | MapState::Assigned
+                )
+            })
+            .filter(|index| {
+                let center = self.index_center(*index);
+                filter(&center)
+                    && self.line_of_sight(pose, &RealWorldLocation::new(center))
+            })
+            .collect();
+        for index in &observed {
+            self.set_cell(*index, MapState::Explored);
+        }
+        observed.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    fn state(map: &CellMap, x: f64, y: f64) -> MapState {
+        map.get_location(&RealWorldLocation::from_xyz(x, y, 0.0))
+            .unwrap()
+    }
+
+    #[test]
+    fn circular_sensor_is_occluded() {
+        let mut map = make_map();
+        map.set_location(
+            &RealWorldLocation::from_xyz(5.5, 4.5, 0.0),
+            MapState::Obstacle,
+        )
+        .unwrap();
+        let sensor = CircularSensor { radius: 2.0 };
+        let pose = RealWorldLocation::from_xyz(4.5, 4.5, 0.0);
+
+        let observed = sensor.observe(&mut map, &pose);
+
+        assert_eq!(state(&map, 3.5, 4.5), MapState::Explored);
+        assert_eq!(state(&map, 5.5, 4.5), MapState::Obstacle);
+        assert_eq!(state(&map, 6.5, 4.5), MapState::Unexplored);
+        // 13 cell centers within the radius, minus the obstacle and the cell
+        // behind it. The diagonal neighbors of the obstacle are in sight.
+        assert_eq!(observed, 11);
+        assert_eq!(sensor.observe(&mut map, &pose), 0);
+    }
+
+    #[test]
+    fn cone_sensor_faces_heading() {
+        let mut map = make_map();
+        let sensor = ConeSensor {
+            range: 3.0,
+            field_of_view: core::f64::consts::FRAC_PI_2,
+            heading: core::f64::consts::FRAC_PI_2,
+        };
+
+        sensor.observe(&mut map, &RealWorldLocation::from_xyz(5.5, 5.5, 0.0));
+
+        assert_eq!(state(&map, 5.5, 5.5), MapState::Explored);
+        assert_eq!(state(&map, 5.5, 8.5), MapState::Explored);
+        assert_eq!(state(&map, 6.5, 7.5), MapState::Explored);
+        assert_eq!(state(&map, 7.5, 6.5), MapState::Unexplored);
+        assert_eq!(state(&map, 5.5, 4.5), MapState::Unexplored);
+    }
+
+    #[test]
+    fn robots_and_outside_poses() {
+        let mut map = make_map();
+        let pose = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        map.set_location(&pose, MapState::MyRobot).unwrap();
+        let sensor = CircularSensor { radius: 1.0 };
+
+        assert_eq!(sensor.observe(&mut map, &pose), 2);
+        assert_eq!(state(&map, 0.5, 0.5), MapState::MyRobot);
+        assert_eq!(
+            sensor.observe(
+                &mut map,
+                &RealWorldLocation::from_xyz(-0.5, 0.5, 0.0)
+            ),
+            0
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use ndarray::Array2;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::cell_map::index_to_internal;
+use crate::wavefront::Front;
+use crate::{Cell, CellMap, GridIndex};
+
+impl CellMap {
+    /// Extract the skeleton of the free space, i.e. the cells of its medial
+    /// axis or generalized Voronoi diagram (GVD).
+    ///
+    /// The skeleton consists of the cells which are about equally far from
+    /// two different parts of the surrounding walls, such as the center line
+    /// of a corridor. Like in [`CellMap::brushfire`], walls are
+    /// [`MapState::Obstacle`](crate::MapState::Obstacle) and
+    /// [`MapState::OutOfMap`](crate::MapState::OutOfMap) cells as well as the
+    /// edges of the map. Only one cell is chosen where the skeleton passes
+    /// between two cells, which keeps it one cell thin in most places.
+    ///
+    /// Skeletons follow the natural corridors of an environment, which makes
+    /// them useful for topological navigation and to find where to split it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{AxisResolution, CellMap, RealWorldLocation};
+    ///
+    /// // A corridor, five cells wide.
+    /// let map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(20.0, 5.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    ///
+    /// let skeleton = map.skeleton();
+    ///
+    /// // The center line, apart from the ends of the corridor.
+    /// let center_line = skeleton
+    ///     .iter()
+    ///     .filter(|cell| cell.location().y() == 2.0)
+    ///     .count();
+    /// assert!(center_line >= 14);
+    /// ```
+    pub fn skeleton(&self) -> Vec<Cell<'_>> {
+        self.skeleton_indices()
+            .into_iter()
+            .map(|index| {
+                Cell::new(
+                    index_to_internal(
+                        index,
+                        *self.offset(),
+                        *self.resolution(),
+                    ),
+                    &self.cells()[index],
+                )
+            })
+            .collect()
+    }
+
+    /// Internal helper returning the indices of the cells of
+    /// [`CellMap::skeleton`], sorted by row and column.
+    pub(crate) fn skeleton_indices(&self) -> Vec<[usize; 2]> {
+        let sites = self.nearest_sites();
+        let clearance = |[row, col]: [usize; 2]| {
+            self.site_distance([row as isize, col as isize], sites[[row, col]])
+        };
+
+        let (rows, columns) = self.cells().dim();
+        let mut skeleton = Vec::new();
+        for ((row, col), _) in self.cells().indexed_iter() {
+            let index = [row, col];
+            if !self.is_passable(index) {
+                continue;
+            }
+            let on_skeleton = GridIndex::from(index)
+                .neighbors([rows, columns])
+                .map(<[usize; 2]>::from)
+                .filter(|neighbor| self.is_passable(*neighbor))
+                .any(|neighbor| {
+                    let (mine, theirs) =
+                        (clearance(index), clearance(neighbor));
+                    let ([s_row, s_col], [n_row, n_col]) =
+                        (sites[index], sites[neighbor]);
+                    // Sites next to each other are parts of the same wall, while
+                    // sites closer to each other than to the cell lie around a
+                    // corner.
+                    let apart =
+                        s_row.abs_diff(n_row) > 1 || s_col.abs_diff(n_col) > 1;
+                    // Of the two cells the skeleton passes between, keep the one
+                    // further away from the walls.
+                    apart
+                        && self.site_distance(sites[index], sites[neighbor])
+                            > mine
+                        && (mine > theirs
+                            || (mine == theirs && index < neighbor))
+                });
+            if on_skeleton {
+                skeleton.push(index);
+            }
+        }
+        skeleton
+    }
+
+    /// Internal helper returning for each cell the index of the closest
+    /// blocked cell, where indices of `-1`, the number of rows or the number
+    /// of columns lie beyond the edges of the map.
+    ///
+    /// Sites are propagated from neighbor to neighbor, which gives the exact
+    /// closest one in almost all cases.
+    fn nearest_sites(&self) -> Array2<[isize; 2]> {
+        let (rows, columns) = self.cells().dim();
+        let distance = |[row, col]: [usize; 2], site| {
+            self.site_distance([row as isize, col as isize], site)
+        };
+        let mut sites = Array2::from_elem((rows, columns), [-1, -1]);
+        let mut distances = Array2::from_elem((rows, columns), f64::INFINITY);
+        let mut queue = BinaryHeap::new();
+        for ((row, col), _) in self.cells().indexed_iter() {
+            let index = [row, col];
+            let (r, c) = (row as isize, col as isize);
+            let candidates: &[[isize; 2]] = if !self.is_passable(index) {
+                &[[r, c]]
+            } else {
+                &[[-1, c], [rows as isize, c], [r, -1], [r, columns as isize]]
+            };
+            for site in candidates {
+                let site_distance = distance(index, *site);
+                if site_distance < distances[index] {
+                    distances[index] = site_distance;
+                    sites[index] = *site;
+                }
+            }
+            queue.push(Front(distances[index], index));
+        }
+
+        while let Some(Front(current, index)) = queue.pop() {
+            if current > distances[index] {
+                continue;
+            }
+            for neighbor in self.neighbor_indices(index) {
+                let next = distance(neighbor, sites[index]);
+                if next < distances[neighbor] {
+                    distances[neighbor] = next;
+                    sites[neighbor] = sites[index];
+                    queue.push(Front(next, neighbor));
+                }
+            }
+        }
+        sites
+    }
+
+    /// Internal helper returning the real-world distance between the centers
+    /// of two cells, which may lie beyond the edges of the map.
+    fn site_distance(
+        &self,
+        [row, col]: [isize; 2],
+        [s_row, s_col]: [isize; 2],
+    ) -> f64 {
+        let dx = (col - s_col) as f64 / self.resolution().x;
+        let dy = (row - s_row) as f64 / self.resolution().y;
+        dx.hypot(dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, MapState, RealWorldLocation};
+
+    fn make_map(width: f64, height: f64) -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(width, height, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    #[test]
+    fn center_line_of_corridor() {
+        for height in [3.0, 4.0] {
+            let map = make_map(12.0, height);
+
+            let skeleton = map.skeleton_indices();
+
+            for col in 2..10 {
+                assert!(skeleton.contains(&[1, col]), "{height} {col}");
+                assert!(!skeleton.contains(&[0, col]));
+                assert!(!skeleton.contains(&[2, col]));
+            }
+        }
+    }
+
+    #[test]
+    fn skeleton_splits_around_obstacle() {
+        let mut map = make_map(9.0, 9.0);
+        map.set_rect(
+            &RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+            &RealWorldLocation::from_xyz(6.0, 6.0, 0.0),
+            MapState::Obstacle,
+        );
+
+        let skeleton = map.skeleton_indices();
+
+        // The skeleton circles the obstacle through the middle of the
+        // three cells wide ring of free space.
+        for i in 3..6 {
+            assert!(skeleton.contains(&[1, i]));
+            assert!(skeleton.contains(&[7, i]));
+            assert!(skeleton.contains(&[i, 1]));
+            assert!(skeleton.contains(&[i, 7]));
+        }
+        assert!(skeleton.iter().all(|index| map.is_passable(*index)));
+    }
+
+    #[test]
+    fn no_skeleton_without_free_space() {
+        let mut map = make_map(3.0, 3.0);
+        map.set_rect(
+            &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            &RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+            MapState::Obstacle,
+        );
+
+        assert!(map.skeleton().is_empty());
+    }
+}
@@ -0,0 +1,373 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{
+    Cell, CellMap, Location, LocationError, LocationType, Mask, OutOfMapError,
+    RealWorldLocation,
+};
+
+/// Map made of several [`CellMap`]s stacked along the `z` axis, e.g. the
+/// floors of a building.
+///
+/// Each slice covers a half-open interval of `z` values. The [`Location`]
+/// functions pick the slice based on the `z` component of the given location
+/// and forward the call to it. Note that the slice's own map still checks the
+/// location against its own bounds, including `z`, so its offset should lie at
+/// (or below) the start of its interval.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+///     SlicedMap,
+/// };
+///
+/// let floor = |z: f64| {
+///     CellMap::new(
+///         RealWorldLocation::from_xyz(0.0, 0.0, z),
+///         RealWorldLocation::from_xyz(10.0, 10.0, z),
+///         AxisResolution::uniform(1.0),
+///     )
+/// };
+/// let mut building = SlicedMap::new();
+/// building.add_slice(0.0..3.0, floor(0.0)).unwrap();
+/// building.add_slice(3.0..6.0, floor(3.0)).unwrap();
+///
+/// let upstairs = RealWorldLocation::from_xyz(2.5, 2.5, 4.0);
+/// building.set_location(&upstairs, MapState::Explored).unwrap();
+///
+/// assert_eq!(building.get_location(&upstairs), Ok(MapState::Explored));
+/// assert_eq!(
+///     building.get_location(&RealWorldLocation::from_xyz(2.5, 2.5, 1.0)),
+///     Ok(MapState::Unexplored)
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct SlicedMap {
+    /// Slices ordered by their interval, which do not overlap.
+    slices: Vec<(Range<f64>, CellMap)>,
+}
+
+impl SlicedMap {
+    /// Create a map without any slices.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a slice covering the `z` values in `z_range`.
+    ///
+    /// # Errors
+    ///
+    /// - [`SlicedMapError::InvalidInterval`] if the interval is empty or
+    ///   contains NaN.
+    /// - [`SlicedMapError::Overlap`] if the interval overlaps the one of an
+    ///   existing slice.
+    pub fn add_slice(
+        &mut self,
+        z_range: Range<f64>,
+        map: CellMap,
+    ) -> Result<(), SlicedMapError> {
+        if z_range.is_empty() {
+            return Err(SlicedMapError::InvalidInterval);
+        }
+        let position = self
+            .slices
+            .partition_point(|(range, _)| range.start < z_range.start);
+        let overlaps_previous =
+            position > 0 && self.slices[position - 1].0.end > z_range.start;
+        let overlaps_next = self
+            .slices
+            .get(position)
+            .is_some_and(|(range, _)| range.start < z_range.end);
+        if overlaps_previous || overlaps_next {
+            return Err(SlicedMapError::Overlap);
+        }
+        self.slices.insert(position, (z_range, map));
+        Ok(())
+    }
+
+    /// Remove the slice containing `z` and return it.
+    pub fn remove_slice(&mut self, z: f64) -> Option<(Range<f64>, CellMap)> {
+        self.position(z).map(|i| self.slices.remove(i))
+    }
+
+    /// The slice containing `z`.
+    pub fn slice(&self, z: f64) -> Option<&CellMap> {
+        self.position(z).map(|i| &self.slices[i].1)
+    }
+
+    /// The slice containing `z`.
+    pub fn slice_mut(&mut self, z: f64) -> Option<&mut CellMap> {
+        self.position(z).map(|i| &mut self.slices[i].1)
+    }
+
+    /// Iterate over the slices and their intervals, ordered along `z`.
+    pub fn slices(&self) -> impl Iterator<Item = (&Range<f64>, &CellMap)> {
+        self.slices.iter().map(|(range, map)| (range, map))
+    }
+
+    /// Number of slices.
+    pub fn len(&self) -> usize {
+        self.slices.len()
+    }
+
+    /// Whether there are no slices.
+    pub fn is_empty(&self) -> bool {
+        self.slices.is_empty()
+    }
+
+    /// The bottom left and top right corners of the box enclosing all slices,
+    /// from the start of the lowest interval to the end of the highest one.
+    ///
+    /// Returns `None` if there are no slices.
+    pub fn bounds(&self) -> Option<[RealWorldLocation; 2]> {
+        let (first, _) = self.slices.first()?;
+        let (last, _) = self.slices.last()?;
+        let [mut min, mut max] = [[f64::INFINITY; 2], [f64::NEG_INFINITY; 2]];
+        for (_, map) in &self.slices {
+            let [low, high] = map.bounds();
+            min = [min[0].min(low.x()), min[1].min(low.y())];
+            max = [max[0].max(high.x()), max[1].max(high.y())];
+        }
+        Some([
+            RealWorldLocation::from_xyz(min[0], min[1], first.start),
+            RealWorldLocation::from_xyz(max[0], max[1], last.end),
+        ])
+    }
+
+    /// Error for a location whose `z` component is not within any slice.
+    fn out_of_slices(&self, coord: &RealWorldLocation) -> LocationError {
+        let bounds = self.bounds();
+        let axes = match &bounds {
+            Some([min, max]) => {
+                let outside = |value: f64, low: f64, high: f64| {
+                    !(low..high).contains(&value)
+                };
+                [
+                    outside(coord.x(), min.x(), max.x()),
+                    outside(coord.y(), min.y(), max.y()),
+                    true,
+                ]
+            }
+            None => [true; 3],
+        };
+        LocationError::from(OutOfMapError::new(*coord, bounds, axes))
+    }
+
+    fn position(&self, z: f64) -> Option<usize> {
+        let position =
+            self.slices.partition_point(|(range, _)| range.start <= z);
+        position
+            .checked_sub(1)
+            .filter(|i| self.slices[*i].0.contains(&z))
+    }
+}
+
+impl Location for SlicedMap {
+    /// Retrieve the value at the given location from the slice containing
+    /// its `z` component.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocationError::OutOfMap`] if no slice contains the location.
+    fn get_location(
+        &self,
+        coord: &RealWorldLocation,
+    ) -> Result<LocationType, LocationError> {
+        self.slice(coord.z())
+            .ok_or_else(|| self.out_of_slices(coord))?
+            .get_location(coord)
+    }
+
+    /// Update the value at the given location in the slice containing its
+    /// `z` component.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SlicedMap::get_location`].
+    fn set_location(
+        &mut self,
+        coord: &RealWorldLocation,
+        value: LocationType,
+    ) -> Result<(), LocationError> {
+        match self.position(coord.z()) {
+            Some(i) => self.slices[i].1.set_location(coord, value),
+            None => Err(self.out_of_slices(coord)),
+        }
+    }
+
+    /// Retrieve the locations from the slice containing the `z` component
+    /// of `center`, or none if there is no such slice.
+    fn locations_within(
+        &self,
+        center: &RealWorldLocation,
+        radius: f64,
+    ) -> Vec<RealWorldLocation> {
+        self.slice(center.z())
+            .map(|slice| slice.locations_within(center, radius))
+            .unwrap_or_default()
+    }
+
+    /// Whether `a` and `b` lie in the same slice and in the same cell of it.
+    fn same_cell(&self, a: &RealWorldLocation, b: &RealWorldLocation) -> bool {
+        match (self.position(a.z()), self.position(b.z())) {
+            (Some(i), Some(j)) => i == j && self.slices[i].1.same_cell(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Mask for SlicedMap {
+    /// Retrieve the matching cells of all slices, ordered along `z`.
+    fn get_map_region(
+        &self,
+        filter: &dyn Fn(LocationType) -> bool,
+    ) -> Vec<Cell<'_>> {
+        self.slices
+            .iter()
+            .flat_map(|(_, map)| map.get_map_region(filter))
+            .collect()
+    }
+}
+
+/// Why a slice could not be added, see [`SlicedMap::add_slice`].
+#[derive(Debug, PartialEq)]
+pub enum SlicedMapError {
+    /// The interval of the slice is empty or contains NaN.
+    InvalidInterval,
+    /// The interval of the slice overlaps the one of an existing slice.
+    Overlap,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, MapState, MaskMapState};
+
+    fn floor(z: f64) -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, z),
+            RealWorldLocation::from_xyz(4.0, 4.0, z),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    fn building() -> SlicedMap {
+        let mut map = SlicedMap::new();
+        map.add_slice(3.0..6.0, floor(3.0)).unwrap();
+        map.add_slice(0.0..3.0, floor(0.0)).unwrap();
+        map
+    }
+
+    #[test]
+    fn slices_are_ordered() {
+        let map = building();
+
+        assert_eq!(
+            map.slices()
+                .map(|(range, _)| range.start)
+                .collect::<Vec<_>>(),
+            [0.0, 3.0]
+        );
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn overlapping_and_invalid_slices() {
+        let mut map = building();
+
+        assert_eq!(
+            map.add_slice(5.0..7.0, floor(5.0)),
+            Err(SlicedMapError::Overlap)
+        );
+        assert_eq!(
+            map.add_slice(-1.0..0.5, floor(-1.0)),
+            Err(SlicedMapError::Overlap)
+        );
+        assert_eq!(
+            map.add_slice(8.0..8.0, floor(8.0)),
+            Err(SlicedMapError::InvalidInterval)
+        );
+        assert_eq!(
+            map.add_slice(f64::NAN..8.0, floor(8.0)),
+            Err(SlicedMapError::InvalidInterval)
+        );
+        assert_eq!(map.add_slice(6.0..9.0, floor(6.0)), Ok(()));
+    }
+
+    #[test]
+    fn routes_by_z() {
+        let mut map = building();
+        let boundary = RealWorldLocation::from_xyz(1.5, 1.5, 3.0);
+        map.set_location(&boundary, MapState::Frontier).unwrap();
+
+        // The interval start belongs to the upper slice.
+        assert_eq!(
+            map.slice(3.0).unwrap().get_location(&boundary),
+            Ok(MapState::Frontier)
+        );
+        assert_eq!(
+            map.get_location(&RealWorldLocation::from_xyz(1.5, 1.5, 2.9)),
+            Ok(MapState::Unexplored)
+        );
+        let above = RealWorldLocation::from_xyz(1.5, 1.5, 6.0);
+        assert_eq!(
+            map.get_location(&above),
+            Err(LocationError::from(OutOfMapError::new(
+                above,
+                map.bounds(),
+                [false, false, true]
+            )))
+        );
+        assert!(matches!(
+            map.set_location(
+                &RealWorldLocation::from_xyz(1.5, 1.5, -1.0),
+                MapState::Explored
+            ),
+            Err(LocationError::OutOfMap(_))
+        ));
+    }
+
+    #[test]
+    fn bounds_enclose_all_slices() {
+        let mut map = building();
+        map.add_slice(
+            6.0..9.0,
+            CellMap::new(
+                RealWorldLocation::from_xyz(-2.0, 1.0, 6.0),
+                RealWorldLocation::from_xyz(3.0, 6.0, 6.0),
+                AxisResolution::uniform(1.0),
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            map.bounds(),
+            Some([
+                RealWorldLocation::from_xyz(-2.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(4.0, 6.0, 9.0),
+            ])
+        );
+        assert_eq!(SlicedMap::new().bounds(), None);
+    }
+
+    #[test]
+    fn mask_spans_all_slices() {
+        let mut map = building();
+        map.set_location(
+            &RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+            MapState::Explored,
+        )
+        .unwrap();
+        map.set_location(
+            &RealWorldLocation::from_xyz(0.5, 0.5, 5.0),
+            MapState::Explored,
+        )
+        .unwrap();
+
+        assert_eq!(map.get_map_state(MapState::Explored).len(), 2);
+        assert!(map.remove_slice(4.0).is_some());
+        assert_eq!(map.get_map_state(MapState::Explored).len(), 1);
+    }
+}
@@ -0,0 +1,345 @@
+//! Visiting order of several goals.
+//!
+//! After clustering frontiers (see
+//! [`CellMap::cluster_frontiers`](crate::CellMap::cluster_frontiers)) or
+//! assigning several goals to a robot, the robot needs to decide in which
+//! order to visit them. Finding the shortest order is the traveling salesman
+//! problem, so [`optimize`] settles for a good order: it starts from the
+//! nearest neighbor tour and improves it with 2-opt moves until no move
+//! shortens it any further.
+//!
+//! [`CellMap::order_goals`] builds the costs from travel distances around
+//! obstacles, see [`CellMap::wavefront`].
+
+use alloc::vec;
+use alloc::vec::Vec;
+use ndarray::Array2;
+
+use crate::{CellMap, RealWorldLocation};
+
+/// Tolerance for accepting a 2-opt move, such that rounding errors do not
+/// cause moves back and forth.
+const EPSILON: f64 = 1e-9;
+
+/// Order in which to visit the goals, see [`optimize`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tour {
+    order: Vec<usize>,
+    length: f64,
+}
+
+impl Tour {
+    /// The indices of the goals, in the order they are visited.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+    /// The sum of the costs from the start to the first goal and between
+    /// consecutive goals.
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+/// Why no tour could be found, see [`optimize`] and
+/// [`CellMap::order_goals`](crate::CellMap::order_goals).
+#[derive(Debug, PartialEq)]
+pub enum TourError {
+    /// The cost matrix is not square or empty.
+    InvalidShape,
+    /// The cost matrix contains a cost which is infinite or NaN, e.g. because
+    /// a goal cannot be reached. The offending `[from, to]` index is given,
+    /// where `0` is the start.
+    NonFiniteCost([usize; 2]),
+}
+
+/// Find a short order to visit all goals, starting from a given location.
+///
+/// `costs` holds the cost of going from one location (row) to another
+/// (column), where location `0` is the start and location `i + 1` is goal
+/// `i`. The costs are assumed to be symmetric. The tour ends at the last
+/// goal, i.e. it does not return to the start.
+///
+/// The result is not necessarily optimal, but free of crossings for
+/// straight-line costs. Ties are broken by the lower index, so the same costs
+/// always result in the same tour.
+///
+/// # Errors
+///
+/// - [`TourError::InvalidShape`] if `costs` is not square or does not even
+///   contain the start.
+/// - [`TourError::NonFiniteCost`] if any cost is infinite or NaN.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::tour::optimize;
+/// use ndarray::Array2;
+///
+/// // Start at 0 on a line, with goals at 3, 1 and 2.
+/// let points = [0.0, 3.0, 1.0, 2.0_f64];
+/// let costs =
+///     Array2::from_shape_fn((4, 4), |(a, b)| (points[a] - points[b]).abs());
+///
+/// let tour = optimize(&costs).unwrap();
+///
+/// assert_eq!(tour.order(), &[1, 2, 0]);
+/// assert_eq!(tour.length(), 3.0);
+/// ```
+pub fn optimize(costs: &Array2<f64>) -> Result<Tour, TourError> {
+    let (rows, columns) = costs.dim();
+    if rows != columns || rows == 0 {
+        return Err(TourError::InvalidShape);
+    }
+    if let Some((index, _)) =
+        costs.indexed_iter().find(|(_, cost)| !cost.is_finite())
+    {
+        return Err(TourError::NonFiniteCost([index.0, index.1]));
+    }
+
+    let mut path = nearest_neighbor(costs);
+    two_opt(costs, &mut path);
+
+    let length = path.windows(2).map(|step| costs[[step[0], step[1]]]).sum();
+    Ok(Tour {
+        order: path
+            .into_iter()
+            .skip(1)
+            .map(|location| location - 1)
+            .collect(),
+        length,
+    })
+}
+
+/// Path starting at location `0`, always moving on to the closest location
+/// which was not visited yet.
+fn nearest_neighbor(costs: &Array2<f64>) -> Vec<usize> {
+    let count = costs.nrows();
+    let mut visited = vec![false; count];
+    let mut path = Vec::with_capacity(count);
+    let mut current = 0;
+    visited[current] = true;
+    path.push(current);
+    for _ in 1..count {
+        current = (0..count)
+            .filter(|location| !visited[*location])
+            .min_by(|a, b| {
+                costs[[current, *a]].total_cmp(&costs[[current, *b]])
+            })
+            .expect("There are unvisited locations left");
+        visited[current] = true;
+        path.push(current);
+    }
+    path
+}
+
+/// Reverse sections of the `path` while doing so shortens it, keeping the
+/// start in place.
+fn two_opt(costs: &Array2<f64>, path: &mut [usize]) {
+    let last = path.len() - 1;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..last.saturating_sub(1) {
+            for j in i + 2..=last {
+                // Replace the edges `i -> i + 1` and `j -> j + 1` by `i -> j`
+                // and `i + 1 -> j + 1`. The end of an open path has no edge.
+                let after = |k: usize| (k < last).then(|| path[k + 1]);
+                let edge = |a: usize, b: Option<usize>| {
+                    b.map_or(0.0, |b| costs[[a, b]])
+                };
+                let before =
+                    costs[[path[i], path[i + 1]]] + edge(path[j], after(j));
+                let reversed =
+                    costs[[path[i], path[j]]] + edge(path[i + 1], after(j));
+                if reversed < before - EPSILON {
+                    path[i + 1..=j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+}
+
+impl CellMap {
+    /// Find a short order to visit `goals` from `start`, measuring the
+    /// travel distance around obstacles, see [`CellMap::wavefront`] and
+    /// [`optimize`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TourError::NonFiniteCost`] if the start or a goal lies
+    /// outside the map, on an obstacle, or cannot be reached from the others.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// // A wall with a door at the top, between the start and goal 0.
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(5.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(6.0, 9.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    /// let goals = [
+    ///     RealWorldLocation::from_xyz(6.5, 0.5, 0.0),
+    ///     RealWorldLocation::from_xyz(4.5, 9.5, 0.0),
+    /// ];
+    ///
+    /// let tour = map
+    ///     .order_goals(&RealWorldLocation::from_xyz(4.5, 0.5, 0.0), &goals)
+    ///     .unwrap();
+    ///
+    /// // Goal 0 is right behind the wall, but only reachable via the door.
+    /// assert_eq!(tour.order(), &[1, 0]);
+    /// ```
+    pub fn order_goals(
+        &self,
+        start: &RealWorldLocation,
+        goals: &[RealWorldLocation],
+    ) -> Result<Tour, TourError> {
+        let locations: Vec<&RealWorldLocation> =
+            core::iter::once(start).chain(goals).collect();
+        let mut costs = Array2::from_elem(
+            (locations.len(), locations.len()),
+            f64::INFINITY,
+        );
+        for (from, location) in locations.iter().enumerate() {
+            let Ok(distances) = self.wavefront(core::slice::from_ref(location))
+            else {
+                continue;
+            };
+            for (to, target) in locations.iter().enumerate() {
+                if let Ok(index) = self.location_to_map_index(target) {
+                    costs[[from, to]] = distances[index];
+                }
+            }
+        }
+        optimize(&costs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, MapState};
+
+    fn straight_line_costs(points: &[(f64, f64)]) -> Array2<f64> {
+        Array2::from_shape_fn((points.len(), points.len()), |(a, b)| {
+            let (a, b) = (points[a], points[b]);
+            ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+        })
+    }
+
+    /// Length of the shortest open path from location `0`, by trying every
+    /// permutation.
+    fn brute_force(costs: &Array2<f64>) -> f64 {
+        fn go(costs: &Array2<f64>, at: usize, visited: &mut Vec<bool>) -> f64 {
+            let mut best: Option<f64> = None;
+            for next in 0..costs.nrows() {
+                if visited[next] {
+                    continue;
+                }
+                visited[next] = true;
+                let length = costs[[at, next]] + go(costs, next, visited);
+                visited[next] = false;
+                best = Some(best.map_or(length, |best| best.min(length)));
+            }
+            best.unwrap_or(0.0)
+        }
+        let mut visited = vec![false; costs.nrows()];
+        visited[0] = true;
+        go(costs, 0, &mut visited)
+    }
+
+    #[test]
+    fn two_opt_removes_crossing() {
+        // Nearest neighbor goes right along the bottom, then has to cross
+        // back to the far corner.
+        let costs = straight_line_costs(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (0.0, 1.2),
+            (1.0, 1.1),
+        ]);
+
+        let tour = optimize(&costs).unwrap();
+
+        assert_eq!(tour.order(), &[0, 1, 2, 4, 3]);
+        assert!((tour.length() - brute_force(&costs)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn close_to_brute_force() {
+        let points: Vec<(f64, f64)> = (0..7)
+            .map(|i| {
+                let i = f64::from(i);
+                ((i * 3.7) % 5.0, (i * 2.3) % 4.0)
+            })
+            .collect();
+        let costs = straight_line_costs(&points);
+
+        let tour = optimize(&costs).unwrap();
+
+        let mut order = tour.order().to_vec();
+        order.sort_unstable();
+        assert_eq!(order, (0..6).collect::<Vec<_>>());
+        assert!(tour.length() <= brute_force(&costs) * 1.1);
+    }
+
+    #[test]
+    fn no_goals() {
+        let tour = optimize(&Array2::zeros((1, 1))).unwrap();
+
+        assert!(tour.order().is_empty());
+        assert_eq!(tour.length(), 0.0);
+    }
+
+    #[test]
+    fn invalid_costs() {
+        assert_eq!(
+            optimize(&Array2::zeros((0, 0))),
+            Err(TourError::InvalidShape)
+        );
+        assert_eq!(
+            optimize(&Array2::zeros((2, 3))),
+            Err(TourError::InvalidShape)
+        );
+        let mut costs = Array2::zeros((3, 3));
+        costs[[2, 1]] = f64::NAN;
+        assert_eq!(optimize(&costs), Err(TourError::NonFiniteCost([2, 1])));
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_rect(
+            &RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
+            &RealWorldLocation::from_xyz(3.0, 5.0, 0.0),
+            MapState::Obstacle,
+        );
+
+        let result = map.order_goals(
+            &RealWorldLocation::from_xyz(0.5, 0.5, 0.0),
+            &[
+                RealWorldLocation::from_xyz(1.5, 4.5, 0.0),
+                RealWorldLocation::from_xyz(4.5, 0.5, 0.0),
+            ],
+        );
+
+        assert_eq!(result, Err(TourError::NonFiniteCost([0, 2])));
+    }
+}
@@ -0,0 +1,236 @@
+use alloc::collections::VecDeque;
+
+use crate::{CellMap, MapDelta, MapDeltaError};
+
+/// A map which keeps snapshots of its previous versions, allowing to undo
+/// changes.
+///
+/// The map is changed in batches using [`VersionedMap::update`], each of which
+/// creates a new version. [`VersionedMap::rollback`] restores a previous
+/// version, e.g. when a partitioning step produced a broken map, and
+/// [`VersionedMap::diff`] shows what changed since then.
+///
+/// Every version holds a full copy of the map. To bound the memory used, a
+/// capacity can be given using [`VersionedMap::with_capacity`], in which case
+/// the oldest versions are discarded first.
+///
+/// # Example
+///
+/// ```
+/// use local_robot_map::{
+///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+///     VersionedMap,
+/// };
+///
+/// let mut map = VersionedMap::new(CellMap::new(
+///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+///     RealWorldLocation::from_xyz(4.0, 4.0, 0.0),
+///     AxisResolution::uniform(1.0),
+/// ));
+/// let cell = RealWorldLocation::from_xyz(1.5, 2.5, 0.0);
+///
+/// map.update(|map| map.set_location(&cell, MapState::Explored))
+///     .unwrap();
+/// map.update(|map| map.set_location(&cell, MapState::Obstacle))
+///     .unwrap();
+///
+/// assert_eq!(map.diff(2).unwrap().len(), 1);
+/// map.rollback(1).unwrap();
+/// assert_eq!(map.map().get_location(&cell), Ok(MapState::Explored));
+/// assert_eq!(map.version(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionedMap<T> {
+    map: T,
+    /// Previous versions, the most recent one last.
+    history: VecDeque<T>,
+    capacity: Option<usize>,
+    version: usize,
+}
+
+impl<T: Clone> VersionedMap<T> {
+    /// Keep track of all versions of `map`.
+    pub fn new(map: T) -> Self {
+        Self {
+            map,
+            history: VecDeque::new(),
+            capacity: None,
+            version: 0,
+        }
+    }
+
+    /// Keep track of at most `capacity` previous versions of `map`.
+    pub fn with_capacity(map: T, capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new(map)
+        }
+    }
+
+    /// The current version of the map.
+    pub fn map(&self) -> &T {
+        &self.map
+    }
+
+    /// Number of batches applied since the map was created, minus the ones
+    /// undone by [`VersionedMap::rollback`].
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Number of previous versions which can be restored.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Change the map, recording its previous version.
+    ///
+    /// A version is recorded even if `batch` does not change anything, or
+    /// returns an error. Use [`VersionedMap::rollback`] to undo it.
+    pub fn update<R>(&mut self, batch: impl FnOnce(&mut T) -> R) -> R {
+        if self.capacity == Some(0) {
+            self.history.clear();
+        } else {
+            if self.capacity == Some(self.history.len()) {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.map.clone());
+        }
+        self.version += 1;
+        batch(&mut self.map)
+    }
+
+    /// The map as it was `n` batches ago, where `0` is the current version.
+    ///
+    /// Returns [`None`] if that version is not recorded.
+    pub fn snapshot(&self, n: usize) -> Option<&T> {
+        match n {
+            0 => Some(&self.map),
+            n => self.history.len().checked_sub(n).map(|i| &self.history[i]),
+        }
+    }
+
+    /// Undo the last `n` batches. Newer versions are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VersionedMapError::NotRecorded`] if fewer than `n` previous
+    /// versions are recorded. In that case the map is left untouched.
+    pub fn rollback(&mut self, n: usize) -> Result<(), VersionedMapError> {
+        if n == 0 {
+            return Ok(());
+        }
+        let index = self
+            .history
+            .len()
+            .checked_sub(n)
+            .ok_or(VersionedMapError::NotRecorded)?;
+        self.history.truncate(index + 1);
+        self.map = self.history.pop_back().expect("Index is in bounds");
+        self.version -= n;
+        Ok(())
+    }
+
+    /// Drop all previous versions, keeping the current map only.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Stop keeping track of versions and return the current map.
+    pub fn into_inner(self) -> T {
+        self.map
+    }
+}
+
+impl VersionedMap<CellMap> {
+    /// Compute the [`MapDelta`] which turns the map as it was `n` batches ago
+    /// into the current one.
+    ///
+    /// # Errors
+    ///
+    /// - [`VersionedMapError::NotRecorded`] if that version is not recorded.
+    /// - [`VersionedMapError::Delta`] if the map changed its dimensions in
+    ///   between, see [`CellMap::diff`].
+    pub fn diff(&self, n: usize) -> Result<MapDelta, VersionedMapError> {
+        let old = self.snapshot(n).ok_or(VersionedMapError::NotRecorded)?;
+        old.diff(&self.map).map_err(VersionedMapError::Delta)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum VersionedMapError {
+    /// The requested version is older than the oldest recorded one.
+    NotRecorded,
+    /// The versions cannot be compared.
+    Delta(MapDeltaError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, LocationType, MapState, RealWorldLocation};
+
+    fn make_map() -> VersionedMap<CellMap> {
+        VersionedMap::with_capacity(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(3.0, 3.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            2,
+        )
+    }
+
+    fn state(map: &CellMap) -> LocationType {
+        map.cells()[[0, 0]]
+    }
+
+    #[test]
+    fn capacity_discards_oldest_versions() {
+        let mut map = make_map();
+        for state in
+            [MapState::Explored, MapState::Frontier, MapState::Obstacle]
+        {
+            map.update(|map| map.set_cell([0, 0], state));
+        }
+
+        assert_eq!(map.version(), 3);
+        assert_eq!(map.history_len(), 2);
+        assert_eq!(map.snapshot(2).map(state), Some(MapState::Explored));
+        assert_eq!(map.snapshot(3), None);
+        assert_eq!(map.rollback(3), Err(VersionedMapError::NotRecorded));
+        assert_eq!(state(map.map()), MapState::Obstacle);
+
+        map.rollback(2).unwrap();
+        assert_eq!(state(map.map()), MapState::Explored);
+        assert_eq!((map.version(), map.history_len()), (1, 0));
+    }
+
+    #[test]
+    fn rollback_discards_newer_versions() {
+        let mut map = make_map();
+        map.update(|map| map.set_cell([0, 0], MapState::Explored));
+        map.update(|map| map.set_cell([0, 0], MapState::Frontier));
+
+        map.rollback(1).unwrap();
+        map.rollback(0).unwrap();
+
+        assert_eq!(state(map.map()), MapState::Explored);
+        assert_eq!(map.history_len(), 1);
+        assert_eq!(
+            map.diff(1).unwrap().changes(),
+            &[([0, 0], MapState::Explored)]
+        );
+    }
+
+    #[test]
+    fn zero_capacity_keeps_no_history() {
+        let mut map = make_map();
+        map.capacity = Some(0);
+        map.update(|map| map.set_cell([0, 0], MapState::Explored));
+
+        assert_eq!(map.history_len(), 0);
+        assert_eq!(map.diff(1), Err(VersionedMapError::NotRecorded));
+        assert!(map.diff(0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{CellMap, LocationError, MapState, RealWorldLocation};
+
+impl CellMap {
+    /// Estimate the information gain of sensing from a location.
+    ///
+    /// Counts the [`MapState::Unexplored`] cells whose center lies within
+    /// `sensor_radius` of `from` and which are visible from the cell
+    /// containing `from`. A cell is visible if the straight line between the
+    /// two cells does not pass through a [`MapState::Obstacle`] cell. Cells in
+    /// any other state, including unexplored ones, do not block the view.
+    ///
+    /// This allows weighting candidate goals (e.g. frontier clusters, see
+    /// [`CellMap::cluster_frontiers`]) by the expected new coverage rather than
+    /// just by their distance.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocationError::OutOfMap`] if `from` lies outside the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// let from = RealWorldLocation::from_xyz(2.5, 5.5, 0.0);
+    /// let open = map.information_gain(&from, 3.0).unwrap();
+    ///
+    /// // A wall right of the robot hides everything behind it.
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(3.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(4.0, 10.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    /// let walled = map.information_gain(&from, 3.0).unwrap();
+    ///
+    /// assert!(walled < open);
+    /// ```
+    pub fn information_gain(
+        &self,
+        from: &RealWorldLocation,
+        sensor_radius: f64,
+    ) -> Result<usize, LocationError> {
+        let start = self.location_to_map_index(from)?;
+
+        Ok(self
+            .disc_indices(from, sensor_radius)
+            .filter(|index| self.cells()[*index] == MapState::Unexplored)
+            .filter(|index| self.is_visible(start, *index))
+            .count())
+    }
+
+    /// Whether the straight line between two locations is unobstructed.
+    ///
+    /// Unlike [`CellMap::information_gain`], the exact segment between the
+    /// locations is followed, visiting every cell it passes through. The
+    /// line is blocked by [`MapState::Obstacle`] and [`MapState::OutOfMap`]
+    /// cells, except for the cells containing `a` and `b` themselves, so that
+    /// e.g. a wall is visible. Locations outside the map are never in sight.
+    ///
+    /// This can be used to score goals by visibility, or to check whether two
+    /// robots can communicate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, Location, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(10.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_location(
+    ///     &RealWorldLocation::from_xyz(5.5, 5.5, 0.0),
+    ///     MapState::Obstacle,
+    /// )
+    /// .unwrap();
+    /// let a = RealWorldLocation::from_xyz(1.2, 1.0, 0.0);
+    /// let behind = RealWorldLocation::from_xyz(9.0, 9.8, 0.0);
+    /// let beside = RealWorldLocation::from_xyz(9.8, 6.0, 0.0);
+    ///
+    /// assert!(!map.line_of_sight(&a, &behind));
+    /// assert!(map.line_of_sight(&a, &beside));
+    /// ```
+    pub fn line_of_sight(
+        &self,
+        a: &RealWorldLocation,
+        b: &RealWorldLocation,
+    ) -> bool {
+        let (Ok(start), Ok(end)) =
+            (self.location_to_map_index(a), self.location_to_map_index(b))
+        else {
+            return false;
+        };
+        self.ray_indices(a, b).iter().all(|index| {
+            *index == start
+                || *index == end
+                || !matches!(
+                    self.cells()[*index],
+                    MapState::Obstacle | MapState::OutOfMap
+                )
+        })
+    }
+
+    /// Internal helper returning the indices of the cells the segment
+    /// between two locations passes through, in order from `from` to `to`.
+    /// Cells outside the map are left out.
+    pub(crate) fn ray_indices(
+        &self,
+        from: &RealWorldLocation,
+        to: &RealWorldLocation,
+    ) -> Vec<[usize; 2]> {
+        // Positions in units of cells, as `[row, col]`.
+        let grid = |location: &RealWorldLocation| {
+            [
+                (location.y - self.offset().y) * self.resolution().y,
+                (location.x - self.offset().x) * self.resolution().x,
+            ]
+        };
+        let (start, end) = (grid(from), grid(to));
+        let delta = [end[0] - start[0], end[1] - start[1]];
+        let mut cell = start.map(|value| value.floor() as isize);
+        let last = end.map(|value| value.floor() as isize);
+        // Fraction of the segment at which the next cell border is crossed,
+        // and the fraction between two borders, per axis.
+        let mut next = [0, 1].map(|axis| {
+            let border = cell[axis] as f64 + f64::from(delta[axis] > 0.0);
+            if delta[axis] == 0.0 {
+                f64::INFINITY
+            } else {
+                (border - start[axis]) / delta[axis]
+            }
+        });
+        let step = delta.map(|delta| 1.0 / delta.abs());
+
+        let mut indices = Vec::new();
+        loop {
+            let index = cell.map(|value| usize::try_from(value).ok());
+            if let [Some(row), Some(col)] = index {
+                if row < self.height() && col < self.width() {
+                    indices.push([row, col]);
+                }
+            }
+            if cell == last {
+                break;
+            }
+            // Never overshoot the last cell along an axis, even if rounding
+            // errors say otherwise. Passing exactly through a corner steps
+            // diagonally, keeping the ray symmetric.
+            let axes = if cell[0] == last[0] {
+                [false, true]
+            } else if cell[1] == last[1] {
+                [true, false]
+            } else {
+                [next[0] <= next[1], next[1] <= next[0]]
+            };
+            for axis in [0, 1].into_iter().filter(|axis| axes[*axis]) {
+                cell[axis] += if delta[axis] > 0.0 { 1 } else { -1 };
+                next[axis] += step[axis];
+            }
+        }
+        indices
+    }
+
+    /// Internal helper checking that no [`MapState::Obstacle`] cell lies
+    /// strictly between two cells.
+    pub(crate) fn is_visible(&self, from: [usize; 2], to: [usize; 2]) -> bool {
+        let line = grid_line(from, to);
+        line.iter()
+            .skip(1)
+            .take(line.len().saturating_sub(2))
+            .all(|index| self.cells()[*index] != MapState::Obstacle)
+    }
+}
+
+/// Indices of the cells traversed by a straight line between the centers of
+/// two cells, including both ends (Bresenham's algorithm).
+pub(crate) fn grid_line(from: [usize; 2], to: [usize; 2]) -> Vec<[usize; 2]> {
+    let [mut row, mut col] = from.map(|i| i as isize);
+    let [to_row, to_col] = to.map(|i| i as isize);
+    let d_row = -(to_row - row).abs();
+    let d_col = (to_col - col).abs();
+    let step_row = if row < to_row { 1 } else { -1 };
+    let step_col = if col < to_col { 1 } else { -1 };
+    let mut error = d_col + d_row;
+
+    let mut line = Vec::new();
+    loop {
+        line.push([row as usize, col as usize]);
+        if row == to_row && col == to_col {
+            return line;
+        }
+        let doubled = 2 * error;
+        if doubled >= d_row {
+            error += d_row;
+            col += step_col;
+        }
+        if doubled <= d_col {
+            error += d_col;
+            row += step_row;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, Location};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(-5.0, -5.0, 0.0),
+            RealWorldLocation::from_xyz(5.0, 5.0, 0.0),
+            AxisResolution::uniform(1.0),
+        )
+    }
+
+    #[test]
+    fn grid_line_endpoints_and_continuity() {
+        for (from, to) in [
+            ([0, 0], [3, 7]),
+            ([5, 2], [0, 0]),
+            ([4, 4], [4, 4]),
+            ([1, 6], [6, 1]),
+        ] {
+            let line = grid_line(from, to);
+
+            assert_eq!(line.first(), Some(&from));
+            assert_eq!(line.last(), Some(&to));
+            for pair in line.windows(2) {
+                assert!(pair[0][0].abs_diff(pair[1][0]) <= 1);
+                assert!(pair[0][1].abs_diff(pair[1][1]) <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn counts_unexplored_in_radius() {
+        let mut map = make_map();
+        let from = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        map.set_location(&from, MapState::MyRobot).unwrap();
+
+        // 3x3 block of centers within sqrt(2), minus the robot's own cell.
+        assert_eq!(map.information_gain(&from, 1.5), Ok(8));
+
+        map.set_location(
+            &RealWorldLocation::from_xyz(1.5, 0.5, 0.0),
+            MapState::Explored,
+        )
+        .unwrap();
+        assert_eq!(map.information_gain(&from, 1.5), Ok(7));
+    }
+
+    #[test]
+    fn obstacles_block_view() {
+        let mut map = make_map();
+        let from = RealWorldLocation::from_xyz(-4.5, 0.5, 0.0);
+        let behind = RealWorldLocation::from_xyz(-2.5, 0.5, 0.0);
+        map.set_location(
+            &RealWorldLocation::from_xyz(-3.5, 0.5, 0.0),
+            MapState::Obstacle,
+        )
+        .unwrap();
+
+        let start = map.location_to_map_index(&from).unwrap();
+        let target = map.location_to_map_index(&behind).unwrap();
+        assert!(!map.is_visible(start, target));
+        assert!(map.is_visible(
+            start,
+            map.location_to_map_index(&RealWorldLocation::from_xyz(
+                -4.5, 2.5, 0.0
+            ))
+            .unwrap()
+        ));
+    }
+
+    #[test]
+    fn ray_visits_every_crossed_cell() {
+        let map = make_map();
+        let ray = map.ray_indices(
+            &RealWorldLocation::from_xyz(-4.5, -4.5, 0.0),
+            &RealWorldLocation::from_xyz(-1.5, -3.2, 0.0),
+        );
+
+        assert_eq!(ray, [[0, 0], [0, 1], [1, 1], [1, 2], [1, 3]]);
+        assert_eq!(
+            map.ray_indices(
+                &RealWorldLocation::from_xyz(-1.5, -3.2, 0.0),
+                &RealWorldLocation::from_xyz(-4.5, -4.5, 0.0),
+            ),
+            ray.into_iter().rev().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn ray_through_corner_is_symmetric() {
+        let map = make_map();
+        let [a, b] = [[-4.5, -4.5], [-2.5, -2.5]]
+            .map(|[x, y]| RealWorldLocation::from_xyz(x, y, 0.0));
+
+        assert_eq!(map.ray_indices(&a, &b), [[0, 0], [1, 1], [2, 2]]);
+        assert_eq!(map.ray_indices(&b, &a), [[2, 2], [1, 1], [0, 0]]);
+    }
+
+    #[test]
+    fn line_of_sight_endpoints() {
+        let mut map = make_map();
+        let wall = RealWorldLocation::from_xyz(0.5, 0.5, 0.0);
+        let from = RealWorldLocation::from_xyz(-3.5, 0.5, 0.0);
+        map.set_location(&wall, MapState::Obstacle).unwrap();
+
+        assert!(map.line_of_sight(&from, &wall));
+        assert!(!map
+            .line_of_sight(&from, &RealWorldLocation::from_xyz(3.5, 0.5, 0.0)));
+        assert!(!map
+            .line_of_sight(&from, &RealWorldLocation::from_xyz(5.5, 0.5, 0.0)));
+        map.set_location(&from, MapState::OutOfMap).unwrap();
+        assert!(map.line_of_sight(&from, &from));
+    }
+
+    #[test]
+    fn outside_map() {
+        let map = make_map();
+
+        assert!(matches!(
+            map.information_gain(
+                &RealWorldLocation::from_xyz(50.0, 0.0, 0.0),
+                2.0
+            ),
+            Err(LocationError::OutOfMap(_))
+        ));
+    }
+}
@@ -0,0 +1,318 @@
+use alloc::collections::BinaryHeap;
+use core::cmp::Ordering;
+use ndarray::Array2;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+
+use crate::{CellMap, LocationError, MapState, RealWorldLocation};
+
+impl CellMap {
+    /// Compute the travel distance from the closest of several seed
+    /// locations to every cell, going around obstacles.
+    ///
+    /// A wavefront is propagated from the cells containing the seeds to all
+    /// cells sharing an edge or a corner, without crossing or cutting the
+    /// corners of [`MapState::Obstacle`] and [`MapState::OutOfMap`] cells.
+    /// Distances are measured between cell centers in real-world units, so
+    /// diagonal steps count as such. Seed cells have a distance of `0.0`,
+    /// while blocked cells and cells which cannot be reached are
+    /// [`f64::INFINITY`]. Robots are not considered obstacles.
+    ///
+    /// Unlike straight-line distances, these do not pass through walls,
+    /// which makes them suitable for partitioning indoor environments (see
+    /// [`crate::partitioning::geodesic_voronoi`]) and for planning.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocationError::OutOfMap`] if a seed lies outside the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(5.0, 3.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// // A wall with a gap at the top.
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(2.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(3.0, 2.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    ///
+    /// let distances = map
+    ///     .wavefront(&[RealWorldLocation::from_xyz(1.5, 0.5, 0.0)])
+    ///     .unwrap();
+    ///
+    /// // Up along the wall, through the gap and back down.
+    /// assert_eq!(distances[[0, 3]], 6.0);
+    /// assert_eq!(distances[[0, 2]], f64::INFINITY);
+    /// ```
+    pub fn wavefront(
+        &self,
+        seeds: &[RealWorldLocation],
+    ) -> Result<Array2<f64>, LocationError> {
+        let mut distances =
+            Array2::from_elem(self.cells().dim(), f64::INFINITY);
+        let mut queue = BinaryHeap::new();
+        for seed in seeds {
+            let index = self.location_to_map_index(seed)?;
+            if self.is_passable(index) {
+                distances[index] = 0.0;
+                queue.push(Front(0.0, index));
+            }
+        }
+
+        let step = |d_row: usize, d_col: usize| {
+            let dx = d_col as f64 / self.resolution().x;
+            let dy = d_row as f64 / self.resolution().y;
+            dx.hypot(dy)
+        };
+        while let Some(Front(distance, index)) = queue.pop() {
+            if distance > distances[index] {
+                continue;
+            }
+            let [row, col] = index;
+            for neighbor in self.neighbor_indices(index) {
+                let [n_row, n_col] = neighbor;
+                // Diagonal steps must not cut the corner of a blocked cell.
+                if !self.is_passable(neighbor)
+                    || !self.is_passable([row, n_col])
+                    || !self.is_passable([n_row, col])
+                {
+                    continue;
+                }
+                let next =
+                    distance + step(row.abs_diff(n_row), col.abs_diff(n_col));
+                if next < distances[neighbor] {
+                    distances[neighbor] = next;
+                    queue.push(Front(next, neighbor));
+                }
+            }
+        }
+        Ok(distances)
+    }
+
+    /// Compute the clearance of every cell, i.e. an approximation of the
+    /// distance from its center to the center of the closest blocked cell.
+    ///
+    /// Like a brushfire, distances are propagated from all
+    /// [`MapState::Obstacle`] and [`MapState::OutOfMap`] cells to the cells
+    /// sharing an edge or a corner with them, in real-world units. The area
+    /// beyond the edges of the map counts as blocked as well, since robots
+    /// cannot go there. Blocked cells have a clearance of `0.0`.
+    ///
+    /// As distances only travel along the 8-connected steps between cells,
+    /// this is a chamfer distance rather than the exact Euclidean one: it is
+    /// exact along the axes and diagonals, and overestimates by up to about
+    /// 8% in between, e.g. `1 + √2` instead of `√5` for a cell two rows and
+    /// one column away.
+    ///
+    /// The clearance allows planning paths which keep a safety margin to
+    /// walls, and its ridges form the medial axis of the free space.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(7.0, 1.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(1.0, 1.0, 0.0),
+    ///     MapState::Obstacle,
+    /// );
+    ///
+    /// let clearance = map.brushfire();
+    ///
+    /// assert_eq!(clearance[[0, 0]], 0.0);
+    /// assert_eq!(clearance[[0, 1]], 1.0);
+    /// // Far from the obstacle, the edges of the map are closest.
+    /// assert_eq!(clearance[[0, 4]], 1.0);
+    /// ```
+    pub fn brushfire(&self) -> Array2<f64> {
+        let (rows, columns) = self.cells().dim();
+        let mut distances = Array2::from_elem((rows, columns), f64::INFINITY);
+        let mut queue = BinaryHeap::new();
+        let step = |d_row: usize, d_col: usize| {
+            let dx = d_col as f64 / self.resolution().x;
+            let dy = d_row as f64 / self.resolution().y;
+            dx.hypot(dy)
+        };
+        for ((row, col), _) in self.cells().indexed_iter() {
+            let index = [row, col];
+            let distance = if !self.is_passable(index) {
+                0.0
+            } else {
+                // Distance to the closest cell beyond the edges of the map.
+                let mut distance = f64::INFINITY;
+                if row == 0 || row + 1 == rows {
+                    distance = distance.min(step(1, 0));
+                }
+                if col == 0 || col + 1 == columns {
+                    distance = distance.min(step(0, 1));
+                }
+                distance
+            };
+            if distance.is_finite() {
+                distances[index] = distance;
+                queue.push(Front(distance, index));
+            }
+        }
+
+        while let Some(Front(distance, index)) = queue.pop() {
+            if distance > distances[index] {
+                continue;
+            }
+            let [row, col] = index;
+            for neighbor in self.neighbor_indices(index) {
+                let [n_row, n_col] = neighbor;
+                let next =
+                    distance + step(row.abs_diff(n_row), col.abs_diff(n_col));
+                if next < distances[neighbor] {
+                    distances[neighbor] = next;
+                    queue.push(Front(next, neighbor));
+                }
+            }
+        }
+        distances
+    }
+
+    /// Internal helper checking whether the wavefront may enter a cell.
+    pub(crate) fn is_passable(&self, index: [usize; 2]) -> bool {
+        !matches!(self.cells()[index], MapState::Obstacle | MapState::OutOfMap)
+    }
+}
+
+/// A cell on the wavefront, ordered such that the closest one is popped
+/// first from a [`BinaryHeap`].
+pub(crate) struct Front(pub(crate) f64, pub(crate) [usize; 2]);
+
+impl PartialEq for Front {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Front {}
+
+impl PartialOrd for Front {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Front {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .0
+            .total_cmp(&self.0)
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AxisResolution;
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(10.0, 5.0, 0.0),
+            AxisResolution::new(2.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn distances_follow_resolution() {
+        let map = make_map();
+        let distances = map
+            .wavefront(&[RealWorldLocation::from_xyz(0.25, 0.5, 0.0)])
+            .unwrap();
+
+        assert_eq!(distances[[0, 0]], 0.0);
+        assert_eq!(distances[[0, 4]], 2.0);
+        assert_eq!(distances[[3, 0]], 3.0);
+        assert!((distances[[1, 1]] - 1.25f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn closest_seed_wins() {
+        let map = make_map();
+        let distances = map
+            .wavefront(&[
+                RealWorldLocation::from_xyz(0.25, 0.5, 0.0),
+                RealWorldLocation::from_xyz(9.75, 0.5, 0.0),
+            ])
+            .unwrap();
+
+        assert_eq!(distances[[0, 2]], 1.0);
+        assert_eq!(distances[[0, 17]], 1.0);
+        assert!(map.wavefront(&[]).unwrap().iter().all(|d| d.is_infinite()));
+        assert!(map
+            .wavefront(&[RealWorldLocation::from_xyz(-1.0, 0.5, 0.0)])
+            .is_err());
+    }
+
+    #[test]
+    fn no_corner_cutting() {
+        let mut map = make_map();
+        map.set_cell([1, 0], MapState::Obstacle);
+        map.set_cell([0, 1], MapState::Obstacle);
+
+        let distances = map
+            .wavefront(&[RealWorldLocation::from_xyz(0.25, 0.5, 0.0)])
+            .unwrap();
+
+        assert_eq!(distances[[1, 1]], f64::INFINITY);
+        assert_eq!(distances[[1, 0]], f64::INFINITY);
+        let finite: Vec<_> =
+            distances.iter().filter(|d| d.is_finite()).collect();
+        assert_eq!(finite, [&0.0]);
+    }
+
+    #[test]
+    fn brushfire_clearance() {
+        let mut map = CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(9.0, 9.0, 0.0),
+            AxisResolution::uniform(1.0),
+        );
+        map.set_cell([4, 4], MapState::Obstacle);
+        map.set_cell([8, 0], MapState::OutOfMap);
+
+        let clearance = map.brushfire();
+
+        assert_eq!(clearance[[4, 4]], 0.0);
+        assert_eq!(clearance[[8, 0]], 0.0);
+        assert_eq!(clearance[[0, 0]], 1.0);
+        assert_eq!(clearance[[4, 2]], 2.0);
+        assert_eq!(clearance[[4, 6]], 2.0);
+        assert!((clearance[[3, 3]] - 2.0f64.sqrt()).abs() < 1e-12);
+        assert!(clearance.iter().all(|d| d.is_finite()));
+    }
+
+    #[test]
+    fn brushfire_follows_resolution() {
+        let map = make_map();
+
+        let clearance = map.brushfire();
+
+        // Cells are 0.5 wide, so the left and right edges are closer.
+        assert_eq!(clearance[[2, 0]], 0.5);
+        assert_eq!(clearance[[2, 2]], 1.5);
+        assert_eq!(clearance[[0, 10]], 1.0);
+    }
+}
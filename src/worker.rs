@@ -0,0 +1,189 @@
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crate::{
+    LocalMap, Location, MaskMapState, Partition, PartitionCancelled,
+    PartitionControl, PartitionOutcome,
+};
+
+/// Result of a partitioning run, see [`Partition::partition_with_control`].
+type PartitionResult<T> = Result<(T, PartitionOutcome), PartitionCancelled<T>>;
+
+/// Handle to a partitioning run on a worker thread, see
+/// [`LocalMap::partition_async`].
+///
+/// Dropping the handle detaches the worker, which then finishes in the
+/// background and discards its result. Use [`PartitionHandle::cancel`] to stop
+/// it early instead.
+#[derive(Debug)]
+pub struct PartitionHandle<T> {
+    control: Arc<PartitionControl>,
+    thread: JoinHandle<PartitionResult<T>>,
+}
+
+impl<T> PartitionHandle<T> {
+    /// Whether the worker has finished, such that [`PartitionHandle::join`]
+    /// returns without blocking.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Request the worker to stop as soon as possible, see
+    /// [`PartitionControl::cancel`].
+    pub fn cancel(&self) {
+        self.control.cancel();
+    }
+
+    /// The control passed to the algorithm.
+    pub fn control(&self) -> &PartitionControl {
+        &self.control
+    }
+
+    /// Wait for the worker to finish and return the partitioned map along with
+    /// the [`PartitionOutcome`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PartitionCancelled`] holding the unchanged map if the run
+    /// was cancelled.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the same payload if the algorithm panicked.
+    pub fn join(self) -> PartitionResult<T> {
+        match self.thread.join() {
+            Ok(result) => result,
+            Err(payload) => std::panic::resume_unwind(payload),
+        }
+    }
+}
+
+impl<T, P> LocalMap<T, P>
+where
+    T: Location + MaskMapState + core::fmt::Debug + Send + 'static,
+    P: Send + 'static,
+{
+    /// Partition the map on a worker thread, keeping the calling thread, e.g.
+    /// the robot's main loop, responsive.
+    ///
+    /// Runs [`Partition::partition_with_control`] with `control`, which can be
+    /// used to follow the progress. The returned [`PartitionHandle`] yields the
+    /// partitioned map once the worker is done.
+    ///
+    /// Requires the `threads` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     partitioning::weighted_voronoi_with_control, AxisResolution,
+    ///     CellMap, LocalMap, PartitionControl, RealWorldLocation, Robot,
+    /// };
+    ///
+    /// let map = LocalMap::new_noexpand(
+    ///     CellMap::new(
+    ///         RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///         RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+    ///         AxisResolution::uniform(1.0),
+    ///     ),
+    ///     Robot::new(RealWorldLocation::from_xyz(5.0, 5.0, 0.0), ()),
+    ///     vec![Robot::new(RealWorldLocation::from_xyz(15.0, 5.0, 0.0), ())],
+    /// )
+    /// .unwrap();
+    ///
+    /// let handle = map.partition_async(
+    ///     weighted_voronoi_with_control,
+    ///     None,
+    ///     PartitionControl::new(),
+    /// );
+    /// // ... keep running the main loop until `handle.is_finished()`.
+    /// let (_map, outcome) = handle.join().unwrap();
+    ///
+    /// assert_eq!(outcome.cell_counts(), [99, 99]);
+    /// ```
+    pub fn partition_async<F, A>(
+        self,
+        partition_algorithm: A,
+        factors: Option<F>,
+        control: PartitionControl,
+    ) -> PartitionHandle<Self>
+    where
+        F: Send + 'static,
+        A: FnOnce(Self, Option<F>, &PartitionControl) -> PartitionResult<Self>
+            + Send
+            + 'static,
+    {
+        let control = Arc::new(control);
+        let worker = Arc::clone(&control);
+        let thread = std::thread::spawn(move || {
+            self.partition_with_control(partition_algorithm, factors, &worker)
+        });
+        PartitionHandle { control, thread }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::partitioning::geodesic_voronoi_with_control;
+    use crate::{AxisResolution, CellMap, RealWorldLocation, Robot};
+
+    fn make_local_map() -> LocalMap<CellMap, ()> {
+        LocalMap::new_noexpand(
+            CellMap::new(
+                RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+                RealWorldLocation::from_xyz(30.0, 30.0, 0.0),
+                AxisResolution::uniform(1.0),
+            ),
+            Robot::new(RealWorldLocation::from_xyz(5.5, 5.5, 0.0), ()),
+            vec![Robot::new(RealWorldLocation::from_xyz(24.5, 24.5, 0.0), ())],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn worker_matches_blocking_partition() {
+        let (expected, expected_outcome) = make_local_map()
+            .partition_with_control(
+                geodesic_voronoi_with_control,
+                None,
+                &PartitionControl::new(),
+            )
+            .unwrap();
+
+        let (map, outcome) = make_local_map()
+            .partition_async(
+                geodesic_voronoi_with_control,
+                None,
+                PartitionControl::new(),
+            )
+            .join()
+            .unwrap();
+
+        assert_eq!(map.map(), expected.map());
+        assert_eq!(outcome.cell_counts(), expected_outcome.cell_counts());
+    }
+
+    #[test]
+    fn cancelled_worker_returns_map() {
+        // Block the worker in its first progress report until cancelled.
+        let (sender, receiver) = mpsc::sync_channel::<()>(0);
+        let receiver = std::sync::Mutex::new(receiver);
+        let control = PartitionControl::with_progress(move |_| {
+            let _ = receiver.lock().unwrap().recv();
+        });
+
+        let handle = make_local_map().partition_async(
+            geodesic_voronoi_with_control,
+            None,
+            control,
+        );
+        handle.cancel();
+        drop(sender);
+
+        let map = handle.join().unwrap_err().into_map();
+        assert_eq!(map.map(), make_local_map().map());
+    }
+}
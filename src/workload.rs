@@ -0,0 +1,280 @@
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num::Float;
+use num::ToPrimitive;
+
+use crate::{CellMap, MapState};
+
+/// Tolerance for deciding which cells a sweep lane overlaps, such that lanes
+/// aligned with the cell borders do not pick up neighboring cells due to
+/// rounding.
+const EPSILON: f64 = 1e-9;
+
+/// Estimated effort of covering a region by sweeping it back and forth, see
+/// [`CellMap::coverage_estimate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoverageEstimate {
+    path_length: f64,
+    sweeps: usize,
+    area: f64,
+}
+
+impl CoverageEstimate {
+    /// Length of the coverage path in real-world units, including the
+    /// transitions between sweeps.
+    pub fn path_length(&self) -> f64 {
+        self.path_length
+    }
+    /// Number of straight sweeps the path consists of.
+    pub fn sweeps(&self) -> usize {
+        self.sweeps
+    }
+    /// Number of turns between the sweeps.
+    pub fn turns(&self) -> usize {
+        self.sweeps.saturating_sub(1)
+    }
+    /// Area of the region, in square real-world units.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+    /// Time needed to follow the path at `speed`, given in real-world units
+    /// per time unit, when every turn takes `turn_duration`.
+    pub fn time(&self, speed: f64, turn_duration: f64) -> f64 {
+        self.path_length / speed
+            + self.turns().to_f64().expect("usize to f64 should work")
+                * turn_duration
+    }
+}
+
+impl CellMap {
+    /// Estimate the effort of covering all cells in `state`, usually the
+    /// [`MapState::Assigned`] region of the current robot, with a sensor
+    /// covering a swath of `sensor_width` real-world units.
+    ///
+    /// The estimate follows a boustrophedon path: the region is cut into lanes
+    /// as wide as the sensor, each of which is swept in alternating
+    /// directions, moving straight to the start of the next sweep. Gaps within
+    /// a lane, e.g. due to obstacles, split it into several sweeps. Lanes are
+    /// laid out along the `x` and along the `y` axis, and the shorter path is
+    /// returned.
+    ///
+    /// Unlike the number of cells, the estimate accounts for the shape of a
+    /// region, e.g. the additional turns a ragged region requires. Its
+    /// [`CoverageEstimate::time`] can therefore serve as a metric of a
+    /// partition, or as feedback to balance the next partitioning, e.g. via
+    /// [`PartitionFactors`](crate::PartitionFactors) weights.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sensor_width` is not positive.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use local_robot_map::{
+    ///     AxisResolution, CellMap, MapState, RealWorldLocation,
+    /// };
+    ///
+    /// let mut map = CellMap::new(
+    ///     RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     RealWorldLocation::from_xyz(20.0, 10.0, 0.0),
+    ///     AxisResolution::uniform(1.0),
+    /// );
+    /// map.set_rect(
+    ///     &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+    ///     &RealWorldLocation::from_xyz(10.0, 4.0, 0.0),
+    ///     MapState::Assigned,
+    /// );
+    ///
+    /// let estimate = map.coverage_estimate(MapState::Assigned, 1.0);
+    ///
+    /// // Four sweeps of 10 units along `x`, one unit apart.
+    /// assert_eq!(estimate.sweeps(), 4);
+    /// assert_eq!(estimate.path_length(), 4.0 * 10.0 + 3.0);
+    /// assert_eq!(estimate.area(), 40.0);
+    /// assert_eq!(estimate.time(2.0, 1.0), 43.0 / 2.0 + 3.0);
+    /// ```
+    pub fn coverage_estimate(
+        &self,
+        state: MapState,
+        sensor_width: f64,
+    ) -> CoverageEstimate {
+        assert!(sensor_width > 0.0, "Sensor width must be positive");
+        let region = self.cells().mapv(|value| value == state);
+        let resolution = self.resolution();
+        let along_x =
+            Lanes::new(region.rows(), 1.0 / resolution.y, resolution.x);
+        let along_y =
+            Lanes::new(region.columns(), 1.0 / resolution.x, resolution.y);
+        let (path_length, sweeps) = [along_x, along_y]
+            .into_iter()
+            .map(|lanes| route(&lanes, sensor_width))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .expect("There are two directions");
+
+        let cells = region.iter().filter(|inside| **inside).count();
+        CoverageEstimate {
+            path_length,
+            sweeps,
+            area: cells.to_f64().expect("usize to f64 should work")
+                / (resolution.x * resolution.y),
+        }
+    }
+}
+
+/// Lines of cells across the sweep direction, along with the size of the cells
+/// across and the resolution along the sweep direction.
+struct Lanes {
+    lines: Vec<Vec<bool>>,
+    across: f64,
+    resolution: f64,
+}
+
+impl Lanes {
+    fn new<'a>(
+        lines: impl IntoIterator<Item = ndarray::ArrayView1<'a, bool>>,
+        across: f64,
+        resolution: f64,
+    ) -> Self {
+        Self {
+            lines: lines.into_iter().map(|line| line.to_vec()).collect(),
+            across,
+            resolution,
+        }
+    }
+}
+
+/// Length and number of sweeps of the boustrophedon path over `lanes` which
+/// are `width` wide.
+fn route(lanes: &Lanes, width: f64) -> (f64, usize) {
+    let to_f64 =
+        |value: usize| value.to_f64().expect("usize to f64 should work");
+    let len = lanes.lines.first().map_or(0, Vec::len);
+    let extent = to_f64(lanes.lines.len()) * lanes.across;
+    let count = (extent / width - EPSILON).ceil().max(0.0).to_usize();
+
+    let mut path_length = 0.0;
+    let mut sweeps = 0;
+    // Where the previous sweep ended, as `(along, across)`.
+    let mut previous: Option<(f64, f64)> = None;
+    let mut forward = true;
+    for lane in 0..count.unwrap_or(0) {
+        // Lines overlapping the lane.
+        let first = (to_f64(lane) * width / lanes.across + EPSILON).floor();
+        let last = (to_f64(lane + 1) * width / lanes.across - EPSILON).ceil();
+        let lines = &lanes.lines[first.to_usize().unwrap_or(0)
+            ..last.to_usize().unwrap_or(0).min(lanes.lines.len())];
+        let covered: Vec<bool> =
+            (0..len).map(|i| lines.iter().any(|line| line[i])).collect();
+
+        // Runs of covered cells as `(start, end)` along the lane.
+        let mut runs: Vec<(f64, f64)> = Vec::new();
+        let mut start = None;
+        for (i, inside) in covered.iter().chain([&false]).enumerate() {
+            match (start, inside) {
+                (None, true) => start = Some(i),
+                (Some(begin), false) => {
+                    runs.push((
+                        to_f64(begin) / lanes.resolution,
+                        to_f64(i) / lanes.resolution,
+                    ));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if runs.is_empty() {
+            continue;
+        }
+        if !forward {
+            runs = runs.into_iter().rev().map(|(a, b)| (b, a)).collect();
+        }
+        forward = !forward;
+
+        let center = (to_f64(lane) + 0.5) * width;
+        for (start, end) in runs {
+            if let Some((along, across)) = previous {
+                path_length += ((start - along).powi(2)
+                    + (center - across).powi(2))
+                .sqrt();
+            }
+            path_length += (end - start).abs();
+            sweeps += 1;
+            previous = Some((end, center));
+        }
+    }
+    (path_length, sweeps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisResolution, RealWorldLocation};
+
+    fn make_map() -> CellMap {
+        CellMap::new(
+            RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            RealWorldLocation::from_xyz(20.0, 20.0, 0.0),
+            AxisResolution::uniform(2.0),
+        )
+    }
+
+    fn assign_rect(map: &mut CellMap, from: (f64, f64), to: (f64, f64)) {
+        map.set_rect(
+            &RealWorldLocation::from_xyz(from.0, from.1, 0.0),
+            &RealWorldLocation::from_xyz(to.0, to.1, 0.0),
+            MapState::Assigned,
+        );
+    }
+
+    #[test]
+    fn lanes_follow_longer_side() {
+        let mut map = make_map();
+        assign_rect(&mut map, (0.0, 0.0), (2.0, 12.0));
+
+        let estimate = map.coverage_estimate(MapState::Assigned, 1.0);
+
+        // Two sweeps of 12 units along `y`.
+        assert_eq!(estimate.sweeps(), 2);
+        assert_eq!(estimate.path_length(), 2.0 * 12.0 + 1.0);
+        assert_eq!(estimate.area(), 24.0);
+    }
+
+    #[test]
+    fn ragged_region_needs_more_effort() {
+        let mut compact = make_map();
+        assign_rect(&mut compact, (0.0, 0.0), (4.0, 4.0));
+        let mut ragged = make_map();
+        // Same area, as a comb with three teeth.
+        assign_rect(&mut ragged, (0.0, 0.0), (4.0, 1.0));
+        for x in [0.0, 1.5, 3.0] {
+            assign_rect(&mut ragged, (x, 1.0), (x + 1.0, 5.0));
+        }
+
+        let compact = compact.coverage_estimate(MapState::Assigned, 1.0);
+        let ragged = ragged.coverage_estimate(MapState::Assigned, 1.0);
+
+        assert_eq!(compact.area(), ragged.area());
+        assert!(ragged.path_length() > compact.path_length());
+    }
+
+    #[test]
+    fn narrow_sensor_needs_several_sweeps_per_cell() {
+        let mut map = make_map();
+        assign_rect(&mut map, (0.0, 0.0), (10.0, 0.5));
+
+        let estimate = map.coverage_estimate(MapState::Assigned, 0.25);
+
+        assert_eq!(estimate.sweeps(), 2);
+        assert_eq!(estimate.path_length(), 2.0 * 10.0 + 0.25);
+    }
+
+    #[test]
+    fn empty_region() {
+        let estimate = make_map().coverage_estimate(MapState::Assigned, 1.0);
+
+        assert_eq!(estimate.sweeps(), 0);
+        assert_eq!(estimate.turns(), 0);
+        assert_eq!(estimate.path_length(), 0.0);
+    }
+}